@@ -4,11 +4,15 @@ use super::stream::SseStream;
 use nexus_provider::error::ProviderError;
 use nexus_provider::types::*;
 
+/// `anthropic-version` sent unless overridden with [`AnthropicClient::with_api_version`].
+pub const DEFAULT_API_VERSION: &str = "2023-06-01";
+
 #[derive(Clone)]
 pub struct AnthropicClient {
     http: reqwest::Client,
     api_key: String,
     base_url: String,
+    api_version: String,
 }
 
 impl AnthropicClient {
@@ -17,6 +21,7 @@ impl AnthropicClient {
             http: reqwest::Client::new(),
             api_key,
             base_url: "https://api.anthropic.com".to_string(),
+            api_version: DEFAULT_API_VERSION.to_string(),
         }
     }
 
@@ -25,9 +30,22 @@ impl AnthropicClient {
             http: reqwest::Client::new(),
             api_key,
             base_url,
+            api_version: DEFAULT_API_VERSION.to_string(),
         }
     }
 
+    /// Authenticate with a different API key.
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = api_key.into();
+        self
+    }
+
+    /// Send a different `anthropic-version`.
+    pub fn with_api_version(mut self, version: impl Into<String>) -> Self {
+        self.api_version = version.into();
+        self
+    }
+
     /// Send a non-streaming Messages API request. Returns the full response.
     pub async fn create_message(
         &self,
@@ -37,7 +55,7 @@ impl AnthropicClient {
             .http
             .post(format!("{}/v1/messages", self.base_url))
             .header("x-api-key", &self.api_key)
-            .header("anthropic-version", "2023-06-01")
+            .header("anthropic-version", &self.api_version)
             .header("content-type", "application/json")
             .json(&request)
             .send()
@@ -45,8 +63,11 @@ impl AnthropicClient {
 
         if !resp.status().is_success() {
             let status = resp.status();
+            let retry_after = retry_after(&resp);
             let body = resp.text().await.unwrap_or_default();
-            return Err(ProviderError::from_anthropic_http(status.as_u16(), &body).into());
+            return Err(ProviderError::from_anthropic_http(status.as_u16(), &body)
+                .with_retry_after(retry_after.as_deref())
+                .into());
         }
 
         let response: MessagesResponse = resp.json().await?;
@@ -64,7 +85,7 @@ impl AnthropicClient {
             .http
             .post(format!("{}/v1/messages", self.base_url))
             .header("x-api-key", &self.api_key)
-            .header("anthropic-version", "2023-06-01")
+            .header("anthropic-version", &self.api_version)
             .header("content-type", "application/json")
             .json(&request)
             .send()
@@ -72,8 +93,11 @@ impl AnthropicClient {
 
         if !resp.status().is_success() {
             let status = resp.status();
+            let retry_after = retry_after(&resp);
             let body = resp.text().await.unwrap_or_default();
-            return Err(ProviderError::from_anthropic_http(status.as_u16(), &body).into());
+            return Err(ProviderError::from_anthropic_http(status.as_u16(), &body)
+                .with_retry_after(retry_after.as_deref())
+                .into());
         }
 
         Ok(SseStream::new(resp.bytes_stream()))
@@ -96,7 +120,7 @@ impl AnthropicClient {
             .http
             .post(format!("{}/v1/messages", self.base_url))
             .header("x-api-key", &self.api_key)
-            .header("anthropic-version", "2023-06-01")
+            .header("anthropic-version", &self.api_version)
             .header("content-type", "application/json");
 
         if let Some(headers) = extra_headers {
@@ -109,10 +133,20 @@ impl AnthropicClient {
 
         if !resp.status().is_success() {
             let status = resp.status();
+            let retry_after = retry_after(&resp);
             let body = resp.text().await.unwrap_or_default();
-            return Err(ProviderError::from_anthropic_http(status.as_u16(), &body).into());
+            return Err(ProviderError::from_anthropic_http(status.as_u16(), &body)
+                .with_retry_after(retry_after.as_deref())
+                .into());
         }
 
         Ok(SseStream::new(resp.bytes_stream()))
     }
 }
+
+fn retry_after(resp: &reqwest::Response) -> Option<String> {
+    resp.headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
@@ -0,0 +1,148 @@
+//! Rotation across several API keys for throughput beyond one key's limits.
+//!
+//! [`KeyRing`] hands out a key per request and counts requests and rate-limit
+//! rejections per key. Throttling is ordered by request sequence rather than
+//! wall-clock time, so rotation needs no clock (and works on wasm32).
+
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+/// How [`KeyRing::next`] picks a key; part of the provider record.
+pub use nexus_provider::provider_config::KeyRotation;
+
+/// Per-key counters, safe to log or return from an API.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyStats {
+    /// Last four characters of the key.
+    pub key_hint: String,
+    pub requests: u64,
+    pub throttles: u64,
+}
+
+struct KeyState {
+    key: String,
+    requests: u64,
+    throttles: u64,
+    /// Request sequence number at the latest throttle.
+    throttled_at: Option<u64>,
+}
+
+struct Ring {
+    keys: Vec<KeyState>,
+    /// Index the next round-robin pick starts from.
+    cursor: usize,
+    /// Requests handed out so far, across all keys.
+    sequence: u64,
+}
+
+pub struct KeyRing {
+    rotation: KeyRotation,
+    ring: Mutex<Ring>,
+}
+
+impl KeyRing {
+    /// `None` if `keys` is empty.
+    pub fn new(keys: Vec<String>, rotation: KeyRotation) -> Option<Self> {
+        if keys.is_empty() {
+            return None;
+        }
+        let keys = keys
+            .into_iter()
+            .map(|key| KeyState { key, requests: 0, throttles: 0, throttled_at: None })
+            .collect();
+        Some(Self {
+            rotation,
+            ring: Mutex::new(Ring { keys, cursor: 0, sequence: 0 }),
+        })
+    }
+
+    pub fn set_rotation(&mut self, rotation: KeyRotation) {
+        self.rotation = rotation;
+    }
+
+    /// The key to send the next request with.
+    pub fn next(&self) -> String {
+        let mut ring = self.ring.lock().unwrap();
+        let len = ring.keys.len();
+        let start = ring.cursor;
+        let index = match self.rotation {
+            KeyRotation::RoundRobin => start,
+            // `min_by_key` keeps the first of equal keys, i.e. the next in turn.
+            KeyRotation::LeastRecentlyThrottled => (0..len)
+                .map(|offset| (start + offset) % len)
+                .min_by_key(|&i| ring.keys[i].throttled_at)
+                .unwrap_or(start),
+        };
+        ring.cursor = (index + 1) % len;
+        ring.sequence += 1;
+        let state = &mut ring.keys[index];
+        state.requests += 1;
+        state.key.clone()
+    }
+
+    /// Record that the provider rate-limited a request sent with `key`.
+    pub fn record_throttle(&self, key: &str) {
+        let mut ring = self.ring.lock().unwrap();
+        let sequence = ring.sequence;
+        if let Some(state) = ring.keys.iter_mut().find(|s| s.key == key) {
+            state.throttles += 1;
+            state.throttled_at = Some(sequence);
+        }
+    }
+
+    pub fn stats(&self) -> Vec<KeyStats> {
+        let ring = self.ring.lock().unwrap();
+        ring.keys
+            .iter()
+            .map(|s| {
+                let chars: Vec<char> = s.key.chars().collect();
+                KeyStats {
+                    key_hint: chars[chars.len().saturating_sub(4)..].iter().collect(),
+                    requests: s.requests,
+                    throttles: s.throttles,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys() -> Vec<String> {
+        vec!["sk-aaaa".into(), "sk-bbbb".into(), "sk-cccc".into()]
+    }
+
+    #[test]
+    fn round_robin_cycles_and_counts() {
+        assert!(KeyRing::new(Vec::new(), KeyRotation::RoundRobin).is_none());
+
+        let ring = KeyRing::new(keys(), KeyRotation::RoundRobin).unwrap();
+        let picked: Vec<String> = (0..4).map(|_| ring.next()).collect();
+        assert_eq!(picked, ["sk-aaaa", "sk-bbbb", "sk-cccc", "sk-aaaa"]);
+
+        ring.record_throttle("sk-bbbb");
+        assert_eq!(ring.next(), "sk-bbbb");
+        let stats = ring.stats();
+        assert_eq!(stats[0], KeyStats { key_hint: "aaaa".into(), requests: 2, throttles: 0 });
+        assert_eq!(stats[1], KeyStats { key_hint: "bbbb".into(), requests: 2, throttles: 1 });
+    }
+
+    #[test]
+    fn least_recently_throttled_avoids_fresh_throttles() {
+        let ring = KeyRing::new(keys(), KeyRotation::LeastRecentlyThrottled).unwrap();
+        assert_eq!(ring.next(), "sk-aaaa");
+        ring.record_throttle("sk-aaaa");
+        assert_eq!(ring.next(), "sk-bbbb");
+        ring.record_throttle("sk-bbbb");
+        assert_eq!(ring.next(), "sk-cccc");
+        ring.record_throttle("sk-cccc");
+        // All throttled: the oldest throttle goes first.
+        assert_eq!(ring.next(), "sk-aaaa");
+        ring.record_throttle("sk-aaaa");
+        assert_eq!(ring.next(), "sk-bbbb");
+    }
+}
@@ -1,7 +1,9 @@
 pub mod client;
+pub mod keys;
 pub mod provider;
 pub mod stream;
 
-pub use client::AnthropicClient;
+pub use client::{AnthropicClient, DEFAULT_API_VERSION};
+pub use keys::{KeyRotation, KeyStats};
 pub use provider::AnthropicProvider;
 pub use stream::SseStream;
@@ -1,18 +1,26 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use futures::stream::BoxStream;
-use futures::StreamExt;
 
-use nexus_provider::types::{inject_cache_control, MessagesRequest, StreamEvent, ThinkingConfig};
-use nexus_provider::{InferenceProvider, InferenceRequest};
+use nexus_provider::error::{ProviderError, ProviderErrorKind};
+use nexus_provider::types::{inject_cache_control, MessagesRequest, ThinkingConfig};
+use nexus_provider::{EventStream, InferenceProvider, InferenceRequest};
 
 use super::client::AnthropicClient;
+use super::keys::{KeyRing, KeyRotation, KeyStats};
 
 /// Beta header required for extended thinking.
 const THINKING_BETA_HEADER: &str = "interleaved-thinking-2025-05-14";
 
+/// Lets the API (or a proxy in front of it) recognize a retried request.
+const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
 pub struct AnthropicProvider {
     client: AnthropicClient,
+    /// Extra `anthropic-beta` features sent with every request.
+    betas: Vec<String>,
+    /// Keys rotated across requests, when more than one is configured.
+    keys: Option<KeyRing>,
+    rotation: KeyRotation,
 }
 
 impl AnthropicProvider {
@@ -22,16 +30,67 @@ impl AnthropicProvider {
         } else {
             AnthropicClient::new(api_key)
         };
-        Self { client }
+        Self { client, betas: Vec::new(), keys: None, rotation: KeyRotation::default() }
+    }
+
+    /// Opt into beta features (e.g. `context-1m-2025-08-07`) on every
+    /// request. The thinking beta is still added when thinking is enabled.
+    pub fn with_beta_headers<I, S>(mut self, betas: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.betas = betas.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Rotate requests across `keys` instead of the key passed to `new`,
+    /// tracking requests and rate limits per key. An empty list keeps the
+    /// single key.
+    pub fn with_api_keys<I, S>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.keys = KeyRing::new(keys.into_iter().map(Into::into).collect(), self.rotation);
+        self
+    }
+
+    /// How [`with_api_keys`](Self::with_api_keys) keys are picked
+    /// (round-robin by default).
+    pub fn with_key_rotation(mut self, rotation: KeyRotation) -> Self {
+        self.rotation = rotation;
+        if let Some(ring) = self.keys.as_mut() {
+            ring.set_rotation(rotation);
+        }
+        self
+    }
+
+    /// Request and throttle counts per rotated key; empty with a single key.
+    pub fn key_stats(&self) -> Vec<KeyStats> {
+        self.keys.as_ref().map(KeyRing::stats).unwrap_or_default()
+    }
+
+    /// Send a different `anthropic-version` than the pinned default.
+    pub fn with_api_version(mut self, version: impl Into<String>) -> Self {
+        self.client = self.client.with_api_version(version);
+        self
+    }
+
+    /// Comma-separated `anthropic-beta` value for a request, if any.
+    fn beta_header(&self, has_thinking: bool) -> Option<String> {
+        let mut betas: Vec<&str> = self.betas.iter().map(String::as_str).collect();
+        if has_thinking && !betas.contains(&THINKING_BETA_HEADER) {
+            betas.push(THINKING_BETA_HEADER);
+        }
+        (!betas.is_empty()).then(|| betas.join(","))
     }
 }
 
-#[async_trait]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 impl InferenceProvider for AnthropicProvider {
-    async fn create_message_stream(
-        &self,
-        request: InferenceRequest,
-    ) -> Result<BoxStream<'static, Result<StreamEvent>>> {
+    async fn create_message_stream(&self, request: InferenceRequest) -> Result<EventStream> {
         // When thinking is enabled, temperature must be omitted (API requirement)
         let (temperature, thinking) = match request.thinking_budget {
             Some(budget) => (
@@ -45,6 +104,7 @@ impl InferenceProvider for AnthropicProvider {
         };
 
         let has_thinking = request.thinking_budget.is_some();
+        let idempotency_key = request.idempotency_key;
 
         let api_request = MessagesRequest {
             model: request.model.clone(),
@@ -61,17 +121,55 @@ impl InferenceProvider for AnthropicProvider {
         let mut body = serde_json::to_value(&api_request)?;
         inject_cache_control(&mut body);
 
-        // Add beta header for extended thinking
-        let extra_headers = if has_thinking {
-            Some(vec![("anthropic-beta", THINKING_BETA_HEADER)])
-        } else {
-            None
+        // Add beta features (extended thinking, configured betas), and the
+        // idempotency key
+        let beta = self.beta_header(has_thinking);
+        let mut headers = Vec::new();
+        if let Some(ref beta) = beta {
+            headers.push(("anthropic-beta", beta.as_str()));
+        }
+        if let Some(ref key) = idempotency_key {
+            headers.push((IDEMPOTENCY_KEY_HEADER, key.as_str()));
+        }
+        let extra_headers = (!headers.is_empty()).then_some(headers);
+
+        let Some(ref keys) = self.keys else {
+            let stream = self.client.create_message_stream_json(body, extra_headers).await?;
+            return Ok(Box::pin(stream));
         };
+        let key = keys.next();
+        let client = self.client.clone().with_api_key(key.as_str());
+        match client.create_message_stream_json(body, extra_headers).await {
+            Ok(stream) => Ok(Box::pin(stream)),
+            Err(e) => {
+                // The caller's retry goes out with the next key.
+                if e.downcast_ref::<ProviderError>()
+                    .is_some_and(|pe| pe.kind == ProviderErrorKind::RateLimit)
+                {
+                    keys.record_throttle(&key);
+                    tracing::debug!(keys = ?keys.stats(), "API key rate-limited, rotating");
+                }
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn beta_header_merges_configured_betas_with_thinking() {
+        let provider = AnthropicProvider::new("key".into(), None);
+        assert_eq!(provider.beta_header(false), None);
+        assert_eq!(provider.beta_header(true).as_deref(), Some(THINKING_BETA_HEADER));
 
-        let stream = self
-            .client
-            .create_message_stream_json(body, extra_headers)
-            .await?;
-        Ok(stream.boxed())
+        let provider = provider.with_beta_headers(["context-1m-2025-08-07", THINKING_BETA_HEADER]);
+        assert_eq!(
+            provider.beta_header(false).as_deref(),
+            Some("context-1m-2025-08-07,interleaved-thinking-2025-05-14")
+        );
+        assert_eq!(provider.beta_header(true), provider.beta_header(false));
     }
 }
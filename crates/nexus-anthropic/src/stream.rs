@@ -115,6 +115,22 @@ fn parse_sse_event(text: &str) -> Result<Option<StreamEvent>> {
                 RawContentBlock::Text { .. } => ContentBlockInfo::Text,
                 RawContentBlock::ToolUse { id, name } => ContentBlockInfo::ToolUse { id, name },
                 RawContentBlock::Thinking { .. } => ContentBlockInfo::Thinking,
+                RawContentBlock::RedactedThinking { data } => {
+                    ContentBlockInfo::RedactedThinking { data }
+                }
+                RawContentBlock::ServerToolUse { id, name } => {
+                    ContentBlockInfo::ServerToolUse { id, name }
+                }
+                RawContentBlock::WebSearchToolResult { tool_use_id, content } => {
+                    ContentBlockInfo::ServerToolResult {
+                        block: ContentBlock::WebSearchToolResult { tool_use_id, content },
+                    }
+                }
+                RawContentBlock::CodeExecutionToolResult { tool_use_id, content } => {
+                    ContentBlockInfo::ServerToolResult {
+                        block: ContentBlock::CodeExecutionToolResult { tool_use_id, content },
+                    }
+                }
             };
             StreamEvent::ContentBlockStart {
                 index: raw.index,
@@ -129,6 +145,7 @@ fn parse_sse_event(text: &str) -> Result<Option<StreamEvent>> {
                     Delta::InputJsonDelta { partial_json }
                 }
                 RawDelta::ThinkingDelta { thinking } => Delta::ThinkingDelta { thinking },
+                RawDelta::SignatureDelta { signature } => Delta::SignatureDelta { signature },
             };
             StreamEvent::ContentBlockDelta {
                 index: raw.index,
@@ -163,3 +180,52 @@ fn parse_sse_event(text: &str) -> Result<Option<StreamEvent>> {
 
     Ok(Some(event))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_server_tool_blocks_and_usage() {
+        let start = parse_sse_event(
+            "event: content_block_start\n\
+             data: {\"index\":1,\"content_block\":{\"type\":\"server_tool_use\",\"id\":\"srvtoolu_1\",\"name\":\"web_search\",\"input\":{}}}",
+        )
+        .unwrap();
+        assert!(matches!(
+            start,
+            Some(StreamEvent::ContentBlockStart {
+                index: 1,
+                content_block: ContentBlockInfo::ServerToolUse { ref id, ref name },
+            }) if id == "srvtoolu_1" && name == "web_search"
+        ));
+
+        let result = parse_sse_event(
+            "event: content_block_start\n\
+             data: {\"index\":2,\"content_block\":{\"type\":\"web_search_tool_result\",\"tool_use_id\":\"srvtoolu_1\",\"content\":[{\"type\":\"web_search_result\",\"url\":\"https://example.com\",\"title\":\"Example\"}]}}",
+        )
+        .unwrap();
+        let Some(StreamEvent::ContentBlockStart {
+            content_block: ContentBlockInfo::ServerToolResult { block },
+            ..
+        }) = result
+        else {
+            panic!("expected a server tool result: {result:?}");
+        };
+        assert!(matches!(
+            block,
+            ContentBlock::WebSearchToolResult { ref tool_use_id, ref content }
+                if tool_use_id == "srvtoolu_1" && content[0]["title"] == "Example"
+        ));
+
+        let delta = parse_sse_event(
+            "event: message_delta\n\
+             data: {\"delta\":{\"stop_reason\":\"end_turn\"},\"usage\":{\"output_tokens\":9,\"server_tool_use\":{\"web_search_requests\":1}}}",
+        )
+        .unwrap();
+        let Some(StreamEvent::MessageDelta { usage: Some(usage), .. }) = delta else {
+            panic!("expected a message delta: {delta:?}");
+        };
+        assert_eq!(usage.server_tool_use.map(|u| u.web_search_requests), Some(1));
+    }
+}
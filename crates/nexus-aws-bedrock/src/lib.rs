@@ -1,5 +1,6 @@
 use anyhow::Result;
 use async_trait::async_trait;
+use aws_sdk_bedrockruntime::error::ProvideErrorMetadata;
 use aws_sdk_bedrockruntime::primitives::Blob;
 use aws_sdk_bedrockruntime::Client as BedrockClient;
 use futures::stream::BoxStream;
@@ -72,10 +73,7 @@ impl InferenceProvider for BedrockProvider {
             .body(Blob::new(body_bytes))
             .send()
             .await
-            .map_err(|e| {
-                let msg = format!("{:?}", e);
-                ProviderError::from_bedrock(&msg)
-            })?;
+            .map_err(|e| bedrock_error(&e, format!("{:?}", e)))?;
 
         let event_stream = output.body;
 
@@ -103,8 +101,7 @@ impl InferenceProvider for BedrockProvider {
                     }
                     Ok(None) => return None,
                     Err(e) => {
-                        let msg = format!("{}", e);
-                        let err = ProviderError::from_bedrock(&msg);
+                        let err = bedrock_error(&e, format!("{}", e));
                         return Some((Err(err.into()), receiver));
                     }
                 }
@@ -115,6 +112,16 @@ impl InferenceProvider for BedrockProvider {
     }
 }
 
+/// Structured error from the SDK's error metadata, falling back to scanning
+/// the formatted error when no service code is available (e.g. dispatch
+/// failures).
+fn bedrock_error(e: &impl ProvideErrorMetadata, formatted: String) -> ProviderError {
+    match e.code() {
+        Some(code) => ProviderError::from_bedrock_code(Some(code), e.message().unwrap_or(&formatted)),
+        None => ProviderError::from_bedrock(&formatted),
+    }
+}
+
 /// Parse a Bedrock EventStream chunk payload into our StreamEvent.
 /// Bedrock wraps Anthropic-format JSON events in its EventStream frames.
 fn parse_bedrock_chunk(bytes: &[u8]) -> Result<Option<StreamEvent>> {
@@ -144,6 +151,9 @@ fn parse_bedrock_chunk(bytes: &[u8]) -> Result<Option<StreamEvent>> {
                     name: cb["name"].as_str().unwrap_or("").to_string(),
                 },
                 "thinking" => ContentBlockInfo::Thinking,
+                "redacted_thinking" => ContentBlockInfo::RedactedThinking {
+                    data: cb["data"].as_str().unwrap_or("").to_string(),
+                },
                 _ => return Ok(None),
             };
             Ok(Some(StreamEvent::ContentBlockStart {
@@ -165,6 +175,9 @@ fn parse_bedrock_chunk(bytes: &[u8]) -> Result<Option<StreamEvent>> {
                 "thinking_delta" => Delta::ThinkingDelta {
                     thinking: delta["thinking"].as_str().unwrap_or("").to_string(),
                 },
+                "signature_delta" => Delta::SignatureDelta {
+                    signature: delta["signature"].as_str().unwrap_or("").to_string(),
+                },
                 _ => return Ok(None),
             };
             Ok(Some(StreamEvent::ContentBlockDelta { index, delta: d }))
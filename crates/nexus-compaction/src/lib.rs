@@ -8,12 +8,17 @@
 //!
 //! 2. **LLM summarization** — Sonnet call to summarize old messages into a
 //!    compact reference. Permanent: replaces messages in the stored conversation.
+//!
+//! As a last resort, [`trim_to_fit`] drops the oldest whole exchanges from
+//! the API message array when a request still exceeds the window.
 
 mod pruning;
 mod summarize;
+mod trimming;
 
-pub use pruning::prune_tool_results;
+pub use pruning::{prune_tool_results, PrunedToolResult};
 pub use summarize::{summarize_conversation, SummarizeResult};
+pub use trimming::{trim_to_fit, DroppedExchange};
 
 use nexus_provider::types::{ContentBlock, Message, Tool};
 
@@ -25,6 +30,12 @@ pub const PRUNE_THRESHOLD_PCT: f64 = 0.5;
 /// Fraction of effective window above which summarization activates.
 pub const SUMMARIZE_THRESHOLD_PCT: f64 = 0.8;
 
+/// Start of the user message that stands in for summarized history.
+pub const SUMMARY_PREFIX: &str = "[Previous conversation context]";
+
+/// Rough token cost of one image in a tool result (a ~1000×1000 image).
+pub const IMAGE_TOKENS: u32 = 1_600;
+
 // ── Token Estimation ──
 
 /// Estimate token count from API messages, system prompt, and tools.
@@ -50,8 +61,20 @@ pub fn estimate_tokens(
                     chars += name.len();
                     chars += input.to_string().len();
                 }
-                ContentBlock::ToolResult { content, .. } => chars += content.len(),
-                ContentBlock::Thinking { thinking } => chars += thinking.len(),
+                ContentBlock::ToolResult { content, .. } => {
+                    chars += content.text().len();
+                    chars += content.images().count() * IMAGE_TOKENS as usize * 3;
+                }
+                ContentBlock::Thinking { thinking, .. } => chars += thinking.len(),
+                ContentBlock::RedactedThinking { data } => chars += data.len(),
+                ContentBlock::ServerToolUse { name, input, .. } => {
+                    chars += name.len();
+                    chars += input.to_string().len();
+                }
+                ContentBlock::WebSearchToolResult { content, .. }
+                | ContentBlock::CodeExecutionToolResult { content, .. } => {
+                    chars += content.to_string().len();
+                }
             }
         }
     }
@@ -1,15 +1,29 @@
 use nexus_provider::types::{ContentBlock, Message, Role};
 
+/// Marker appended to pruned stubs so repeated passes skip them.
+const PRUNED_MARKER: &str = "pruned, id=";
+
+/// Original content of a tool result that was replaced with a stub. Only
+/// the text is kept; images in a pruned result are dropped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrunedToolResult {
+    pub tool_use_id: String,
+    pub tool_name: String,
+    pub content: String,
+}
+
 /// Prune old tool results from API messages to reclaim context space.
 ///
 /// Keeps the last `keep_recent` tool results intact. Earlier results are
-/// replaced with compact stubs showing tool name and content size.
-/// Also stubs out the matching `ToolUse.input` args for pruned calls
-/// (write_file/edit_file args can be huge).
+/// replaced with compact stubs showing tool name, content size, and the
+/// tool_use_id the content can be restored by. Also stubs out the matching
+/// `ToolUse.input` args for pruned calls (write_file/edit_file args can be huge).
 ///
 /// Operates in-place on the API message array — stored ChatMessages are
-/// untouched.
-pub fn prune_tool_results(messages: &mut [Message], keep_recent: usize) {
+/// untouched. Returns the original content of every newly pruned result so
+/// the caller can keep it retrievable; results pruned by an earlier pass are
+/// left alone and not returned again.
+pub fn prune_tool_results(messages: &mut [Message], keep_recent: usize) -> Vec<PrunedToolResult> {
     // First pass: collect (message_idx, block_idx) of every ToolResult, in order.
     let mut tool_result_positions: Vec<(usize, usize)> = Vec::new();
 
@@ -23,7 +37,7 @@ pub fn prune_tool_results(messages: &mut [Message], keep_recent: usize) {
 
     let total = tool_result_positions.len();
     if total <= keep_recent {
-        return; // Nothing to prune
+        return Vec::new(); // Nothing to prune
     }
 
     let prune_count = total - keep_recent;
@@ -46,6 +60,7 @@ pub fn prune_tool_results(messages: &mut [Message], keep_recent: usize) {
     // Collect tool_use_ids that we're pruning (for stubbing their args too)
     let mut pruned_tool_use_ids: std::collections::HashSet<String> =
         std::collections::HashSet::new();
+    let mut pruned = Vec::new();
 
     // Second pass: replace pruned tool results with stubs
     for &(msg_idx, block_idx) in to_prune {
@@ -56,17 +71,29 @@ pub fn prune_tool_results(messages: &mut [Message], keep_recent: usize) {
             is_error,
         } = block
         {
+            let text = content.text();
+            if is_pruned_stub(&text) {
+                continue;
+            }
             let tool_name = tool_names
                 .get(tool_use_id)
                 .map(|s| s.as_str())
                 .unwrap_or("unknown");
-            let char_count = content.len();
-            let stub = format!("[{}: {} chars]", tool_name, char_count);
+            let char_count = text.len();
+            let stub = format!(
+                "[{}: {} chars, {}{}]",
+                tool_name, char_count, PRUNED_MARKER, tool_use_id
+            );
             pruned_tool_use_ids.insert(tool_use_id.clone());
+            pruned.push(PrunedToolResult {
+                tool_use_id: tool_use_id.clone(),
+                tool_name: tool_name.to_string(),
+                content: text.into_owned(),
+            });
 
             messages[msg_idx].content[block_idx] = ContentBlock::ToolResult {
                 tool_use_id: tool_use_id.clone(),
-                content: stub,
+                content: stub.into(),
                 is_error: *is_error,
             };
         }
@@ -87,11 +114,19 @@ pub fn prune_tool_results(messages: &mut [Message], keep_recent: usize) {
     }
 
     tracing::info!(
-        pruned = prune_count,
+        pruned = pruned.len(),
+        candidates = prune_count,
         kept = keep_recent,
         total,
         "Tool result pruning"
     );
+
+    pruned
+}
+
+/// Whether a tool result's content is a stub left by an earlier pruning pass.
+fn is_pruned_stub(content: &str) -> bool {
+    content.starts_with('[') && content.ends_with(']') && content.contains(PRUNED_MARKER)
 }
 
 #[cfg(test)]
@@ -111,7 +146,7 @@ mod tests {
             role: Role::User,
             content: vec![ContentBlock::ToolResult {
                 tool_use_id: id.to_string(),
-                content: result.to_string(),
+                content: result.into(),
                 is_error: None,
             }],
         };
@@ -144,7 +179,7 @@ mod tests {
         for msg in &messages {
             for block in &msg.content {
                 if let ContentBlock::ToolResult { content, .. } = block {
-                    if content.starts_with('[') {
+                    if content.text().starts_with('[') {
                         stubs += 1;
                     } else {
                         full += 1;
@@ -189,7 +224,7 @@ mod tests {
                 } = b
                 {
                     if tool_use_id == "tool_0" {
-                        Some(content.clone())
+                        Some(content.text().into_owned())
                     } else {
                         None
                     }
@@ -199,7 +234,55 @@ mod tests {
             })
             .unwrap();
 
-        assert_eq!(first_result, "[read_file: 5000 chars]");
+        assert_eq!(first_result, "[read_file: 5000 chars, pruned, id=tool_0]");
+    }
+
+    #[test]
+    fn prune_returns_original_content() {
+        let mut messages = Vec::new();
+        for i in 0..4 {
+            let (a, u) = make_tool_pair(
+                &format!("tool_{}", i),
+                "read_file",
+                &format!("contents of file {}", i),
+            );
+            messages.push(a);
+            messages.push(u);
+        }
+
+        let pruned = prune_tool_results(&mut messages, 3);
+
+        assert_eq!(
+            pruned,
+            vec![PrunedToolResult {
+                tool_use_id: "tool_0".to_string(),
+                tool_name: "read_file".to_string(),
+                content: "contents of file 0".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn prune_skips_already_pruned_stubs() {
+        let mut messages = Vec::new();
+        for i in 0..5 {
+            let (a, u) = make_tool_pair(
+                &format!("tool_{}", i),
+                "read_file",
+                &"x".repeat(1000),
+            );
+            messages.push(a);
+            messages.push(u);
+        }
+
+        let first = prune_tool_results(&mut messages, 3);
+        assert_eq!(first.len(), 2);
+
+        // A tighter second pass only returns the newly pruned result.
+        let second = prune_tool_results(&mut messages, 1);
+        let ids: Vec<&str> = second.iter().map(|p| p.tool_use_id.as_str()).collect();
+        assert_eq!(ids, vec!["tool_2", "tool_3"]);
+        assert!(second.iter().all(|p| p.content == "x".repeat(1000)));
     }
 
     #[test]
@@ -52,6 +52,7 @@ pub async fn summarize_conversation(
             thinking_budget: None,
             messages,
             tools: Vec::new(),
+            idempotency_key: None,
         })
         .await
         .map_err(|e| anyhow::anyhow!("stream creation failed: {}", e))?;
@@ -0,0 +1,148 @@
+use nexus_provider::types::{ContentBlock, Message, Role};
+
+use crate::{estimate_tokens, SUMMARY_PREFIX};
+
+/// An exchange removed by [`trim_to_fit`], for logging.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DroppedExchange {
+    /// Start of the prompt that opened the exchange.
+    pub preview: String,
+    pub messages: usize,
+    pub tokens: u32,
+}
+
+const PREVIEW_CHARS: usize = 60;
+
+/// A user message that opens an exchange: one carrying the user's own
+/// content rather than tool results for the previous assistant message.
+fn opens_exchange(message: &Message) -> bool {
+    message.role == Role::User
+        && !message.content.iter().any(|b| matches!(b, ContentBlock::ToolResult { .. }))
+}
+
+fn first_text(message: &Message) -> &str {
+    message
+        .content
+        .iter()
+        .find_map(|b| match b {
+            ContentBlock::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+/// Drop the oldest exchanges (a prompt plus everything up to the next one)
+/// until the messages' estimated tokens fit `budget`.
+///
+/// Compaction summaries and the latest exchange are pinned and never
+/// dropped, so the result can still exceed `budget`. Whole exchanges go at
+/// once, keeping tool calls paired with their results. Operates on the API
+/// message array; returns what was dropped, oldest first.
+pub fn trim_to_fit(messages: &mut Vec<Message>, budget: u32) -> Vec<DroppedExchange> {
+    let mut starts: Vec<usize> = messages
+        .iter()
+        .enumerate()
+        .filter(|(_, m)| opens_exchange(m))
+        .map(|(i, _)| i)
+        .collect();
+    if starts.first() != Some(&0) {
+        starts.insert(0, 0);
+    }
+    let ranges: Vec<(usize, usize)> = starts
+        .iter()
+        .zip(starts.iter().skip(1).chain(std::iter::once(&messages.len())))
+        .map(|(&start, &end)| (start, end))
+        .collect();
+
+    let mut total = estimate_tokens(messages, None, &[]);
+    let mut dropped = Vec::new();
+    let mut keep = vec![true; messages.len()];
+    // The last exchange is the current prompt and this turn's work.
+    for &(start, end) in ranges.iter().take(ranges.len().saturating_sub(1)) {
+        if total <= budget {
+            break;
+        }
+        if first_text(&messages[start]).starts_with(SUMMARY_PREFIX) {
+            continue;
+        }
+        let tokens = estimate_tokens(&messages[start..end], None, &[]);
+        total = total.saturating_sub(tokens);
+        keep[start..end].iter_mut().for_each(|k| *k = false);
+        let text = first_text(&messages[start]).split_whitespace().collect::<Vec<_>>().join(" ");
+        let mut preview: String = text.chars().take(PREVIEW_CHARS).collect();
+        if text.chars().count() > PREVIEW_CHARS {
+            preview.push('…');
+        }
+        dropped.push(DroppedExchange { preview, messages: end - start, tokens });
+    }
+
+    if !dropped.is_empty() {
+        let mut keep = keep.into_iter();
+        messages.retain(|_| keep.next().unwrap_or(true));
+    }
+    dropped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(role: Role, text: &str) -> Message {
+        Message { role, content: vec![ContentBlock::Text { text: text.to_string() }] }
+    }
+
+    fn tool_round(id: &str, result: &str) -> [Message; 2] {
+        [
+            Message {
+                role: Role::Assistant,
+                content: vec![ContentBlock::ToolUse {
+                    id: id.to_string(),
+                    name: "read_file".to_string(),
+                    input: serde_json::json!({}),
+                }],
+            },
+            Message {
+                role: Role::User,
+                content: vec![ContentBlock::ToolResult {
+                    tool_use_id: id.to_string(),
+                    content: result.into(),
+                    is_error: None,
+                }],
+            },
+        ]
+    }
+
+    #[test]
+    fn drops_oldest_unpinned_exchanges_until_it_fits() {
+        let big = "x".repeat(3_000);
+        let mut messages = vec![
+            text(Role::User, &format!("{SUMMARY_PREFIX}\n\nearlier work")),
+            text(Role::Assistant, "Understood, I have the previous context."),
+            text(Role::User, "first question"),
+        ];
+        messages.extend(tool_round("t1", &big));
+        messages.push(text(Role::Assistant, "first answer"));
+        messages.push(text(Role::User, "second question"));
+        messages.extend(tool_round("t2", &big));
+        messages.push(text(Role::Assistant, "second answer"));
+        messages.push(text(Role::User, "current question"));
+        messages.extend(tool_round("t3", &big));
+
+        let before = messages.len();
+        let dropped = trim_to_fit(&mut messages, 2_100);
+        assert_eq!(dropped.len(), 1);
+        assert_eq!(dropped[0].preview, "first question");
+        assert_eq!(dropped[0].messages, 4);
+        assert_eq!(messages.len(), before - 4);
+        assert!(first_text(&messages[0]).starts_with(SUMMARY_PREFIX));
+        assert_eq!(first_text(&messages[2]), "second question");
+        assert!(estimate_tokens(&messages, None, &[]) <= 2_100);
+
+        // A tight budget drops the rest, but never the summary or the
+        // current exchange.
+        let dropped = trim_to_fit(&mut messages, 10);
+        assert_eq!(dropped.len(), 1);
+        assert_eq!(trim_to_fit(&mut messages, 10), Vec::new());
+        assert_eq!(first_text(&messages[2]), "current question");
+    }
+}
@@ -1,8 +1,8 @@
 use chrono::{DateTime, Utc};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ProcessKind {
     Bash,
@@ -10,7 +10,7 @@ pub enum ProcessKind {
     SubAgent,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ProcessStatus {
     Running,
@@ -19,7 +19,7 @@ pub enum ProcessStatus {
     Cancelled,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BgProcess {
     pub id: String,
@@ -34,6 +34,14 @@ pub struct BgProcess {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub exit_code: Option<i32>,
     pub is_error: bool,
+    /// OS process id of a running shell command; signalled to stop it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    /// Start time of `pid` as the OS reports it, recorded with the pid. A
+    /// restarted daemon only adopts or signals `pid` if this still matches,
+    /// so a reused pid is never mistaken for the command.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid_started: Option<String>,
     #[serde(skip)]
     pub output_path: PathBuf,
     #[serde(skip_serializing_if = "Option::is_none")]
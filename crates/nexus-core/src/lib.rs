@@ -16,12 +16,23 @@ pub struct InjectedMessage {
 
 // ── Tool result ──
 
+/// A base64-encoded image returned by a tool (a screenshot, a rendered chart).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolImage {
+    /// MIME type, e.g. `image/png`.
+    pub media_type: String,
+    pub data: String,
+}
+
 /// Result of dispatching a single tool call.
 pub struct ToolResult {
     pub content: String,
     pub is_error: bool,
     /// Ephemeral messages to inject alongside this result.
     pub injected_messages: Vec<InjectedMessage>,
+    /// Images sent to the model as image blocks after `content`. Text
+    /// transforms (fencing, truncation, pruning) only apply to `content`.
+    pub images: Vec<ToolImage>,
 }
 
 impl ToolResult {
@@ -30,6 +41,7 @@ impl ToolResult {
             content,
             is_error: false,
             injected_messages: Vec::new(),
+            images: Vec::new(),
         }
     }
 
@@ -38,8 +50,14 @@ impl ToolResult {
             content,
             is_error: true,
             injected_messages: Vec::new(),
+            images: Vec::new(),
         }
     }
+
+    pub fn with_images(mut self, images: Vec<ToolImage>) -> Self {
+        self.images = images;
+        self
+    }
 }
 
 // ── Module-owned types (decoupled from provider-specific types) ──
@@ -58,6 +76,9 @@ pub enum StopReason {
     MaxTokens,
     StopSequence,
     ToolUse,
+    Refusal,
+    /// A provider stop reason with no dedicated variant (e.g. `pause_turn`).
+    Other(String),
 }
 
 impl std::fmt::Display for StopReason {
@@ -67,6 +88,8 @@ impl std::fmt::Display for StopReason {
             Self::MaxTokens => write!(f, "max_tokens"),
             Self::StopSequence => write!(f, "stop_sequence"),
             Self::ToolUse => write!(f, "tool_use"),
+            Self::Refusal => write!(f, "refusal"),
+            Self::Other(s) => write!(f, "{s}"),
         }
     }
 }
@@ -152,6 +175,9 @@ pub struct TurnEndEvent<'a> {
     pub round_count: usize,
     pub turn_cost: f64,
     pub error: Option<&'a str>,
+    /// Modules push tags here to checkpoint the conversation as it stands
+    /// after this turn, for a later rollback.
+    pub checkpoint_tags: &'a mut Vec<String>,
 }
 
 /// PreCompact — fires before context compaction.
@@ -168,6 +194,20 @@ pub enum CompactionLayer {
     Summarize,
 }
 
+/// PostCompact — fires after a summarization pass seals a span.
+///
+/// The consumed messages stay in the conversation file but drop out of the
+/// active path, so this is the last point where modules can ingest the
+/// discarded segment (e.g. into long-term memory) alongside its summary.
+pub struct PostCompactEvent<'a> {
+    pub conversation_id: &'a str,
+    /// Index of the span that was just sealed.
+    pub span_index: u32,
+    pub summary: &'a str,
+    /// IDs of the messages folded into the summary.
+    pub consumed_message_ids: &'a [String],
+}
+
 /// SubagentStart — fires when a sub-agent spawns.
 pub struct SubagentStartEvent<'a> {
     pub parent_conversation_id: &'a str,
@@ -279,13 +319,16 @@ pub trait DaemonModule: Send + Sync {
     }
 
     /// Turn fully complete (results persisted, cleanup done).
-    async fn turn_end(&self, _event: &TurnEndEvent<'_>) {}
+    async fn turn_end(&self, _event: &mut TurnEndEvent<'_>) {}
 
     // ── Compaction ──
 
     /// Before context compaction. Save any state that would be lost.
     async fn pre_compact(&self, _event: &PreCompactEvent<'_>) {}
 
+    /// After a span is sealed with a summary. Ingest the discarded segment.
+    async fn post_compact(&self, _event: &PostCompactEvent<'_>) {}
+
     // ── Sub-agents ──
 
     async fn subagent_start(&self, _event: &SubagentStartEvent<'_>) {}
@@ -403,7 +446,7 @@ impl ModuleRegistry {
         StopDecision::Stop
     }
 
-    pub async fn fire_turn_end(&self, event: &TurnEndEvent<'_>) {
+    pub async fn fire_turn_end(&self, event: &mut TurnEndEvent<'_>) {
         for module in &self.modules {
             module.turn_end(event).await;
         }
@@ -417,6 +460,12 @@ impl ModuleRegistry {
         }
     }
 
+    pub async fn fire_post_compact(&self, event: &PostCompactEvent<'_>) {
+        for module in &self.modules {
+            module.post_compact(event).await;
+        }
+    }
+
     // ── Sub-agents ──
 
     pub async fn fire_subagent_start(&self, event: &SubagentStartEvent<'_>) {
@@ -14,12 +14,7 @@ fn main() {
     let mut reader = stdin.lock();
     let mut writer = stdout.lock();
 
-    loop {
-        let msg = match read_message(&mut reader) {
-            Ok(msg) => msg,
-            Err(_) => break,
-        };
-
+    while let Ok(msg) = read_message(&mut reader) {
         let json: serde_json::Value = match serde_json::from_str(&msg) {
             Ok(v) => v,
             Err(_) => continue,
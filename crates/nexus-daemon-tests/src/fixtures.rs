@@ -1,6 +1,7 @@
 use serde_json::{json, Value};
 
 use crate::client::DaemonClient;
+use crate::harness::TestDaemon;
 
 pub fn provider_body(name: &str) -> Value {
     json!({
@@ -71,3 +72,27 @@ pub async fn setup_mock_agent(
 
     (provider_id, agent_id, conversation_id)
 }
+
+/// Spawn a daemon in a fresh home whose nexus.json is `config`, served on
+/// an OS-assigned port. Keep the home alive as long as the daemon.
+pub async fn spawn_with_config(config: Value) -> (TestDaemon, tempfile::TempDir) {
+    spawn_with_files(config, &[]).await
+}
+
+/// Like [`spawn_with_config`], also writing `files` (paths relative to
+/// `.nexus`) before the daemon starts.
+pub async fn spawn_with_files(config: Value, files: &[(&str, &str)]) -> (TestDaemon, tempfile::TempDir) {
+    let home = tempfile::TempDir::new().unwrap();
+    let nexus_dir = home.path().join(".nexus");
+    let mut config = config;
+    config["server"] = json!({ "host": "127.0.0.1", "port": 0 });
+    std::fs::create_dir_all(&nexus_dir).unwrap();
+    std::fs::write(nexus_dir.join("nexus.json"), config.to_string()).unwrap();
+    for (path, content) in files {
+        let path = nexus_dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+    let d = TestDaemon::spawn_at_path(home.path().to_path_buf()).await.unwrap();
+    (d, home)
+}
@@ -94,6 +94,12 @@ impl TestDaemon {
         crate::sse::SseSubscription::connect(format!("{}/api/events", self.base_url))
     }
 
+    /// An SSE client that doesn't read until resumed; see
+    /// [`SseSubscription::connect_paused`](crate::sse::SseSubscription::connect_paused).
+    pub async fn sse_paused(&self) -> (crate::sse::SseSubscription, tokio::sync::oneshot::Sender<()>) {
+        crate::sse::SseSubscription::connect_paused(format!("{}/api/events", self.base_url)).await
+    }
+
     async fn wait_ready(&self, timeout: Duration) -> anyhow::Result<()> {
         let client = reqwest::Client::new();
         let url = format!("{}/api/status", self.base_url);
@@ -2,11 +2,14 @@ pub mod client;
 pub mod fixtures;
 pub mod harness;
 pub mod mock_llm;
+pub mod scripted;
 pub mod sse;
 
 #[cfg(test)]
 mod tests {
+    mod a2a;
     mod agents;
+    mod batches;
     mod browse;
     mod chat;
     mod conversation_paths;
@@ -28,4 +31,5 @@ mod tests {
     mod hooks;
     mod processes;
     mod settings;
+    mod websocket;
 }
@@ -6,7 +6,7 @@
 
 use axum::body::Body;
 use axum::extract::State;
-use axum::http::{Response, StatusCode};
+use axum::http::{HeaderMap, Response, StatusCode};
 use axum::routing::post;
 use axum::Router;
 use std::collections::VecDeque;
@@ -20,6 +20,8 @@ pub struct MockLlmServer {
     pub port: u16,
     /// Captured request bodies (for asserting what the daemon sent).
     pub requests: Arc<Mutex<Vec<serde_json::Value>>>,
+    /// `idempotency-key` header of each captured request.
+    idempotency_keys: Arc<Mutex<Vec<Option<String>>>>,
     _task: JoinHandle<()>,
 }
 
@@ -37,12 +39,26 @@ pub enum MockResponse {
 struct MockState {
     responses: Arc<Mutex<VecDeque<MockResponse>>>,
     requests: Arc<Mutex<Vec<serde_json::Value>>>,
+    idempotency_keys: Arc<Mutex<Vec<Option<String>>>>,
+    answer_titles: bool,
 }
 
 impl MockLlmServer {
     /// Start a mock server with a queue of responses.
     /// Each `POST /v1/messages` pops the next response from the queue.
     pub async fn start(responses: Vec<MockResponse>) -> Self {
+        Self::start_inner(responses, false).await
+    }
+
+    /// Like [`start`](Self::start), but auto-title requests are answered
+    /// with `KEEP` instead of consuming the queue, so multi-turn scripts
+    /// don't race the title generated after each turn. Title requests are
+    /// not captured.
+    pub async fn start_answering_titles(responses: Vec<MockResponse>) -> Self {
+        Self::start_inner(responses, true).await
+    }
+
+    async fn start_inner(responses: Vec<MockResponse>, answer_titles: bool) -> Self {
         // Bind port 0 and convert directly — no drop+rebind race
         let std_listener = TcpListener::bind("127.0.0.1:0").expect("bind for port");
         std_listener.set_nonblocking(true).expect("set nonblocking");
@@ -52,8 +68,11 @@ impl MockLlmServer {
         let state = MockState {
             responses: Arc::new(Mutex::new(VecDeque::from(responses))),
             requests: Arc::new(Mutex::new(Vec::new())),
+            idempotency_keys: Arc::new(Mutex::new(Vec::new())),
+            answer_titles,
         };
         let requests = state.requests.clone();
+        let idempotency_keys = state.idempotency_keys.clone();
 
         let app = Router::new()
             .route("/v1/messages", post(handle_messages))
@@ -70,6 +89,7 @@ impl MockLlmServer {
             url,
             port,
             requests,
+            idempotency_keys,
             _task: task,
         }
     }
@@ -78,15 +98,34 @@ impl MockLlmServer {
     pub fn captured_requests(&self) -> Vec<serde_json::Value> {
         self.requests.lock().unwrap().clone()
     }
+
+    /// `idempotency-key` headers, in the order of `captured_requests`.
+    pub fn captured_idempotency_keys(&self) -> Vec<Option<String>> {
+        self.idempotency_keys.lock().unwrap().clone()
+    }
 }
 
 async fn handle_messages(
     State(state): State<MockState>,
+    headers: HeaderMap,
     body: String,
 ) -> Response<Body> {
     // Capture the request body
     if let Ok(json) = serde_json::from_str::<serde_json::Value>(&body) {
+        if state.answer_titles && is_title_request(&json) {
+            return Response::builder()
+                .status(StatusCode::OK)
+                .header("content-type", "text/event-stream")
+                .body(Body::from(text_response("KEEP")))
+                .unwrap();
+        }
         state.requests.lock().unwrap().push(json);
+        state.idempotency_keys.lock().unwrap().push(
+            headers
+                .get("idempotency-key")
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string),
+        );
     }
 
     let response = state
@@ -124,6 +163,13 @@ async fn handle_messages(
     }
 }
 
+/// The auto-title module's requests carry its fixed system prompt.
+fn is_title_request(body: &serde_json::Value) -> bool {
+    body["system"]
+        .to_string()
+        .contains("Generate a short conversation title")
+}
+
 // ── Response builders ──────────────────────────────────────────────
 
 /// Build an SSE response for a simple text reply.
@@ -150,6 +196,31 @@ pub fn text_response_with_usage(text: &str, input_tokens: u32, output_tokens: u3
     )
 }
 
+/// Build an SSE response for a text reply streamed as many deltas.
+pub fn chunked_text_response(chunk: &str, count: usize) -> String {
+    let escaped = chunk.replace('\\', "\\\\").replace('"', "\\\"");
+    let delta = format!(
+        "event: content_block_delta\n\
+         data: {{\"index\":0,\"delta\":{{\"type\":\"text_delta\",\"text\":\"{escaped}\"}}}}\n\n"
+    );
+    let mut body = String::from(
+        "event: message_start\n\
+         data: {\"message\":{\"id\":\"msg_mock_001\",\"type\":\"message\",\"role\":\"assistant\",\"model\":\"mock-model\",\"usage\":{\"input_tokens\":100,\"output_tokens\":0}}}\n\n\
+         event: content_block_start\n\
+         data: {\"index\":0,\"content_block\":{\"type\":\"text\",\"text\":\"\"}}\n\n",
+    );
+    body.push_str(&delta.repeat(count));
+    body.push_str(
+        "event: content_block_stop\n\
+         data: {\"index\":0}\n\n\
+         event: message_delta\n\
+         data: {\"delta\":{\"stop_reason\":\"end_turn\"},\"usage\":{\"output_tokens\":25}}\n\n\
+         event: message_stop\n\
+         data: {}\n\n",
+    );
+    body
+}
+
 /// Build an SSE response for a tool use call.
 pub fn tool_use_response(tool_name: &str, tool_id: &str, args_json: &str) -> String {
     let escaped_args = args_json.replace('\\', "\\\\").replace('"', "\\\"");
@@ -0,0 +1,180 @@
+//! Scripted multi-turn conversations against a real daemon.
+//!
+//! [`ScriptedConversation`] queues the model's side of every turn on a
+//! [`MockLlmServer`], runs the user prompts one after another through
+//! `/api/chat`, and collects each turn's events into a [`TurnTranscript`].
+//! Tools run for real inside the daemon; the script only decides which tool
+//! calls the model makes.
+//!
+//! ```ignore
+//! let transcripts = ScriptedConversation::new()
+//!     .turn("Say hello from the shell")
+//!     .tool_call("bash", json!({ "description": "Echo", "command": "echo hello" }))
+//!     .reply("The shell said hello.")
+//!     .expect(|t| assert!(t.tool_results[0]["content"].as_str().unwrap().contains("hello")))
+//!     .run()
+//!     .await;
+//! ```
+
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use crate::fixtures::setup_mock_agent;
+use crate::harness::TestDaemon;
+use crate::mock_llm::{self, MockLlmServer, MockResponse};
+
+const TURN_TIMEOUT: Duration = Duration::from_secs(15);
+
+type Check = Box<dyn Fn(&TurnTranscript) + Send>;
+
+/// Everything observed during one turn.
+#[derive(Debug, Default)]
+pub struct TurnTranscript {
+    /// Every event for the conversation, RUN_STARTED through the terminal event.
+    pub events: Vec<Value>,
+    /// Concatenated TEXT_MESSAGE_CONTENT deltas.
+    pub text: String,
+    /// Tool names from TOOL_CALL_START, in order.
+    pub tool_calls: Vec<String>,
+    /// TOOL_CALL_RESULT events, in order.
+    pub tool_results: Vec<Value>,
+    /// The RUN_ERROR message, if the turn failed.
+    pub error: Option<String>,
+}
+
+struct ScriptedTurn {
+    prompt: String,
+    responses: Vec<MockResponse>,
+    tool_calls: Vec<String>,
+    checks: Vec<Check>,
+}
+
+#[derive(Default)]
+pub struct ScriptedConversation {
+    turns: Vec<ScriptedTurn>,
+}
+
+impl ScriptedConversation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a turn with the given user prompt.
+    pub fn turn(mut self, prompt: &str) -> Self {
+        self.turns.push(ScriptedTurn {
+            prompt: prompt.to_string(),
+            responses: Vec::new(),
+            tool_calls: Vec::new(),
+            checks: Vec::new(),
+        });
+        self
+    }
+
+    /// The model calls `tool` with `args` in the current turn. The turn
+    /// fails unless the daemon starts that tool call.
+    pub fn tool_call(mut self, tool: &str, args: Value) -> Self {
+        let n = self.turns.iter().map(|t| t.tool_calls.len()).sum::<usize>();
+        let turn = self.current();
+        turn.responses.push(MockResponse::Sse(mock_llm::tool_use_response(
+            tool,
+            &format!("toolu_scripted_{n}"),
+            &args.to_string(),
+        )));
+        turn.tool_calls.push(tool.to_string());
+        self
+    }
+
+    /// The model answers with `text`, ending the current turn.
+    pub fn reply(mut self, text: &str) -> Self {
+        self.current()
+            .responses
+            .push(MockResponse::Sse(mock_llm::text_response(text)));
+        self
+    }
+
+    /// Queue an arbitrary provider response (errors, delays, custom SSE).
+    pub fn respond(mut self, response: MockResponse) -> Self {
+        self.current().responses.push(response);
+        self
+    }
+
+    /// Assert on the current turn's transcript once it finishes.
+    pub fn expect(mut self, check: impl Fn(&TurnTranscript) + Send + 'static) -> Self {
+        self.current().checks.push(Box::new(check));
+        self
+    }
+
+    fn current(&mut self) -> &mut ScriptedTurn {
+        self.turns
+            .last_mut()
+            .expect("call .turn(prompt) before scripting the model")
+    }
+
+    /// Run every turn in one conversation on a fresh daemon and mock
+    /// provider. Panics when a turn does not finish, makes different tool
+    /// calls than scripted, or fails a check.
+    pub async fn run(mut self) -> Vec<TurnTranscript> {
+        let responses = self
+            .turns
+            .iter_mut()
+            .flat_map(|t| std::mem::take(&mut t.responses))
+            .collect();
+        let mock = MockLlmServer::start_answering_titles(responses).await;
+        let d = TestDaemon::spawn().await.unwrap();
+        let client = d.client();
+        let mut sse = d.sse();
+        sse.expect_sync().await;
+        let (_, _, conv_id) = setup_mock_agent(&client, &mock.url).await;
+
+        let mut transcripts = Vec::new();
+        for (i, turn) in self.turns.into_iter().enumerate() {
+            let (status, body) = client
+                .post(
+                    "/api/chat",
+                    &json!({ "conversationId": conv_id, "message": turn.prompt }),
+                )
+                .await;
+            assert!(status.is_success(), "turn {i}: start failed: {body}");
+
+            let transcript = collect_turn(&mut sse, &conv_id, i).await;
+            assert_eq!(
+                transcript.tool_calls, turn.tool_calls,
+                "turn {i}: tool calls differ from the script"
+            );
+            for check in &turn.checks {
+                check(&transcript);
+            }
+            transcripts.push(transcript);
+        }
+        transcripts
+    }
+}
+
+async fn collect_turn(
+    sse: &mut crate::sse::SseSubscription,
+    conv_id: &str,
+    turn: usize,
+) -> TurnTranscript {
+    let mut t = TurnTranscript::default();
+    loop {
+        let event = sse
+            .next_matching(|e| e["threadId"] == conv_id && e.get("runId").is_some(), TURN_TIMEOUT)
+            .await
+            .unwrap_or_else(|| panic!("turn {turn}: no RUN_FINISHED within {TURN_TIMEOUT:?}"));
+        match event["type"].as_str().unwrap_or_default() {
+            "TEXT_MESSAGE_CONTENT" => t.text.push_str(event["delta"].as_str().unwrap_or_default()),
+            "TOOL_CALL_START" => t
+                .tool_calls
+                .push(event["toolCallName"].as_str().unwrap_or_default().to_string()),
+            "TOOL_CALL_RESULT" => t.tool_results.push(event.clone()),
+            "RUN_ERROR" => t.error = event["message"].as_str().map(str::to_string),
+            _ => {}
+        }
+        let done = matches!(event["type"].as_str(), Some("RUN_FINISHED" | "RUN_ERROR"));
+        t.events.push(event);
+        if done {
+            return t;
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use futures::StreamExt;
 use serde_json::Value;
 use std::time::Duration;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
 
 /// An open SSE connection to `/api/events`.
 ///
@@ -16,43 +16,33 @@ impl SseSubscription {
         let (tx, rx) = mpsc::unbounded_channel();
 
         let task = tokio::spawn(async move {
-            let client = reqwest::Client::new();
-            let resp = match client
-                .get(&url)
-                .header("Accept", "text/event-stream")
-                .send()
-                .await
-            {
+            let resp = match open(&url).await {
                 Ok(r) => r,
                 Err(e) => {
                     eprintln!("SSE connection failed: {e}");
                     return;
                 }
             };
+            read_events(resp, tx).await;
+        });
 
-            let mut stream = resp.bytes_stream();
-            let mut buf = String::new();
-
-            while let Some(chunk) = stream.next().await {
-                let Ok(bytes) = chunk else { break };
-                buf.push_str(&String::from_utf8_lossy(&bytes));
+        Self { rx, _task: task }
+    }
 
-                while let Some(end) = buf.find("\n\n") {
-                    let event_str = buf[..end].to_string();
-                    buf = buf[end + 2..].to_string();
+    /// Connect, but leave the stream unread until the returned sender fires
+    /// (or is dropped). The daemon's subscriber for this client falls behind
+    /// in the meantime, once the socket buffers fill up.
+    pub async fn connect_paused(url: String) -> (Self, oneshot::Sender<()>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let (resume_tx, resume_rx) = oneshot::channel();
 
-                    for line in event_str.lines() {
-                        if let Some(data) = line.strip_prefix("data: ") {
-                            if let Ok(val) = serde_json::from_str::<Value>(data) {
-                                let _ = tx.send(val);
-                            }
-                        }
-                    }
-                }
-            }
+        let resp = open(&url).await.expect("SSE connection failed");
+        let task = tokio::spawn(async move {
+            let _ = resume_rx.await;
+            read_events(resp, tx).await;
         });
 
-        Self { rx, _task: task }
+        (Self { rx, _task: task }, resume_tx)
     }
 
     /// Wait for an event matching `predicate`, up to `timeout`.
@@ -135,3 +125,35 @@ impl SseSubscription {
         }
     }
 }
+
+async fn open(url: &str) -> reqwest::Result<reqwest::Response> {
+    reqwest::Client::new()
+        .get(url)
+        .header("Accept", "text/event-stream")
+        .send()
+        .await
+}
+
+/// Parse `data: <json>` lines from the stream into `tx` until it ends.
+async fn read_events(resp: reqwest::Response, tx: mpsc::UnboundedSender<Value>) {
+    let mut stream = resp.bytes_stream();
+    let mut buf = String::new();
+
+    while let Some(chunk) = stream.next().await {
+        let Ok(bytes) = chunk else { break };
+        buf.push_str(&String::from_utf8_lossy(&bytes));
+
+        while let Some(end) = buf.find("\n\n") {
+            let event_str = buf[..end].to_string();
+            buf = buf[end + 2..].to_string();
+
+            for line in event_str.lines() {
+                if let Some(data) = line.strip_prefix("data: ") {
+                    if let Ok(val) = serde_json::from_str::<Value>(data) {
+                        let _ = tx.send(val);
+                    }
+                }
+            }
+        }
+    }
+}
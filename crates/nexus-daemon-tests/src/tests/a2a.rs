@@ -0,0 +1,152 @@
+use serde_json::{json, Value};
+
+use crate::fixtures::{setup_mock_agent, spawn_with_config};
+use crate::harness::TestDaemon;
+use crate::mock_llm::{self, MockLlmServer, MockResponse};
+
+fn send_message(id: u64, text: &str, context_id: Option<&str>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": "message/send",
+        "params": {
+            "message": {
+                "kind": "message",
+                "role": "user",
+                "messageId": format!("msg-{id}"),
+                "contextId": context_id,
+                "parts": [{ "kind": "text", "text": text }],
+            },
+        },
+    })
+}
+
+#[tokio::test]
+async fn a2a_agent_card_is_served() {
+    let d = TestDaemon::spawn().await.unwrap();
+    let (status, card) = d.client().get("/.well-known/agent.json").await;
+    assert!(status.is_success());
+    assert_eq!(card["url"], format!("http://127.0.0.1:{}/a2a", d.port));
+    assert_eq!(card["capabilities"]["streaming"], true);
+    assert_eq!(card["skills"][0]["id"], "chat");
+}
+
+#[tokio::test]
+async fn a2a_message_send_runs_task_to_completion() {
+    let mock = MockLlmServer::start_answering_titles(vec![
+        MockResponse::Sse(mock_llm::text_response("Hello, peer")),
+        MockResponse::Sse(mock_llm::text_response("Still here")),
+    ])
+    .await;
+    let d = TestDaemon::spawn().await.unwrap();
+    let client = d.client();
+    setup_mock_agent(&client, &mock.url).await;
+
+    let (status, resp) = client.post("/a2a", &send_message(1, "hi", None)).await;
+    assert!(status.is_success());
+    assert_eq!(resp["id"], 1);
+    let task = &resp["result"];
+    assert_eq!(task["kind"], "task");
+    assert_eq!(task["status"]["state"], "completed");
+    assert_eq!(task["artifacts"][0]["parts"][0]["text"], "Hello, peer");
+    let task_id = task["id"].as_str().unwrap();
+    let context_id = task["contextId"].as_str().unwrap();
+
+    // The context is a regular conversation holding the exchange.
+    let (_, conv) = client.get(&format!("/api/conversations/{context_id}")).await;
+    assert_eq!(conv["messages"].as_array().unwrap().len(), 2);
+
+    // A follow-up in the same context continues that conversation.
+    let (_, resp) = client.post("/a2a", &send_message(2, "again", Some(context_id))).await;
+    assert_eq!(resp["result"]["contextId"], context_id);
+    assert_eq!(resp["result"]["artifacts"][0]["parts"][0]["text"], "Still here");
+
+    let get = json!({ "jsonrpc": "2.0", "id": 3, "method": "tasks/get", "params": { "id": task_id } });
+    let (_, resp) = client.post("/a2a", &get).await;
+    assert_eq!(resp["result"]["status"]["state"], "completed");
+
+    let cancel = json!({ "jsonrpc": "2.0", "id": 4, "method": "tasks/cancel", "params": { "id": task_id } });
+    let (_, resp) = client.post("/a2a", &cancel).await;
+    assert_eq!(resp["error"]["code"], -32002);
+}
+
+#[tokio::test]
+async fn a2a_rejects_unknown_methods_and_tasks() {
+    let d = TestDaemon::spawn().await.unwrap();
+    let client = d.client();
+
+    let (_, resp) = client
+        .post("/a2a", &json!({ "jsonrpc": "2.0", "id": 1, "method": "tasks/nope" }))
+        .await;
+    assert_eq!(resp["error"]["code"], -32601);
+
+    let get = json!({ "jsonrpc": "2.0", "id": 2, "method": "tasks/get", "params": { "id": "missing" } });
+    let (_, resp) = client.post("/a2a", &get).await;
+    assert_eq!(resp["error"]["code"], -32001);
+
+    let (_, resp) = client.post("/a2a", &send_message(3, "hi", Some("no-such-context"))).await;
+    assert_eq!(resp["error"]["code"], -32602);
+}
+
+#[tokio::test]
+async fn a2a_message_stream_sends_status_and_artifact_updates() {
+    let mock = MockLlmServer::start_answering_titles(vec![MockResponse::Sse(
+        mock_llm::text_response("Streamed reply"),
+    )])
+    .await;
+    let d = TestDaemon::spawn().await.unwrap();
+    setup_mock_agent(&d.client(), &mock.url).await;
+
+    let mut req = send_message(1, "hi", None);
+    req["method"] = json!("message/stream");
+    let body = reqwest::Client::new()
+        .post(format!("{}/a2a", d.base_url))
+        .json(&req)
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+
+    let results: Vec<Value> = body
+        .lines()
+        .filter_map(|l| l.strip_prefix("data:"))
+        .map(|data| serde_json::from_str::<Value>(data.trim()).unwrap()["result"].clone())
+        .collect();
+    assert_eq!(results[0]["kind"], "task");
+    assert_eq!(results[0]["status"]["state"], "submitted");
+
+    let text: String = results
+        .iter()
+        .filter(|r| r["kind"] == "artifact-update")
+        .filter_map(|r| r["artifact"]["parts"][0]["text"].as_str())
+        .collect();
+    assert_eq!(text, "Streamed reply");
+
+    let last = results.last().unwrap();
+    assert_eq!(last["kind"], "status-update");
+    assert_eq!(last["final"], true);
+    assert_eq!(last["status"]["state"], "completed");
+}
+
+#[tokio::test]
+async fn a2a_task_fails_when_run_ends_without_starting() {
+    // A prompt blocked by moderation ends its turn before RUN_STARTED; the
+    // tracker must notice from turn state rather than wait for the event.
+    let mock = MockLlmServer::start_answering_titles(vec![]).await;
+    let (d, _home) = spawn_with_config(json!({
+        "moderation": { "rules": [{ "pattern": "(?i)drop table", "reason": "sql" }] },
+    }))
+    .await;
+    let client = d.client();
+    setup_mock_agent(&client, &mock.url).await;
+
+    let request = send_message(1, "DROP TABLE users", None);
+    let send = client.post("/a2a", &request);
+    let (status, resp) = tokio::time::timeout(std::time::Duration::from_secs(10), send)
+        .await
+        .expect("blocking message/send should return");
+    assert!(status.is_success());
+    assert_eq!(resp["result"]["status"]["state"], "failed", "{resp}");
+}
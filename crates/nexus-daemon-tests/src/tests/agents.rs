@@ -128,3 +128,71 @@ async fn list_includes_created() {
     let (_, body) = c.get("/api/agents").await;
     assert_eq!(body.as_array().unwrap().len(), 2);
 }
+
+#[tokio::test]
+async fn agent_files_are_loaded_and_read_only() {
+    let (d, home) = fixtures::spawn_with_files(
+        json!({ "providers": [{ "id": "prov-1", "name": "Anthropic", "type": "anthropic" }] }),
+        &[(
+            "agents/reviewer.toml",
+            "provider = \"Anthropic\"\nmodel = \"claude-test\"\nsystem_prompt = \"Review.\"\nthinking_budget = 2000\n",
+        )],
+    )
+    .await;
+    let c = d.client();
+
+    let (status, agent) = c.get("/api/agents/reviewer").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(agent["provider_id"], "prov-1");
+    assert_eq!(agent["thinking_budget"], 2000);
+    assert!(agent["source"].as_str().unwrap().ends_with("reviewer.toml"));
+
+    let (status, _) = c.put("/api/agents/reviewer", &json!({ "name": "Renamed" })).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (status, _) = c.delete("/api/agents/reviewer").await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    // Saving the store (here via set-active) must not copy the file agent into nexus.json
+    let (status, _) = c.put("/api/agents/active", &json!({ "agent_id": "reviewer" })).await;
+    assert_eq!(status, StatusCode::OK);
+    let saved: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(home.path().join(".nexus/nexus.json")).unwrap()).unwrap();
+    assert_eq!(saved["agents"], json!([]));
+    assert_eq!(saved["active_agent_id"], "reviewer");
+}
+
+#[tokio::test]
+async fn create_agent_from_profile() {
+    let d = TestDaemon::spawn().await.unwrap();
+    let c = d.client();
+    let provider_id = create_provider(&c).await;
+
+    let (status, profiles) = c.get("/api/agents/profiles").await;
+    assert_eq!(status, StatusCode::OK);
+    let ids: Vec<&str> = profiles
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, ["coding", "research", "extraction"]);
+
+    let (status, agent) = c
+        .post(
+            "/api/agents/profiles/coding",
+            &json!({ "provider_id": provider_id, "model": "claude-test" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(agent["name"], "Coding Agent");
+    assert_eq!(agent["thinking_budget"], 8000);
+    assert!(agent["system_prompt"].as_str().unwrap().contains("software engineer"));
+
+    let (status, _) = c
+        .post(
+            "/api/agents/profiles/nope",
+            &json!({ "provider_id": provider_id, "model": "claude-test" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
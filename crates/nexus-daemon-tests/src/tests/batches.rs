@@ -0,0 +1,93 @@
+use serde_json::{json, Value};
+
+use crate::fixtures::setup_mock_agent;
+use crate::harness::TestDaemon;
+use crate::mock_llm::{self, MockLlmServer, MockResponse};
+
+async fn wait_for_batch(client: &crate::client::DaemonClient, id: &str) -> Value {
+    for _ in 0..100 {
+        let (status, batch) = client.get(&format!("/api/batches/{id}")).await;
+        assert!(status.is_success());
+        if batch["status"] != "running" {
+            return batch;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+    panic!("batch {id} did not finish");
+}
+
+#[tokio::test]
+async fn batch_runs_each_input_as_a_turn() {
+    let mock = MockLlmServer::start_answering_titles(vec![
+        MockResponse::Sse(mock_llm::text_response("spam")),
+        MockResponse::Sse(mock_llm::text_response("billing")),
+    ])
+    .await;
+    let d = TestDaemon::spawn().await.unwrap();
+    let client = d.client();
+    setup_mock_agent(&client, &mock.url).await;
+
+    let (status, batch) = client
+        .post(
+            "/api/batches",
+            &json!({ "inputs": ["Win a prize", "Refund please"], "concurrency": 1 }),
+        )
+        .await;
+    assert_eq!(status.as_u16(), 201);
+    assert_eq!(batch["progress"]["total"], 2);
+
+    let batch = wait_for_batch(&client, batch["id"].as_str().unwrap()).await;
+    assert_eq!(batch["status"], "completed");
+    assert_eq!(batch["progress"]["completed"], 2);
+    let items = batch["items"].as_array().unwrap();
+    assert_eq!(items[0]["output"], "spam");
+    assert_eq!(items[1]["output"], "billing");
+
+    // Each input ran in its own stored conversation.
+    let conv_id = items[1]["conversationId"].as_str().unwrap();
+    let (_, conv) = client.get(&format!("/api/conversations/{conv_id}")).await;
+    assert_eq!(conv["messages"][0]["parts"][0]["text"], "Refund please");
+}
+
+#[tokio::test]
+async fn batch_rejects_empty_inputs_and_unknown_ids() {
+    let d = TestDaemon::spawn().await.unwrap();
+    let client = d.client();
+
+    let (status, _) = client.post("/api/batches", &json!({ "inputs": [] })).await;
+    assert_eq!(status.as_u16(), 400);
+    let (status, _) = client.get("/api/batches/missing").await;
+    assert_eq!(status.as_u16(), 404);
+    let (status, _) = client.post("/api/batches/missing/cancel", &json!({})).await;
+    assert_eq!(status.as_u16(), 404);
+}
+
+#[tokio::test]
+async fn batch_reads_replies_from_the_stored_conversation() {
+    let mock = MockLlmServer::start_answering_titles(vec![
+        MockResponse::Sse(mock_llm::chunked_text_response("ab", 500)),
+        MockResponse::Error {
+            status: 400,
+            body: json!({
+                "type": "error",
+                "error": { "type": "invalid_request_error", "message": "bad input" },
+            })
+            .to_string(),
+        },
+    ])
+    .await;
+    let d = TestDaemon::spawn().await.unwrap();
+    let client = d.client();
+    setup_mock_agent(&client, &mock.url).await;
+
+    let (_, batch) = client
+        .post("/api/batches", &json!({ "inputs": ["Long", "Broken"], "concurrency": 1 }))
+        .await;
+    let batch = wait_for_batch(&client, batch["id"].as_str().unwrap()).await;
+
+    let items = batch["items"].as_array().unwrap();
+    assert_eq!(items[0]["status"], "completed");
+    assert_eq!(items[0]["output"], "ab".repeat(500));
+    assert_eq!(items[1]["status"], "failed", "{batch}");
+    assert!(items[1]["error"].as_str().unwrap().contains("bad input"), "{batch}");
+}
@@ -149,3 +149,64 @@ async fn switch_into_sealed_span_returns_409() {
         "Switching to a sealed span message should return 409 CONFLICT"
     );
 }
+
+#[tokio::test]
+async fn rollback_to_checkpoint_restores_the_tagged_path() {
+    let mock = MockLlmServer::start_answering_titles(vec![
+        MockResponse::Sse(mock_llm::text_response("Plan ready")),
+        MockResponse::Sse(mock_llm::text_response("Went another way")),
+    ])
+    .await;
+
+    let d = TestDaemon::spawn().await.unwrap();
+    let client = d.client();
+    let mut sse = d.sse();
+    sse.expect_sync().await;
+
+    let (_, _, conv_id) = setup_mock_agent(&client, &mock.url).await;
+
+    // A turn_end hook tags the conversation after the first turn.
+    client
+        .post("/api/debug/hooks/checkpoint", &json!({ "tag": "plan approved" }))
+        .await;
+    client
+        .post("/api/chat", &json!({ "conversationId": &conv_id, "message": "Plan it" }))
+        .await;
+    sse.expect_event_type("RUN_FINISHED", Duration::from_secs(10))
+        .await;
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let (_, conv) = client.get(&format!("/api/conversations/{conv_id}")).await;
+    let tagged_path = conv["active_path"].clone();
+
+    client.post_empty("/api/debug/hooks/clear").await;
+    client
+        .post("/api/chat", &json!({ "conversationId": &conv_id, "message": "Do something else" }))
+        .await;
+    sse.expect_event_type("RUN_FINISHED", Duration::from_secs(10))
+        .await;
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let (_, checkpoints) = client
+        .get(&format!("/api/conversations/{conv_id}/checkpoints"))
+        .await;
+    assert_eq!(checkpoints.as_array().unwrap().len(), 1, "{checkpoints}");
+    assert_eq!(checkpoints[0]["tag"], "plan approved");
+    assert_eq!(checkpoints[0]["message_id"], tagged_path.as_array().unwrap().last().unwrap().clone());
+
+    let (status, conv) = client
+        .post_empty(&format!("/api/conversations/{conv_id}/checkpoints/plan%20approved/rollback"))
+        .await;
+    assert_eq!(status.as_u16(), 200, "{conv}");
+    assert_eq!(conv["active_path"], tagged_path);
+    let (_, conv) = client.get(&format!("/api/conversations/{conv_id}")).await;
+    assert_eq!(conv["active_path"], tagged_path);
+
+    let (status, _) = client
+        .post(&format!("/api/conversations/{conv_id}/checkpoints"), &json!({ "tag": "manual" }))
+        .await;
+    assert_eq!(status.as_u16(), 201);
+    let (status, _) = client
+        .post_empty(&format!("/api/conversations/{conv_id}/checkpoints/unknown/rollback"))
+        .await;
+    assert_eq!(status.as_u16(), 404);
+}
@@ -2,7 +2,9 @@ use std::time::Duration;
 
 use serde_json::json;
 
+use crate::fixtures::setup_mock_agent;
 use crate::harness::TestDaemon;
+use crate::mock_llm::{self, MockLlmServer, MockResponse};
 
 #[tokio::test]
 async fn task_state_preset_emits_event() {
@@ -100,3 +102,42 @@ async fn force_compact_seals_span() {
         "Empty conversation shouldn't compact"
     );
 }
+
+#[tokio::test]
+async fn force_compact_event_carries_summary_and_trigger() {
+    let mock = MockLlmServer::start_answering_titles(vec![MockResponse::Sse(
+        mock_llm::text_response("Hello"),
+    )])
+    .await;
+    let d = TestDaemon::spawn().await.unwrap();
+    let client = d.client();
+    let mut sse = d.sse();
+    sse.expect_sync().await;
+
+    let (_, _, conv_id) = setup_mock_agent(&client, &mock.url).await;
+    client
+        .post("/api/chat", &json!({ "conversationId": &conv_id, "message": "hi" }))
+        .await;
+    sse.expect_event_type("RUN_FINISHED", Duration::from_secs(10))
+        .await;
+
+    let (status, body) = client
+        .post(
+            &format!("/api/debug/compact/{conv_id}"),
+            &json!({ "keep_recent": 0 }),
+        )
+        .await;
+    assert_eq!(status.as_u16(), 200);
+    assert_eq!(body["compacted"].as_bool(), Some(true));
+
+    let event = sse.expect_custom("compaction", Duration::from_secs(5)).await;
+    let value = &event["value"];
+    assert_eq!(value["consumed_count"].as_u64(), Some(2));
+    assert_eq!(value["compaction_number"].as_u64(), Some(1));
+    assert_eq!(value["full"].as_bool(), Some(true));
+    assert_eq!(value["trigger"].as_str(), Some("manual"));
+    assert!(value["summary"]
+        .as_str()
+        .unwrap()
+        .contains("2 messages summarized"));
+}
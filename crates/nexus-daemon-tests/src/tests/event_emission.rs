@@ -87,6 +87,26 @@ async fn rename_conversation_emits_title_update() {
     assert!(event.is_some(), "Expected 'title_update' CUSTOM event");
 }
 
+#[tokio::test]
+async fn purge_conversations_emits_threads_purged() {
+    let d = TestDaemon::spawn().await.unwrap();
+    let c = d.client();
+    let mut sse = d.sse();
+    sse.expect_sync().await;
+
+    c.post_empty("/api/conversations").await;
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+
+    c.post("/api/conversations/purge", &json!({ "older_than_secs": 1 }))
+        .await;
+
+    let event = sse
+        .next_matching(|e| is_custom(e, "threads_purged"), Duration::from_secs(3))
+        .await
+        .expect("Expected 'threads_purged' CUSTOM event");
+    assert_eq!(event["value"]["count"], 1);
+}
+
 // ── Agent events ──
 
 #[tokio::test]
@@ -521,17 +541,14 @@ async fn no_data_prefixed_events() {
 
     // Collect all CUSTOM events for 2 seconds
     let mut events = Vec::new();
-    loop {
-        match sse
-            .next_matching(
-                |e| e.get("type").and_then(|t| t.as_str()) == Some("CUSTOM"),
-                Duration::from_secs(2),
-            )
-            .await
-        {
-            Some(e) => events.push(e),
-            None => break,
-        }
+    while let Some(e) = sse
+        .next_matching(
+            |e| e.get("type").and_then(|t| t.as_str()) == Some("CUSTOM"),
+            Duration::from_secs(2),
+        )
+        .await
+    {
+        events.push(e);
     }
 
     // None should have "data:" prefix
@@ -544,3 +561,500 @@ async fn no_data_prefixed_events() {
     }
     assert!(!events.is_empty(), "Expected at least some CUSTOM events");
 }
+
+// ── Usage events ──
+
+#[tokio::test]
+async fn inference_call_emits_inference_usage() {
+    use crate::mock_llm::{self, MockLlmServer, MockResponse};
+
+    let mock = MockLlmServer::start(vec![MockResponse::Sse(
+        mock_llm::text_response_with_usage("usage", 150, 42),
+    )])
+    .await;
+
+    let d = TestDaemon::spawn().await.unwrap();
+    let c = d.client();
+    let mut sse = d.sse();
+    sse.expect_sync().await;
+
+    let (_, _, conv_id) = fixtures::setup_mock_agent(&c, &mock.url).await;
+    c.post(
+        "/api/chat",
+        &json!({ "conversationId": conv_id, "message": "Hi" }),
+    )
+    .await;
+
+    let event = sse
+        .expect_custom("inference_usage", Duration::from_secs(10))
+        .await;
+    assert_eq!(event["threadId"], conv_id);
+    let value = &event["value"];
+    assert_eq!(value["source"], "turn");
+    assert_eq!(value["round"], 0);
+    assert_eq!(value["inputTokens"], 150);
+    assert_eq!(value["outputTokens"], 42);
+}
+
+// ── Warning events ──
+
+#[tokio::test]
+async fn failed_tool_call_emits_agent_warning() {
+    use crate::mock_llm::{self, MockLlmServer, MockResponse};
+
+    let mock = MockLlmServer::start(vec![
+        MockResponse::Sse(mock_llm::tool_use_response(
+            "no_such_tool",
+            "toolu_warn_001",
+            r#"{"description":"Call a tool that does not exist"}"#,
+        )),
+        MockResponse::Sse(mock_llm::text_response("Done")),
+    ])
+    .await;
+
+    let d = TestDaemon::spawn().await.unwrap();
+    let c = d.client();
+    let mut sse = d.sse();
+    sse.expect_sync().await;
+
+    let (_, _, conv_id) = fixtures::setup_mock_agent(&c, &mock.url).await;
+    c.post(
+        "/api/chat",
+        &json!({ "conversationId": conv_id, "message": "Try it" }),
+    )
+    .await;
+
+    let event = sse
+        .expect_custom("agent_warning", Duration::from_secs(10))
+        .await;
+    assert_eq!(event["threadId"], conv_id);
+    assert_eq!(event["value"]["kind"], "tool_error");
+    assert_eq!(event["value"]["details"]["toolName"], "no_such_tool");
+    assert_eq!(event["value"]["details"]["toolCallId"], "toolu_warn_001");
+}
+
+#[tokio::test]
+async fn malformed_tool_args_are_repaired_with_event() {
+    use crate::mock_llm::{self, MockLlmServer, MockResponse};
+
+    let mock = MockLlmServer::start(vec![
+        MockResponse::Sse(mock_llm::tool_use_response(
+            "no_such_tool",
+            "toolu_fix_001",
+            "{'description': 'Sloppy JSON', 'items': [1, 2,],}",
+        )),
+        MockResponse::Sse(mock_llm::text_response("Done")),
+    ])
+    .await;
+
+    let d = TestDaemon::spawn().await.unwrap();
+    let c = d.client();
+    let mut sse = d.sse();
+    sse.expect_sync().await;
+
+    let (_, _, conv_id) = fixtures::setup_mock_agent(&c, &mock.url).await;
+    c.post(
+        "/api/chat",
+        &json!({ "conversationId": conv_id, "message": "Try it" }),
+    )
+    .await;
+
+    let event = sse
+        .expect_custom("tool_args_repaired", Duration::from_secs(10))
+        .await;
+    assert_eq!(event["threadId"], conv_id);
+    assert_eq!(event["value"]["toolCallId"], "toolu_fix_001");
+    assert_eq!(event["value"]["toolName"], "no_such_tool");
+    assert_eq!(event["value"]["repairs"], json!(["single_quotes", "trailing_comma"]));
+
+    // The next round replays the repaired arguments, not `{}`.
+    sse.expect_event_type("RUN_FINISHED", Duration::from_secs(10)).await;
+    let sent = mock.captured_requests()[1]["messages"].to_string();
+    assert!(sent.contains(r#""input":{"description":"Sloppy JSON","items":[1,2]}"#), "{sent}");
+}
+
+#[tokio::test]
+async fn refusal_stop_reason_finishes_run_with_warning() {
+    use crate::mock_llm::{self, MockLlmServer, MockResponse};
+
+    let refusal = mock_llm::text_response("I can't help with that").replace("end_turn", "refusal");
+    let mock = MockLlmServer::start(vec![MockResponse::Sse(refusal)]).await;
+
+    let d = TestDaemon::spawn().await.unwrap();
+    let c = d.client();
+    let mut sse = d.sse();
+    sse.expect_sync().await;
+
+    let (_, _, conv_id) = fixtures::setup_mock_agent(&c, &mock.url).await;
+    c.post(
+        "/api/chat",
+        &json!({ "conversationId": conv_id, "message": "Do something bad" }),
+    )
+    .await;
+
+    let event = sse
+        .expect_custom("agent_warning", Duration::from_secs(10))
+        .await;
+    assert_eq!(event["value"]["kind"], "refusal");
+    assert_eq!(event["value"]["details"]["stopReason"], "refusal");
+
+    // Finishes normally rather than erroring
+    sse.expect_event_type("RUN_FINISHED", Duration::from_secs(10))
+        .await;
+}
+
+#[tokio::test]
+async fn pause_turn_resumes_with_the_partial_response() {
+    use crate::mock_llm::{self, MockLlmServer, MockResponse};
+
+    let paused = "event: message_start\n\
+         data: {\"message\":{\"id\":\"msg_paused\",\"type\":\"message\",\"role\":\"assistant\",\"model\":\"mock-model\",\"usage\":{\"input_tokens\":50,\"output_tokens\":0}}}\n\n\
+         event: content_block_start\n\
+         data: {\"index\":0,\"content_block\":{\"type\":\"server_tool_use\",\"id\":\"srvtoolu_1\",\"name\":\"web_search\",\"input\":{}}}\n\n\
+         event: content_block_delta\n\
+         data: {\"index\":0,\"delta\":{\"type\":\"input_json_delta\",\"partial_json\":\"{\\\"query\\\":\\\"rust\\\"}\"}}\n\n\
+         event: content_block_stop\n\
+         data: {\"index\":0}\n\n\
+         event: content_block_start\n\
+         data: {\"index\":1,\"content_block\":{\"type\":\"web_search_tool_result\",\"tool_use_id\":\"srvtoolu_1\",\"content\":[{\"type\":\"web_search_result\",\"url\":\"https://example.com\",\"title\":\"Example\"}]}}\n\n\
+         event: content_block_stop\n\
+         data: {\"index\":1}\n\n\
+         event: message_delta\n\
+         data: {\"delta\":{\"stop_reason\":\"pause_turn\"},\"usage\":{\"output_tokens\":10}}\n\n\
+         event: message_stop\n\
+         data: {}\n\n"
+        .to_string();
+    let mock = MockLlmServer::start_answering_titles(vec![
+        MockResponse::Sse(paused),
+        MockResponse::Sse(mock_llm::text_response("Rust is a language")),
+    ])
+    .await;
+
+    let d = TestDaemon::spawn().await.unwrap();
+    let c = d.client();
+    let mut sse = d.sse();
+    sse.expect_sync().await;
+
+    let (_, _, conv_id) = fixtures::setup_mock_agent(&c, &mock.url).await;
+    c.post(
+        "/api/chat",
+        &json!({ "conversationId": conv_id, "message": "Search for rust" }),
+    )
+    .await;
+
+    // No unusual-stop warning: the turn carries on to the final answer.
+    let next = sse
+        .next_matching(
+            |e| e["name"] == "agent_warning" || e["type"] == "RUN_FINISHED",
+            Duration::from_secs(10),
+        )
+        .await
+        .unwrap();
+    assert_eq!(next["type"], "RUN_FINISHED", "{next}");
+
+    let requests = mock.captured_requests();
+    assert_eq!(requests.len(), 2);
+    // The paused response goes back as the last message, server blocks and all.
+    let last = requests[1]["messages"].as_array().unwrap().last().unwrap().clone();
+    assert_eq!(last["role"], "assistant");
+    let types: Vec<&str> = last["content"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|b| b["type"].as_str())
+        .collect();
+    assert_eq!(types, ["server_tool_use", "web_search_tool_result"]);
+    assert_eq!(last["content"][0]["input"]["query"], "rust");
+}
+
+// ── Moderation events ──
+
+/// Spawn a daemon whose nexus.json carries the given `moderation` block.
+async fn spawn_with_moderation(moderation: Value) -> (TestDaemon, tempfile::TempDir) {
+    fixtures::spawn_with_config(json!({ "moderation": moderation })).await
+}
+
+#[tokio::test]
+async fn turn_summary_follows_each_turn() {
+    use crate::mock_llm::{self, MockLlmServer, MockResponse};
+
+    let mock = MockLlmServer::start_answering_titles(vec![
+        MockResponse::Sse(mock_llm::tool_use_response(
+            "no_such_tool",
+            "toolu_sum_001",
+            r#"{"description":"Look around"}"#,
+        )),
+        MockResponse::Sse(mock_llm::text_response("All done here.")),
+    ])
+    .await;
+
+    let (d, _home) = fixtures::spawn_with_config(json!({ "conversations": { "turn_summary": "heuristic" } })).await;
+    let c = d.client();
+    let mut sse = d.sse();
+    sse.expect_sync().await;
+
+    let (_, _, conv_id) = fixtures::setup_mock_agent(&c, &mock.url).await;
+    c.post(
+        "/api/chat",
+        &json!({ "conversationId": conv_id, "message": "Have a look" }),
+    )
+    .await;
+
+    let event = sse.expect_custom("turn_summary", Duration::from_secs(10)).await;
+    assert_eq!(event["threadId"], conv_id);
+    assert_eq!(event["value"]["turn"], 1);
+    assert_eq!(event["value"]["text"], "All done here. · no_such_tool");
+    assert_eq!(event["value"]["source"], "heuristic");
+}
+
+#[tokio::test]
+async fn moderation_rewrites_prompt_and_withholds_output() {
+    use crate::mock_llm::{self, MockLlmServer, MockResponse};
+
+    let mock = MockLlmServer::start(vec![MockResponse::Sse(mock_llm::text_response(
+        "The launch code is TOP SECRET",
+    ))])
+    .await;
+    let (d, _home) = spawn_with_moderation(json!({
+        "rules": [
+            { "pattern": r"\b\d{3}-\d{2}-\d{4}\b", "action": "rewrite", "reason": "ssn" },
+            { "pattern": "(?i)top secret", "action": "block", "stages": ["output"], "reason": "classified" },
+        ],
+    }))
+    .await;
+    let c = d.client();
+    let mut sse = d.sse();
+    sse.expect_sync().await;
+
+    let (_, _, conv_id) = fixtures::setup_mock_agent(&c, &mock.url).await;
+    c.post(
+        "/api/chat",
+        &json!({ "conversationId": conv_id, "message": "My SSN is 123-45-6789" }),
+    )
+    .await;
+
+    let input = sse.expect_custom("moderated", Duration::from_secs(10)).await;
+    assert_eq!(input["threadId"], conv_id);
+    assert_eq!(input["value"]["stage"], "input");
+    assert_eq!(input["value"]["action"], "rewrite");
+    assert_eq!(input["value"]["moderator"], "keyword");
+    assert_eq!(input["value"]["reason"], "ssn");
+
+    // The reply is held back until moderated: no text streams before the
+    // output verdict, and what streams after it is the notice.
+    let output = sse
+        .next_matching(
+            |e| e["type"] == "TEXT_MESSAGE_CONTENT" || e["name"] == "moderated",
+            Duration::from_secs(10),
+        )
+        .await
+        .unwrap();
+    assert_eq!(output["name"], "moderated", "text streamed before moderation: {output}");
+    assert_eq!(output["value"]["stage"], "output");
+    assert_eq!(output["value"]["action"], "block");
+    assert_eq!(
+        output["value"]["text"],
+        "[Response withheld by moderation: classified]"
+    );
+    let streamed = sse
+        .expect_event_type("TEXT_MESSAGE_CONTENT", Duration::from_secs(10))
+        .await;
+    assert_eq!(streamed["delta"], "[Response withheld by moderation: classified]");
+    sse.expect_event_type("RUN_FINISHED", Duration::from_secs(10))
+        .await;
+
+    // The model only ever saw the rewritten prompt.
+    let sent = mock.captured_requests()[0]["messages"].to_string();
+    assert!(sent.contains("My SSN is [redacted]"), "{sent}");
+    assert!(!sent.contains("6789"));
+
+    // Both the stored prompt and the stored reply are the moderated text.
+    let (_, conv) = c.get(&format!("/api/conversations/{conv_id}")).await;
+    let texts: Vec<&str> = conv["messages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|m| m["parts"][0]["text"].as_str())
+        .collect();
+    assert_eq!(
+        texts,
+        ["My SSN is [redacted]", "[Response withheld by moderation: classified]"]
+    );
+}
+
+#[tokio::test]
+async fn moderation_blocks_prompt_before_inference() {
+    use crate::mock_llm::{self, MockLlmServer, MockResponse};
+
+    let mock = MockLlmServer::start_answering_titles(vec![MockResponse::Sse(
+        mock_llm::text_response("Hello"),
+    )])
+    .await;
+    let (d, _home) = spawn_with_moderation(json!({
+        "rules": [{ "pattern": "(?i)drop table", "reason": "sql" }],
+    }))
+    .await;
+    let c = d.client();
+    let mut sse = d.sse();
+    sse.expect_sync().await;
+
+    let (_, _, conv_id) = fixtures::setup_mock_agent(&c, &mock.url).await;
+    c.post(
+        "/api/chat",
+        &json!({ "conversationId": conv_id, "message": "please DROP TABLE users" }),
+    )
+    .await;
+
+    let event = sse.expect_custom("moderated", Duration::from_secs(10)).await;
+    assert_eq!(event["value"]["stage"], "input");
+    assert_eq!(event["value"]["action"], "block");
+    let error = sse.expect_event_type("RUN_ERROR", Duration::from_secs(10)).await;
+    assert_eq!(error["message"], "Message blocked by moderation: sql");
+    assert!(mock.captured_requests().is_empty());
+
+    // The blocked prompt must not come back as history on the next turn.
+    c.post(
+        "/api/chat",
+        &json!({ "conversationId": conv_id, "message": "hi again" }),
+    )
+    .await;
+    sse.expect_event_type("RUN_FINISHED", Duration::from_secs(10)).await;
+    let requests = mock.captured_requests();
+    assert_eq!(requests.len(), 1);
+    let sent = requests[0]["messages"].to_string();
+    assert!(sent.contains("hi again"), "{sent}");
+    assert!(!sent.contains("DROP TABLE"), "blocked prompt resent: {sent}");
+}
+
+#[tokio::test]
+async fn guardrail_failure_asks_agent_to_repair() {
+    use crate::mock_llm::{self, MockLlmServer, MockResponse};
+
+    let mock = MockLlmServer::start_answering_titles(vec![
+        MockResponse::Sse(mock_llm::text_response("Sure, her name is Ada.")),
+        MockResponse::Sse(mock_llm::text_response(r#"{"name": "Ada"}"#)),
+    ])
+    .await;
+    let (d, _home) = fixtures::spawn_with_config(json!({
+        "guardrails": {
+            "checks": [{
+                "type": "json_schema",
+                "schema": { "type": "object", "required": ["name"] },
+            }],
+        },
+    }))
+    .await;
+    let c = d.client();
+    let mut sse = d.sse();
+    sse.expect_sync().await;
+
+    let (_, _, conv_id) = fixtures::setup_mock_agent(&c, &mock.url).await;
+    c.post(
+        "/api/chat",
+        &json!({ "conversationId": conv_id, "message": "Who wrote the first program? Answer as JSON." }),
+    )
+    .await;
+
+    let warning = sse.expect_custom("agent_warning", Duration::from_secs(10)).await;
+    assert_eq!(warning["threadId"], conv_id);
+    assert_eq!(warning["value"]["kind"], "guardrail");
+    assert_eq!(warning["value"]["details"]["attempt"], 1);
+    assert_eq!(warning["value"]["details"]["maxRetries"], 2);
+    assert_eq!(warning["value"]["details"]["exhausted"], false);
+    let errors = warning["value"]["details"]["errors"].as_array().unwrap();
+    assert!(errors[0].as_str().unwrap().starts_with("json_schema: response is not valid JSON"));
+
+    sse.expect_event_type("RUN_FINISHED", Duration::from_secs(10)).await;
+    let requests = mock.captured_requests();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[1].to_string().contains("failed validation"));
+}
+
+// ── Background process events ──
+
+#[tokio::test]
+async fn background_bash_emits_started_and_completed() {
+    use crate::mock_llm::{self, MockLlmServer, MockResponse};
+
+    let mock = MockLlmServer::start(vec![
+        MockResponse::Sse(mock_llm::tool_use_response(
+            "bash",
+            "toolu_bg_events",
+            r#"{"description":"Say hi","command":"echo hi","run_in_background":true}"#,
+        )),
+        MockResponse::Sse(mock_llm::text_response("Started")),
+    ])
+    .await;
+    let d = TestDaemon::spawn().await.unwrap();
+    let c = d.client();
+    let mut sse = d.sse();
+    sse.expect_sync().await;
+
+    let (_, _, conv_id) = fixtures::setup_mock_agent(&c, &mock.url).await;
+    c.post(
+        "/api/chat",
+        &json!({ "conversationId": conv_id, "message": "Run it in the background" }),
+    )
+    .await;
+
+    let started = sse
+        .expect_custom("bg_process_started", Duration::from_secs(10))
+        .await;
+    assert_eq!(started["threadId"], conv_id);
+    let process = &started["value"];
+    assert!(process["id"].is_string(), "{process}");
+    assert_eq!(process["conversationId"], conv_id);
+    assert_eq!(process["label"], "Say hi");
+    assert_eq!(process["kind"], "bash");
+    assert_eq!(process["status"], "running");
+    assert!(process["command"].as_str().unwrap().contains("echo hi"), "{process}");
+
+    let completed = sse
+        .next_matching(|e| is_custom(e, "bg_process_completed"), Duration::from_secs(10))
+        .await
+        .expect("Expected 'bg_process_completed' CUSTOM event");
+    assert_eq!(completed["value"]["id"], process["id"]);
+    assert_eq!(completed["value"]["status"], "completed");
+    assert_eq!(completed["value"]["exitCode"], 0);
+}
+
+// ── Stream health events ──
+
+#[tokio::test]
+async fn lagging_client_gets_events_dropped() {
+    use crate::mock_llm::{self, MockLlmServer, MockResponse};
+
+    // Far more delta events than the channel holds plus what the socket
+    // buffers absorb while the client isn't reading.
+    let mock = MockLlmServer::start_answering_titles(vec![MockResponse::Sse(
+        mock_llm::chunked_text_response(&"x".repeat(1024), 12_000),
+    )])
+    .await;
+    let d = TestDaemon::spawn().await.unwrap();
+    let c = d.client();
+    let mut sse = d.sse();
+    sse.expect_sync().await;
+    let (mut slow, resume) = d.sse_paused().await;
+
+    let (_, _, conv_id) = fixtures::setup_mock_agent(&c, &mock.url).await;
+    c.post(
+        "/api/chat",
+        &json!({ "conversationId": conv_id, "message": "Say a lot" }),
+    )
+    .await;
+    sse.expect_event_type("RUN_FINISHED", Duration::from_secs(30))
+        .await;
+
+    resume.send(()).unwrap();
+    let dropped = slow
+        .next_matching(|e| is_custom(e, "events_dropped"), Duration::from_secs(30))
+        .await
+        .expect("Expected 'events_dropped' CUSTOM event for the lagging client");
+    assert!(dropped["value"]["count"].as_u64().unwrap() > 0, "{dropped}");
+
+    // The client that kept up saw no gap.
+    let notice = sse
+        .next_matching(|e| is_custom(e, "events_dropped"), Duration::from_millis(200))
+        .await;
+    assert!(notice.is_none(), "{notice:?}");
+}
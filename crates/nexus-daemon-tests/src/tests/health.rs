@@ -9,6 +9,7 @@ async fn status_returns_ok() {
     let (status, body) = c.get("/api/status").await;
     assert_eq!(status, StatusCode::OK);
     assert_eq!(body["status"], "ok");
+    assert_eq!(body["dropped_events"], 0);
 }
 
 #[tokio::test]
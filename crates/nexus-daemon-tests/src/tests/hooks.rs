@@ -454,3 +454,61 @@ async fn hooks_clear_endpoint_resets_state() {
         "Records should be empty after clear, got: {records:?}"
     );
 }
+
+// ── Test 7: post_compact fires after threshold compaction ──
+
+#[tokio::test]
+async fn post_compact_fires_after_threshold_compaction() {
+    // Five short turns build up history; the sixth prompt is large enough
+    // to cross the summarization threshold, so that turn compacts first.
+    let mut responses: Vec<MockResponse> = (0..5)
+        .map(|i| MockResponse::Sse(mock_llm::text_response(&format!("reply {i}"))))
+        .collect();
+    responses.push(MockResponse::Sse(mock_llm::text_response("Summary of the early turns")));
+    responses.push(MockResponse::Sse(mock_llm::text_response("reply 5")));
+    let mock = MockLlmServer::start_answering_titles(responses).await;
+
+    let d = TestDaemon::spawn().await.unwrap();
+    let client = d.client();
+    let mut sse = d.sse();
+    sse.expect_sync().await;
+
+    let (_, _, conv_id) = setup_mock_agent(&client, &mock.url).await;
+    for i in 0..5 {
+        start_turn(&client, &conv_id, &format!("prompt {i}")).await;
+        sse.expect_event_type("RUN_FINISHED", Duration::from_secs(10))
+            .await;
+        wait_for_hook(&client, &conv_id, "turn_end", Duration::from_secs(5)).await;
+        client.post_empty("/api/debug/hooks/clear").await;
+    }
+
+    start_turn(&client, &conv_id, &"lorem ipsum ".repeat(60_000)).await;
+    let compaction = sse.expect_custom("compaction", Duration::from_secs(10)).await;
+
+    sse.expect_event_type("RUN_FINISHED", Duration::from_secs(10))
+        .await;
+    let records = wait_for_hook(&client, &conv_id, "turn_end", Duration::from_secs(5)).await;
+    let record = records
+        .iter()
+        .find(|r| r["hook"] == "post_compact")
+        .unwrap();
+    let names = hook_names(&records, Some(&conv_id));
+    assert_eq!(
+        names.iter().filter(|n| *n == "post_compact").count(),
+        1,
+        "one compaction, one hook: {names:?}"
+    );
+
+    let details = &record["details"];
+    assert_eq!(details["span_index"], 0);
+    assert_eq!(details["consumed_count"], compaction["value"]["consumed_count"]);
+    assert!(details["consumed_count"].as_u64().unwrap() > 0);
+    assert_eq!(details["summary"], compaction["value"]["summary"]);
+    assert!(
+        details["summary"]
+            .as_str()
+            .unwrap()
+            .contains("Summary of the early turns"),
+        "{details}"
+    );
+}
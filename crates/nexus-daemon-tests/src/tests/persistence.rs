@@ -102,3 +102,111 @@ async fn agents_survive_restart() {
     assert_eq!(status.as_u16(), 200, "Agent should survive restart");
     assert_eq!(body["name"].as_str(), Some("Persistent Agent"));
 }
+
+#[tokio::test]
+async fn interrupted_run_is_recovered_from_event_log() {
+    let home_path = tempfile::tempdir().unwrap();
+    let home = home_path.path().to_path_buf();
+    let nexus_dir = home.join(".nexus");
+    std::fs::create_dir_all(&nexus_dir).unwrap();
+    let config = json!({
+        "server": { "host": "127.0.0.1", "port": 0 },
+        "conversations": { "event_log": true, "recover_interrupted": true }
+    });
+    std::fs::write(nexus_dir.join("nexus.json"), config.to_string()).unwrap();
+
+    {
+        let d = TestDaemon::spawn_at_path(home.clone()).await.unwrap();
+        let (status, _) = d.client().post("/api/conversations", &json!({ "id": "c1" })).await;
+        assert!(status.is_success());
+    }
+
+    // A run that finished one tool round and died waiting on the second.
+    let ev = |event: serde_json::Value| {
+        let mut event = event;
+        event["threadId"] = json!("c1");
+        event["runId"] = json!("r1");
+        event.to_string()
+    };
+    let lines = [
+        ev(json!({"type": "RUN_STARTED"})),
+        ev(json!({"type": "TOOL_CALL_START", "toolCallId": "tc1", "toolCallName": "bash"})),
+        ev(json!({"type": "TOOL_CALL_ARGS", "toolCallId": "tc1", "delta": "{\"command\":\"make\"}"})),
+        ev(json!({"type": "TOOL_CALL_END", "toolCallId": "tc1"})),
+        ev(json!({"type": "TOOL_CALL_RESULT", "toolCallId": "tc1", "content": "built", "isError": false})),
+        ev(json!({"type": "TOOL_CALL_START", "toolCallId": "tc2", "toolCallName": "bash"})),
+    ];
+    let log = nexus_dir.join("conversations/c1.events.jsonl");
+    let mut content = std::fs::read_to_string(&log).unwrap_or_default();
+    content.push_str(&(lines.join("\n") + "\n"));
+    std::fs::write(&log, content).unwrap();
+
+    for _ in 0..2 {
+        let d = TestDaemon::spawn_at_path(home.clone()).await.unwrap();
+        let (_, conv) = d.client().get("/api/conversations/c1").await;
+        let messages = conv["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 3, "recovered exactly once: {messages:?}");
+        assert_eq!(messages[0]["parts"][0]["toolCallId"], "tc1");
+        assert_eq!(messages[0]["parts"][0]["args"]["command"], "make");
+        assert_eq!(messages[1]["parts"][0]["result"], "built");
+        assert_eq!(messages[2]["role"], "assistant");
+        assert_eq!(conv["active_path"].as_array().unwrap().len(), 3);
+    }
+}
+
+#[tokio::test]
+async fn saved_run_is_not_recovered_twice() {
+    use crate::mock_llm::{self, MockLlmServer, MockResponse};
+    use std::time::Duration;
+
+    let home_path = tempfile::tempdir().unwrap();
+    let home = home_path.path().to_path_buf();
+    let nexus_dir = home.join(".nexus");
+    std::fs::create_dir_all(&nexus_dir).unwrap();
+    let config = json!({
+        "server": { "host": "127.0.0.1", "port": 0 },
+        "conversations": { "event_log": true, "recover_interrupted": true }
+    });
+    std::fs::write(nexus_dir.join("nexus.json"), config.to_string()).unwrap();
+
+    let mock = MockLlmServer::start_answering_titles(vec![MockResponse::Sse(
+        mock_llm::text_response("All done"),
+    )])
+    .await;
+    let conv_id = {
+        let d = TestDaemon::spawn_at_path(home.clone()).await.unwrap();
+        let c = d.client();
+        let mut sse = d.sse();
+        sse.expect_sync().await;
+        let (_, _, conv_id) = fixtures::setup_mock_agent(&c, &mock.url).await;
+        c.post("/api/chat", &json!({ "conversationId": conv_id, "message": "hi" }))
+            .await;
+        sse.expect_event_type("RUN_FINISHED", Duration::from_secs(10)).await;
+
+        let log = nexus_dir.join(format!("conversations/{conv_id}.events.jsonl"));
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+        loop {
+            let (_, conv) = c.get(&format!("/api/conversations/{conv_id}")).await;
+            let logged = std::fs::read_to_string(&log).unwrap_or_default();
+            if conv["messages"].as_array().unwrap().len() == 2 && logged.contains("RUN_FINISHED") {
+                break;
+            }
+            assert!(tokio::time::Instant::now() < deadline, "turn never saved: {conv}");
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        conv_id
+    };
+
+    // The daemon saved the text-only reply but died before logging the end
+    // of the run.
+    let log = nexus_dir.join(format!("conversations/{conv_id}.events.jsonl"));
+    let content = std::fs::read_to_string(&log).unwrap();
+    let kept: Vec<&str> = content.lines().filter(|l| !l.contains("RUN_FINISHED")).collect();
+    std::fs::write(&log, kept.join("\n") + "\n").unwrap();
+
+    let d = TestDaemon::spawn_at_path(home).await.unwrap();
+    let (_, conv) = d.client().get(&format!("/api/conversations/{conv_id}")).await;
+    let messages = conv["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 2, "reply appended again: {messages:?}");
+    assert_eq!(messages[1]["parts"][0]["text"], "All done");
+}
@@ -114,3 +114,55 @@ async fn api_key_not_leaked_in_get() {
     assert!(body.get("api_key").is_none());
     assert_eq!(body["has_api_key"], true);
 }
+
+#[tokio::test]
+async fn response_cache_disabled_by_default() {
+    let d = TestDaemon::spawn().await.unwrap();
+    let c = d.client();
+
+    let (status, body) = c.get("/api/providers/cache").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!({ "enabled": false }));
+    let (status, _) = c.delete("/api/providers/cache").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn response_cache_serves_repeated_requests() {
+    use std::time::Duration;
+
+    use crate::mock_llm::{self, MockLlmServer, MockResponse};
+
+    let mock = MockLlmServer::start_answering_titles(vec![MockResponse::Sse(
+        mock_llm::text_response("Cached answer"),
+    )])
+    .await;
+    let (d, _home) = fixtures::spawn_with_config(json!({ "response_cache": { "ttl_secs": 600 } })).await;
+    let c = d.client();
+    let mut sse = d.sse();
+    sse.expect_sync().await;
+
+    let (_, _, first) = fixtures::setup_mock_agent(&c, &mock.url).await;
+    let (_, second) = c.post("/api/conversations", &json!({})).await;
+    for conv_id in [first.as_str(), second["id"].as_str().unwrap()] {
+        c.post("/api/chat", &json!({ "conversationId": conv_id, "message": "What is 2+2?" }))
+            .await;
+        sse.expect_event_type("RUN_FINISHED", Duration::from_secs(10))
+            .await;
+        let (_, conv) = c.get(&format!("/api/conversations/{conv_id}")).await;
+        let reply = conv["messages"][1].to_string();
+        assert!(reply.contains("Cached answer"), "reply: {reply}");
+    }
+
+    assert_eq!(mock.captured_requests().len(), 1);
+    // Auto-title requests go through the cache too, so count at least the turn.
+    let (_, stats) = c.get("/api/providers/cache").await;
+    assert_eq!(stats["enabled"], true);
+    assert!(stats["hits"].as_u64().unwrap() >= 1, "stats: {stats}");
+    assert!(stats["entries"].as_u64().unwrap() >= 1, "stats: {stats}");
+
+    let (status, _) = c.delete("/api/providers/cache").await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, stats) = c.get("/api/providers/cache").await;
+    assert_eq!(stats["entries"], 0);
+}
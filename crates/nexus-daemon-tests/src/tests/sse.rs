@@ -18,3 +18,12 @@ async fn sync_has_empty_active_runs_when_idle() {
     let sync = sse.expect_sync().await;
     assert_eq!(sync["activeRuns"].as_array().unwrap().len(), 0);
 }
+
+#[tokio::test]
+async fn sync_carries_schema_version() {
+    let d = TestDaemon::spawn().await.unwrap();
+    let mut sse = d.sse();
+
+    let sync = sse.expect_sync().await;
+    assert_eq!(sync["schemaVersion"], 1);
+}
@@ -2,7 +2,7 @@ use std::time::Duration;
 
 use serde_json::json;
 
-use crate::fixtures::setup_mock_agent;
+use crate::fixtures::{setup_mock_agent, spawn_with_config, spawn_with_files};
 use crate::harness::TestDaemon;
 use crate::mock_llm::{self, MockLlmServer, MockResponse};
 
@@ -10,7 +10,7 @@ fn is_type(event: &serde_json::Value, ty: &str) -> bool {
     event.get("type").and_then(|t| t.as_str()) == Some(ty)
 }
 
-fn _is_custom(event: &serde_json::Value, name: &str) -> bool {
+fn is_custom(event: &serde_json::Value, name: &str) -> bool {
     is_type(event, "CUSTOM") && event.get("name").and_then(|n| n.as_str()) == Some(name)
 }
 
@@ -256,6 +256,35 @@ async fn provider_error_emits_run_error() {
     );
 }
 
+#[tokio::test]
+async fn retry_after_provider_error_reuses_idempotency_key() {
+    let mock = MockLlmServer::start_answering_titles(vec![
+        mock_llm::error_response("overloaded_error", "Server is overloaded"),
+        MockResponse::Sse(mock_llm::text_response("Recovered")),
+        MockResponse::Sse(mock_llm::text_response("Second turn")),
+    ])
+    .await;
+
+    let d = TestDaemon::spawn().await.unwrap();
+    let client = d.client();
+    let mut sse = d.sse();
+    sse.expect_sync().await;
+
+    let (_, _, conv_id) = setup_mock_agent(&client, &mock.url).await;
+    start_turn(&client, &conv_id, "Hello").await;
+    sse.expect_event_type("RUN_FINISHED", Duration::from_secs(15))
+        .await;
+    start_turn(&client, &conv_id, "Again").await;
+    sse.expect_event_type("RUN_FINISHED", Duration::from_secs(10))
+        .await;
+
+    let keys = mock.captured_idempotency_keys();
+    assert_eq!(keys.len(), 3);
+    let first = keys[0].as_deref().expect("requests carry an idempotency key");
+    assert_eq!(keys[1].as_deref(), Some(first), "the retry reuses the key");
+    assert_ne!(keys[2].as_deref(), Some(first), "a new turn gets a new key");
+}
+
 #[tokio::test]
 async fn abort_stops_running_turn() {
     // Delayed response gives us time to abort.
@@ -409,3 +438,316 @@ async fn multi_turn_context_includes_history() {
         messages.len()
     );
 }
+
+#[tokio::test]
+async fn thinking_budget_override_applies_to_single_turn() {
+    let mock = MockLlmServer::start(vec![
+        MockResponse::Sse(mock_llm::text_response("Thought about it")),
+        MockResponse::Sse(mock_llm::text_response("Quick answer")),
+    ])
+    .await;
+
+    let d = TestDaemon::spawn().await.unwrap();
+    let client = d.client();
+    let mut sse = d.sse();
+    sse.expect_sync().await;
+
+    let (_, _, conv_id) = setup_mock_agent(&client, &mock.url).await;
+
+    let (status, _) = client
+        .post(
+            "/api/chat",
+            &json!({ "conversationId": conv_id, "message": "Think hard", "thinkingBudget": 2048 }),
+        )
+        .await;
+    assert!(status.is_success());
+    sse.expect_event_type("RUN_FINISHED", Duration::from_secs(10))
+        .await;
+
+    start_turn(&client, &conv_id, "Now quickly").await;
+    sse.expect_event_type("RUN_FINISHED", Duration::from_secs(10))
+        .await;
+
+    let requests = mock.captured_requests();
+    assert_eq!(requests[0]["thinking"]["budget_tokens"], 2048);
+    // The override doesn't stick: the agent has no thinking budget
+    assert!(requests[1].get("thinking").is_none());
+}
+
+#[tokio::test]
+async fn best_of_streams_only_the_judged_candidate() {
+    // Candidates are requested concurrently, so either may get either reply.
+    let mock = MockLlmServer::start_answering_titles(vec![
+        MockResponse::Sse(mock_llm::text_response("Answer alpha")),
+        MockResponse::Sse(mock_llm::text_response("Answer beta")),
+        MockResponse::Sse(mock_llm::text_response("2")),
+    ])
+    .await;
+
+    let d = TestDaemon::spawn().await.unwrap();
+    let client = d.client();
+    let mut sse = d.sse();
+    sse.expect_sync().await;
+
+    let (_, _, conv_id) = setup_mock_agent(&client, &mock.url).await;
+
+    let (status, _) = client
+        .post(
+            "/api/chat",
+            &json!({ "conversationId": conv_id, "message": "Pick one", "bestOf": { "n": 9 } }),
+        )
+        .await;
+    assert_eq!(status.as_u16(), 400);
+
+    let (status, _) = client
+        .post(
+            "/api/chat",
+            &json!({
+                "conversationId": conv_id,
+                "message": "Pick one",
+                "bestOf": { "n": 2, "criteria": "Prefer brevity" },
+            }),
+        )
+        .await;
+    assert!(status.is_success());
+
+    let mut deltas = String::new();
+    let mut best_of_calls = 0;
+    loop {
+        let event = sse
+            .next_matching(|_| true, Duration::from_secs(10))
+            .await
+            .expect("turn should finish");
+        if is_type(&event, "TEXT_MESSAGE_CONTENT") {
+            deltas.push_str(event["delta"].as_str().unwrap());
+        }
+        if is_custom(&event, "inference_usage") && event["value"]["source"] == "best_of" {
+            best_of_calls += 1;
+        }
+        if is_type(&event, "RUN_FINISHED") {
+            break;
+        }
+    }
+
+    let requests = mock.captured_requests();
+    assert_eq!(requests.len(), 3);
+    let judge = requests[2].to_string();
+    assert!(judge.contains("Prefer brevity"));
+    assert!(judge.contains("Answer alpha") && judge.contains("Answer beta"));
+    // Only the winner streamed; the loser and the judge are billed separately.
+    assert!(deltas == "Answer alpha" || deltas == "Answer beta", "streamed {deltas:?}");
+    assert_eq!(best_of_calls, 2);
+}
+
+#[tokio::test]
+async fn agent_system_prompt_is_rendered_as_template() {
+    let mock = MockLlmServer::start(vec![MockResponse::Sse(mock_llm::text_response("ok"))]).await;
+
+    let d = TestDaemon::spawn().await.unwrap();
+    let client = d.client();
+    let mut sse = d.sse();
+    sse.expect_sync().await;
+
+    let (_, agent_id, conv_id) = setup_mock_agent(&client, &mock.url).await;
+    let (status, _) = client
+        .put(
+            &format!("/api/agents/{agent_id}"),
+            &json!({ "system_prompt": "I am {{ agent_name }}.{% if has_tools %} I have tools.{% endif %}" }),
+        )
+        .await;
+    assert!(status.is_success());
+
+    start_turn(&client, &conv_id, "Hi").await;
+    sse.expect_event_type("RUN_FINISHED", Duration::from_secs(10))
+        .await;
+
+    let system = mock.captured_requests()[0]["system"].to_string();
+    assert!(system.contains("I am mock-agent. I have tools."), "system: {system}");
+    assert!(!system.contains("{{"), "system: {system}");
+}
+
+#[tokio::test]
+async fn scripted_conversation_runs_tool_turn_then_text_turn() {
+    use crate::scripted::ScriptedConversation;
+
+    let transcripts = ScriptedConversation::new()
+        .turn("Say hello from the shell")
+        .tool_call(
+            "bash",
+            json!({ "description": "Echo", "command": "echo scripted-hello" }),
+        )
+        .reply("The shell said hello")
+        .expect(|t| {
+            assert_eq!(t.tool_results.len(), 1);
+            assert!(t.tool_results[0]["content"]
+                .as_str()
+                .unwrap()
+                .contains("scripted-hello"));
+            assert_eq!(t.text, "The shell said hello");
+        })
+        .turn("Thanks")
+        .reply("You're welcome")
+        .expect(|t| assert!(t.tool_calls.is_empty() && t.error.is_none()))
+        .run()
+        .await;
+
+    assert_eq!(transcripts.len(), 2);
+    assert_eq!(transcripts[1].text, "You're welcome");
+}
+
+#[tokio::test]
+async fn racing_agent_streams_from_the_first_provider_to_respond() {
+    let slow = MockLlmServer::start_answering_titles(vec![MockResponse::Delayed {
+        delay_ms: 3000,
+        sse: mock_llm::text_response("Slow answer"),
+    }])
+    .await;
+    let fast = MockLlmServer::start_answering_titles(vec![MockResponse::Sse(
+        mock_llm::text_response("Fast answer"),
+    )])
+    .await;
+
+    let (d, _home) = spawn_with_config(json!({
+        "providers": [
+            { "id": "hosted", "name": "Hosted", "type": "anthropic", "endpoint": slow.url, "api_key": "k" },
+            { "id": "local", "name": "Local", "type": "anthropic", "endpoint": fast.url, "api_key": "k" },
+        ],
+        "agents": [{
+            "id": "racer",
+            "name": "Racer",
+            "provider_id": "hosted",
+            "model": "hosted-model",
+            "race": { "provider_id": "local", "model": "local-model" },
+        }],
+        "active_agent_id": "racer",
+    }))
+    .await;
+    let client = d.client();
+    let mut sse = d.sse();
+    sse.expect_sync().await;
+
+    let (_, conv) = client.post("/api/conversations", &json!({})).await;
+    let conv_id = conv["id"].as_str().unwrap().to_string();
+    start_turn(&client, &conv_id, "Hello").await;
+
+    let started = std::time::Instant::now();
+    let content = sse
+        .expect_event_type("TEXT_MESSAGE_CONTENT", Duration::from_secs(10))
+        .await;
+    assert_eq!(content["delta"], "Fast answer");
+    sse.expect_event_type("RUN_FINISHED", Duration::from_secs(10))
+        .await;
+    assert!(started.elapsed() < Duration::from_secs(3), "waited for the slow provider");
+
+    assert_eq!(fast.captured_requests()[0]["model"], "local-model");
+    assert_eq!(slow.captured_requests()[0]["model"], "hosted-model");
+}
+
+#[tokio::test]
+async fn environment_block_describes_the_turn_when_enabled() {
+    let mock = MockLlmServer::start_answering_titles(vec![MockResponse::Sse(
+        mock_llm::text_response("Hi"),
+    )])
+    .await;
+
+    let work_dir = tempfile::TempDir::new().unwrap();
+    let work = work_dir.path().to_string_lossy().to_string();
+    let (d, _home) = spawn_with_config(json!({
+        "agent": { "environment": { "enabled": true, "fields": ["cwd", "tools", "workspace"] } },
+        "filesystem": { "allowed_directories": [work] },
+    }))
+    .await;
+    let client = d.client();
+    let mut sse = d.sse();
+    sse.expect_sync().await;
+
+    let (_, _, conv_id) = setup_mock_agent(&client, &mock.url).await;
+    start_turn(&client, &conv_id, "Hello").await;
+    sse.expect_event_type("RUN_FINISHED", Duration::from_secs(10))
+        .await;
+
+    let request = &mock.captured_requests()[0];
+    let messages = request["messages"].to_string();
+    assert!(messages.contains("<environment>"), "no environment block: {messages}");
+    assert!(messages.contains(&format!("Working directory: {work}")));
+    assert!(messages.contains("filesystem ("));
+    assert!(!messages.contains("OS: "), "unconfigured field rendered");
+    assert!(!request["system"].to_string().contains("<environment>"));
+}
+
+#[tokio::test]
+async fn skills_are_indexed_in_system_prompt_and_loadable() {
+    let mock = MockLlmServer::start_answering_titles(vec![MockResponse::Sse(
+        mock_llm::text_response("Hi"),
+    )])
+    .await;
+
+    let (d, _home) = spawn_with_files(
+        json!({}),
+        &[("skills/release.md", "---\ndescription: Cut and tag a release\n---\n1. Bump the version.\n")],
+    )
+    .await;
+    let client = d.client();
+    let mut sse = d.sse();
+    sse.expect_sync().await;
+
+    let (_, _, conv_id) = setup_mock_agent(&client, &mock.url).await;
+    start_turn(&client, &conv_id, "Hello").await;
+    sse.expect_event_type("RUN_FINISHED", Duration::from_secs(10))
+        .await;
+
+    let request = &mock.captured_requests()[0];
+    let system = request["system"].to_string();
+    assert!(system.contains("<skills>"), "no skills index: {system}");
+    assert!(system.contains("release: Cut and tag a release"));
+    assert!(!system.contains("Bump the version"), "skill body inlined");
+    let tools: Vec<&str> = request["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|t| t["name"].as_str())
+        .collect();
+    assert!(tools.contains(&"load_skill"), "tools: {tools:?}");
+}
+
+#[tokio::test]
+async fn tool_error_streak_injects_a_reflection_prompt() {
+    let missing = r#"{"description":"Reading","path":"/tmp/nexus-reflection-missing.txt"}"#;
+    let mock = MockLlmServer::start_answering_titles(vec![
+        MockResponse::Sse(mock_llm::tool_use_response("nexus_read_file", "toolu_r1", missing)),
+        MockResponse::Sse(mock_llm::tool_use_response("nexus_read_file", "toolu_r2", missing)),
+        MockResponse::Sse(mock_llm::text_response("Trying something else")),
+    ])
+    .await;
+
+    let (d, _home) = spawn_with_config(json!({ "agent": { "reflect_after_errors": 2 } })).await;
+    let client = d.client();
+    let mut sse = d.sse();
+    sse.expect_sync().await;
+
+    let (_, _, conv_id) = setup_mock_agent(&client, &mock.url).await;
+    start_turn(&client, &conv_id, "Read the file").await;
+    sse.expect_event_type("RUN_FINISHED", Duration::from_secs(10))
+        .await;
+
+    let requests: Vec<String> = mock
+        .captured_requests()
+        .iter()
+        .map(|r| r["messages"].to_string())
+        .collect();
+    // Only the request after the second failure carries the prompt.
+    assert!(!requests[1].contains("<reflection>"), "reflected after one error");
+    assert!(requests[2].contains("<reflection>"), "no reflection: {}", requests[2]);
+    assert!(requests[2].contains("The last 2 tool calls failed"));
+
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+    let conv = loop {
+        let (_, conv) = client.get(&format!("/api/conversations/{conv_id}")).await;
+        if conv.to_string().contains("Trying something else") {
+            break conv;
+        }
+        assert!(tokio::time::Instant::now() < deadline, "turn never saved: {conv}");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    };
+    assert!(!conv.to_string().contains("<reflection>"), "reflection prompt persisted");
+}
@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::fixtures::setup_mock_agent;
+use crate::harness::TestDaemon;
+use crate::mock_llm::{self, MockLlmServer, MockResponse};
+
+type Socket = tokio_tungstenite::WebSocketStream<
+    tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+>;
+
+async fn connect(d: &TestDaemon) -> Socket {
+    let url = format!("ws://127.0.0.1:{}/api/ws", d.port);
+    let (socket, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+    socket
+}
+
+async fn send(socket: &mut Socket, frame: Value) {
+    socket.send(Message::text(frame.to_string())).await.unwrap();
+}
+
+/// Read frames until one matches `pred`, or panic after `timeout`.
+async fn expect(socket: &mut Socket, pred: impl Fn(&Value) -> bool, timeout: Duration) -> Value {
+    tokio::time::timeout(timeout, async {
+        while let Some(msg) = socket.next().await {
+            if let Message::Text(text) = msg.unwrap() {
+                let frame: Value = serde_json::from_str(&text).unwrap();
+                if pred(&frame) {
+                    return frame;
+                }
+            }
+        }
+        panic!("socket closed");
+    })
+    .await
+    .expect("timed out waiting for frame")
+}
+
+#[tokio::test]
+async fn websocket_session_streams_turn_events() {
+    let mock = MockLlmServer::start(vec![MockResponse::Sse(mock_llm::text_response(
+        "Hello over ws",
+    ))])
+    .await;
+    let d = TestDaemon::spawn().await.unwrap();
+    let (_, _, conv_id) = setup_mock_agent(&d.client(), &mock.url).await;
+
+    let mut socket = connect(&d).await;
+    send(&mut socket, json!({ "type": "session", "conversationId": conv_id })).await;
+    let opened = expect(&mut socket, |f| f["type"] == "session_opened", Duration::from_secs(5)).await;
+    assert_eq!(opened["conversationId"], conv_id.as_str());
+
+    send(&mut socket, json!({ "type": "prompt", "message": "hi" })).await;
+    let content = expect(
+        &mut socket,
+        |f| f["type"] == "TEXT_MESSAGE_CONTENT",
+        Duration::from_secs(10),
+    )
+    .await;
+    assert_eq!(content["threadId"], conv_id.as_str());
+    assert_eq!(content["delta"], "Hello over ws");
+    expect(&mut socket, |f| f["type"] == "RUN_FINISHED", Duration::from_secs(10)).await;
+}
+
+#[tokio::test]
+async fn websocket_rejects_frames_without_session() {
+    let d = TestDaemon::spawn().await.unwrap();
+    let mut socket = connect(&d).await;
+
+    send(&mut socket, json!({ "type": "prompt", "message": "hi" })).await;
+    let err = expect(&mut socket, |f| f["type"] == "error", Duration::from_secs(5)).await;
+    assert!(err["message"].as_str().unwrap().contains("no session"));
+
+    send(&mut socket, json!({ "type": "session", "conversationId": "missing" })).await;
+    let err = expect(&mut socket, |f| f["type"] == "error", Duration::from_secs(5)).await;
+    assert!(err["message"].as_str().unwrap().contains("not found"));
+
+    // Without an id a new conversation is created
+    send(&mut socket, json!({ "type": "session" })).await;
+    let opened = expect(&mut socket, |f| f["type"] == "session_opened", Duration::from_secs(5)).await;
+    let id = opened["conversationId"].as_str().unwrap();
+    let (status, _) = d.client().get(&format!("/api/conversations/{id}")).await;
+    assert!(status.is_success());
+}
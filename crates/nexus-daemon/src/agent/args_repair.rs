@@ -0,0 +1,140 @@
+//! Lenient repair of malformed tool-call arguments.
+//!
+//! Models behind OpenAI-compatible local servers in particular sometimes emit
+//! almost-JSON: a trailing comma, single-quoted strings, a raw newline inside
+//! a string. Rather than run the tool with `{}` and waste the round, the
+//! agent loop tries [`repair`] when strict parsing fails and reports what it
+//! fixed with a `tool_args_repaired` event.
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// One kind of fix [`repair`] applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Repair {
+    /// `,` directly before `}` or `]` was dropped.
+    TrailingComma,
+    /// A `'…'` string was rewritten as `"…"`.
+    SingleQuotes,
+    /// A raw newline, tab or other control character inside a string was escaped.
+    UnescapedControl,
+}
+
+/// Arguments that parse after repair.
+#[derive(Debug)]
+pub struct Repaired {
+    pub value: Value,
+    /// Re-serialized arguments, to hand to the tool instead of the original.
+    pub json: String,
+    pub repairs: Vec<Repair>,
+}
+
+/// Repair `raw` if it is invalid JSON that the fixes above make valid.
+/// Returns `None` if `raw` already parses, or still doesn't after repair.
+pub fn repair(raw: &str) -> Option<Repaired> {
+    if serde_json::from_str::<Value>(raw).is_ok() {
+        return None;
+    }
+    let (fixed, repairs) = rewrite(raw);
+    if repairs.is_empty() {
+        return None;
+    }
+    let value: Value = serde_json::from_str(&fixed).ok()?;
+    let json = serde_json::to_string(&value).ok()?;
+    Some(Repaired { value, json, repairs })
+}
+
+fn note(repair: Repair, repairs: &mut Vec<Repair>) {
+    if !repairs.contains(&repair) {
+        repairs.push(repair);
+    }
+}
+
+fn rewrite(raw: &str) -> (String, Vec<Repair>) {
+    let mut out = String::with_capacity(raw.len());
+    let mut repairs = Vec::new();
+    // Quote character of the string we are inside, if any.
+    let mut quote: Option<char> = None;
+    let mut chars = raw.chars();
+
+    while let Some(c) = chars.next() {
+        match quote {
+            None => match c {
+                '"' => {
+                    quote = Some('"');
+                    out.push('"');
+                }
+                '\'' => {
+                    quote = Some('\'');
+                    out.push('"');
+                    note(Repair::SingleQuotes, &mut repairs);
+                }
+                ',' => {
+                    let rest = chars.clone().find(|c| !c.is_whitespace());
+                    if matches!(rest, Some('}') | Some(']') | None) {
+                        note(Repair::TrailingComma, &mut repairs);
+                    } else {
+                        out.push(',');
+                    }
+                }
+                _ => out.push(c),
+            },
+            Some(q) => match c {
+                '\\' => match chars.next() {
+                    // `\'` is only meaningful inside single quotes
+                    Some('\'') => out.push('\''),
+                    Some(escaped) => {
+                        out.push('\\');
+                        out.push(escaped);
+                    }
+                    None => out.push('\\'),
+                },
+                _ if c == q => {
+                    quote = None;
+                    out.push('"');
+                }
+                '"' => out.push_str("\\\""),
+                '\n' | '\r' | '\t' => {
+                    out.push_str(match c {
+                        '\n' => "\\n",
+                        '\r' => "\\r",
+                        _ => "\\t",
+                    });
+                    note(Repair::UnescapedControl, &mut repairs);
+                }
+                c if (c as u32) < 0x20 => {
+                    out.push_str(&format!("\\u{:04x}", c as u32));
+                    note(Repair::UnescapedControl, &mut repairs);
+                }
+                _ => out.push(c),
+            },
+        }
+    }
+    (out, repairs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn valid_and_hopeless_input_is_left_alone() {
+        assert!(repair(r#"{"path": "a.rs"}"#).is_none());
+        assert!(repair(r#"{"path": "a.rs""#).is_none());
+        assert!(repair("").is_none());
+    }
+
+    #[test]
+    fn fixes_trailing_commas_single_quotes_and_newlines() {
+        let repaired = repair("{'path': 'it\\'s \"here\".rs', 'lines': [1, 2,],}").unwrap();
+        assert_eq!(repaired.value, json!({"path": "it's \"here\".rs", "lines": [1, 2]}));
+        assert_eq!(repaired.repairs, vec![Repair::SingleQuotes, Repair::TrailingComma]);
+
+        let repaired = repair("{\"content\": \"line 1\nline 2\t, end\"}").unwrap();
+        assert_eq!(repaired.value["content"], "line 1\nline 2\t, end");
+        assert_eq!(repaired.repairs, vec![Repair::UnescapedControl]);
+        assert_eq!(serde_json::from_str::<Value>(&repaired.json).unwrap(), repaired.value);
+    }
+}
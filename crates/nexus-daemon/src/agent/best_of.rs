@@ -0,0 +1,432 @@
+//! Best-of-N sampling for the first round of a turn.
+//!
+//! [`sample`] requests N completions concurrently, asks a [`Judge`] to pick
+//! one, and returns the winner as a replayed event stream, so the agent loop
+//! streams and persists it like any other round. Only the first round is
+//! sampled; tool rounds after it run normally.
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::StreamExt;
+use tokio_util::sync::CancellationToken;
+
+use nexus_provider::types::*;
+use nexus_provider::{EventStream, InferenceProvider, InferenceRequest};
+
+use super::emitter::{CallUsage, TurnEmitter};
+
+/// Upper bound on candidates per request.
+pub const MAX_CANDIDATES: u32 = 8;
+
+const JUDGE_PROMPT: &str = "You compare candidate responses to the same request \
+and pick the best one: the most correct, complete and helpful. \
+Reply with the number of the best candidate only.";
+
+/// Best-of-N settings for one turn.
+#[derive(Clone)]
+pub struct BestOf {
+    pub n: u32,
+    pub judge: Arc<dyn Judge>,
+}
+
+/// What a judge sees: the request and the provider it may call.
+pub struct JudgeContext<'a> {
+    pub provider: &'a dyn InferenceProvider,
+    pub model: &'a str,
+    /// Text of the latest user message.
+    pub task: &'a str,
+}
+
+/// The judge's pick and what judging cost.
+#[derive(Debug, Clone, Default)]
+pub struct Judgement {
+    pub index: usize,
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+}
+
+#[async_trait]
+pub trait Judge: Send + Sync {
+    /// Pick the best candidate by index.
+    async fn pick(&self, ctx: &JudgeContext<'_>, candidates: &[String]) -> Result<Judgement>;
+}
+
+/// Asks the turn's model to compare the candidates, optionally against
+/// caller-supplied criteria.
+pub struct LlmJudge {
+    pub criteria: Option<String>,
+}
+
+#[async_trait]
+impl Judge for LlmJudge {
+    async fn pick(&self, ctx: &JudgeContext<'_>, candidates: &[String]) -> Result<Judgement> {
+        let mut prompt = format!("<request>\n{}\n</request>\n\n", ctx.task);
+        if let Some(ref criteria) = self.criteria {
+            prompt.push_str(&format!("<criteria>\n{criteria}\n</criteria>\n\n"));
+        }
+        for (i, candidate) in candidates.iter().enumerate() {
+            prompt.push_str(&format!("<candidate number=\"{}\">\n{candidate}\n</candidate>\n\n", i + 1));
+        }
+
+        let events = collect(
+            ctx.provider,
+            InferenceRequest {
+                model: ctx.model.to_string(),
+                max_tokens: 16,
+                system: Some(JUDGE_PROMPT.to_string()),
+                temperature: Some(0.0),
+                thinking_budget: None,
+                messages: vec![Message {
+                    role: Role::User,
+                    content: vec![ContentBlock::Text { text: prompt }],
+                }],
+                tools: Vec::new(),
+                idempotency_key: None,
+            },
+            &CancellationToken::new(),
+        )
+        .await?;
+        let reply = candidate_text(&events);
+        let index = parse_pick(&reply, candidates.len())
+            .ok_or_else(|| anyhow!("judge reply {reply:?} names no candidate"))?;
+        let (input_tokens, output_tokens) = usage(&events);
+        Ok(Judgement { index, input_tokens, output_tokens })
+    }
+}
+
+/// Sample `best_of.n` completions of `request` and return the winner's
+/// events as a stream. Losing candidates and the judge call are reported as
+/// `inference_usage` (source `best_of`); their total cost is returned.
+pub async fn sample(
+    provider: &dyn InferenceProvider,
+    best_of: &BestOf,
+    request: InferenceRequest,
+    emitter: &TurnEmitter,
+    cancel: &CancellationToken,
+) -> Result<(EventStream, f64)> {
+    let attempts = futures::future::join_all(
+        (0..best_of.n.clamp(1, MAX_CANDIDATES)).map(|i| {
+            // Candidates are deliberate duplicates; keep their keys apart.
+            let mut candidate = request.clone();
+            candidate.idempotency_key = request.idempotency_key.as_ref().map(|k| format!("{k}-{i}"));
+            collect(provider, candidate, cancel)
+        }),
+    )
+    .await;
+    let mut first_error = None;
+    let mut candidates = Vec::new();
+    for attempt in attempts {
+        match attempt {
+            Ok(events) => candidates.push(events),
+            Err(e) => {
+                tracing::warn!("Best-of candidate failed: {:#}", e);
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    if candidates.is_empty() {
+        return Err(first_error.unwrap_or_else(|| anyhow!("no candidates sampled")));
+    }
+
+    let model = request.model.as_str();
+    let mut extra_cost = 0.0;
+    let winner = if candidates.len() == 1 {
+        0
+    } else {
+        let texts: Vec<String> = candidates.iter().map(|c| candidate_text(c)).collect();
+        let task = latest_user_text(&request.messages);
+        let ctx = JudgeContext { provider, model, task: &task };
+        match best_of.judge.pick(&ctx, &texts).await {
+            Ok(judgement) if judgement.index < candidates.len() => {
+                if judgement.input_tokens + judgement.output_tokens > 0 {
+                    extra_cost += report(emitter, model, judgement.input_tokens, judgement.output_tokens, 0, 0);
+                }
+                judgement.index
+            }
+            Ok(judgement) => {
+                tracing::warn!(index = judgement.index, "Judge picked a missing candidate, keeping the first");
+                0
+            }
+            Err(e) => {
+                tracing::warn!("Judge failed, keeping the first candidate: {:#}", e);
+                0
+            }
+        }
+    };
+    tracing::info!(candidates = candidates.len(), winner, "Best-of sampling done");
+
+    let events = candidates.swap_remove(winner);
+    for loser in &candidates {
+        let (input, output) = usage(loser);
+        let (cache_read, cache_creation) = cache_usage(loser);
+        extra_cost += report(emitter, model, input, output, cache_read, cache_creation);
+    }
+    let stream: EventStream = Box::pin(futures::stream::iter(events.into_iter().map(Ok)));
+    Ok((stream, extra_cost))
+}
+
+/// Run one request to completion, keeping every event for replay.
+async fn collect(
+    provider: &dyn InferenceProvider,
+    request: InferenceRequest,
+    cancel: &CancellationToken,
+) -> Result<Vec<StreamEvent>> {
+    let mut stream = provider.create_message_stream(request).await?;
+    let mut events = Vec::new();
+    while let Some(event) = stream.next().await {
+        if cancel.is_cancelled() {
+            break;
+        }
+        match event? {
+            StreamEvent::Error { error_type, message } => {
+                return Err(nexus_provider::error::ProviderError::from_anthropic_stream(
+                    error_type.as_deref(),
+                    &message,
+                )
+                .into());
+            }
+            StreamEvent::MessageStop => {
+                events.push(StreamEvent::MessageStop);
+                break;
+            }
+            event => events.push(event),
+        }
+    }
+    Ok(events)
+}
+
+/// What the judge sees of a candidate: its text, plus any tool calls.
+fn candidate_text(events: &[StreamEvent]) -> String {
+    let mut text = String::new();
+    for event in events {
+        match event {
+            StreamEvent::ContentBlockDelta { delta: Delta::TextDelta { text: chunk }, .. } => {
+                text.push_str(chunk);
+            }
+            StreamEvent::ContentBlockStart {
+                content_block: ContentBlockInfo::ToolUse { name, .. },
+                ..
+            } => text.push_str(&format!("\n[calls tool {name}]")),
+            _ => {}
+        }
+    }
+    text.trim().to_string()
+}
+
+fn usage(events: &[StreamEvent]) -> (u32, u32) {
+    let mut input = 0;
+    let mut output = 0;
+    for event in events {
+        match event {
+            StreamEvent::MessageStart { usage: Some(u), .. } => input = u.input_tokens,
+            StreamEvent::MessageDelta { usage: Some(u), .. } => output = u.output_tokens,
+            _ => {}
+        }
+    }
+    (input, output)
+}
+
+fn cache_usage(events: &[StreamEvent]) -> (u32, u32) {
+    events
+        .iter()
+        .find_map(|event| match event {
+            StreamEvent::MessageStart { usage: Some(u), .. } => {
+                Some((u.cache_read_input_tokens, u.cache_creation_input_tokens))
+            }
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+/// Emit `inference_usage` for a call outside the turn's rounds; returns its cost.
+fn report(
+    emitter: &TurnEmitter,
+    model: &str,
+    input_tokens: u32,
+    output_tokens: u32,
+    cache_read: u32,
+    cache_creation: u32,
+) -> f64 {
+    let cost = nexus_pricing::calculate_cost_with_cache(
+        model,
+        input_tokens,
+        cache_creation,
+        cache_read,
+        output_tokens,
+    );
+    emitter.call_usage(&CallUsage {
+        source: "best_of",
+        round: Some(0),
+        model,
+        input_tokens,
+        output_tokens,
+        cache_read_input_tokens: cache_read,
+        cache_creation_input_tokens: cache_creation,
+        cost,
+    });
+    cost
+}
+
+fn latest_user_text(messages: &[Message]) -> String {
+    messages
+        .iter()
+        .rev()
+        .find(|m| m.role == Role::User)
+        .map(|m| {
+            m.content
+                .iter()
+                .filter_map(|b| match b {
+                    ContentBlock::Text { text } => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_default()
+}
+
+/// The first number in the judge's reply, as a 0-based index.
+fn parse_pick(reply: &str, candidates: usize) -> Option<usize> {
+    let digits: String = reply
+        .chars()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(char::is_ascii_digit)
+        .collect();
+    let number: usize = digits.parse().ok()?;
+    (1..=candidates).contains(&number).then(|| number - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A judge backed by a closure over the candidate texts.
+    struct FnJudge<F>(F);
+
+    #[async_trait]
+    impl<F> Judge for FnJudge<F>
+    where
+        F: Fn(&[String]) -> usize + Send + Sync,
+    {
+        async fn pick(&self, _ctx: &JudgeContext<'_>, candidates: &[String]) -> Result<Judgement> {
+            Ok(Judgement { index: (self.0)(candidates), ..Default::default() })
+        }
+    }
+
+    fn text_events(text: &str, output_tokens: u32) -> Vec<StreamEvent> {
+        vec![
+            StreamEvent::MessageStart {
+                message_id: "m".into(),
+                model: "mock".into(),
+                role: Role::Assistant,
+                usage: Some(Usage { input_tokens: 10, output_tokens: 0, ..Default::default() }),
+            },
+            StreamEvent::ContentBlockStart { index: 0, content_block: ContentBlockInfo::Text },
+            StreamEvent::ContentBlockDelta {
+                index: 0,
+                delta: Delta::TextDelta { text: text.into() },
+            },
+            StreamEvent::ContentBlockStop { index: 0 },
+            StreamEvent::MessageDelta {
+                stop_reason: Some(StopReason::EndTurn),
+                usage: Some(Usage { output_tokens, ..Default::default() }),
+            },
+            StreamEvent::MessageStop,
+        ]
+    }
+
+    #[test]
+    fn parse_pick_reads_first_number() {
+        assert_eq!(parse_pick("2", 3), Some(1));
+        assert_eq!(parse_pick("Candidate 3 is best.", 3), Some(2));
+        assert_eq!(parse_pick("4", 3), None);
+        assert_eq!(parse_pick("0", 3), None);
+        assert_eq!(parse_pick("none", 3), None);
+    }
+
+    #[test]
+    fn candidate_text_and_usage() {
+        let events = text_events("Hello", 7);
+        assert_eq!(candidate_text(&events), "Hello");
+        assert_eq!(usage(&events), (10, 7));
+    }
+
+    #[tokio::test]
+    async fn fn_judge_picks_by_closure() {
+        struct Unused;
+        #[async_trait]
+        impl InferenceProvider for Unused {
+            async fn create_message_stream(&self, _: InferenceRequest) -> Result<EventStream> {
+                unreachable!()
+            }
+        }
+
+        let judge = FnJudge(|c: &[String]| {
+            c.iter().enumerate().max_by_key(|(_, t)| t.len()).map_or(0, |(i, _)| i)
+        });
+        let ctx = JudgeContext { provider: &Unused, model: "m", task: "t" };
+        let pick = judge.pick(&ctx, &["a".into(), "abc".into(), "ab".into()]).await.unwrap();
+        assert_eq!(pick.index, 1);
+    }
+
+    #[tokio::test]
+    async fn candidates_stay_distinct_behind_a_warm_cache() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Mutex;
+
+        use nexus_provider::cache::{CachingProvider, ResponseCache};
+
+        /// Answers each call with a different text.
+        struct Counting(AtomicUsize);
+        #[async_trait]
+        impl InferenceProvider for Counting {
+            async fn create_message_stream(&self, _: InferenceRequest) -> Result<EventStream> {
+                let call = self.0.fetch_add(1, Ordering::SeqCst);
+                Ok(Box::pin(futures::stream::iter(text_events(&format!("answer {call}"), 5).into_iter().map(Ok))))
+            }
+        }
+
+        let inner = Arc::new(Counting(AtomicUsize::new(0)));
+        let cache = Arc::new(ResponseCache::new(std::time::Duration::from_secs(60), 16));
+        let provider = CachingProvider::new(inner.clone(), cache, "p");
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let judge = {
+            let seen = Arc::clone(&seen);
+            FnJudge(move |c: &[String]| {
+                seen.lock().unwrap().push(c.to_vec());
+                0
+            })
+        };
+        let best_of = BestOf { n: 3, judge: Arc::new(judge) };
+        let (tx, _rx) = tokio::sync::broadcast::channel(64);
+        let emitter = TurnEmitter::new(tx, "t1".into(), "r1".into());
+        let request = InferenceRequest {
+            model: "mock".into(),
+            max_tokens: 16,
+            system: None,
+            temperature: None,
+            thinking_budget: None,
+            messages: vec![Message {
+                role: Role::User,
+                content: vec![ContentBlock::Text { text: "hi".into() }],
+            }],
+            tools: Vec::new(),
+            idempotency_key: Some("k".into()),
+        };
+
+        for _ in 0..2 {
+            let (stream, _) =
+                sample(&provider, &best_of, request.clone(), &emitter, &CancellationToken::new()).await.unwrap();
+            let _: Vec<_> = stream.collect().await;
+        }
+        assert_eq!(inner.0.load(Ordering::SeqCst), 3);
+        for candidates in seen.lock().unwrap().iter() {
+            let mut distinct = candidates.clone();
+            distinct.sort();
+            distinct.dedup();
+            assert_eq!(distinct.len(), 3, "{candidates:?}");
+        }
+    }
+}
@@ -1,7 +1,56 @@
+use serde::Serialize;
 use tokio::sync::broadcast;
 
 use super::events::{AgUiEvent, EventEnvelope};
 
+/// Cap on `agent_warning` message length — tool errors can carry whole
+/// command outputs, and the full text is already in TOOL_CALL_RESULT.
+const WARNING_MESSAGE_MAX_CHARS: usize = 500;
+
+/// Token usage and cost of a single provider call (`inference_usage`).
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallUsage<'a> {
+    /// `"turn"` for agent rounds, `"compaction"` for summarization calls,
+    /// `"best_of"` for discarded best-of candidates and the judge.
+    pub source: &'a str,
+    /// Agent round within the turn. Absent for compaction calls.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub round: Option<usize>,
+    pub model: &'a str,
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    pub cache_read_input_tokens: u32,
+    pub cache_creation_input_tokens: u32,
+    pub cost: f64,
+}
+
+/// What started a compaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompactionTrigger {
+    /// The context crossed the summarization threshold during turn setup.
+    Threshold,
+    /// Requested explicitly (the debug compact endpoint).
+    Manual,
+}
+
+/// A compaction that sealed a span (`compaction`). Keys stay snake_case
+/// for compatibility with the original `{ sealed_span_index, consumed_count }`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Compaction<'a> {
+    pub sealed_span_index: usize,
+    pub consumed_count: usize,
+    /// The summary that now stands in for the consumed messages.
+    pub summary: &'a str,
+    /// 1 for the conversation's first compaction, 2 for the second, ...
+    pub compaction_number: usize,
+    /// Every active message was summarized; otherwise recent messages were
+    /// kept verbatim after the summary.
+    pub full: bool,
+    pub trigger: CompactionTrigger,
+}
+
 /// Facade over the broadcast channel that eliminates boilerplate from event
 /// emission sites. Owns the sender + conversation/run identifiers so callers
 /// only provide event-specific fields.
@@ -33,7 +82,6 @@ impl TurnEmitter {
         &self.thread_id
     }
 
-    #[allow(dead_code)] // part of emitter API, used in tests
     pub fn run_id(&self) -> &str {
         &self.run_id
     }
@@ -146,6 +194,15 @@ impl TurnEmitter {
         });
     }
 
+    /// Per-call usage, emitted after every provider call. `usage_update`
+    /// carries the running totals; this carries the individual increments.
+    pub fn call_usage(&self, usage: &CallUsage<'_>) {
+        self.emit(AgUiEvent::Custom {
+            name: "inference_usage".to_string(),
+            value: serde_json::to_value(usage).unwrap_or_default(),
+        });
+    }
+
     pub fn thinking_start(&self) {
         self.emit(AgUiEvent::Custom {
             name: "thinking_start".to_string(),
@@ -174,6 +231,82 @@ impl TurnEmitter {
         });
     }
 
+    /// Non-fatal problem the user may want to see: a failed tool call, a
+    /// provider error that will be retried, or a failed compaction. The run
+    /// continues; fatal errors still go through `run_error`.
+    pub fn warning(&self, kind: &str, message: impl Into<String>, details: serde_json::Value) {
+        let message: String = message.into();
+        let message = if message.chars().count() > WARNING_MESSAGE_MAX_CHARS {
+            let mut truncated: String = message.chars().take(WARNING_MESSAGE_MAX_CHARS).collect();
+            truncated.push('…');
+            truncated
+        } else {
+            message
+        };
+        self.emit(AgUiEvent::Custom {
+            name: "agent_warning".to_string(),
+            value: serde_json::json!({
+                "kind": kind,
+                "message": message,
+                "details": details,
+            }),
+        });
+    }
+
+    /// A moderator blocked, rewrote or flagged content. For output, `text`
+    /// is what was streamed in place of the model's text.
+    pub fn moderated(
+        &self,
+        stage: crate::moderation::ModerationStage,
+        finding: &crate::moderation::ModerationFinding,
+        text: Option<&str>,
+    ) {
+        let mut value = serde_json::json!({
+            "stage": stage,
+            "action": finding.verdict.action(),
+            "moderator": finding.moderator,
+            "reason": finding.verdict.reason(),
+        });
+        if let Some(text) = text {
+            value["text"] = serde_json::json!(text);
+        }
+        self.emit(AgUiEvent::Custom {
+            name: "moderated".to_string(),
+            value,
+        });
+    }
+
+    /// A tool call's arguments were malformed JSON and were repaired before
+    /// the tool ran.
+    pub fn tool_args_repaired(
+        &self,
+        tool_call_id: &str,
+        tool_name: &str,
+        repairs: &[super::args_repair::Repair],
+    ) {
+        self.emit(AgUiEvent::Custom {
+            name: "tool_args_repaired".to_string(),
+            value: serde_json::json!({
+                "toolCallId": tool_call_id,
+                "toolName": tool_name,
+                "repairs": repairs,
+            }),
+        });
+    }
+
+    /// One-line summary of the turn that just finished, for timeline
+    /// breadcrumbs. `turn` counts the conversation's prompts from 1.
+    pub fn turn_summary(&self, turn: usize, text: &str, source: &str) {
+        self.emit(AgUiEvent::Custom {
+            name: "turn_summary".to_string(),
+            value: serde_json::json!({
+                "turn": turn,
+                "text": text,
+                "source": source,
+            }),
+        });
+    }
+
     pub fn retry(
         &self,
         attempt: u32,
@@ -215,13 +348,10 @@ impl TurnEmitter {
         });
     }
 
-    pub fn compaction(&self, sealed_span_index: usize, consumed_count: usize) {
+    pub fn compaction(&self, compaction: &Compaction<'_>) {
         self.emit(AgUiEvent::Custom {
             name: "compaction".to_string(),
-            value: serde_json::json!({
-                "sealed_span_index": sealed_span_index,
-                "consumed_count": consumed_count,
-            }),
+            value: serde_json::to_value(compaction).unwrap_or_default(),
         });
     }
 
@@ -265,19 +395,19 @@ mod tests {
         emitter.text_delta("msg-1", "world");
         emitter.text_end("msg-1");
 
-        let e1 = serde_json::to_value(&rx.try_recv().unwrap()).unwrap();
+        let e1 = serde_json::to_value(rx.try_recv().unwrap()).unwrap();
         assert_eq!(e1["type"], "TEXT_MESSAGE_START");
         assert_eq!(e1["messageId"], "msg-1");
 
-        let e2 = serde_json::to_value(&rx.try_recv().unwrap()).unwrap();
+        let e2 = serde_json::to_value(rx.try_recv().unwrap()).unwrap();
         assert_eq!(e2["type"], "TEXT_MESSAGE_CONTENT");
         assert_eq!(e2["delta"], "hello ");
 
-        let e3 = serde_json::to_value(&rx.try_recv().unwrap()).unwrap();
+        let e3 = serde_json::to_value(rx.try_recv().unwrap()).unwrap();
         assert_eq!(e3["type"], "TEXT_MESSAGE_CONTENT");
         assert_eq!(e3["delta"], "world");
 
-        let e4 = serde_json::to_value(&rx.try_recv().unwrap()).unwrap();
+        let e4 = serde_json::to_value(rx.try_recv().unwrap()).unwrap();
         assert_eq!(e4["type"], "TEXT_MESSAGE_END");
         assert_eq!(e4["messageId"], "msg-1");
     }
@@ -292,17 +422,17 @@ mod tests {
         emitter.tool_end("tc-1");
         emitter.tool_result("tc-1", "file.txt", false);
 
-        let e1 = serde_json::to_value(&rx.try_recv().unwrap()).unwrap();
+        let e1 = serde_json::to_value(rx.try_recv().unwrap()).unwrap();
         assert_eq!(e1["type"], "TOOL_CALL_START");
         assert_eq!(e1["toolCallName"], "bash");
 
-        let e2 = serde_json::to_value(&rx.try_recv().unwrap()).unwrap();
+        let e2 = serde_json::to_value(rx.try_recv().unwrap()).unwrap();
         assert_eq!(e2["type"], "TOOL_CALL_ARGS");
 
-        let e3 = serde_json::to_value(&rx.try_recv().unwrap()).unwrap();
+        let e3 = serde_json::to_value(rx.try_recv().unwrap()).unwrap();
         assert_eq!(e3["type"], "TOOL_CALL_END");
 
-        let e4 = serde_json::to_value(&rx.try_recv().unwrap()).unwrap();
+        let e4 = serde_json::to_value(rx.try_recv().unwrap()).unwrap();
         assert_eq!(e4["type"], "TOOL_CALL_RESULT");
         assert_eq!(e4["isError"], false);
     }
@@ -312,7 +442,7 @@ mod tests {
         let emitter = make_emitter();
         let mut rx = emitter.sender().subscribe();
         emitter.activity("Reading files...");
-        let json = serde_json::to_value(&rx.try_recv().unwrap()).unwrap();
+        let json = serde_json::to_value(rx.try_recv().unwrap()).unwrap();
         assert_eq!(json["type"], "CUSTOM");
         assert_eq!(json["name"], "activity_update");
         assert_eq!(json["value"]["activity"], "Reading files...");
@@ -323,7 +453,7 @@ mod tests {
         let emitter = make_emitter();
         let mut rx = emitter.sender().subscribe();
         emitter.retry(2, 5, "RateLimit", 4000);
-        let json = serde_json::to_value(&rx.try_recv().unwrap()).unwrap();
+        let json = serde_json::to_value(rx.try_recv().unwrap()).unwrap();
         assert_eq!(json["name"], "retry");
         assert_eq!(json["value"]["attempt"], 2);
         assert_eq!(json["value"]["maxAttempts"], 5);
@@ -335,18 +465,126 @@ mod tests {
         let emitter = make_emitter();
         let mut rx = emitter.sender().subscribe();
         emitter.usage(1000, 500, 800, 200, 200_000, 0.05);
-        let json = serde_json::to_value(&rx.try_recv().unwrap()).unwrap();
+        let json = serde_json::to_value(rx.try_recv().unwrap()).unwrap();
         assert_eq!(json["name"], "usage_update");
         assert_eq!(json["value"]["inputTokens"], 1000);
         assert_eq!(json["value"]["contextWindow"], 200_000);
     }
 
+    #[test]
+    fn call_usage_sends_per_call_fields() {
+        let emitter = make_emitter();
+        let mut rx = emitter.sender().subscribe();
+        emitter.call_usage(&CallUsage {
+            source: "turn",
+            round: Some(2),
+            model: "claude-test",
+            input_tokens: 120,
+            output_tokens: 30,
+            cache_read_input_tokens: 1000,
+            cache_creation_input_tokens: 0,
+            cost: 0.002,
+        });
+        let json = serde_json::to_value(rx.try_recv().unwrap()).unwrap();
+        assert_eq!(json["name"], "inference_usage");
+        assert_eq!(json["value"]["source"], "turn");
+        assert_eq!(json["value"]["round"], 2);
+        assert_eq!(json["value"]["inputTokens"], 120);
+        assert_eq!(json["value"]["cacheReadInputTokens"], 1000);
+        assert_eq!(json["value"]["cost"], 0.002);
+    }
+
+    #[test]
+    fn call_usage_omits_round_for_compaction() {
+        let emitter = make_emitter();
+        let mut rx = emitter.sender().subscribe();
+        emitter.call_usage(&CallUsage {
+            source: "compaction",
+            round: None,
+            model: "claude-test",
+            input_tokens: 5000,
+            output_tokens: 400,
+            cache_read_input_tokens: 0,
+            cache_creation_input_tokens: 0,
+            cost: 0.01,
+        });
+        let json = serde_json::to_value(rx.try_recv().unwrap()).unwrap();
+        assert_eq!(json["value"]["source"], "compaction");
+        assert!(json["value"].get("round").is_none());
+    }
+
+    #[test]
+    fn warning_truncates_long_messages() {
+        let emitter = make_emitter();
+        let mut rx = emitter.sender().subscribe();
+        emitter.warning(
+            "tool_error",
+            "x".repeat(2000),
+            serde_json::json!({"toolName": "bash", "toolCallId": "tc1"}),
+        );
+        let json = serde_json::to_value(rx.try_recv().unwrap()).unwrap();
+        assert_eq!(json["name"], "agent_warning");
+        assert_eq!(json["value"]["kind"], "tool_error");
+        assert_eq!(json["value"]["details"]["toolName"], "bash");
+        assert_eq!(
+            json["value"]["message"].as_str().unwrap().chars().count(),
+            WARNING_MESSAGE_MAX_CHARS + 1
+        );
+    }
+
+    #[test]
+    fn moderated_event() {
+        use crate::moderation::{ModerationFinding, ModerationStage, ModerationVerdict};
+
+        let emitter = make_emitter();
+        let mut rx = emitter.sender().subscribe();
+        let finding = ModerationFinding {
+            moderator: "keyword".into(),
+            verdict: ModerationVerdict::Block { reason: "secrets".into() },
+        };
+        emitter.moderated(ModerationStage::Output, &finding, Some("[withheld]"));
+        let json = serde_json::to_value(rx.try_recv().unwrap()).unwrap();
+        assert_eq!(json["name"], "moderated");
+        assert_eq!(json["value"]["stage"], "output");
+        assert_eq!(json["value"]["action"], "block");
+        assert_eq!(json["value"]["moderator"], "keyword");
+        assert_eq!(json["value"]["reason"], "secrets");
+        assert_eq!(json["value"]["text"], "[withheld]");
+    }
+
+    #[test]
+    fn tool_args_repaired_event() {
+        use crate::agent::args_repair::Repair;
+
+        let emitter = make_emitter();
+        let mut rx = emitter.sender().subscribe();
+        emitter.tool_args_repaired("tc1", "write_file", &[Repair::TrailingComma, Repair::SingleQuotes]);
+        let json = serde_json::to_value(rx.try_recv().unwrap()).unwrap();
+        assert_eq!(json["name"], "tool_args_repaired");
+        assert_eq!(json["value"]["toolCallId"], "tc1");
+        assert_eq!(json["value"]["toolName"], "write_file");
+        assert_eq!(json["value"]["repairs"], serde_json::json!(["trailing_comma", "single_quotes"]));
+    }
+
+    #[test]
+    fn turn_summary_event() {
+        let emitter = make_emitter();
+        let mut rx = emitter.sender().subscribe();
+        emitter.turn_summary(3, "Fixed the test · edit_file", "heuristic");
+        let json = serde_json::to_value(rx.try_recv().unwrap()).unwrap();
+        assert_eq!(json["name"], "turn_summary");
+        assert_eq!(json["runId"], "run-1");
+        assert_eq!(json["value"]["turn"], 3);
+        assert_eq!(json["value"]["text"], "Fixed the test · edit_file");
+        assert_eq!(json["value"]["source"], "heuristic");
+    }
+
     #[test]
     fn run_error_with_details() {
         let emitter = make_emitter();
         let mut rx = emitter.sender().subscribe();
         emitter.run_error("boom", Some(serde_json::json!({"kind": "ContextLength"})));
-        let json = serde_json::to_value(&rx.try_recv().unwrap()).unwrap();
+        let json = serde_json::to_value(rx.try_recv().unwrap()).unwrap();
         assert_eq!(json["type"], "RUN_ERROR");
         assert_eq!(json["message"], "boom");
         assert_eq!(json["details"]["kind"], "ContextLength");
@@ -356,11 +594,22 @@ mod tests {
     fn compaction_event() {
         let emitter = make_emitter();
         let mut rx = emitter.sender().subscribe();
-        emitter.compaction(3, 42);
-        let json = serde_json::to_value(&rx.try_recv().unwrap()).unwrap();
+        emitter.compaction(&Compaction {
+            sealed_span_index: 3,
+            consumed_count: 42,
+            summary: "User is refactoring the parser.",
+            compaction_number: 4,
+            full: false,
+            trigger: CompactionTrigger::Threshold,
+        });
+        let json = serde_json::to_value(rx.try_recv().unwrap()).unwrap();
         assert_eq!(json["name"], "compaction");
         assert_eq!(json["value"]["sealed_span_index"], 3);
         assert_eq!(json["value"]["consumed_count"], 42);
+        assert_eq!(json["value"]["summary"], "User is refactoring the parser.");
+        assert_eq!(json["value"]["compaction_number"], 4);
+        assert_eq!(json["value"]["full"], false);
+        assert_eq!(json["value"]["trigger"], "threshold");
     }
 
     #[test]
@@ -371,7 +620,7 @@ mod tests {
             "summary": "found 3 files",
             "input_tokens": 100,
         }));
-        let json = serde_json::to_value(&rx.try_recv().unwrap()).unwrap();
+        let json = serde_json::to_value(rx.try_recv().unwrap()).unwrap();
         assert_eq!(json["name"], "sub_agent_end");
         assert_eq!(json["value"]["agent_type"], "explore");
         assert_eq!(json["value"]["summary"], "found 3 files");
@@ -1,10 +1,17 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+
+pub mod replay;
+
+/// Version of the event wire format. Bump when a variant or field is
+/// renamed or removed; adding variants or optional fields does not require
+/// a bump. Sent to clients in the `SYNC` event.
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
 
 /// AG-UI protocol events streamed to the frontend via SSE.
 ///
 /// Event-specific data only — routing metadata (`threadId`, `runId`) lives
 /// on [`EventEnvelope`], which wraps this enum for broadcast.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum AgUiEvent {
     #[serde(rename = "RUN_STARTED")]
@@ -73,10 +80,20 @@ pub enum AgUiEvent {
     Sync {
         #[serde(rename = "activeRuns")]
         active_runs: Vec<String>,
+        #[serde(rename = "schemaVersion", default)]
+        schema_version: u32,
     },
 }
 
 impl AgUiEvent {
+    /// SYNC event stamped with the current [`EVENT_SCHEMA_VERSION`].
+    pub fn sync(active_runs: Vec<String>) -> Self {
+        Self::Sync {
+            active_runs,
+            schema_version: EVENT_SCHEMA_VERSION,
+        }
+    }
+
     pub fn is_run_started(&self) -> bool {
         matches!(self, Self::RunStarted)
     }
@@ -89,12 +106,13 @@ impl AgUiEvent {
 /// Envelope wrapping an [`AgUiEvent`] with routing metadata.
 ///
 /// Serializes to a flat JSON object that merges `threadId`/`runId` with the
-/// event's own fields, preserving the existing AG-UI wire format.
-#[derive(Debug, Clone, Serialize)]
+/// event's own fields, preserving the existing AG-UI wire format. Also
+/// deserializes from that format (e.g. lines of a conversation event log).
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EventEnvelope {
-    #[serde(rename = "threadId", skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "threadId", default, skip_serializing_if = "Option::is_none")]
     pub thread_id: Option<String>,
-    #[serde(rename = "runId", skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "runId", default, skip_serializing_if = "Option::is_none")]
     pub run_id: Option<String>,
     #[serde(flatten)]
     pub event: AgUiEvent,
@@ -120,7 +138,7 @@ mod tests {
 
     #[test]
     fn run_started_serializes_correctly() {
-        let json = serde_json::to_value(&envelope(AgUiEvent::RunStarted)).unwrap();
+        let json = serde_json::to_value(envelope(AgUiEvent::RunStarted)).unwrap();
         assert_eq!(json["type"], "RUN_STARTED");
         assert_eq!(json["threadId"], "t1");
         assert_eq!(json["runId"], "r1");
@@ -128,7 +146,7 @@ mod tests {
 
     #[test]
     fn text_message_content_serializes_correctly() {
-        let json = serde_json::to_value(&envelope(AgUiEvent::TextMessageContent {
+        let json = serde_json::to_value(envelope(AgUiEvent::TextMessageContent {
             message_id: "m1".into(),
             delta: "hello".into(),
         }))
@@ -140,7 +158,7 @@ mod tests {
 
     #[test]
     fn tool_call_result_serializes_correctly() {
-        let json = serde_json::to_value(&envelope(AgUiEvent::ToolCallResult {
+        let json = serde_json::to_value(envelope(AgUiEvent::ToolCallResult {
             tool_call_id: "tc1".into(),
             content: "output".into(),
             is_error: true,
@@ -153,7 +171,7 @@ mod tests {
 
     #[test]
     fn custom_event_serializes_correctly() {
-        let json = serde_json::to_value(&envelope(AgUiEvent::Custom {
+        let json = serde_json::to_value(envelope(AgUiEvent::Custom {
             name: "bg_process_started".into(),
             value: serde_json::json!({"id": "p1"}),
         }))
@@ -165,7 +183,7 @@ mod tests {
 
     #[test]
     fn run_error_optional_details() {
-        let json = serde_json::to_value(&envelope(AgUiEvent::RunError {
+        let json = serde_json::to_value(envelope(AgUiEvent::RunError {
             message: "boom".into(),
             details: None,
         }))
@@ -176,14 +194,14 @@ mod tests {
 
     #[test]
     fn run_finished_has_running_processes_serializes() {
-        let json = serde_json::to_value(&envelope(AgUiEvent::RunFinished {
+        let json = serde_json::to_value(envelope(AgUiEvent::RunFinished {
             has_running_processes: true,
         }))
         .unwrap();
         assert_eq!(json["type"], "RUN_FINISHED");
         assert_eq!(json["hasRunningProcesses"], true);
 
-        let json_false = serde_json::to_value(&envelope(AgUiEvent::RunFinished {
+        let json_false = serde_json::to_value(envelope(AgUiEvent::RunFinished {
             has_running_processes: false,
         }))
         .unwrap();
@@ -195,15 +213,50 @@ mod tests {
         let env = EventEnvelope {
             thread_id: None,
             run_id: None,
-            event: AgUiEvent::Sync {
-                active_runs: vec!["conv1".into()],
-            },
+            event: AgUiEvent::sync(vec!["conv1".into()]),
         };
         let json = serde_json::to_value(&env).unwrap();
         assert_eq!(json["type"], "SYNC");
         assert!(json.get("threadId").is_none());
         assert!(json.get("runId").is_none());
         assert_eq!(json["activeRuns"], serde_json::json!(["conv1"]));
+        assert_eq!(json["schemaVersion"], EVENT_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn envelope_round_trips_through_wire_format() {
+        let events = [
+            envelope(AgUiEvent::ToolCallResult {
+                tool_call_id: "tc1".into(),
+                content: "output".into(),
+                is_error: false,
+            }),
+            envelope(AgUiEvent::RunError {
+                message: "boom".into(),
+                details: None,
+            }),
+            EventEnvelope {
+                thread_id: Some("t1".into()),
+                run_id: None,
+                event: AgUiEvent::Custom {
+                    name: "title_update".into(),
+                    value: serde_json::json!({"title": "T"}),
+                },
+            },
+        ];
+        for env in events {
+            let json = serde_json::to_string(&env).unwrap();
+            let back: EventEnvelope = serde_json::from_str(&json).unwrap();
+            assert_eq!(serde_json::to_string(&back).unwrap(), json);
+        }
+    }
+
+    #[test]
+    fn run_finished_without_flag_deserializes() {
+        let env: EventEnvelope =
+            serde_json::from_str(r#"{"type":"RUN_FINISHED","threadId":"t1"}"#).unwrap();
+        assert!(env.event.is_run_terminal());
+        assert!(env.run_id.is_none());
     }
 
     #[test]
@@ -214,7 +267,7 @@ mod tests {
         let env_none = EventEnvelope {
             thread_id: None,
             run_id: None,
-            event: AgUiEvent::Sync { active_runs: vec![] },
+            event: AgUiEvent::sync(vec![]),
         };
         assert_eq!(env_none.thread_id(), None);
     }
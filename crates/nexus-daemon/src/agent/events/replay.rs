@@ -0,0 +1,364 @@
+//! Rebuild a transcript from a persisted event log.
+//!
+//! The conversation file is the source of truth, but when it's lost or
+//! suspect the JSONL event log (`conversations.event_log`) still has every
+//! turn-scoped event. [`replay`] folds those events back into assistant
+//! messages and tool results in the same [`ChatMessage`] shape the
+//! conversation store uses, with compaction boundaries and run errors kept
+//! as separate entries.
+//!
+//! User messages are never broadcast as events, so they don't appear in a
+//! replayed transcript.
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::{AgUiEvent, EventEnvelope};
+use crate::conversation::{ChatMessage, MessagePart, MessageRole};
+
+#[derive(Debug, Default, Serialize)]
+pub struct Transcript {
+    pub entries: Vec<TranscriptEntry>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TranscriptEntry {
+    Message(ChatMessage),
+    /// Context was compacted here; messages before it were summarized.
+    Compaction {
+        sealed_span_index: u64,
+        consumed_count: u64,
+    },
+    /// The run ended with an error after the preceding messages.
+    RunError { run_id: String, message: String },
+}
+
+/// Reconstruct a transcript from events in log order. Lines that don't
+/// parse as an [`EventEnvelope`] are skipped.
+pub fn replay<'a>(events: impl IntoIterator<Item = &'a serde_json::Value>) -> Transcript {
+    let mut replayer = Replayer::default();
+    for value in events {
+        if let Ok(envelope) = serde_json::from_value::<EventEnvelope>(value.clone()) {
+            replayer.apply(&envelope);
+        }
+    }
+    replayer.flush();
+    replayer.transcript
+}
+
+/// The last run in `events` that started but never finished or errored (the
+/// daemon stopped mid-turn), with the rounds it completed: assistant
+/// messages whose tool calls all have results, and those results. Returns
+/// the run id and its messages, which may be empty.
+pub fn interrupted_run<'a>(
+    events: impl IntoIterator<Item = &'a serde_json::Value>,
+) -> Option<(String, Vec<ChatMessage>)> {
+    let envelopes: Vec<EventEnvelope> = events
+        .into_iter()
+        .filter_map(|value| serde_json::from_value(value.clone()).ok())
+        .collect();
+    let run_id = envelopes
+        .iter()
+        .rev()
+        .find(|e| e.event.is_run_started())
+        .and_then(|e| e.run_id.clone())?;
+    let in_run = |e: &&EventEnvelope| e.run_id.as_deref() == Some(run_id.as_str());
+    if envelopes.iter().filter(in_run).any(|e| e.event.is_run_terminal()) {
+        return None;
+    }
+
+    let mut replayer = Replayer::default();
+    for envelope in envelopes.iter().filter(in_run) {
+        replayer.apply(envelope);
+    }
+    replayer.flush();
+    let messages = replayer
+        .transcript
+        .entries
+        .into_iter()
+        .filter_map(|e| match e {
+            TranscriptEntry::Message(m) => Some(m),
+            _ => None,
+        })
+        .collect();
+    Some((run_id, complete_rounds(messages)))
+}
+
+/// Cut `messages` after the last point where every tool call has a result,
+/// so the history stays valid for the provider.
+fn complete_rounds(mut messages: Vec<ChatMessage>) -> Vec<ChatMessage> {
+    let mut keep = 0;
+    while let Some(msg) = messages.get(keep) {
+        if msg.role != MessageRole::Assistant {
+            break;
+        }
+        let calls: Vec<&str> = msg
+            .parts
+            .iter()
+            .filter_map(|p| match p {
+                MessagePart::ToolCall { tool_call_id, .. } => Some(tool_call_id.as_str()),
+                _ => None,
+            })
+            .collect();
+        if calls.is_empty() {
+            keep += 1;
+            continue;
+        }
+        let answered = messages.get(keep + 1).is_some_and(|results| {
+            results.role == MessageRole::User
+                && calls.iter().all(|id| {
+                    results.parts.iter().any(
+                        |p| matches!(p, MessagePart::ToolResult { tool_call_id, .. } if tool_call_id == id),
+                    )
+                })
+        });
+        if !answered {
+            break;
+        }
+        keep += 2;
+    }
+    messages.truncate(keep);
+    messages
+}
+
+#[derive(Default)]
+struct Replayer {
+    transcript: Transcript,
+    run_id: Option<String>,
+    assistant: Vec<MessagePart>,
+    results: Vec<MessagePart>,
+    tool_args: HashMap<String, String>,
+}
+
+impl Replayer {
+    fn apply(&mut self, envelope: &EventEnvelope) {
+        if envelope.run_id.is_some() && envelope.run_id != self.run_id {
+            self.flush();
+            self.run_id = envelope.run_id.clone();
+        }
+
+        match &envelope.event {
+            AgUiEvent::TextMessageStart { .. } => {
+                self.start_assistant_part(MessagePart::Text { text: String::new() });
+            }
+            AgUiEvent::TextMessageContent { delta, .. } => {
+                match self.assistant.last_mut() {
+                    Some(MessagePart::Text { text }) if self.results.is_empty() => text.push_str(delta),
+                    _ => self.start_assistant_part(MessagePart::Text { text: delta.clone() }),
+                }
+            }
+            AgUiEvent::ToolCallStart { tool_call_id, tool_call_name } => {
+                self.start_assistant_part(MessagePart::ToolCall {
+                    tool_call_id: tool_call_id.clone(),
+                    tool_name: tool_call_name.clone(),
+                    args: serde_json::Value::Null,
+                    result: None,
+                    is_error: false,
+                });
+            }
+            AgUiEvent::ToolCallArgs { tool_call_id, delta } => {
+                self.tool_args.entry(tool_call_id.clone()).or_default().push_str(delta);
+            }
+            AgUiEvent::ToolCallEnd { tool_call_id } => {
+                let raw = self.tool_args.remove(tool_call_id).unwrap_or_default();
+                let parsed = serde_json::from_str(&raw).unwrap_or(serde_json::Value::String(raw));
+                for part in &mut self.assistant {
+                    if let MessagePart::ToolCall { tool_call_id: id, args, .. } = part {
+                        if id == tool_call_id {
+                            *args = parsed;
+                            break;
+                        }
+                    }
+                }
+            }
+            AgUiEvent::ToolCallResult { tool_call_id, content, is_error } => {
+                self.results.push(MessagePart::ToolResult {
+                    tool_call_id: tool_call_id.clone(),
+                    result: content.clone(),
+                    is_error: *is_error,
+                    images: Vec::new(),
+                });
+            }
+            AgUiEvent::Custom { name, value } => match name.as_str() {
+                "thinking_start" => {
+                    self.start_assistant_part(MessagePart::Thinking { thinking: String::new() });
+                }
+                "thinking_delta" => {
+                    if let Some(MessagePart::Thinking { thinking }) = self.assistant.last_mut() {
+                        thinking.push_str(value["delta"].as_str().unwrap_or_default());
+                    }
+                }
+                "compaction" => {
+                    self.flush();
+                    self.transcript.entries.push(TranscriptEntry::Compaction {
+                        sealed_span_index: value["sealed_span_index"].as_u64().unwrap_or_default(),
+                        consumed_count: value["consumed_count"].as_u64().unwrap_or_default(),
+                    });
+                }
+                _ => {}
+            },
+            AgUiEvent::RunError { message, .. } => {
+                self.flush();
+                self.transcript.entries.push(TranscriptEntry::RunError {
+                    run_id: self.run_id.clone().unwrap_or_default(),
+                    message: message.clone(),
+                });
+            }
+            AgUiEvent::RunFinished { .. } => self.flush(),
+            _ => {}
+        }
+    }
+
+    /// New assistant output after tool results starts the next round's message.
+    fn start_assistant_part(&mut self, part: MessagePart) {
+        if !self.results.is_empty() {
+            self.flush();
+        }
+        self.assistant.push(part);
+    }
+
+    fn flush(&mut self) {
+        let assistant = std::mem::take(&mut self.assistant);
+        let results = std::mem::take(&mut self.results);
+        for (role, parts) in [(MessageRole::Assistant, assistant), (MessageRole::User, results)] {
+            if parts.is_empty() {
+                continue;
+            }
+            self.transcript.entries.push(TranscriptEntry::Message(ChatMessage {
+                id: Uuid::new_v4().to_string(),
+                role,
+                parts,
+                timestamp: Utc::now(),
+                parent_id: None,
+                source: None,
+                metadata: self.run_id.as_ref().map(|r| serde_json::json!({ "runId": r })),
+            }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn ev(run: &str, event: serde_json::Value) -> serde_json::Value {
+        let mut event = event;
+        event["threadId"] = json!("t1");
+        event["runId"] = json!(run);
+        event
+    }
+
+    #[test]
+    fn replays_tool_round_trip_and_compaction() {
+        let events = vec![
+            ev("r1", json!({"type": "RUN_STARTED"})),
+            ev("r1", json!({"type": "TEXT_MESSAGE_START", "messageId": "m1"})),
+            ev("r1", json!({"type": "TEXT_MESSAGE_CONTENT", "messageId": "m1", "delta": "Let me "})),
+            ev("r1", json!({"type": "TEXT_MESSAGE_CONTENT", "messageId": "m1", "delta": "look"})),
+            ev("r1", json!({"type": "TEXT_MESSAGE_END", "messageId": "m1"})),
+            ev("r1", json!({"type": "TOOL_CALL_START", "toolCallId": "tc1", "toolCallName": "bash"})),
+            ev("r1", json!({"type": "TOOL_CALL_ARGS", "toolCallId": "tc1", "delta": "{\"command\":"})),
+            ev("r1", json!({"type": "TOOL_CALL_ARGS", "toolCallId": "tc1", "delta": "\"ls\"}"})),
+            ev("r1", json!({"type": "TOOL_CALL_END", "toolCallId": "tc1"})),
+            ev("r1", json!({"type": "TOOL_CALL_RESULT", "toolCallId": "tc1", "content": "a.txt", "isError": false})),
+            ev("r1", json!({"type": "TEXT_MESSAGE_START", "messageId": "m2"})),
+            ev("r1", json!({"type": "TEXT_MESSAGE_CONTENT", "messageId": "m2", "delta": "Done"})),
+            json!("truncated line"),
+            ev("r1", json!({"type": "RUN_FINISHED"})),
+            ev("r2", json!({"type": "RUN_STARTED"})),
+            ev("r2", json!({"type": "CUSTOM", "name": "compaction", "value": {"sealed_span_index": 0, "consumed_count": 4}})),
+            ev("r2", json!({"type": "RUN_ERROR", "message": "boom"})),
+        ];
+
+        let transcript = replay(&events);
+        let kinds: Vec<String> = transcript
+            .entries
+            .iter()
+            .map(|e| match e {
+                TranscriptEntry::Message(m) => format!("{:?}", m.role),
+                TranscriptEntry::Compaction { .. } => "Compaction".into(),
+                TranscriptEntry::RunError { .. } => "RunError".into(),
+            })
+            .collect();
+        assert_eq!(kinds, ["Assistant", "User", "Assistant", "Compaction", "RunError"]);
+
+        let TranscriptEntry::Message(first) = &transcript.entries[0] else { unreachable!() };
+        assert!(matches!(&first.parts[0], MessagePart::Text { text } if text == "Let me look"));
+        assert!(matches!(&first.parts[1], MessagePart::ToolCall { args, .. } if args["command"] == "ls"));
+
+        let TranscriptEntry::Message(results) = &transcript.entries[1] else { unreachable!() };
+        assert!(matches!(&results.parts[0], MessagePart::ToolResult { result, .. } if result == "a.txt"));
+
+        assert!(matches!(
+            &transcript.entries[4],
+            TranscriptEntry::RunError { run_id, message } if run_id == "r2" && message == "boom"
+        ));
+    }
+
+    #[test]
+    fn interrupted_run_keeps_only_answered_tool_rounds() {
+        let call = |run: &str, id: &str| {
+            [
+                ev(run, json!({"type": "TOOL_CALL_START", "toolCallId": id, "toolCallName": "bash"})),
+                ev(run, json!({"type": "TOOL_CALL_ARGS", "toolCallId": id, "delta": "{}"})),
+                ev(run, json!({"type": "TOOL_CALL_END", "toolCallId": id})),
+            ]
+        };
+        let result = |run: &str, id: &str| {
+            ev(run, json!({"type": "TOOL_CALL_RESULT", "toolCallId": id, "content": "ok", "isError": false}))
+        };
+
+        let mut events = vec![ev("r1", json!({"type": "RUN_STARTED"}))];
+        events.extend(call("r1", "tc1"));
+        events.push(result("r1", "tc1"));
+        events.push(ev("r1", json!({"type": "RUN_FINISHED"})));
+        assert!(interrupted_run(&events).is_none());
+
+        events.push(ev("r2", json!({"type": "RUN_STARTED"})));
+        events.extend(call("r2", "tc2"));
+        events.extend(call("r2", "tc3"));
+        events.push(result("r2", "tc2"));
+        events.push(result("r2", "tc3"));
+        events.extend(call("r2", "tc4"));
+        let (run_id, messages) = interrupted_run(&events).unwrap();
+        assert_eq!(run_id, "r2");
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].parts.len(), 2);
+        assert_eq!(messages[1].role, MessageRole::User);
+
+        // Nothing survives when the only round is still missing a result.
+        events.truncate(events.len() - 5);
+        events.push(result("r2", "tc2"));
+        let (_, messages) = interrupted_run(&events).unwrap();
+        assert!(messages.is_empty());
+    }
+
+    #[test]
+    fn interrupted_run_recovers_only_moderated_text() {
+        // Output moderation runs before a text block streams, so the log
+        // holds the replacement and never the model's original text.
+        let events = vec![
+            ev("r1", json!({"type": "RUN_STARTED"})),
+            ev("r1", json!({"type": "CUSTOM", "name": "moderated", "value": {
+                "stage": "output", "action": "block", "reason": "secrets",
+                "text": "[Response withheld by moderation: secrets]",
+            }})),
+            ev("r1", json!({"type": "TEXT_MESSAGE_START", "messageId": "m1"})),
+            ev("r1", json!({"type": "TEXT_MESSAGE_CONTENT", "messageId": "m1",
+                "delta": "[Response withheld by moderation: secrets]"})),
+            ev("r1", json!({"type": "TEXT_MESSAGE_END", "messageId": "m1"})),
+        ];
+
+        let (_, messages) = interrupted_run(&events).unwrap();
+        assert_eq!(messages.len(), 1);
+        assert!(matches!(
+            &messages[0].parts[..],
+            [MessagePart::Text { text }] if text == "[Response withheld by moderation: secrets]"
+        ));
+    }
+}
@@ -1,3 +1,5 @@
+pub mod args_repair;
+pub mod best_of;
 pub mod emitter;
 pub mod events;
 pub mod run;
@@ -11,6 +13,7 @@ use crate::bg_process::ProcessManager;
 use crate::config::{FetchConfig, FilesystemConfig};
 use crate::mcp::McpManager;
 use crate::module::ModuleRegistry;
+use crate::pruned_results::PrunedResultStore;
 use nexus_provider::InferenceProvider;
 use nexus_core::tasks::TaskStateStore;
 
@@ -48,6 +51,8 @@ pub struct AgentTurnResult {
     /// Structured error details (serialized ProviderError) for the frontend.
     #[allow(dead_code)] // read by the caller that constructs AgentTurnResult
     pub error_details: Option<serde_json::Value>,
+    /// Stop reason of the final round, if the provider reported one.
+    pub stop_reason: Option<nexus_provider::types::StopReason>,
 }
 
 /// Inference configuration for a single turn.
@@ -59,6 +64,12 @@ pub struct InferenceConfig<'a> {
     pub thinking_budget: Option<u32>,
     pub system_prompt: Option<String>,
     pub state_update: Option<String>,
+    /// Sample the first round N times and keep the judge's pick.
+    pub best_of: Option<best_of::BestOf>,
+    /// Drop the oldest exchanges rather than fail on context overflow.
+    pub hard_context_cap: bool,
+    /// Inject a reflection prompt after this many tool errors in a row.
+    pub reflect_after_errors: Option<u32>,
 }
 
 /// Conversation context for a single turn.
@@ -77,13 +88,59 @@ pub struct TurnServices<'a> {
     pub filesystem_config: &'a FilesystemConfig,
     pub task_store: &'a tokio::sync::RwLock<TaskStateStore>,
     pub pending_questions: &'a tokio::sync::RwLock<PendingQuestionStore>,
+    pub pruned_results: &'a PrunedResultStore,
     pub process_manager: Option<Arc<ProcessManager>>,
     pub bg_sub_agent_deps: Option<Arc<sub_agent::BgSubAgentDeps>>,
     pub control_plane: Option<Arc<crate::control_plane::ControlPlaneDeps>>,
+    /// Skills `load_skill` reads from. `None` for background sub-agents.
+    pub skills: Option<Arc<crate::skills::SkillLibrary>>,
     /// Module registry for hook dispatch.
     pub modules: Arc<ModuleRegistry>,
+    /// Output moderation. `None` for sub-agents, whose text goes to the
+    /// parent agent rather than the user.
+    pub moderation: Option<Arc<crate::moderation::Moderation>>,
+    /// Final-answer validation for the active agent. `None` for sub-agents.
+    pub guardrails: Option<crate::guardrail::GuardrailSet>,
 }
 
+/// Failures raised by the agent loop itself, before or around inference.
+/// Provider failures surface as `nexus_provider::error::ProviderError`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AgentError {
+    /// The request's estimated input tokens exceed the model's context
+    /// window, even after emergency pruning.
+    ContextOverflow { estimated: u32, window: u32 },
+}
+
+impl AgentError {
+    /// Structured details for `RUN_ERROR`, shaped like a serialized
+    /// `ProviderError` so the frontend can show both the same way.
+    pub fn details(&self) -> serde_json::Value {
+        match self {
+            Self::ContextOverflow { estimated, window } => serde_json::json!({
+                "kind": "ContextOverflow",
+                "message": self.to_string(),
+                "retryable": false,
+                "estimated": estimated,
+                "window": window,
+            }),
+        }
+    }
+}
+
+impl std::fmt::Display for AgentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ContextOverflow { estimated, window } => write!(
+                f,
+                "Context overflow: ~{estimated} input tokens exceed the model's {window}-token context window"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AgentError {}
+
 pub fn context_window_for_model(model: &str) -> u32 {
     nexus_pricing::context_window(model)
 }
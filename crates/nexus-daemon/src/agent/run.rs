@@ -4,23 +4,25 @@ use std::time::Instant;
 use anyhow::Result;
 use futures::StreamExt;
 use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
 
 use nexus_provider::types::*;
 use crate::bg_process::ProcessManager;
 use crate::bg_process::tools::BgProcessToolHandler;
 use crate::system_prompt::fence_tool_result;
-use super::emitter::TurnEmitter;
+use super::emitter::{CallUsage, TurnEmitter};
 use super::sub_agent::SubAgentHandler;
 use super::tool_dispatch::{
     self, AskUserHandler, BashHandler, ControlPlaneHandler, FetchHandler, FilesystemHandler,
-    McpToolHandler, ResourceToolHandler, TaskToolHandler, ToolContext,
+    McpToolHandler, PrunedResultHandler, ResourceToolHandler, SkillHandler, TaskToolHandler,
+    ToolContext,
 };
 use crate::module::{
     PreToolUseEvent, PreToolUseDecision, PostToolUseEvent, PostToolUseFailureEvent,
-    StopEvent, StopDecision, PreCompactEvent, CompactionLayer,
+    StopEvent, StopDecision, PreCompactEvent, CompactionLayer, ToolImage,
 };
 use nexus_provider::InferenceRequest;
-use super::{AgentTurnResult, InferenceConfig, TimingSpan, TurnContext, TurnServices};
+use super::{AgentError, AgentTurnResult, InferenceConfig, TimingSpan, TurnContext, TurnServices};
 
 const MAX_ROUNDS: usize = 50;
 
@@ -41,6 +43,8 @@ struct StreamResult {
     output_tokens: u32,
     cache_creation_input_tokens: u32,
     cache_read_input_tokens: u32,
+    /// Server-side web searches the provider ran, billed per request.
+    web_search_requests: u32,
 }
 
 /// Runs a single agent turn: inference → tool calls → loop.
@@ -72,9 +76,15 @@ pub async fn run_agent_turn(
     let context_window = super::context_window_for_model(inference.model);
     let mut turn_error: Option<String> = None;
     let mut turn_error_details: Option<serde_json::Value> = None;
+    let mut final_stop_reason: Option<StopReason> = None;
     let mut turn_cost: f64 = 0.0;
     let mut retried_after_prune = false;
+    // Best-of-N samples the first request that goes out, which pre-flight
+    // pruning or trimming can push past round 0.
+    let mut sampled_first = false;
     let mut retry_count: u32 = 0;
+    let mut guardrail_retries: u32 = 0;
+    let mut error_streak = ErrorStreak::new(inference.reflect_after_errors);
 
     // Construct stable handlers once — these don't change between rounds.
     let ask_handler = AskUserHandler { pending_questions: services.pending_questions };
@@ -102,7 +112,11 @@ pub async fn run_agent_turn(
     let control_plane_handler = services.control_plane.as_ref().map(|deps| ControlPlaneHandler {
         deps: Arc::clone(deps),
     });
+    let skill_handler = services.skills.as_ref().map(|library| SkillHandler {
+        library: Arc::clone(library),
+    });
     let resource_handler = ResourceToolHandler { mcp: services.mcp };
+    let pruned_handler = PrunedResultHandler { store: services.pruned_results };
     let mcp_handler = McpToolHandler { mcp: services.mcp };
 
     for round in 0..MAX_ROUNDS {
@@ -150,7 +164,8 @@ pub async fn run_agent_turn(
                     threshold = aggressive_threshold,
                     "Mid-turn aggressive pruning (>85% context)"
                 );
-                nexus_compaction::prune_tool_results(&mut messages, 1);
+                let pruned = nexus_compaction::prune_tool_results(&mut messages, 1);
+                services.pruned_results.insert(conversation_id, pruned);
             } else if estimated > prune_threshold {
                 // HOOK: PreCompact
                 services.modules.fire_pre_compact(&PreCompactEvent {
@@ -166,7 +181,8 @@ pub async fn run_agent_turn(
                     threshold = prune_threshold,
                     "Mid-turn pruning (>70% context)"
                 );
-                nexus_compaction::prune_tool_results(&mut messages, 3);
+                let pruned = nexus_compaction::prune_tool_results(&mut messages, 3);
+                services.pruned_results.insert(conversation_id, pruned);
             }
         }
 
@@ -184,27 +200,122 @@ pub async fn run_agent_turn(
             inject_state_update(&mut messages_for_api, state);
         }
 
-        let stream = match inference.provider
-            .create_message_stream(InferenceRequest {
-                model: inference.model.to_string(),
-                max_tokens: inference.max_tokens,
-                system: inference.system_prompt.clone(),
-                temperature: inference.temperature,
-                thinking_budget: inference.thinking_budget,
-                messages: messages_for_api,
-                tools: tools.clone(),
-            })
-            .await
-        {
-            Ok(s) => s,
+        let llm_span = tracing::info_span!(
+            "chat",
+            otel.name = format!("chat {}", inference.model),
+            otel.kind = "client",
+            gen_ai.operation.name = "chat",
+            gen_ai.request.model = %inference.model,
+            gen_ai.request.max_tokens = inference.max_tokens,
+            nexus.round = round,
+            gen_ai.usage.input_tokens = tracing::field::Empty,
+            gen_ai.usage.output_tokens = tracing::field::Empty,
+            gen_ai.usage.cache_read_input_tokens = tracing::field::Empty,
+            gen_ai.usage.cache_creation_input_tokens = tracing::field::Empty,
+            gen_ai.response.finish_reasons = tracing::field::Empty,
+            nexus.cost_usd = tracing::field::Empty,
+            error.type = tracing::field::Empty,
+        );
+
+        let mut request = InferenceRequest {
+            model: inference.model.to_string(),
+            max_tokens: inference.max_tokens,
+            system: inference.system_prompt.clone(),
+            temperature: inference.temperature,
+            thinking_budget: inference.thinking_budget,
+            messages: messages_for_api,
+            tools: tools.clone(),
+            idempotency_key: None,
+        };
+        // Pre-flight budget check: an oversized request would only come
+        // back as an opaque 400. Prune every tool result once and retry the
+        // round; if that still doesn't fit, drop the oldest exchanges when
+        // the hard cap is on, else fail with the numbers.
+        if let Err(overflow) = check_context_budget(&request, context_window) {
+            if !retried_after_prune {
+                retried_after_prune = true;
+                tracing::warn!(%overflow, "Pre-flight context overflow, emergency pruning");
+                services.modules.fire_pre_compact(&PreCompactEvent {
+                    conversation_id,
+                    estimated_tokens: nexus_compaction::estimate_tokens(
+                        &request.messages,
+                        request.system.as_deref(),
+                        &request.tools,
+                    ),
+                    context_window,
+                    layer: CompactionLayer::Prune,
+                }).await;
+                let pruned = nexus_compaction::prune_tool_results(&mut messages, 0);
+                services.pruned_results.insert(conversation_id, pruned);
+                continue;
+            }
+            if inference.hard_context_cap {
+                // System prompt, tools and the state update stay as they are.
+                let overhead = nexus_compaction::estimate_tokens(
+                    &request.messages,
+                    request.system.as_deref(),
+                    &request.tools,
+                )
+                .saturating_sub(nexus_compaction::estimate_tokens(&messages, None, &[]));
+                let dropped = nexus_compaction::trim_to_fit(
+                    &mut messages,
+                    context_window.saturating_sub(overhead),
+                );
+                if !dropped.is_empty() {
+                    for exchange in &dropped {
+                        tracing::warn!(
+                            prompt = %exchange.preview,
+                            messages = exchange.messages,
+                            tokens = exchange.tokens,
+                            "Hard context cap: dropped exchange"
+                        );
+                    }
+                    continue;
+                }
+            }
+            tracing::error!(%overflow, "Request exceeds the context window");
+            llm_span.record("error.type", "context_overflow");
+            let details = Some(overflow.details());
+            emitter.run_error(overflow.to_string(), details.clone());
+            turn_error = Some(overflow.to_string());
+            turn_error_details = details;
+            break;
+        }
+        // Same run + same context = same key, so retries below reuse it.
+        request.idempotency_key = Some(nexus_provider::idempotency::idempotency_key(
+            emitter.run_id(),
+            &request,
+        ));
+        let created = match inference.best_of {
+            // Best-of-N: sample the first request N times; the winner is
+            // replayed through consume_stream like a live stream.
+            Some(ref best_of) if !sampled_first => {
+                super::best_of::sample(inference.provider, best_of, request, emitter, &cancel)
+                    .instrument(llm_span.clone())
+                    .await
+            }
+            _ => inference.provider
+                .create_message_stream(request)
+                .instrument(llm_span.clone())
+                .await
+                .map(|s| (s, 0.0)),
+        };
+        let stream = match created {
+            Ok((s, sampling_cost)) => {
+                turn_cost += sampling_cost;
+                sampled_first = true;
+                s
+            }
             Err(e) => {
+                llm_span.record("error.type", provider_error_type(&e).as_str());
                 // Retry once on ContextLength with aggressive pruning
                 if !retried_after_prune {
                     if let Some(pe) = e.downcast_ref::<nexus_provider::error::ProviderError>() {
                         if matches!(pe.kind, nexus_provider::error::ProviderErrorKind::ContextLength) {
                             retried_after_prune = true;
                             tracing::warn!("Context length exceeded, aggressive pruning and retrying");
-                            nexus_compaction::prune_tool_results(&mut messages, 1);
+                            let pruned = nexus_compaction::prune_tool_results(&mut messages, 1);
+                            services.pruned_results.insert(conversation_id, pruned);
                             continue;
                         }
                     }
@@ -212,9 +323,9 @@ pub async fn run_agent_turn(
 
                 // Retry transient errors with exponential backoff
                 if let Some(pe) = e.downcast_ref::<nexus_provider::error::ProviderError>() {
-                    if pe.retryable && retry_count < crate::retry::MAX_RETRIES {
+                    if pe.is_retryable() && retry_count < crate::retry::MAX_RETRIES {
                         retry_count += 1;
-                        let delay = crate::retry::backoff_delay(retry_count);
+                        let delay = crate::retry::retry_delay(retry_count, pe.retry_after_ms);
                         tracing::warn!(
                             attempt = retry_count,
                             delay_ms = delay,
@@ -222,6 +333,7 @@ pub async fn run_agent_turn(
                             "Retrying after transient error"
                         );
                         emitter.retry(retry_count, crate::retry::MAX_RETRIES, format!("{:?}", pe.kind), delay);
+                        emitter.warning("retry", e.to_string(), retry_details(retry_count, pe, delay));
                         tokio::select! {
                             _ = tokio::time::sleep(std::time::Duration::from_millis(delay)) => {}
                             _ = cancel.cancelled() => {
@@ -245,18 +357,22 @@ pub async fn run_agent_turn(
 
         // Consume the stream, emitting AG-UI events
         let stream_result =
-            match consume_stream(stream, emitter, &cancel).await {
+            match consume_stream(stream, emitter, &cancel, services.moderation.as_deref())
+                .instrument(llm_span.clone())
+                .await
+            {
                 Ok(r) => {
                     // Successful stream consumption — reset retry counter
                     retry_count = 0;
                     r
                 }
                 Err(e) => {
+                    llm_span.record("error.type", provider_error_type(&e).as_str());
                     // Retry transient SSE errors by restarting the round
                     if let Some(pe) = e.downcast_ref::<nexus_provider::error::ProviderError>() {
-                        if pe.retryable && retry_count < crate::retry::MAX_RETRIES {
+                        if pe.is_retryable() && retry_count < crate::retry::MAX_RETRIES {
                             retry_count += 1;
-                            let delay = crate::retry::backoff_delay(retry_count);
+                            let delay = crate::retry::retry_delay(retry_count, pe.retry_after_ms);
                             tracing::warn!(
                                 attempt = retry_count,
                                 delay_ms = delay,
@@ -264,6 +380,7 @@ pub async fn run_agent_turn(
                                 "Retrying after stream error"
                             );
                             emitter.retry(retry_count, crate::retry::MAX_RETRIES, format!("{:?}", pe.kind), delay);
+                            emitter.warning("retry", e.to_string(), retry_details(retry_count, pe, delay));
                             tokio::select! {
                                 _ = tokio::time::sleep(std::time::Duration::from_millis(delay)) => {}
                                 _ = cancel.cancelled() => {
@@ -291,6 +408,7 @@ pub async fn run_agent_turn(
         let round_output_tokens = stream_result.output_tokens;
         let round_cache_creation = stream_result.cache_creation_input_tokens;
         let round_cache_read = stream_result.cache_read_input_tokens;
+        let round_web_searches = stream_result.web_search_requests;
 
         let inference_duration = inference_start.elapsed().as_millis() as u64;
         let llm_span_id = format!("t-llm-{}", round);
@@ -339,9 +457,29 @@ pub async fn run_agent_turn(
             round_cache_creation,
             round_cache_read,
             round_output_tokens,
-        );
+        ) + nexus_pricing::web_search_cost(round_web_searches);
         turn_cost += round_cost;
 
+        llm_span.record("gen_ai.usage.input_tokens", round_input_tokens);
+        llm_span.record("gen_ai.usage.output_tokens", round_output_tokens);
+        llm_span.record("gen_ai.usage.cache_read_input_tokens", round_cache_read);
+        llm_span.record("gen_ai.usage.cache_creation_input_tokens", round_cache_creation);
+        if let Some(ref reason) = stop_reason {
+            llm_span.record("gen_ai.response.finish_reasons", reason.as_str());
+        }
+        llm_span.record("nexus.cost_usd", round_cost);
+        drop(llm_span);
+
+        emitter.call_usage(&CallUsage {
+            source: "turn",
+            round: Some(round),
+            model: inference.model,
+            input_tokens: round_input_tokens,
+            output_tokens: round_output_tokens,
+            cache_read_input_tokens: round_cache_read,
+            cache_creation_input_tokens: round_cache_creation,
+            cost: round_cost,
+        });
         emitter.usage(
             cumulative_input,
             cumulative_output,
@@ -360,6 +498,7 @@ pub async fn run_agent_turn(
         new_messages.push(assistant_msg);
 
         round_count = round + 1;
+        final_stop_reason.clone_from(&stop_reason);
 
         match stop_reason {
             Some(StopReason::ToolUse) if !tool_calls.is_empty() => {
@@ -389,7 +528,11 @@ pub async fn run_agent_turn(
                 if let Some(ref cph) = control_plane_handler {
                     handlers.push(cph);
                 }
+                if let Some(ref sh) = skill_handler {
+                    handlers.push(sh);
+                }
                 handlers.push(&resource_handler);
+                handlers.push(&pruned_handler);
                 handlers.push(&mcp_handler);
 
                 for tc in &tool_calls {
@@ -422,9 +565,10 @@ pub async fn run_agent_turn(
                             // Feed denial reason back as a tool error
                             let content = format!("Tool call denied: {}", reason);
                             emitter.tool_result(&tc.id, &content, true);
+                            injected_blocks.extend(error_streak.record(&tc.name, Some(&content)));
                             result_blocks.push(ContentBlock::ToolResult {
                                 tool_use_id: tc.id.clone(),
-                                content: fence_tool_result(&content),
+                                content: fence_tool_result(&content).into(),
                                 is_error: Some(true),
                             });
                             continue;
@@ -442,7 +586,20 @@ pub async fn run_agent_turn(
                         emitter,
                         cancel: &cancel,
                     };
-                    let mut result = tool_dispatch::dispatch_tool_call(&handlers, ctx).await;
+                    let tool_span = tracing::info_span!(
+                        "execute_tool",
+                        otel.name = format!("execute_tool {}", tc.name),
+                        gen_ai.operation.name = "execute_tool",
+                        gen_ai.tool.name = %tc.name,
+                        gen_ai.tool.call.id = %tc.id,
+                        error.type = tracing::field::Empty,
+                    );
+                    let mut result = tool_dispatch::dispatch_tool_call(&handlers, ctx)
+                        .instrument(tool_span.clone())
+                        .await;
+                    if result.is_error {
+                        tool_span.record("error.type", "tool_error");
+                    }
 
                     // HOOK: PostToolUse / PostToolUseFailure
                     if result.is_error {
@@ -467,10 +624,22 @@ pub async fn run_agent_turn(
                     }
                     let content = result.content;
                     let is_error = result.is_error;
+                    let images = result.images;
 
                     let tool_duration = tool_start.elapsed().as_millis() as u64;
 
                     emitter.tool_result(&tc.id, &content, is_error);
+                    if let Some(prompt) = error_streak.record(&tc.name, is_error.then_some(content.as_str())) {
+                        tracing::info!(tool = %tc.name, "Tool error streak; asking the model to reflect");
+                        injected_blocks.push(prompt);
+                    }
+                    if is_error {
+                        emitter.warning(
+                            "tool_error",
+                            &content,
+                            serde_json::json!({ "toolName": tc.name, "toolCallId": tc.id }),
+                        );
+                    }
 
                     timing_spans.push(TimingSpan {
                         id: format!("t-tool-{}", tc.id),
@@ -484,7 +653,7 @@ pub async fn run_agent_turn(
 
                     result_blocks.push(ContentBlock::ToolResult {
                         tool_use_id: tc.id.clone(),
-                        content: fence_tool_result(&content),
+                        content: tool_result_content(&content, images),
                         is_error: Some(is_error),
                     });
                 }
@@ -522,9 +691,84 @@ pub async fn run_agent_turn(
                     // not persisted to conversation history.
                 }
             }
+            Some(StopReason::PauseTurn) => {
+                // The provider paused a long server-tool turn. The partial
+                // response, server tool calls and results included, is
+                // already the last message; sending it back resumes it.
+                tracing::debug!(round, "Provider paused the turn, resuming");
+            }
+            Some(ref sr @ (StopReason::Refusal | StopReason::Other(_))) => {
+                // Not an error, but the turn didn't end the way the model
+                // normally ends one. Finish without the Stop hook (a forced
+                // continuation would just re-prompt a refusal) and flag it.
+                let kind = if *sr == StopReason::Refusal { "refusal" } else { "stop_reason" };
+                tracing::warn!(stop_reason = sr.as_str(), "Turn ended with unusual stop reason");
+                emitter.warning(
+                    kind,
+                    format!("Model stopped with reason: {}", sr.as_str()),
+                    serde_json::json!({ "stopReason": sr.as_str() }),
+                );
+
+                let round_duration = round_start.elapsed().as_millis() as u64;
+                timing_spans.push(TimingSpan {
+                    id: round_span_id,
+                    name: format!("round:{}", round + 1),
+                    parent_id: Some(turn_span_id.clone()),
+                    start_ms: round_start_ms,
+                    end_ms: round_start_ms + round_duration,
+                    duration_ms: round_duration,
+                    metadata: None,
+                });
+                break;
+            }
             _ => {
                 // end_turn, max_tokens, or no tool calls
 
+                // Guardrails — validate the final answer; on failure feed the
+                // errors back and let the model repair it.
+                if let Some(ref guardrails) = services.guardrails {
+                    let answer = final_text(&messages);
+                    let errors = guardrails.validate(&answer);
+                    if !errors.is_empty() {
+                        let exhausted = guardrail_retries >= guardrails.max_retries;
+                        emitter.warning(
+                            "guardrail",
+                            if exhausted {
+                                format!("Response still fails validation: {}", errors.join("; "))
+                            } else {
+                                format!("Response failed validation, retrying: {}", errors.join("; "))
+                            },
+                            serde_json::json!({
+                                "attempt": guardrail_retries + 1,
+                                "maxRetries": guardrails.max_retries,
+                                "errors": errors,
+                                "exhausted": exhausted,
+                            }),
+                        );
+                        if !exhausted {
+                            guardrail_retries += 1;
+                            tracing::info!(attempt = guardrail_retries, "Guardrail failed, asking model to repair");
+                            messages.push(Message {
+                                role: Role::User,
+                                content: vec![ContentBlock::Text {
+                                    text: crate::guardrail::repair_prompt(&errors),
+                                }],
+                            });
+                            let round_duration = round_start.elapsed().as_millis() as u64;
+                            timing_spans.push(TimingSpan {
+                                id: round_span_id,
+                                name: format!("round:{}", round + 1),
+                                parent_id: Some(turn_span_id.clone()),
+                                start_ms: round_start_ms,
+                                end_ms: round_start_ms + round_duration,
+                                duration_ms: round_duration,
+                                metadata: None,
+                            });
+                            continue;
+                        }
+                    }
+                }
+
                 // HOOK: Stop — modules can force continuation.
                 if let Some(ref sr) = stop_reason {
                     let core_sr = crate::module::stop_reason_from_api(sr);
@@ -627,16 +871,23 @@ pub async fn run_agent_turn(
         turn_cost,
         error: turn_error,
         error_details: turn_error_details,
+        stop_reason: final_stop_reason,
     })
 }
 
 /// Consume the provider stream, emit AG-UI events, return accumulated content.
+///
+/// With moderation configured, each text block is held back until it is
+/// complete and has passed output moderation, then emitted in one delta, so
+/// no consumer of the event stream ever sees text a moderator replaced.
 async fn consume_stream(
-    mut stream: futures::stream::BoxStream<'static, Result<StreamEvent>>,
+    mut stream: nexus_provider::EventStream,
     emitter: &TurnEmitter,
     cancel: &CancellationToken,
+    moderation: Option<&crate::moderation::Moderation>,
 ) -> Result<StreamResult>
 {
+    let moderation = moderation.filter(|m| !m.is_empty());
     let mut content_blocks: Vec<ContentBlock> = Vec::new();
     let mut stop_reason = None;
     let mut pending_tool_calls: Vec<PendingToolCall> = Vec::new();
@@ -644,11 +895,15 @@ async fn consume_stream(
     let mut output_tokens: u32 = 0;
     let mut cache_creation_input_tokens: u32 = 0;
     let mut cache_read_input_tokens: u32 = 0;
+    let mut web_search_requests: u32 = 0;
 
     // Track current content blocks by index
     let mut current_text: Option<(usize, String)> = None;
     let mut current_tool: Option<(usize, PendingToolCall)> = None;
-    let mut current_thinking: Option<(usize, String)> = None;
+    let mut current_thinking: Option<(usize, String, Option<String>)> = None;
+    // Server tools run provider-side: their calls and results are kept for
+    // the next round but never dispatched locally.
+    let mut current_server_tool: Option<(usize, String, String, String)> = None;
     let mut message_id = String::new();
 
     while let Some(event) = stream.next().await {
@@ -669,6 +924,9 @@ async fn consume_stream(
                     input_tokens = u.input_tokens;
                     cache_creation_input_tokens = u.cache_creation_input_tokens;
                     cache_read_input_tokens = u.cache_read_input_tokens;
+                    if let Some(server) = u.server_tool_use {
+                        web_search_requests = server.web_search_requests;
+                    }
                 }
             }
             StreamEvent::ContentBlockStart {
@@ -676,7 +934,9 @@ async fn consume_stream(
                 content_block,
             } => match content_block {
                 ContentBlockInfo::Text => {
-                    emitter.text_start(&message_id);
+                    if moderation.is_none() {
+                        emitter.text_start(&message_id);
+                    }
                     current_text = Some((index, String::new()));
                 }
                 ContentBlockInfo::ToolUse { id, name } => {
@@ -692,7 +952,18 @@ async fn consume_stream(
                 }
                 ContentBlockInfo::Thinking => {
                     emitter.thinking_start();
-                    current_thinking = Some((index, String::new()));
+                    current_thinking = Some((index, String::new(), None));
+                }
+                ContentBlockInfo::RedactedThinking { data } => {
+                    // Nothing to show; kept only so the next round can send it back
+                    content_blocks.push(ContentBlock::RedactedThinking { data });
+                }
+                ContentBlockInfo::ServerToolUse { id, name } => {
+                    emitter.activity(format!("Running {name}"));
+                    current_server_tool = Some((index, id, name, String::new()));
+                }
+                ContentBlockInfo::ServerToolResult { block } => {
+                    content_blocks.push(block);
                 }
             },
             StreamEvent::ContentBlockDelta { index, delta } => match delta {
@@ -700,11 +971,18 @@ async fn consume_stream(
                     if let Some((idx, ref mut buf)) = current_text {
                         if idx == index {
                             buf.push_str(&text);
-                            emitter.text_delta(&message_id, text);
+                            if moderation.is_none() {
+                                emitter.text_delta(&message_id, text);
+                            }
                         }
                     }
                 }
                 Delta::InputJsonDelta { partial_json } => {
+                    if let Some((idx, _, _, ref mut json)) = current_server_tool {
+                        if idx == index {
+                            json.push_str(&partial_json);
+                        }
+                    }
                     if let Some((idx, ref mut tc)) = current_tool {
                         if idx == index {
                             tc.args_json.push_str(&partial_json);
@@ -713,29 +991,55 @@ async fn consume_stream(
                     }
                 }
                 Delta::ThinkingDelta { thinking } => {
-                    if let Some((idx, ref mut buf)) = current_thinking {
+                    if let Some((idx, ref mut buf, _)) = current_thinking {
                         if idx == index {
                             buf.push_str(&thinking);
                             emitter.thinking_delta(thinking);
                         }
                     }
                 }
+                Delta::SignatureDelta { signature } => {
+                    if let Some((idx, _, ref mut sig)) = current_thinking {
+                        if idx == index {
+                            sig.get_or_insert_with(String::new).push_str(&signature);
+                        }
+                    }
+                }
             },
             StreamEvent::ContentBlockStop { index } => {
                 if let Some((idx, text)) = current_text.take() {
                     if idx == index {
+                        let text = match moderation {
+                            Some(moderation) => {
+                                let text = moderate_output(moderation, text, emitter).await;
+                                emitter.text_start(&message_id);
+                                if !text.is_empty() {
+                                    emitter.text_delta(&message_id, text.as_str());
+                                }
+                                text
+                            }
+                            None => text,
+                        };
                         emitter.text_end(&message_id);
                         content_blocks.push(ContentBlock::Text { text });
                     } else {
                         current_text = Some((idx, text));
                     }
                 }
-                if let Some((idx, tc)) = current_tool.take() {
+                if let Some((idx, mut tc)) = current_tool.take() {
                     if idx == index {
                         emitter.tool_end(&tc.id);
-                        let input: serde_json::Value =
-                            serde_json::from_str(&tc.args_json)
-                                .unwrap_or_else(|_| serde_json::json!({}));
+                        let input: serde_json::Value = match serde_json::from_str(&tc.args_json) {
+                            Ok(input) => input,
+                            Err(_) => match super::args_repair::repair(&tc.args_json) {
+                                Some(repaired) => {
+                                    emitter.tool_args_repaired(&tc.id, &tc.name, &repaired.repairs);
+                                    tc.args_json = repaired.json;
+                                    repaired.value
+                                }
+                                None => serde_json::json!({}),
+                            },
+                        };
                         content_blocks.push(ContentBlock::ToolUse {
                             id: tc.id.clone(),
                             name: tc.name.clone(),
@@ -746,12 +1050,20 @@ async fn consume_stream(
                         current_tool = Some((idx, tc));
                     }
                 }
-                if let Some((idx, thinking)) = current_thinking.take() {
+                if let Some((idx, id, name, json)) = current_server_tool.take() {
+                    if idx == index {
+                        let input = serde_json::from_str(&json).unwrap_or_else(|_| serde_json::json!({}));
+                        content_blocks.push(ContentBlock::ServerToolUse { id, name, input });
+                    } else {
+                        current_server_tool = Some((idx, id, name, json));
+                    }
+                }
+                if let Some((idx, thinking, signature)) = current_thinking.take() {
                     if idx == index {
                         emitter.thinking_end();
-                        content_blocks.push(ContentBlock::Thinking { thinking });
+                        content_blocks.push(ContentBlock::Thinking { thinking, signature });
                     } else {
-                        current_thinking = Some((idx, thinking));
+                        current_thinking = Some((idx, thinking, signature));
                     }
                 }
             }
@@ -762,6 +1074,9 @@ async fn consume_stream(
                 stop_reason = sr;
                 if let Some(u) = u {
                     output_tokens = u.output_tokens;
+                    if let Some(server) = u.server_tool_use {
+                        web_search_requests = web_search_requests.max(server.web_search_requests);
+                    }
                 }
             }
             StreamEvent::MessageStop => break,
@@ -784,9 +1099,55 @@ async fn consume_stream(
         output_tokens,
         cache_creation_input_tokens,
         cache_read_input_tokens,
+        web_search_requests,
     })
 }
 
+/// Text of the latest assistant message.
+fn final_text(messages: &[Message]) -> String {
+    messages
+        .iter()
+        .rev()
+        .find(|m| m.role == Role::Assistant)
+        .map(|m| {
+            m.content
+                .iter()
+                .filter_map(|b| match b {
+                    ContentBlock::Text { text } => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Run output moderation over a finished text block and return the text
+/// to emit and keep: a notice when blocked, the rewrite when rewritten.
+async fn moderate_output(
+    moderation: &crate::moderation::Moderation,
+    text: String,
+    emitter: &TurnEmitter,
+) -> String {
+    use crate::moderation::{ModerationStage, ModerationVerdict};
+
+    let outcome = moderation.check(ModerationStage::Output, &text).await;
+    if outcome.findings.is_empty() {
+        return text;
+    }
+    let replacement = match outcome.blocked() {
+        Some(reason) => format!("[Response withheld by moderation: {reason}]"),
+        None => outcome.text.clone(),
+    };
+    for finding in &outcome.findings {
+        let shown = match finding.verdict {
+            ModerationVerdict::Annotate { .. } => None,
+            _ => Some(replacement.as_str()),
+        };
+        emitter.moderated(ModerationStage::Output, finding, shown);
+    }
+    replacement
+}
+
 /// Inject a `<state_update>` user message into the API messages.
 ///
 /// Insertion point: before the last message, UNLESS the last message
@@ -813,6 +1174,95 @@ fn inject_state_update(messages: &mut Vec<Message>, state: &str) {
     messages.insert(pos, state_msg);
 }
 
+/// `agent_warning` details for a provider error that is about to be retried.
+fn retry_details(
+    attempt: u32,
+    error: &nexus_provider::error::ProviderError,
+    delay_ms: u64,
+) -> serde_json::Value {
+    serde_json::json!({
+        "attempt": attempt,
+        "maxAttempts": crate::retry::MAX_RETRIES,
+        "errorKind": format!("{:?}", error.kind),
+        "delayMs": delay_ms,
+    })
+}
+
+/// `error.type` span attribute for a failed provider call: the provider
+/// error kind, or `_OTHER` for errors that didn't come from the provider.
+fn provider_error_type(e: &anyhow::Error) -> String {
+    e.downcast_ref::<nexus_provider::error::ProviderError>()
+        .map(|pe| format!("{:?}", pe.kind))
+        .unwrap_or_else(|| "_OTHER".to_string())
+}
+
+/// Fail fast when the request's estimated input can't fit `window`.
+fn check_context_budget(request: &InferenceRequest, window: u32) -> Result<(), AgentError> {
+    let estimated = nexus_compaction::estimate_tokens(
+        &request.messages,
+        request.system.as_deref(),
+        &request.tools,
+    );
+    if estimated > window {
+        return Err(AgentError::ContextOverflow { estimated, window });
+    }
+    Ok(())
+}
+
+/// API content for a tool result: the fenced text, then the tool's images
+/// as image blocks (images bypass the text transforms).
+fn tool_result_content(content: &str, images: Vec<ToolImage>) -> ToolResultContent {
+    ToolResultContent::with_images(
+        fence_tool_result(content),
+        images
+            .into_iter()
+            .map(|image| ImageSource::base64(image.media_type, image.data))
+            .collect(),
+    )
+}
+
+/// Consecutive tool failures within a turn. Once `threshold` pile up the
+/// model is asked to step back before it tries the same thing again.
+struct ErrorStreak {
+    threshold: Option<u32>,
+    failures: Vec<String>,
+}
+
+impl ErrorStreak {
+    fn new(threshold: Option<u32>) -> Self {
+        Self { threshold: threshold.filter(|&n| n > 0), failures: Vec::new() }
+    }
+
+    /// Record one tool result. Returns the reflection prompt when this
+    /// error completes a streak, which then starts over.
+    fn record(&mut self, tool_name: &str, error: Option<&str>) -> Option<String> {
+        let threshold = self.threshold?;
+        let Some(error) = error else {
+            self.failures.clear();
+            return None;
+        };
+        let first_line = error.lines().next().unwrap_or_default();
+        let summary: String = first_line.chars().take(200).collect();
+        self.failures.push(format!("- {tool_name}: {summary}"));
+        if self.failures.len() < threshold as usize {
+            return None;
+        }
+        let failures = std::mem::take(&mut self.failures);
+        Some(format!(
+            "<reflection>\n\
+             The last {} tool calls failed:\n{}\n\n\
+             Before calling another tool, reply with:\n\
+             1. What you tried and why each attempt failed.\n\
+             2. What those attempts have in common.\n\
+             3. A different approach to take next.\n\
+             Then continue with that approach.\n\
+             </reflection>",
+            failures.len(),
+            failures.join("\n"),
+        ))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -851,7 +1301,7 @@ mod tests {
             role: Role::User,
             content: vec![ContentBlock::ToolResult {
                 tool_use_id: id.to_string(),
-                content: content.to_string(),
+                content: content.into(),
                 is_error: Some(false),
             }],
         }
@@ -938,4 +1388,198 @@ mod tests {
         assert_eq!(msgs.len(), 1);
         assert!(matches!(&msgs[0].content[0], ContentBlock::Text { text } if text == "<state/>"));
     }
+
+    #[tokio::test]
+    async fn consume_stream_keeps_thinking_signature_and_redacted_blocks() {
+        let (tx, _rx) = tokio::sync::broadcast::channel(64);
+        let emitter = TurnEmitter::new(tx, "t1".into(), "r1".into());
+        let events = vec![
+            StreamEvent::ContentBlockStart { index: 0, content_block: ContentBlockInfo::Thinking },
+            StreamEvent::ContentBlockDelta {
+                index: 0,
+                delta: Delta::ThinkingDelta { thinking: "let me think".into() },
+            },
+            StreamEvent::ContentBlockDelta {
+                index: 0,
+                delta: Delta::SignatureDelta { signature: "sig-abc".into() },
+            },
+            StreamEvent::ContentBlockStop { index: 0 },
+            StreamEvent::ContentBlockStart {
+                index: 1,
+                content_block: ContentBlockInfo::RedactedThinking { data: "opaque".into() },
+            },
+            StreamEvent::ContentBlockStop { index: 1 },
+            StreamEvent::MessageStop,
+        ];
+        let stream = futures::stream::iter(events.into_iter().map(Ok)).boxed();
+
+        let result = consume_stream(stream, &emitter, &CancellationToken::new(), None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            result.content_blocks,
+            vec![
+                ContentBlock::Thinking {
+                    thinking: "let me think".into(),
+                    signature: Some("sig-abc".into()),
+                },
+                ContentBlock::RedactedThinking { data: "opaque".into() },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn consume_stream_holds_text_until_moderated() {
+        let (tx, mut rx) = tokio::sync::broadcast::channel(64);
+        let emitter = TurnEmitter::new(tx, "t1".into(), "r1".into());
+        let config: crate::config::ModerationConfig = serde_json::from_value(serde_json::json!({
+            "rules": [{ "pattern": "hunter2", "action": "block", "reason": "secrets" }],
+        }))
+        .unwrap();
+        let moderation = crate::moderation::Moderation::from_config(Some(&config)).unwrap();
+        let events = vec![
+            StreamEvent::ContentBlockStart { index: 0, content_block: ContentBlockInfo::Text },
+            StreamEvent::ContentBlockDelta {
+                index: 0,
+                delta: Delta::TextDelta { text: "The password is ".into() },
+            },
+            StreamEvent::ContentBlockDelta {
+                index: 0,
+                delta: Delta::TextDelta { text: "hunter2".into() },
+            },
+            StreamEvent::ContentBlockStop { index: 0 },
+            StreamEvent::MessageStop,
+        ];
+        let stream = futures::stream::iter(events.into_iter().map(Ok)).boxed();
+
+        let result = consume_stream(stream, &emitter, &CancellationToken::new(), Some(&moderation))
+            .await
+            .unwrap();
+
+        let notice = "[Response withheld by moderation: secrets]";
+        assert_eq!(result.content_blocks, vec![ContentBlock::Text { text: notice.into() }]);
+        let mut deltas = Vec::new();
+        while let Ok(envelope) = rx.try_recv() {
+            let json = serde_json::to_value(envelope).unwrap();
+            assert!(!json.to_string().contains("hunter2"), "leaked: {json}");
+            if json["type"] == "TEXT_MESSAGE_CONTENT" {
+                deltas.push(json["delta"].as_str().unwrap().to_string());
+            }
+        }
+        assert_eq!(deltas, [notice]);
+    }
+
+    #[tokio::test]
+    async fn consume_stream_keeps_server_tool_blocks_without_dispatching() {
+        let (tx, _rx) = tokio::sync::broadcast::channel(64);
+        let emitter = TurnEmitter::new(tx, "t1".into(), "r1".into());
+        let result_block = ContentBlock::WebSearchToolResult {
+            tool_use_id: "srvtoolu_1".into(),
+            content: serde_json::json!([{"type": "web_search_result", "url": "https://example.com"}]),
+        };
+        let events = vec![
+            StreamEvent::ContentBlockStart {
+                index: 0,
+                content_block: ContentBlockInfo::ServerToolUse {
+                    id: "srvtoolu_1".into(),
+                    name: "web_search".into(),
+                },
+            },
+            StreamEvent::ContentBlockDelta {
+                index: 0,
+                delta: Delta::InputJsonDelta { partial_json: r#"{"query": "rust"}"#.into() },
+            },
+            StreamEvent::ContentBlockStop { index: 0 },
+            StreamEvent::ContentBlockStart {
+                index: 1,
+                content_block: ContentBlockInfo::ServerToolResult { block: result_block.clone() },
+            },
+            StreamEvent::ContentBlockStop { index: 1 },
+            StreamEvent::MessageDelta {
+                stop_reason: Some(StopReason::EndTurn),
+                usage: Some(Usage {
+                    output_tokens: 10,
+                    server_tool_use: Some(ServerToolUsage { web_search_requests: 1 }),
+                    ..Default::default()
+                }),
+            },
+            StreamEvent::MessageStop,
+        ];
+        let stream = futures::stream::iter(events.into_iter().map(Ok)).boxed();
+
+        let result = consume_stream(stream, &emitter, &CancellationToken::new(), None)
+            .await
+            .unwrap();
+
+        assert!(result.tool_calls.is_empty());
+        assert_eq!(result.web_search_requests, 1);
+        assert_eq!(
+            result.content_blocks,
+            vec![
+                ContentBlock::ServerToolUse {
+                    id: "srvtoolu_1".into(),
+                    name: "web_search".into(),
+                    input: serde_json::json!({"query": "rust"}),
+                },
+                result_block,
+            ]
+        );
+    }
+
+    #[test]
+    fn tool_result_images_skip_fencing() {
+        let image = ToolImage { media_type: "image/png".into(), data: "iVBORw0KGgo=".into() };
+        let content = tool_result_content("screenshot taken", vec![image]);
+
+        assert_eq!(content.text(), fence_tool_result("screenshot taken"));
+        let images: Vec<_> = content.images().collect();
+        assert_eq!(images, [&ImageSource::base64("image/png", "iVBORw0KGgo=")]);
+
+        assert!(matches!(tool_result_content("plain", Vec::new()), ToolResultContent::Text(_)));
+    }
+
+    #[test]
+    fn context_budget_rejects_requests_over_the_window() {
+        let request = |text: &str| InferenceRequest {
+            model: "m".into(),
+            max_tokens: 1024,
+            system: None,
+            temperature: None,
+            thinking_budget: None,
+            messages: vec![user_text(text)],
+            tools: Vec::new(),
+            idempotency_key: None,
+        };
+        assert!(check_context_budget(&request("hello"), 1_000).is_ok());
+
+        let err = check_context_budget(&request(&"x".repeat(9_000)), 1_000).unwrap_err();
+        let AgentError::ContextOverflow { estimated, window } = err.clone();
+        assert!(estimated > window);
+        assert_eq!(err.details()["kind"], "ContextOverflow");
+        assert_eq!(err.details()["window"], 1_000);
+        assert!(err.to_string().contains("1000-token context window"));
+    }
+
+    #[test]
+    fn error_streak_prompts_for_reflection_after_consecutive_failures() {
+        let mut off = ErrorStreak::new(None);
+        assert!((0..10).all(|_| off.record("bash", Some("boom")).is_none()));
+
+        let mut streak = ErrorStreak::new(Some(3));
+        assert!(streak.record("bash", Some("exit 1")).is_none());
+        assert!(streak.record("bash", Some("exit 1")).is_none());
+        // A success breaks the streak.
+        assert!(streak.record("read_file", None).is_none());
+        assert!(streak.record("bash", Some("exit 1\nstack trace")).is_none());
+        assert!(streak.record("edit_file", Some("oldText not found")).is_none());
+        let prompt = streak.record("bash", Some("exit 2")).unwrap();
+        assert!(prompt.contains("The last 3 tool calls failed"));
+        assert!(prompt.contains("- bash: exit 1\n- edit_file: oldText not found\n- bash: exit 2"));
+        assert!(!prompt.contains("stack trace"));
+        assert!(prompt.contains("A different approach"));
+
+        // The streak starts over after prompting.
+        assert!(streak.record("bash", Some("exit 2")).is_none());
+    }
 }
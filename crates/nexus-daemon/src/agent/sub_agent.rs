@@ -262,6 +262,7 @@ impl ToolHandler for SubAgentHandler<'_> {
                     content: serde_json::json!({ "error": e }).to_string(),
                     is_error: true,
                     injected_messages: Vec::new(),
+                    images: Vec::new(),
                 };
             }
         };
@@ -321,6 +322,9 @@ impl ToolHandler for SubAgentHandler<'_> {
             thinking_budget: None,
             system_prompt: Some(config.system_prompt),
             state_update: None,
+            best_of: None,
+            hard_context_cap: false,
+            reflect_after_errors: self.inference.reflect_after_errors,
         };
         let sub_context = super::TurnContext {
             conversation_id: ctx.conversation_id.to_string(),
@@ -335,10 +339,14 @@ impl ToolHandler for SubAgentHandler<'_> {
             filesystem_config: self.services.filesystem_config,
             task_store: self.services.task_store,
             pending_questions: self.services.pending_questions,
+            pruned_results: self.services.pruned_results,
             process_manager: None,
             bg_sub_agent_deps: None,
             control_plane: self.services.control_plane.clone(),
+            skills: self.services.skills.clone(),
             modules: Arc::clone(&self.services.modules),
+            moderation: None,
+            guardrails: None,
         };
         // Sub-agent gets its own emitter with a fresh run_id
         let sub_emitter = TurnEmitter::new(
@@ -390,7 +398,7 @@ impl ToolHandler for SubAgentHandler<'_> {
             }
         };
 
-        ToolResult { content, is_error, injected_messages: Vec::new() }
+        ToolResult { content, is_error, injected_messages: Vec::new(), images: Vec::new() }
     }
 }
 
@@ -408,6 +416,7 @@ impl SubAgentHandler<'_> {
                         .to_string(),
                     is_error: true,
                 injected_messages: Vec::new(),
+                images: Vec::new(),
                 };
             }
         };
@@ -449,6 +458,7 @@ impl SubAgentHandler<'_> {
         let model = self.inference.model.to_string();
         let max_tokens = self.inference.max_tokens;
         let temperature = self.inference.temperature;
+        let reflect_after_errors = self.inference.reflect_after_errors;
         let cumulative_cost = self.cumulative_cost;
         let process_id = spawn_result.process_id.clone();
         let cancel_token = spawn_result.cancel_token;
@@ -472,6 +482,9 @@ impl SubAgentHandler<'_> {
                 thinking_budget: None,
                 system_prompt: Some(system_prompt),
                 state_update: None,
+                best_of: None,
+                hard_context_cap: false,
+                reflect_after_errors,
             };
             let bg_context = super::TurnContext {
                 conversation_id: conversation_id.clone(),
@@ -486,10 +499,14 @@ impl SubAgentHandler<'_> {
                 filesystem_config: &bg_deps.filesystem_config,
                 task_store: bg_deps.tasks.store(),
                 pending_questions: &bg_deps.turns.pending_questions,
+                pruned_results: &bg_deps.turns.pruned_results,
                 process_manager: Some(bg_deps.turns.process_manager.clone()),
                 bg_sub_agent_deps: None,
                 control_plane: None,
+                skills: None,
                 modules: Arc::clone(&bg_deps.modules),
+                moderation: None,
+                guardrails: None,
             };
 
             let result = tokio::select! {
@@ -532,6 +549,7 @@ impl SubAgentHandler<'_> {
             .to_string(),
             is_error: false,
             injected_messages: Vec::new(),
+            images: Vec::new(),
         }
     }
 }
@@ -2,6 +2,7 @@ use async_trait::async_trait;
 use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use nexus_tools::ask_user::{self, AskUserArgs, PendingQuestion, PendingQuestionStore, UserAnswer};
@@ -130,7 +131,7 @@ impl ToolHandler for AskUserHandler<'_> {
             }
         };
 
-        ToolResult { content, is_error, injected_messages: Vec::new() }
+        ToolResult { content, is_error, injected_messages: Vec::new(), images: Vec::new() }
     }
 }
 
@@ -152,7 +153,7 @@ impl ToolHandler for TaskToolHandler<'_> {
         let (content, is_error) = tasks::tools::handle_builtin(
             ctx.tool_name, &args, ctx.conversation_id, self.task_store, ctx.emitter,
         ).await;
-        ToolResult { content, is_error, injected_messages: Vec::new() }
+        ToolResult { content, is_error, injected_messages: Vec::new(), images: Vec::new() }
     }
 }
 
@@ -176,6 +177,7 @@ impl ToolHandler for FetchHandler<'_> {
                     content: format!("Invalid fetch arguments: {e}"),
                     is_error: true,
                     injected_messages: Vec::new(),
+                    images: Vec::new(),
                 };
             }
         };
@@ -188,11 +190,13 @@ impl ToolHandler for FetchHandler<'_> {
                 content,
                 is_error: false,
             injected_messages: Vec::new(),
+            images: Vec::new(),
             },
             Err(e) => ToolResult {
                 content: e,
                 is_error: true,
             injected_messages: Vec::new(),
+            images: Vec::new(),
             },
         }
     }
@@ -201,37 +205,67 @@ impl ToolHandler for FetchHandler<'_> {
 // ── FilesystemHandler ──
 
 pub struct FilesystemHandler {
-    pub validator: filesystem::PathValidator,
+    /// Serves the unprefixed tools.
+    pub workspace: Box<dyn filesystem::Workspace>,
+    /// Serves `<name>__<tool>`, keyed by name.
+    pub named: HashMap<String, Box<dyn filesystem::Workspace>>,
 }
 
 impl FilesystemHandler {
     pub fn new(config: &FilesystemConfig) -> Self {
+        let named = filesystem::active_workspaces(config)
+            .into_iter()
+            .map(|ws| {
+                let local = filesystem::LocalWorkspace::new(&ws.allowed_directories);
+                (ws.name.clone(), Box::new(local) as Box<dyn filesystem::Workspace>)
+            })
+            .collect();
         Self {
-            validator: filesystem::PathValidator::new(&config.allowed_directories),
+            workspace: Box::new(filesystem::LocalWorkspace::new(&config.allowed_directories)),
+            named,
         }
     }
+
+    /// The workspace and unprefixed tool name for `tool_name`, if handled here.
+    fn route<'a>(&'a self, tool_name: &'a str) -> Option<(&'a dyn filesystem::Workspace, &'a str)> {
+        if filesystem::is_filesystem_tool(tool_name) {
+            return Some((self.workspace.as_ref(), tool_name));
+        }
+        let (name, tool) = filesystem::split_namespaced(tool_name)?;
+        Some((self.named.get(name)?.as_ref(), tool))
+    }
 }
 
 #[async_trait]
 impl ToolHandler for FilesystemHandler {
     fn can_handle(&self, tool_name: &str) -> bool {
-        filesystem::is_filesystem_tool(tool_name)
+        self.route(tool_name).is_some()
     }
 
     async fn handle(&self, ctx: &ToolContext<'_>) -> ToolResult {
         // Activity update
         ctx.emitter.activity(format!("{}...", ctx.tool_name));
 
-        match filesystem::execute(ctx.tool_name, ctx.args_json, &self.validator) {
+        let Some((workspace, tool)) = self.route(ctx.tool_name) else {
+            return ToolResult {
+                content: format!("Unknown filesystem tool: '{}'", ctx.tool_name),
+                is_error: true,
+                injected_messages: Vec::new(),
+                images: Vec::new(),
+            };
+        };
+        match filesystem::execute(tool, ctx.args_json, workspace) {
             Ok(content) => ToolResult {
                 content,
                 is_error: false,
             injected_messages: Vec::new(),
+            images: Vec::new(),
             },
             Err(e) => ToolResult {
                 content: e,
                 is_error: true,
             injected_messages: Vec::new(),
+            images: Vec::new(),
             },
         }
     }
@@ -261,6 +295,7 @@ impl ToolHandler for BashHandler {
                     content: "Missing required field: 'command'".to_string(),
                     is_error: true,
                 injected_messages: Vec::new(),
+                images: Vec::new(),
                 };
             }
         };
@@ -287,7 +322,7 @@ impl ToolHandler for BashHandler {
         )
         .await;
 
-        ToolResult { content, is_error, injected_messages: Vec::new() }
+        ToolResult { content, is_error, injected_messages: Vec::new(), images: Vec::new() }
     }
 }
 
@@ -314,25 +349,35 @@ impl BashHandler {
 
         let process_id = spawn_result.process_id.clone();
         let cancel_token = spawn_result.cancel_token;
-        let output_path = spawn_result.output_path;
-        let command_owned = command.to_string();
-        let working_dir = self.working_dir.clone();
         let pm = Arc::clone(&self.process_manager);
 
+        let mut child = match bash::spawn_background(
+            command,
+            self.working_dir.as_deref(),
+            &spawn_result.output_path,
+        ) {
+            Ok(child) => child,
+            Err(e) => {
+                pm.complete(&process_id, None, true).await;
+                return ToolResult::error(format!("Failed to start command: {e}"));
+            }
+        };
+        pm.set_pid(&process_id, child.id()).await;
+
         tokio::spawn(async move {
-            let result = tokio::select! {
-                result = bash::execute(&command_owned, None, working_dir.as_deref()) => result,
+            // Stopping signals the whole process group; killing the shell
+            // here also covers platforms without process groups.
+            let status = tokio::select! {
+                status = child.wait() => status,
                 _ = cancel_token.cancelled() => {
-                    ("Cancelled".to_string(), true)
+                    let _ = child.start_kill();
+                    child.wait().await
                 }
             };
-
-            let (output, is_error) = result;
-            let exit_code = if is_error { Some(1) } else { Some(0) };
-
-            // Write output to file
-            let _ = tokio::fs::write(&output_path, &output).await;
-
+            let (exit_code, is_error) = match status {
+                Ok(status) => (status.code(), !status.success()),
+                Err(_) => (None, true),
+            };
             pm.complete(&process_id, exit_code, is_error).await;
         });
 
@@ -340,10 +385,11 @@ impl BashHandler {
             content: serde_json::json!({
                 "process_id": spawn_result.process_id,
                 "status": "running",
-                "message": "Process started in background. You will be notified when it completes. Use process_output to read output."
+                "message": "Process started in background with no timeout. You will be notified when it exits. Use process_output with offset to poll its output while it runs, and process_stop to end it."
             }).to_string(),
             is_error: false,
             injected_messages: Vec::new(),
+            images: Vec::new(),
         }
     }
 }
@@ -368,7 +414,47 @@ impl ToolHandler for ResourceToolHandler<'_> {
             self.mcp,
         )
         .await;
-        ToolResult { content, is_error, injected_messages: Vec::new() }
+        ToolResult { content, is_error, injected_messages: Vec::new(), images: Vec::new() }
+    }
+}
+
+// ── PrunedResultHandler ──
+
+pub struct PrunedResultHandler<'a> {
+    pub store: &'a crate::pruned_results::PrunedResultStore,
+}
+
+#[async_trait]
+impl ToolHandler for PrunedResultHandler<'_> {
+    fn can_handle(&self, tool_name: &str) -> bool {
+        crate::pruned_results::is_fetch_pruned(tool_name)
+    }
+
+    async fn handle(&self, ctx: &ToolContext<'_>) -> ToolResult {
+        let (content, is_error) = crate::pruned_results::execute(
+            ctx.args_json,
+            ctx.conversation_id,
+            self.store,
+        );
+        ToolResult { content, is_error, injected_messages: Vec::new(), images: Vec::new() }
+    }
+}
+
+// ── SkillHandler ──
+
+pub struct SkillHandler {
+    pub library: Arc<crate::skills::SkillLibrary>,
+}
+
+#[async_trait]
+impl ToolHandler for SkillHandler {
+    fn can_handle(&self, tool_name: &str) -> bool {
+        crate::skills::is_load_skill(tool_name)
+    }
+
+    async fn handle(&self, ctx: &ToolContext<'_>) -> ToolResult {
+        let (content, is_error) = crate::skills::execute(ctx.args_json, &self.library);
+        ToolResult { content, is_error, injected_messages: Vec::new(), images: Vec::new() }
     }
 }
 
@@ -393,7 +479,7 @@ impl ToolHandler for ControlPlaneHandler {
             &self.deps,
         )
         .await;
-        ToolResult { content, is_error, injected_messages: Vec::new() }
+        ToolResult { content, is_error, injected_messages: Vec::new(), images: Vec::new() }
     }
 }
 
@@ -410,7 +496,6 @@ impl ToolHandler for McpToolHandler<'_> {
     }
 
     async fn handle(&self, ctx: &ToolContext<'_>) -> ToolResult {
-        let (content, is_error) = self.mcp.call_tool(ctx.tool_name, ctx.args_json).await;
-        ToolResult { content, is_error, injected_messages: Vec::new() }
+        self.mcp.call_tool(ctx.tool_name, ctx.args_json).await
     }
 }
@@ -0,0 +1,249 @@
+//! Agent definitions loaded from `~/.nexus/agents/*.{toml,yaml,yml}`.
+//!
+//! Each file defines one agent. File-defined agents are listed alongside
+//! the ones in `nexus.json` but are never written back to it, and the REST
+//! API refuses to edit or delete them — change the file and restart.
+//!
+//! ```toml
+//! name = "Reviewer"
+//! provider = "Default (Anthropic)"   # provider id or name
+//! model = "claude-sonnet-4-5"
+//! system_prompt_file = "reviewer.md" # relative to this file
+//! thinking_budget = 8000
+//! mcp_servers = ["github"]           # MCP server ids or names
+//! race = { provider = "Local", model = "qwen3-8b" }  # optional
+//! ```
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Utc;
+use nexus_provider::provider_config::Provider;
+use serde::Deserialize;
+
+use super::types::{AgentEntry, RaceTarget};
+use crate::config::McpServerConfig;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AgentFile {
+    /// Defaults to the file stem.
+    id: Option<String>,
+    /// Defaults to the file stem.
+    name: Option<String>,
+    provider: String,
+    model: String,
+    system_prompt: Option<String>,
+    system_prompt_file: Option<String>,
+    temperature: Option<f32>,
+    max_tokens: Option<u32>,
+    thinking_budget: Option<u32>,
+    /// Omitted = all servers, `[]` = none.
+    mcp_servers: Option<Vec<String>>,
+    /// Provider and model to race requests against.
+    race: Option<RaceFile>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RaceFile {
+    /// Provider id or name.
+    provider: String,
+    model: String,
+}
+
+/// Load every agent file in `dir`. A missing directory yields no agents;
+/// a file that fails to parse or resolve is skipped with a warning.
+pub fn load_agent_files(
+    dir: &Path,
+    providers: &[Provider],
+    mcp_servers: &[McpServerConfig],
+) -> Vec<AgentEntry> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<_> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| matches!(extension(p), Some("toml" | "yaml" | "yml")))
+        .collect();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .filter_map(|path| match load_agent_file(&path, providers, mcp_servers) {
+            Ok(agent) => Some(agent),
+            Err(e) => {
+                tracing::warn!("Skipping agent file {}: {:#}", path.display(), e);
+                None
+            }
+        })
+        .collect()
+}
+
+fn load_agent_file(
+    path: &Path,
+    providers: &[Provider],
+    mcp_servers: &[McpServerConfig],
+) -> Result<AgentEntry> {
+    let content = fs::read_to_string(path)?;
+    let file: AgentFile = match extension(path) {
+        Some("toml") => toml::from_str(&content)?,
+        _ => serde_yaml::from_str(&content)?,
+    };
+
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default()
+        .to_string();
+
+    let provider_id = resolve_provider(providers, &file.provider)?;
+    let race = file
+        .race
+        .map(|race| {
+            Ok::<_, anyhow::Error>(RaceTarget {
+                provider_id: resolve_provider(providers, &race.provider)?,
+                model: race.model,
+            })
+        })
+        .transpose()?;
+
+    let system_prompt = match (file.system_prompt, file.system_prompt_file) {
+        (Some(_), Some(_)) => bail!("set system_prompt or system_prompt_file, not both"),
+        (Some(prompt), None) => Some(prompt),
+        (None, Some(rel)) => {
+            let prompt_path = path.parent().unwrap_or(Path::new(".")).join(rel);
+            Some(
+                fs::read_to_string(&prompt_path)
+                    .with_context(|| format!("reading {}", prompt_path.display()))?,
+            )
+        }
+        (None, None) => None,
+    };
+
+    let mcp_server_ids = file
+        .mcp_servers
+        .map(|refs| {
+            refs.iter()
+                .map(|r| {
+                    mcp_servers
+                        .iter()
+                        .find(|s| &s.id == r || &s.name == r)
+                        .map(|s| s.id.clone())
+                        .ok_or_else(|| anyhow!("unknown MCP server {r:?}"))
+                })
+                .collect::<Result<Vec<_>>>()
+        })
+        .transpose()?;
+
+    let now = Utc::now();
+    Ok(AgentEntry {
+        id: file.id.unwrap_or_else(|| stem.clone()),
+        name: file.name.unwrap_or(stem),
+        provider_id,
+        model: file.model,
+        system_prompt,
+        temperature: file.temperature,
+        max_tokens: file.max_tokens,
+        thinking_budget: file.thinking_budget,
+        mcp_server_ids,
+        race,
+        source: Some(path.display().to_string()),
+        created_at: now,
+        updated_at: now,
+    })
+}
+
+/// Look up a provider by id or name.
+fn resolve_provider(providers: &[Provider], reference: &str) -> Result<String> {
+    providers
+        .iter()
+        .find(|p| p.id == reference || p.name == reference)
+        .map(|p| p.id.clone())
+        .ok_or_else(|| anyhow!("unknown provider {reference:?}"))
+}
+
+fn extension(path: &Path) -> Option<&str> {
+    path.extension().and_then(|e| e.to_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nexus_provider::provider_config::ProviderType;
+
+    fn provider() -> Provider {
+        Provider {
+            id: "p1".into(),
+            name: "Anthropic".into(),
+            provider_type: ProviderType::Anthropic,
+            endpoint: None,
+            api_key: None,
+            api_keys: Vec::new(),
+            key_rotation: None,
+            beta_headers: Vec::new(),
+            api_version: None,
+            aws_region: None,
+            aws_profile: None,
+            throttle: None,
+            tool_results: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn loads_toml_and_yaml_agents() {
+        let dir = std::env::temp_dir().join(format!("nexus-agents-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("prompt.md"), "Review carefully.").unwrap();
+        fs::write(
+            dir.join("reviewer.toml"),
+            r#"
+provider = "Anthropic"
+model = "claude-test"
+system_prompt_file = "prompt.md"
+thinking_budget = 4000
+mcp_servers = []
+race = { provider = "Anthropic", model = "claude-fast" }
+"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("helper.yaml"),
+            "id: helper-1\nname: Helper\nprovider: p1\nmodel: claude-other\nmax_tokens: 1024\n",
+        )
+        .unwrap();
+        fs::write(dir.join("broken.toml"), "provider = \"nope\"\nmodel = \"m\"\n").unwrap();
+        fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let agents = load_agent_files(&dir, &[provider()], &[]);
+        assert_eq!(agents.len(), 2);
+
+        let helper = &agents[0];
+        assert_eq!(helper.id, "helper-1");
+        assert_eq!(helper.name, "Helper");
+        assert_eq!(helper.max_tokens, Some(1024));
+        assert!(helper.mcp_server_ids.is_none());
+        assert!(helper.race.is_none());
+
+        let reviewer = &agents[1];
+        assert_eq!(reviewer.id, "reviewer");
+        assert_eq!(reviewer.provider_id, "p1");
+        assert_eq!(reviewer.system_prompt.as_deref(), Some("Review carefully."));
+        assert_eq!(reviewer.thinking_budget, Some(4000));
+        assert_eq!(reviewer.mcp_server_ids, Some(vec![]));
+        let race = reviewer.race.as_ref().unwrap();
+        assert_eq!((race.provider_id.as_str(), race.model.as_str()), ("p1", "claude-fast"));
+        assert!(reviewer.source.as_deref().unwrap().ends_with("reviewer.toml"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn missing_directory_yields_no_agents() {
+        let agents = load_agent_files(Path::new("/nonexistent/agents"), &[], &[]);
+        assert!(agents.is_empty());
+    }
+}
@@ -1,3 +1,5 @@
+pub mod file;
+pub mod profiles;
 pub mod service;
 pub mod store;
 pub mod types;
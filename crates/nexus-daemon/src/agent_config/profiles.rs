@@ -0,0 +1,117 @@
+//! Built-in agent presets.
+//!
+//! A profile is a ready-made system prompt and sampling setup; creating an
+//! agent from one only needs a provider and model
+//! (`POST /api/agents/profiles/{id}`).
+
+use serde::Serialize;
+
+use super::store::CreateAgentParams;
+
+#[derive(Debug, Serialize)]
+pub struct AgentProfile {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub system_prompt: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    pub max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thinking_budget: Option<u32>,
+    /// Whether the agent gets MCP servers (all of them) or none.
+    pub mcp_servers: bool,
+}
+
+impl AgentProfile {
+    pub fn params(&self, name: Option<String>, provider_id: String, model: String) -> CreateAgentParams {
+        CreateAgentParams {
+            name: name.unwrap_or_else(|| self.name.to_string()),
+            provider_id,
+            model,
+            system_prompt: Some(self.system_prompt.to_string()),
+            temperature: self.temperature,
+            max_tokens: Some(self.max_tokens),
+            thinking_budget: self.thinking_budget,
+            mcp_server_ids: if self.mcp_servers { None } else { Some(Vec::new()) },
+        }
+    }
+}
+
+pub const PROFILES: &[AgentProfile] = &[
+    AgentProfile {
+        id: "coding",
+        name: "Coding Agent",
+        description: "Reads, edits and tests code in the workspace with the filesystem and shell tools.",
+        system_prompt: "You are a careful software engineer working in the user's repository.\n\
+            - Read the relevant files and search the codebase before changing anything; \
+            follow the conventions you find.\n\
+            - Prefer small, focused edits over rewrites. Do not touch code unrelated to the task.\n\
+            - After a change, build and run the relevant tests with the shell tool and fix what breaks.\n\
+            - Never claim something works without having run it. Report failures with the actual output.\n\
+            - Use git to inspect history and diffs; do not commit, push or rewrite history unless asked.",
+        temperature: None,
+        max_tokens: 16384,
+        thinking_budget: Some(8000),
+        mcp_servers: true,
+    },
+    AgentProfile {
+        id: "research",
+        name: "Research Agent",
+        description: "Answers questions from web sources using the fetch tool, with citations.",
+        system_prompt: "You are a research assistant.\n\
+            - Use the fetch tool to consult primary sources; prefer official documentation, \
+            papers and original announcements over summaries.\n\
+            - Cross-check claims that matter against a second source.\n\
+            - Cite the URL for every non-obvious fact, inline, next to the claim.\n\
+            - Separate what the sources say from your own inference, and say when sources disagree \
+            or when you could not find an answer.\n\
+            - Finish with a short summary of the findings.",
+        temperature: None,
+        max_tokens: 8192,
+        thinking_budget: Some(4000),
+        mcp_servers: true,
+    },
+    AgentProfile {
+        id: "extraction",
+        name: "Extraction Agent",
+        description: "Turns unstructured text into JSON matching a schema or field list you provide.",
+        system_prompt: "You extract structured data from the text the user provides.\n\
+            - Reply with a single JSON value and nothing else: no prose, no code fences.\n\
+            - Follow the schema or field list in the request exactly, including field names and types.\n\
+            - Use null for values that are absent from the text. Never guess or invent values.\n\
+            - Copy values as written unless the schema asks for normalisation (dates, numbers, units).",
+        temperature: Some(0.0),
+        max_tokens: 8192,
+        thinking_budget: None,
+        mcp_servers: false,
+    },
+];
+
+pub fn get(id: &str) -> Option<&'static AgentProfile> {
+    PROFILES.iter().find(|p| p.id == id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profiles_build_create_params() {
+        let params = get("extraction")
+            .unwrap()
+            .params(None, "prov".into(), "claude-test".into());
+        assert_eq!(params.name, "Extraction Agent");
+        assert_eq!(params.temperature, Some(0.0));
+        assert_eq!(params.mcp_server_ids, Some(vec![]));
+
+        let params = get("coding")
+            .unwrap()
+            .params(Some("Mine".into()), "prov".into(), "claude-test".into());
+        assert_eq!(params.name, "Mine");
+        assert_eq!(params.thinking_budget, Some(8000));
+        assert!(params.mcp_server_ids.is_none());
+
+        assert!(get("missing").is_none());
+    }
+}
@@ -13,9 +13,25 @@ pub struct CreateAgentParams {
     pub system_prompt: Option<String>,
     pub temperature: Option<f32>,
     pub max_tokens: Option<u32>,
+    pub thinking_budget: Option<u32>,
     pub mcp_server_ids: Option<Vec<String>>,
 }
 
+/// Returned (inside `anyhow::Error`) when updating or deleting an agent that
+/// was loaded from an agent file. Those are edited on disk, not through the API.
+#[derive(Debug)]
+pub struct FileDefinedAgent {
+    pub source: String,
+}
+
+impl std::fmt::Display for FileDefinedAgent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "agent is defined in {}; edit that file instead", self.source)
+    }
+}
+
+impl std::error::Error for FileDefinedAgent {}
+
 pub struct AgentStore {
     agents: Vec<AgentEntry>,
     active_agent_id: Option<String>,
@@ -29,6 +45,23 @@ impl AgentStore {
         }
     }
 
+    /// Add agents loaded from agent files. A file agent whose id is already
+    /// taken is skipped: replacing the stored agent would drop it from
+    /// nexus.json on the next save.
+    pub fn add_file_agents(&mut self, agents: Vec<AgentEntry>) {
+        for agent in agents {
+            if self.get(&agent.id).is_some() {
+                tracing::warn!(
+                    id = %agent.id,
+                    source = agent.source.as_deref().unwrap_or_default(),
+                    "Skipping agent file: id is already in use"
+                );
+                continue;
+            }
+            self.agents.push(agent);
+        }
+    }
+
     pub fn list(&self) -> &[AgentEntry] {
         &self.agents
     }
@@ -56,8 +89,10 @@ impl AgentStore {
             system_prompt: params.system_prompt,
             temperature: params.temperature,
             max_tokens: params.max_tokens,
-            thinking_budget: None,
+            thinking_budget: params.thinking_budget,
             mcp_server_ids: params.mcp_server_ids,
+            race: None,
+            source: None,
             created_at: now,
             updated_at: now,
         };
@@ -70,6 +105,9 @@ impl AgentStore {
         let Some(agent) = self.agents.iter_mut().find(|a| a.id == id) else {
             return Ok(None);
         };
+        if let Some(ref source) = agent.source {
+            return Err(FileDefinedAgent { source: source.clone() }.into());
+        }
 
         if let Some(name) = updates.name {
             agent.name = name;
@@ -103,6 +141,9 @@ impl AgentStore {
     }
 
     pub fn delete(&mut self, id: &str) -> Result<bool> {
+        if let Some(source) = self.get(id).and_then(|a| a.source.clone()) {
+            return Err(FileDefinedAgent { source }.into());
+        }
         let len = self.agents.len();
         self.agents.retain(|a| a.id != id);
         if self.agents.len() < len {
@@ -119,7 +160,12 @@ impl AgentStore {
 
     fn save(&self) -> Result<()> {
         let mut config = NexusConfig::load()?;
-        config.agents = self.agents.clone();
+        config.agents = self
+            .agents
+            .iter()
+            .filter(|a| a.source.is_none())
+            .cloned()
+            .collect();
         config.active_agent_id = self.active_agent_id.clone();
         config.save()
     }
@@ -139,3 +185,68 @@ pub struct AgentUpdate {
     pub mcp_server_ids: Option<Vec<String>>,
     pub set_mcp_server_ids: bool,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agent(id: &str, source: Option<&str>) -> AgentEntry {
+        AgentEntry {
+            id: id.into(),
+            name: id.into(),
+            provider_id: "p1".into(),
+            model: "m".into(),
+            system_prompt: None,
+            temperature: None,
+            max_tokens: None,
+            thinking_budget: None,
+            mcp_server_ids: None,
+            race: None,
+            source: source.map(String::from),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn rename(name: &str) -> AgentUpdate {
+        AgentUpdate {
+            name: Some(name.into()),
+            provider_id: None,
+            model: None,
+            system_prompt: None,
+            temperature: None,
+            set_temperature: false,
+            max_tokens: None,
+            set_max_tokens: false,
+            thinking_budget: None,
+            set_thinking_budget: false,
+            mcp_server_ids: None,
+            set_mcp_server_ids: false,
+        }
+    }
+
+    #[test]
+    fn file_agent_does_not_replace_stored_agent() {
+        let mut store = AgentStore::new(vec![agent("a1", None)], None);
+        store.add_file_agents(vec![
+            agent("a1", Some("/agents/a1.toml")),
+            agent("a2", Some("/agents/a2.toml")),
+        ]);
+
+        assert_eq!(store.list().len(), 2);
+        assert!(store.get("a1").unwrap().source.is_none());
+        assert!(store.get("a2").unwrap().source.is_some());
+    }
+
+    #[test]
+    fn file_agents_reject_update_and_delete() {
+        let mut store = AgentStore::new(Vec::new(), None);
+        store.add_file_agents(vec![agent("a1", Some("/agents/a1.toml"))]);
+
+        let err = store.update("a1", rename("x")).unwrap_err();
+        assert!(err.downcast_ref::<FileDefinedAgent>().is_some(), "{err}");
+        let err = store.delete("a1").unwrap_err();
+        assert!(err.downcast_ref::<FileDefinedAgent>().is_some(), "{err}");
+        assert_eq!(store.get("a1").unwrap().name, "a1");
+    }
+}
@@ -19,8 +19,22 @@ pub struct AgentEntry {
     /// MCP server IDs this agent can use. None = all servers, Some([]) = no servers.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub mcp_server_ids: Option<Vec<String>>,
+    /// Second provider/model to race every request against; the first to
+    /// start producing content is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub race: Option<RaceTarget>,
+    /// Path of the agent file this entry was loaded from. Such agents are
+    /// read-only and never saved to `nexus.json`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
     #[serde(default = "chrono::Utc::now")]
     pub created_at: DateTime<Utc>,
     #[serde(default = "chrono::Utc::now")]
     pub updated_at: DateTime<Utc>,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RaceTarget {
+    pub provider_id: String,
+    pub model: String,
+}
@@ -46,7 +46,7 @@ impl DaemonModule for AutoTitleModule {
         "auto_title"
     }
 
-    async fn turn_end(&self, event: &TurnEndEvent<'_>) {
+    async fn turn_end(&self, event: &mut TurnEndEvent<'_>) {
         if event.error.is_some() {
             return;
         }
@@ -129,20 +129,26 @@ impl DaemonModule for AutoTitleModule {
 impl AutoTitleModule {
     /// Resolve an InferenceProvider from the active agent's provider config.
     async fn resolve_provider(&self) -> Option<(Arc<dyn InferenceProvider>, String)> {
-        let agent = self.agents.active_agent().await?;
-        let provider_record = self.providers.get(&agent.provider_id).await?;
+        resolve_fast_provider(&self.agents, &self.providers, &self.model_tiers).await
+    }
+}
 
-        let title_model = self.model_tiers.resolve(
-            &provider_record.provider_type,
-            ModelTier::Fast,
-        );
+/// The active agent's provider with its fast-tier model.
+pub(crate) async fn resolve_fast_provider(
+    agents: &AgentService,
+    providers: &ProviderService,
+    model_tiers: &ModelTierConfig,
+) -> Option<(Arc<dyn InferenceProvider>, String)> {
+    let agent = agents.active_agent().await?;
+    let provider_record = providers.get(&agent.provider_id).await?;
 
-        match self.providers.get_client(&provider_record).await {
-            Ok(instance) => Some((instance, title_model)),
-            Err(e) => {
-                tracing::warn!("auto_title: failed to create provider: {}", e);
-                None
-            }
+    let fast_model = model_tiers.resolve(&provider_record.provider_type, ModelTier::Fast);
+
+    match providers.get_client(&provider_record).await {
+        Ok(instance) => Some((instance, fast_model)),
+        Err(e) => {
+            tracing::warn!("failed to create fast-tier provider: {}", e);
+            None
         }
     }
 }
@@ -179,22 +185,56 @@ async fn call_title_model(
     model: &str,
     summary: &str,
 ) -> Result<TitleResult, String> {
+    let (text, cost) = complete(provider, model, TITLE_PROMPT, summary, 30).await?;
+    let text = text.trim().to_string();
+
+    if text.is_empty() || text.eq_ignore_ascii_case("KEEP") {
+        return Ok(TitleResult { title: None, cost });
+    }
+
+    let cleaned = text
+        .trim_matches(|c: char| c == '"' || c == '\'')
+        .trim()
+        .chars()
+        .take(100)
+        .collect::<String>();
+
+    if cleaned.is_empty() {
+        Ok(TitleResult { title: None, cost })
+    } else {
+        Ok(TitleResult {
+            title: Some(cleaned),
+            cost,
+        })
+    }
+}
+
+/// One short, tool-less completion: streams `prompt` under `system` and
+/// returns the response text with its cost in USD.
+pub(crate) async fn complete(
+    provider: &dyn InferenceProvider,
+    model: &str,
+    system: &str,
+    prompt: &str,
+    max_tokens: u32,
+) -> Result<(String, f64), String> {
     let messages = vec![Message {
         role: Role::User,
         content: vec![ContentBlock::Text {
-            text: summary.to_string(),
+            text: prompt.to_string(),
         }],
     }];
 
     let mut stream = provider
         .create_message_stream(InferenceRequest {
             model: model.to_string(),
-            max_tokens: 30,
-            system: Some(TITLE_PROMPT.to_string()),
+            max_tokens,
+            system: Some(system.to_string()),
             temperature: None,
             thinking_budget: None,
             messages,
             tools: Vec::new(),
+            idempotency_key: None,
         })
         .await
         .map_err(|e| format!("stream creation failed: {}", e))?;
@@ -233,25 +273,5 @@ async fn call_title_model(
     }
 
     let cost = nexus_pricing::calculate_cost(model, input_tokens, output_tokens);
-    let text = text.trim().to_string();
-
-    if text.is_empty() || text.eq_ignore_ascii_case("KEEP") {
-        return Ok(TitleResult { title: None, cost });
-    }
-
-    let cleaned = text
-        .trim_matches(|c: char| c == '"' || c == '\'')
-        .trim()
-        .chars()
-        .take(100)
-        .collect::<String>();
-
-    if cleaned.is_empty() {
-        Ok(TitleResult { title: None, cost })
-    } else {
-        Ok(TitleResult {
-            title: Some(cleaned),
-            cost,
-        })
-    }
+    Ok((text, cost))
 }
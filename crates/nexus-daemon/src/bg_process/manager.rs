@@ -1,6 +1,8 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
 use chrono::Utc;
 use tokio::sync::{broadcast, Mutex};
@@ -13,6 +15,11 @@ use nexus_core::bg_process::*;
 
 const MAX_CONCURRENT_PER_CONVERSATION: usize = 5;
 const PREVIEW_CHARS: usize = 500;
+/// Longest chunk returned by an offset read, in bytes.
+const MAX_READ_BYTES: usize = 100_000;
+/// Process records, kept next to their output files so a restarted daemon
+/// can find processes that outlived it.
+const REGISTRY_FILE: &str = "processes.json";
 
 pub struct ProcessManager {
     processes: Mutex<HashMap<String, BgProcess>>,
@@ -36,8 +43,18 @@ impl ProcessManager {
         message_queue: Arc<MessageQueue>,
     ) -> Self {
         std::fs::create_dir_all(&base_dir).ok();
+        let processes = std::fs::read_to_string(base_dir.join(REGISTRY_FILE))
+            .ok()
+            .and_then(|json| serde_json::from_str::<Vec<BgProcess>>(&json).ok())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|mut p| {
+                p.output_path = base_dir.join(format!("{}.out", p.id));
+                (p.id.clone(), p)
+            })
+            .collect();
         Self {
-            processes: Mutex::new(HashMap::new()),
+            processes: Mutex::new(processes),
             cancels: Mutex::new(HashMap::new()),
             base_dir,
             agent_tx,
@@ -82,12 +99,15 @@ impl ProcessManager {
             completed_at: None,
             exit_code: None,
             is_error: false,
+            pid: None,
+            pid_started: None,
             output_path: output_path.clone(),
             output_preview: None,
             output_size: 0,
         };
 
         procs.insert(id.clone(), process.clone());
+        self.save(&procs);
         drop(procs);
 
         let cancel = CancellationToken::new();
@@ -110,6 +130,61 @@ impl ProcessManager {
         })
     }
 
+    /// Record the OS process id of a spawned shell command, along with its
+    /// start time so it can be recognised after a restart.
+    pub async fn set_pid(&self, process_id: &str, pid: Option<u32>) {
+        let mut procs = self.processes.lock().await;
+        if let Some(proc) = procs.get_mut(process_id) {
+            proc.pid = pid;
+            proc.pid_started = pid.and_then(process_start_time);
+            self.save(&procs);
+        }
+    }
+
+    /// Pick up processes left running by a previous daemon. Shell commands
+    /// that are still alive, and still the same process (same pid and start
+    /// time), are watched until they exit and can be stopped as usual; the
+    /// rest are marked failed.
+    pub async fn reattach(self: &Arc<Self>) {
+        let mut procs = self.processes.lock().await;
+        let mut cancels = self.cancels.lock().await;
+        for proc in procs.values_mut() {
+            if proc.status != ProcessStatus::Running || cancels.contains_key(&proc.id) {
+                continue;
+            }
+            let started = proc.pid_started.clone();
+            let Some(pid) = proc.pid.filter(|&pid| is_same_process(pid, started.as_deref()))
+            else {
+                proc.status = ProcessStatus::Failed;
+                proc.completed_at = Some(Utc::now());
+                proc.is_error = true;
+                proc.pid = None;
+                proc.pid_started = None;
+                continue;
+            };
+
+            let token = CancellationToken::new();
+            cancels.insert(proc.id.clone(), token.clone());
+            let pm = Arc::clone(self);
+            let id = proc.id.clone();
+            tokio::spawn(async move {
+                loop {
+                    tokio::select! {
+                        _ = token.cancelled() => return,
+                        _ = tokio::time::sleep(Duration::from_secs(1)) => {}
+                    }
+                    // Not our child, so its exit status is lost: report
+                    // it as failed with an unknown exit code.
+                    if !is_same_process(pid, started.as_deref()) {
+                        pm.complete(&id, None, true).await;
+                        return;
+                    }
+                }
+            });
+        }
+        self.save(&procs);
+    }
+
     /// Mark a process as completed/failed and enqueue a notification message.
     pub async fn complete(
         &self,
@@ -133,6 +208,8 @@ impl ProcessManager {
         proc.completed_at = Some(Utc::now());
         proc.exit_code = exit_code;
         proc.is_error = is_error;
+        proc.pid = None;
+        proc.pid_started = None;
 
         // Read output preview + size
         if let Ok(meta) = std::fs::metadata(&proc.output_path) {
@@ -147,6 +224,7 @@ impl ProcessManager {
 
         let snapshot = proc.clone();
         let conv_id = proc.conversation_id.clone();
+        self.save(&procs);
         drop(procs);
 
         // Remove cancel token
@@ -175,57 +253,75 @@ impl ProcessManager {
             .await;
     }
 
-    /// Cancel a running process.
+    /// Cancel a running process, signalling its process group if it is a
+    /// shell command. The group is signalled and the process marked
+    /// cancelled before the watcher's token fires, so the watcher finds it
+    /// finished rather than killing only the shell and reporting a failure.
     pub async fn cancel(&self, process_id: &str) -> Result<(), String> {
-        let cancels = self.cancels.lock().await;
-        let Some(token) = cancels.get(process_id) else {
+        let Some(token) = self.cancels.lock().await.get(process_id).cloned() else {
             return Err("Process not found or already finished".to_string());
         };
-        token.cancel();
-        drop(cancels);
 
         let mut procs = self.processes.lock().await;
-        if let Some(proc) = procs.get_mut(process_id) {
-            proc.status = ProcessStatus::Cancelled;
-            proc.completed_at = Some(Utc::now());
-
-            let snapshot = proc.clone();
-            let conv_id = proc.conversation_id.clone();
+        let Some(proc) = procs.get_mut(process_id) else {
             drop(procs);
+            token.cancel();
+            return Err("Process not found".to_string());
+        };
+        if let Some(pid) = proc.pid.take() {
+            stop_process_group(pid, proc.pid_started.take().as_deref());
+        }
+        proc.status = ProcessStatus::Cancelled;
+        proc.completed_at = Some(Utc::now());
 
-            self.cancels.lock().await.remove(process_id);
+        let snapshot = proc.clone();
+        let conv_id = proc.conversation_id.clone();
+        self.save(&procs);
+        drop(procs);
 
-            let _ = self.agent_tx.send(EventEnvelope {
-                thread_id: Some(conv_id),
-                run_id: None,
-                event: AgUiEvent::Custom {
-                    name: "bg_process_cancelled".to_string(),
-                    value: serde_json::to_value(&snapshot).unwrap_or_default(),
-                },
-            });
+        token.cancel();
+        self.cancels.lock().await.remove(process_id);
 
-            Ok(())
-        } else {
-            Err("Process not found".to_string())
-        }
+        let _ = self.agent_tx.send(EventEnvelope {
+            thread_id: Some(conv_id),
+            run_id: None,
+            event: AgUiEvent::Custom {
+                name: "bg_process_cancelled".to_string(),
+                value: serde_json::to_value(&snapshot).unwrap_or_default(),
+            },
+        });
+
+        Ok(())
     }
 
-    /// List all processes for a conversation.
+    /// List all processes for a conversation. Running processes report the
+    /// output written so far.
     pub async fn list(&self, conversation_id: &str) -> Vec<BgProcess> {
         let procs = self.processes.lock().await;
         procs
             .values()
             .filter(|p| p.conversation_id == conversation_id)
             .cloned()
+            .map(|mut p| {
+                if p.status == ProcessStatus::Running {
+                    if let Ok(meta) = std::fs::metadata(&p.output_path) {
+                        p.output_size = meta.len();
+                    }
+                }
+                p
+            })
             .collect()
     }
 
-    /// Read output from a process file. Supports tail (last N lines) and head (first N lines).
+    /// Read output from a process file. Supports tail (last N lines), head
+    /// (first N lines) and offset (bytes after a position, ending with the
+    /// offset to poll from next).
     pub async fn read_output(
         &self,
         process_id: &str,
         tail: Option<usize>,
         head: Option<usize>,
+        offset: Option<u64>,
     ) -> Result<String, String> {
         let procs = self.processes.lock().await;
         let Some(proc) = procs.get(process_id) else {
@@ -234,6 +330,12 @@ impl ProcessManager {
         let path = proc.output_path.clone();
         drop(procs);
 
+        if let Some(offset) = offset {
+            let (chunk, next_offset) =
+                read_chunk(&path, offset).map_err(|e| format!("Failed to read output: {e}"))?;
+            return Ok(format!("{chunk}\n[next_offset: {next_offset}]"));
+        }
+
         let content =
             std::fs::read_to_string(&path).map_err(|e| format!("Failed to read output: {e}"))?;
 
@@ -269,14 +371,19 @@ impl ProcessManager {
 
         let mut cancels = self.cancels.lock().await;
         for id in &ids {
-            if let Some(token) = cancels.remove(id) {
-                token.cancel();
-            }
+            // Signal the group before the watcher kills the shell.
             if let Some(proc) = procs.remove(id) {
+                if let (Some(pid), ProcessStatus::Running) = (proc.pid, proc.status) {
+                    stop_process_group(pid, proc.pid_started.as_deref());
+                }
                 let _ = std::fs::remove_file(&proc.output_path);
             }
+            if let Some(token) = cancels.remove(id) {
+                token.cancel();
+            }
         }
         drop(cancels);
+        self.save(&procs);
         drop(procs);
 
         self.message_queue.clear(conversation_id).await;
@@ -289,6 +396,103 @@ impl ProcessManager {
             .values()
             .any(|p| p.conversation_id == conversation_id && p.status == ProcessStatus::Running)
     }
+
+    /// Write the process records to the registry file.
+    fn save(&self, procs: &HashMap<String, BgProcess>) {
+        let mut records: Vec<&BgProcess> = procs.values().collect();
+        records.sort_by_key(|p| p.started_at);
+        let result = serde_json::to_vec_pretty(&records)
+            .map_err(std::io::Error::other)
+            .and_then(|json| std::fs::write(self.base_dir.join(REGISTRY_FILE), json));
+        if let Err(e) = result {
+            tracing::warn!("Failed to save background process registry: {}", e);
+        }
+    }
+}
+
+/// Ask a background command and everything it started to terminate. Does
+/// nothing unless `pid` is still the process recorded as started at `started`.
+/// Up to [`MAX_READ_BYTES`] of the file from `offset`, and the offset just
+/// past them. Only the chunk is read, so polling a growing log stays cheap.
+/// A character split by the end of the chunk is left for the next read.
+fn read_chunk(path: &Path, offset: u64) -> std::io::Result<(String, u64)> {
+    let mut file = std::fs::File::open(path)?;
+    let start = offset.min(file.metadata()?.len());
+    file.seek(SeekFrom::Start(start))?;
+    let mut bytes = Vec::new();
+    file.take(MAX_READ_BYTES as u64).read_to_end(&mut bytes)?;
+    bytes.truncate(char_boundary(&bytes));
+    Ok((String::from_utf8_lossy(&bytes).into_owned(), start + bytes.len() as u64))
+}
+
+/// Length of `bytes` without a multi-byte UTF-8 character cut off at the
+/// end.
+fn char_boundary(bytes: &[u8]) -> usize {
+    // The lead byte of the last character is at most 3 bytes back.
+    for back in 1..=bytes.len().min(4) {
+        let lead = bytes[bytes.len() - back];
+        if lead & 0xC0 == 0x80 {
+            continue;
+        }
+        let width = match lead {
+            0xC0..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF7 => 4,
+            _ => 1,
+        };
+        return if back < width { bytes.len() - back } else { bytes.len() };
+    }
+    bytes.len()
+}
+
+fn stop_process_group(pid: u32, started: Option<&str>) {
+    if !is_same_process(pid, started) {
+        tracing::warn!(pid, "Not signalling background process: pid no longer matches the record");
+        return;
+    }
+    #[cfg(unix)]
+    {
+        // The command was spawned as a process group leader, so its pgid is its pid.
+        unsafe {
+            libc::kill(-(pid as libc::pid_t), libc::SIGTERM);
+        }
+    }
+}
+
+/// Whether `pid` is running and started at `started`. False when either
+/// start time is unknown, since the pid alone may have been reused.
+fn is_same_process(pid: u32, started: Option<&str>) -> bool {
+    started.is_some_and(|started| process_start_time(pid).as_deref() == Some(started))
+}
+
+/// When `pid` started, in an OS-specific format that only needs to compare
+/// equal for the same process. `None` if it isn't running or the platform
+/// can't tell.
+fn process_start_time(pid: u32) -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        // Field 22 of /proc/<pid>/stat: start time in clock ticks after boot.
+        // The command name (field 2) may contain spaces, so count from the ')'.
+        // An exited but unreaped process (state Z) is not running.
+        let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+        let (_, rest) = stat.rsplit_once(')')?;
+        let fields: Vec<&str> = rest.split_whitespace().collect();
+        (fields.first() != Some(&"Z")).then(|| fields.get(19).map(|s| s.to_string()))?
+    }
+    #[cfg(all(unix, not(target_os = "linux")))]
+    {
+        let output = std::process::Command::new("ps")
+            .args(["-o", "lstart=", "-p", &pid.to_string()])
+            .output()
+            .ok()?;
+        let started = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (output.status.success() && !started.is_empty()).then_some(started)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = pid;
+        None
+    }
 }
 
 /// Format a completed background process into a notification message.
@@ -305,8 +509,12 @@ fn format_bg_notification(process: &BgProcess) -> String {
         process.label, process.id, status
     );
 
-    if let Some(code) = process.exit_code {
-        text.push_str(&format!(" | Exit code: {}", code));
+    match (process.exit_code, process.status) {
+        (Some(code), _) => text.push_str(&format!(" | Exit code: {}", code)),
+        (None, ProcessStatus::Completed | ProcessStatus::Failed) => {
+            text.push_str(" | Exit code: unknown")
+        }
+        _ => {}
     }
 
     text.push_str(&format!(" | Output: {} bytes", process.output_size));
@@ -460,13 +668,16 @@ mod tests {
             .await
             .unwrap();
 
-        assert!(result.cancel_token.is_cancelled() == false);
+        assert!(!result.cancel_token.is_cancelled());
         pm.cancel(&result.process_id).await.unwrap();
         assert!(result.cancel_token.is_cancelled());
 
+        // The watcher completing after the token fires changes nothing.
+        pm.complete(&result.process_id, None, true).await;
         let listed = pm.list("conv1").await;
         assert_eq!(listed[0].status, ProcessStatus::Cancelled);
         assert!(listed[0].completed_at.is_some());
+        assert!(pm.message_queue.drain("conv1").await.is_empty());
 
         pm.cleanup_conversation("conv1").await;
     }
@@ -495,7 +706,7 @@ mod tests {
             .unwrap();
         std::fs::write(&result.output_path, "line1\nline2\nline3\n").unwrap();
 
-        let output = pm.read_output(&result.process_id, None, None).await.unwrap();
+        let output = pm.read_output(&result.process_id, None, None, None).await.unwrap();
         assert_eq!(output, "line1\nline2\nline3\n");
 
         pm.cleanup_conversation("conv1").await;
@@ -510,7 +721,7 @@ mod tests {
             .unwrap();
         std::fs::write(&result.output_path, "line1\nline2\nline3").unwrap();
 
-        let output = pm.read_output(&result.process_id, Some(1), None).await.unwrap();
+        let output = pm.read_output(&result.process_id, Some(1), None, None).await.unwrap();
         assert_eq!(output, "line3");
 
         pm.cleanup_conversation("conv1").await;
@@ -525,16 +736,142 @@ mod tests {
             .unwrap();
         std::fs::write(&result.output_path, "line1\nline2\nline3").unwrap();
 
-        let output = pm.read_output(&result.process_id, None, Some(2)).await.unwrap();
+        let output = pm.read_output(&result.process_id, None, Some(2), None).await.unwrap();
         assert_eq!(output, "line1\nline2");
 
         pm.cleanup_conversation("conv1").await;
     }
 
+    #[tokio::test]
+    async fn read_output_from_offset_returns_only_new_output() {
+        let pm = make_pm();
+        let result = pm
+            .spawn("conv1", "t".into(), "t".into(), ProcessKind::Bash)
+            .await
+            .unwrap();
+
+        std::fs::write(&result.output_path, "one\n").unwrap();
+        let first = pm.read_output(&result.process_id, None, None, Some(0)).await.unwrap();
+        assert_eq!(first, "one\n\n[next_offset: 4]");
+
+        std::fs::write(&result.output_path, "one\ntwo\n").unwrap();
+        let next = pm.read_output(&result.process_id, None, None, Some(4)).await.unwrap();
+        assert_eq!(next, "two\n\n[next_offset: 8]");
+        let past_end = pm.read_output(&result.process_id, None, None, Some(99)).await.unwrap();
+        assert_eq!(past_end, "\n[next_offset: 8]");
+        assert_eq!(pm.list("conv1").await[0].output_size, 8);
+
+        // A character split across writes is returned whole by the next poll.
+        std::fs::write(&result.output_path, b"one\ntwo\n\xc3").unwrap();
+        let split = pm.read_output(&result.process_id, None, None, Some(8)).await.unwrap();
+        assert_eq!(split, "\n[next_offset: 8]");
+        std::fs::write(&result.output_path, "one\ntwo\n\u{e9}!").unwrap();
+        let whole = pm.read_output(&result.process_id, None, None, Some(8)).await.unwrap();
+        assert_eq!(whole, "\u{e9}!\n[next_offset: 11]");
+
+        pm.cleanup_conversation("conv1").await;
+    }
+
+    /// Kills the listed process groups when dropped, so a failing test
+    /// doesn't leave its commands running.
+    struct KillGroups(Vec<u32>);
+
+    impl Drop for KillGroups {
+        fn drop(&mut self) {
+            #[cfg(unix)]
+            for &pid in &self.0 {
+                unsafe {
+                    libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn restarted_manager_reattaches_live_commands_and_fails_dead_ones() {
+        let dir = std::env::temp_dir().join(format!("nexus-bg-test-{}", Uuid::new_v4()));
+        let (tx, _rx) = broadcast::channel(16);
+        let (queue, _queue_rx) = MessageQueue::new();
+        let queue = Arc::new(queue);
+
+        let before = ProcessManager::new(dir.clone(), tx.clone(), queue.clone());
+        let live = before
+            .spawn("conv1", "server".into(), "sleep 30".into(), ProcessKind::Bash)
+            .await
+            .unwrap();
+        let mut child = nexus_tools::bash::spawn_background("sleep 30", None, &live.output_path).unwrap();
+        before.set_pid(&live.process_id, child.id()).await;
+        let mut groups = KillGroups(child.id().into_iter().collect());
+
+        let dead = before
+            .spawn("conv1", "gone".into(), "true".into(), ProcessKind::Bash)
+            .await
+            .unwrap();
+        let mut exited = nexus_tools::bash::spawn_background("true", None, &dead.output_path).unwrap();
+        before.set_pid(&dead.process_id, exited.id()).await;
+        exited.wait().await.unwrap();
+
+        let later = before
+            .spawn("conv1", "later".into(), "sleep 30".into(), ProcessKind::Bash)
+            .await
+            .unwrap();
+        let mut exits_later = nexus_tools::bash::spawn_background("sleep 30", None, &later.output_path).unwrap();
+        before.set_pid(&later.process_id, exits_later.id()).await;
+        groups.0.extend(exits_later.id());
+
+        // A live pid that belongs to some other process now: this test's own.
+        let reused = before
+            .spawn("conv1", "reused".into(), "true".into(), ProcessKind::Bash)
+            .await
+            .unwrap();
+        before.set_pid(&reused.process_id, Some(std::process::id())).await;
+        drop(before);
+        let registry = dir.join(REGISTRY_FILE);
+        let mut records: Vec<serde_json::Value> =
+            serde_json::from_str(&std::fs::read_to_string(&registry).unwrap()).unwrap();
+        for record in &mut records {
+            if record["id"] == reused.process_id.as_str() {
+                record["pidStarted"] = "0".into();
+            }
+        }
+        std::fs::write(&registry, serde_json::to_vec(&records).unwrap()).unwrap();
+
+        let after = Arc::new(ProcessManager::new(dir.clone(), tx, queue));
+        after.reattach().await;
+        let find = |id: &str, list: &[BgProcess]| list.iter().find(|p| p.id == id).unwrap().clone();
+        let status = |id: &str, list: &[BgProcess]| find(id, list).status;
+        let listed = after.list("conv1").await;
+        assert_eq!(status(&live.process_id, &listed), ProcessStatus::Running);
+        assert_eq!(status(&later.process_id, &listed), ProcessStatus::Running);
+        assert_eq!(status(&dead.process_id, &listed), ProcessStatus::Failed);
+        assert_eq!(status(&reused.process_id, &listed), ProcessStatus::Failed);
+
+        // Its exit code went to the old daemon, so it can't be reported as a success.
+        exits_later.start_kill().unwrap();
+        exits_later.wait().await.unwrap();
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        let exited = loop {
+            let exited = find(&later.process_id, &after.list("conv1").await);
+            if exited.status != ProcessStatus::Running || tokio::time::Instant::now() > deadline {
+                break exited;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        };
+        assert_eq!(exited.status, ProcessStatus::Failed);
+        assert_eq!(exited.exit_code, None);
+
+        after.cancel(&live.process_id).await.unwrap();
+        let exit = tokio::time::timeout(Duration::from_secs(5), child.wait()).await;
+        assert!(exit.is_ok(), "stopping a reattached process should kill it");
+        assert_eq!(status(&live.process_id, &after.list("conv1").await), ProcessStatus::Cancelled);
+
+        std::fs::remove_dir_all(dir).ok();
+    }
+
     #[tokio::test]
     async fn read_output_not_found() {
         let pm = make_pm();
-        let err = pm.read_output("nonexistent", None, None).await.unwrap_err();
+        let err = pm.read_output("nonexistent", None, None, None).await.unwrap_err();
         assert!(err.contains("not found"));
     }
 
@@ -586,6 +923,8 @@ mod tests {
             completed_at: Some(Utc::now()),
             exit_code: Some(0),
             is_error: false,
+            pid: None,
+            pid_started: None,
             output_path: PathBuf::from("/tmp/test.out"),
             output_preview: Some("all tests pass".to_string()),
             output_size: 1234,
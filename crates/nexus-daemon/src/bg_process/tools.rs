@@ -13,8 +13,9 @@ impl nexus_tools::bg_process::ProcessBackend for ProcessManager {
         process_id: &str,
         tail: Option<usize>,
         head: Option<usize>,
+        offset: Option<u64>,
     ) -> Result<String, String> {
-        self.read_output(process_id, tail, head).await
+        self.read_output(process_id, tail, head, offset).await
     }
 
     async fn list_json(&self, conversation_id: &str) -> String {
@@ -47,6 +48,6 @@ impl ToolHandler for BgProcessToolHandler<'_> {
             self.process_manager,
         )
         .await;
-        ToolResult { content, is_error, injected_messages: Vec::new() }
+        ToolResult { content, is_error, injected_messages: Vec::new(), images: Vec::new() }
     }
 }
@@ -0,0 +1,351 @@
+//! Command-line client for a running daemon.
+//!
+//! `nexus` with no subcommand (or `nexus serve`) starts the daemon as
+//! before. The other subcommands talk to a daemon over its HTTP API, so
+//! they use the same agents, providers, tools and conversation store as the
+//! UI. The daemon address comes from `server.host`/`server.port` in
+//! `~/.nexus/nexus.json`, or `--url`.
+
+use std::io::Write;
+
+use anyhow::{bail, Context, Result};
+use clap::{Parser, Subcommand};
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+use crate::config::NexusConfig;
+
+#[derive(Debug, Parser)]
+#[command(name = "nexus", about = "Nexus agent daemon and CLI client")]
+pub struct Cli {
+    /// Daemon URL (defaults to the server address in nexus.json)
+    #[arg(long, global = true)]
+    url: Option<String>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Run the daemon (default)
+    Serve,
+    /// Interactive chat in a new or existing conversation
+    Chat {
+        /// Conversation to continue instead of starting a new one
+        #[arg(long)]
+        conversation: Option<String>,
+    },
+    /// Send one prompt to a new conversation, print the reply, and exit
+    Run {
+        #[arg(long)]
+        prompt: String,
+    },
+    /// Manage stored conversations
+    #[command(subcommand)]
+    Sessions(SessionsCommand),
+}
+
+#[derive(Debug, Subcommand)]
+enum SessionsCommand {
+    /// List conversations, most recently updated first
+    List,
+    /// Continue a conversation interactively, or send it one prompt
+    Resume {
+        id: String,
+        /// Send this prompt, print the reply, and exit
+        #[arg(long)]
+        prompt: Option<String>,
+    },
+    /// Delete a conversation
+    Delete { id: String },
+}
+
+impl Cli {
+    /// Parse arguments. Returns `None` when the daemon should start.
+    pub fn parse_command() -> Option<Self> {
+        let cli = Self::parse();
+        match cli.command {
+            None | Some(Command::Serve) => None,
+            Some(_) => Some(cli),
+        }
+    }
+}
+
+pub async fn run(cli: Cli) -> Result<()> {
+    let client = Client::new(cli.url)?;
+    match cli.command {
+        None | Some(Command::Serve) => unreachable!("serve is handled by main"),
+        Some(Command::Chat { conversation }) => client.chat(conversation).await,
+        Some(Command::Run { prompt }) => {
+            let id = client.create_conversation().await?;
+            eprintln!("conversation {id}");
+            let mut events = client.subscribe().await?;
+            client.send(&id, &prompt, &mut events).await
+        }
+        Some(Command::Sessions(SessionsCommand::List)) => client.list().await,
+        Some(Command::Sessions(SessionsCommand::Resume { id, prompt })) => {
+            client.ensure_exists(&id).await?;
+            match prompt {
+                Some(prompt) => {
+                    let mut events = client.subscribe().await?;
+                    client.send(&id, &prompt, &mut events).await
+                }
+                None => client.chat(Some(id)).await,
+            }
+        }
+        Some(Command::Sessions(SessionsCommand::Delete { id })) => client.delete(&id).await,
+    }
+}
+
+struct Client {
+    http: reqwest::Client,
+    base: String,
+}
+
+impl Client {
+    fn new(url: Option<String>) -> Result<Self> {
+        let base = match url {
+            Some(url) => url,
+            None => {
+                let server = NexusConfig::load()?.server;
+                format!("http://{}:{}", server.host, server.port)
+            }
+        };
+        Ok(Self {
+            http: reqwest::Client::new(),
+            base: base.trim_end_matches('/').to_string(),
+        })
+    }
+
+    async fn create_conversation(&self) -> Result<String> {
+        let body: Value = self
+            .http
+            .post(format!("{}/api/conversations", self.base))
+            .json(&json!({}))
+            .send()
+            .await
+            .with_context(|| format!("Is the daemon running at {}?", self.base))?
+            .error_for_status()?
+            .json()
+            .await?;
+        body["id"]
+            .as_str()
+            .map(str::to_string)
+            .context("daemon returned no conversation id")
+    }
+
+    async fn list(&self) -> Result<()> {
+        let threads: Vec<Value> = self
+            .http
+            .get(format!("{}/api/conversations", self.base))
+            .send()
+            .await
+            .with_context(|| format!("Is the daemon running at {}?", self.base))?
+            .error_for_status()?
+            .json()
+            .await?;
+        for t in threads {
+            println!(
+                "{}  {}  {:>4} msgs  {}",
+                t["id"].as_str().unwrap_or_default(),
+                t["updated_at"].as_str().unwrap_or_default(),
+                t["message_count"].as_u64().unwrap_or_default(),
+                t["title"].as_str().unwrap_or_default(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Fail with a clear message instead of starting a turn the daemon
+    /// would reject.
+    async fn ensure_exists(&self, id: &str) -> Result<()> {
+        let resp = self
+            .http
+            .get(format!("{}/api/conversations/{id}", self.base))
+            .send()
+            .await
+            .with_context(|| format!("Is the daemon running at {}?", self.base))?;
+        match resp.status() {
+            reqwest::StatusCode::NOT_FOUND => bail!("no conversation {id}"),
+            status if !status.is_success() => bail!("lookup failed: HTTP {status}"),
+            _ => Ok(()),
+        }
+    }
+
+    async fn delete(&self, id: &str) -> Result<()> {
+        let resp = self
+            .http
+            .delete(format!("{}/api/conversations/{id}", self.base))
+            .send()
+            .await?;
+        if !resp.status().is_success() {
+            bail!("delete failed: HTTP {}", resp.status());
+        }
+        Ok(())
+    }
+
+    /// Read prompts from stdin until EOF or `/exit`, streaming each reply.
+    async fn chat(&self, conversation: Option<String>) -> Result<()> {
+        let id = match conversation {
+            Some(id) => id,
+            None => self.create_conversation().await?,
+        };
+        eprintln!("conversation {id} — /exit or Ctrl-D to quit");
+
+        let mut events = self.subscribe().await?;
+        let mut lines = BufReader::new(tokio::io::stdin()).lines();
+        loop {
+            eprint!("> ");
+            let Some(line) = lines.next_line().await? else { break };
+            let line = line.trim();
+            if line == "/exit" {
+                break;
+            }
+            if line.is_empty() {
+                continue;
+            }
+            if let Err(e) = self.send(&id, line, &mut events).await {
+                eprintln!("error: {e}");
+            }
+        }
+        Ok(())
+    }
+
+    /// Open the global event stream and wait for SYNC, so no event from a
+    /// turn started afterwards can be missed.
+    async fn subscribe(&self) -> Result<EventStream> {
+        let resp = self
+            .http
+            .get(format!("{}/api/events", self.base))
+            .send()
+            .await
+            .with_context(|| format!("Is the daemon running at {}?", self.base))?
+            .error_for_status()?;
+        let mut events = EventStream {
+            bytes: resp.bytes_stream().boxed(),
+            buf: String::new(),
+        };
+        while let Some(event) = events.next().await? {
+            if event["type"] == "SYNC" {
+                return Ok(events);
+            }
+        }
+        bail!("event stream closed before SYNC")
+    }
+
+    /// Start a turn and print its output until the run ends.
+    async fn send(&self, id: &str, message: &str, events: &mut EventStream) -> Result<()> {
+        self.http
+            .post(format!("{}/api/chat", self.base))
+            .json(&json!({ "conversationId": id, "message": message }))
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let mut stdout = std::io::stdout();
+        while let Some(event) = events.next().await? {
+            if event["threadId"] != id || event.get("runId").is_none() {
+                continue;
+            }
+            match event["type"].as_str().unwrap_or_default() {
+                "TEXT_MESSAGE_CONTENT" => {
+                    print!("{}", event["delta"].as_str().unwrap_or_default());
+                    stdout.flush().ok();
+                }
+                "TOOL_CALL_START" => {
+                    eprintln!("\n[{}]", event["toolCallName"].as_str().unwrap_or_default());
+                }
+                "TOOL_CALL_RESULT" if event["isError"] == true => {
+                    eprintln!("[tool error] {}", event["content"].as_str().unwrap_or_default());
+                }
+                "CUSTOM" if event["name"] == "agent_warning" => {
+                    eprintln!("[warning] {}", event["value"]["message"].as_str().unwrap_or_default());
+                }
+                "RUN_FINISHED" => {
+                    println!();
+                    return Ok(());
+                }
+                "RUN_ERROR" => {
+                    println!();
+                    bail!("{}", event["message"].as_str().unwrap_or("run failed"));
+                }
+                _ => {}
+            }
+        }
+        bail!("event stream closed mid-run")
+    }
+}
+
+/// Incremental reader over the daemon's SSE stream.
+struct EventStream {
+    bytes: BoxStream<'static, reqwest::Result<bytes::Bytes>>,
+    buf: String,
+}
+
+impl EventStream {
+    async fn next(&mut self) -> Result<Option<Value>> {
+        loop {
+            if let Some(event) = take_event(&mut self.buf) {
+                return Ok(Some(event));
+            }
+            match self.bytes.next().await {
+                Some(chunk) => self.buf.push_str(&String::from_utf8_lossy(&chunk?)),
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+/// Pop the next complete SSE frame's JSON `data:` payload off `buf`.
+/// Frames without parseable data (keep-alives, comments) are skipped.
+fn take_event(buf: &mut String) -> Option<Value> {
+    while let Some(end) = buf.find("\n\n") {
+        let frame: String = buf.drain(..end + 2).collect();
+        let data: String = frame
+            .lines()
+            .filter_map(|l| l.strip_prefix("data:"))
+            .map(str::trim_start)
+            .collect();
+        if let Ok(value) = serde_json::from_str(&data) {
+            return Some(value);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn take_event_skips_keepalives_and_keeps_partial_frames() {
+        let mut buf = String::from(": keep-alive\n\ndata: {\"type\":\"SYNC\"}\n\ndata: {\"type\":");
+        assert_eq!(take_event(&mut buf).unwrap()["type"], "SYNC");
+        assert!(take_event(&mut buf).is_none());
+
+        buf.push_str("\"RUN_STARTED\"}\n\n");
+        assert_eq!(take_event(&mut buf).unwrap()["type"], "RUN_STARTED");
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn no_subcommand_means_serve() {
+        let cli = Cli::try_parse_from(["nexus"]).unwrap();
+        assert!(cli.command.is_none());
+
+        let cli = Cli::try_parse_from(["nexus", "sessions", "delete", "abc"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Sessions(SessionsCommand::Delete { ref id })) if id == "abc"
+        ));
+
+        let cli = Cli::try_parse_from(["nexus", "sessions", "resume", "abc", "--prompt", "and now?"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Sessions(SessionsCommand::Resume { ref id, prompt: Some(ref p) }))
+                if id == "abc" && p == "and now?"
+        ));
+    }
+}
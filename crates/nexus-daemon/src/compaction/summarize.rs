@@ -175,13 +175,14 @@ mod tests {
                 tool_call_id: tool_call_id.to_string(),
                 result: "ok".to_string(),
                 is_error: false,
+                images: Vec::new(),
             }],
         )
     }
 
     #[test]
     fn safe_split_no_tools() {
-        let msgs = vec![
+        let msgs = [
             text_user("1", "hi"),
             text_assistant("2", "hello"),
             text_user("3", "how"),
@@ -194,7 +195,7 @@ mod tests {
 
     #[test]
     fn safe_split_boundary_between_tool_call_and_result() {
-        let msgs = vec![
+        let msgs = [
             text_user("1", "do something"),
             tool_call_assistant("2", "tool_0"),
             tool_result_user("3", "tool_0"),
@@ -207,7 +208,7 @@ mod tests {
 
     #[test]
     fn safe_split_boundary_after_tool_result() {
-        let msgs = vec![
+        let msgs = [
             text_user("1", "do something"),
             tool_call_assistant("2", "tool_0"),
             tool_result_user("3", "tool_0"),
@@ -220,7 +221,7 @@ mod tests {
 
     #[test]
     fn safe_split_boundary_on_text_assistant() {
-        let msgs = vec![
+        let msgs = [
             text_user("1", "hi"),
             text_assistant("2", "hello"),
             text_user("3", "how"),
@@ -123,6 +123,23 @@ pub struct NexusConfig {
     pub active_agent_id: Option<String>,
     #[serde(default)]
     pub model_tiers: ModelTierConfig,
+    #[serde(default)]
+    pub conversations: ConversationStorageConfig,
+    #[serde(default)]
+    pub events: EventStreamConfig,
+    /// Push run traces to Langfuse. Absent = disabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub langfuse: Option<LangfuseConfig>,
+    /// Screen user prompts and model output. Absent = disabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub moderation: Option<ModerationConfig>,
+    /// Validate the agent's final answer and ask it to repair failures.
+    /// Absent = disabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guardrails: Option<GuardrailsConfig>,
+    /// Serve repeated inference requests from a cache. Absent = disabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_cache: Option<ResponseCacheConfig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -139,11 +156,334 @@ pub struct ServerConfig {
     pub host: String,
     #[serde(default = "default_port")]
     pub port: u16,
+    /// On SIGTERM/Ctrl-C, how long active turns get to finish their current
+    /// tool call and save before the daemon exits anyway.
+    #[serde(default = "default_shutdown_grace_secs")]
+    pub shutdown_grace_secs: u64,
+}
+
+// ── Conversation Storage ────────────────────────────────────────────────
+
+/// On-disk format for conversation files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageCompression {
+    #[default]
+    None,
+    Gzip,
+}
+
+/// How conversation files are written and maintained. Reads detect the
+/// format from the file header, so changing `compression` never strands
+/// existing files.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationStorageConfig {
+    #[serde(default)]
+    pub compression: StorageCompression,
+    /// Refuse to persist a conversation whose encoded file would exceed
+    /// this many bytes. `None` = unlimited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_file_bytes: Option<u64>,
+    /// Delete conversations not updated within this many days. Checked
+    /// hourly by a background task. `None` = keep forever.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl_days: Option<u32>,
+    /// Append every thread-scoped event to `<id>.events.jsonl` alongside the
+    /// conversation file, for replay and audit.
+    #[serde(default)]
+    pub event_log: bool,
+    /// With `event_log`, fold runs the daemon stopped in the middle of back
+    /// into their conversations at startup, so finished tool calls aren't
+    /// lost with the turn.
+    #[serde(default)]
+    pub recover_interrupted: bool,
+    /// Start even if another live daemon holds the store lock, taking its
+    /// lease. The previous holder stops on its next renewal.
+    #[serde(default)]
+    pub lock_takeover: bool,
+    /// Generate titles with the fast model tier after each turn.
+    #[serde(default = "default_true")]
+    pub auto_title: bool,
+    /// Emit a one-line `turn_summary` event after each turn.
+    #[serde(default)]
+    pub turn_summary: TurnSummaryMode,
+}
+
+/// How `turn_summary` events are produced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TurnSummaryMode {
+    /// No summaries.
+    #[default]
+    Off,
+    /// Built from the turn's tool calls and the start of the final answer.
+    Heuristic,
+    /// Written by the fast model tier; falls back to the heuristic on failure.
+    Model,
+}
+
+impl Default for ConversationStorageConfig {
+    fn default() -> Self {
+        Self {
+            compression: StorageCompression::default(),
+            max_file_bytes: None,
+            ttl_days: None,
+            event_log: false,
+            recover_interrupted: false,
+            lock_takeover: false,
+            auto_title: true,
+            turn_summary: TurnSummaryMode::default(),
+        }
+    }
+}
+
+impl ConversationStorageConfig {
+    /// `ttl_days` as a duration, or an error if it reaches past the
+    /// earliest date a cutoff can represent.
+    pub fn ttl(&self) -> Result<Option<chrono::Duration>> {
+        let Some(days) = self.ttl_days else {
+            return Ok(None);
+        };
+        chrono::Duration::try_days(i64::from(days))
+            .filter(|age| chrono::Utc::now().checked_sub_signed(*age).is_some())
+            .map(Some)
+            .ok_or_else(|| anyhow::anyhow!("conversations.ttl_days = {days} is out of range"))
+    }
+}
+
+// ── Event Stream ────────────────────────────────────────────────────────
+
+/// What to do with an SSE client that falls behind the event channel and
+/// misses events.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LagPolicy {
+    /// Keep streaming and send an `events_dropped` notice so the client can
+    /// reload persisted state.
+    #[default]
+    Notify,
+    /// Close the stream; the client reconnects and replays active turns
+    /// from the SYNC buffer.
+    Disconnect,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EventStreamConfig {
+    #[serde(default)]
+    pub lag_policy: LagPolicy,
+}
+
+// ── Trace Export ────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LangfuseConfig {
+    #[serde(default = "default_langfuse_host")]
+    pub host: String,
+    pub public_key: String,
+    pub secret_key: String,
+}
+
+fn default_langfuse_host() -> String {
+    "https://cloud.langfuse.com".to_string()
+}
+
+// ── Moderation ──────────────────────────────────────────────────────────
+
+/// Where in the turn content is screened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationStage {
+    /// The user's prompt, before inference.
+    Input,
+    /// The model's text, after each inference round.
+    Output,
+}
+
+/// What a matching rule or a flagged API result does to the content.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationAction {
+    /// Stop the prompt before inference, or withhold the model's text.
+    #[default]
+    Block,
+    /// Replace each match with the rule's `replacement`.
+    Rewrite,
+    /// Let the content through, but emit a `moderated` event.
+    Annotate,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModerationConfig {
+    /// Regex rules, applied in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<ModerationRule>,
+    /// OpenAI-compatible moderation API, consulted after the rules.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api: Option<ModerationApiConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModerationRule {
+    /// Regex (use `(?i)` for case-insensitive keyword lists).
+    pub pattern: String,
+    #[serde(default)]
+    pub action: ModerationAction,
+    /// Text substituted for each match by `rewrite`. Defaults to `[redacted]`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<String>,
+    /// Shown to the user when the rule fires. Defaults to the pattern.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(default = "all_moderation_stages")]
+    pub stages: Vec<ModerationStage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModerationApiConfig {
+    #[serde(default = "default_moderation_endpoint")]
+    pub endpoint: String,
+    pub api_key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// `block` or `annotate` (`rewrite` is treated as `block`).
+    #[serde(default)]
+    pub action: ModerationAction,
+    #[serde(default = "all_moderation_stages")]
+    pub stages: Vec<ModerationStage>,
+}
+
+// ── Response cache ──────────────────────────────────────────────────────
+
+/// Response cache keyed by a semantic hash of each request (see
+/// `nexus_provider::cache`). Meant for eval loops and re-runs; turns whose
+/// context changes every time (timestamps in the state update) won't hit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseCacheConfig {
+    #[serde(default = "default_response_cache_ttl")]
+    pub ttl_secs: u64,
+    #[serde(default = "default_response_cache_entries")]
+    pub max_entries: usize,
+}
+
+fn default_response_cache_ttl() -> u64 {
+    3600
+}
+
+fn default_response_cache_entries() -> usize {
+    1000
+}
+
+// ── Guardrails ──────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuardrailsConfig {
+    /// Repair attempts per turn before the invalid answer is kept.
+    #[serde(default = "default_guardrail_retries")]
+    pub max_retries: u32,
+    pub checks: Vec<GuardrailCheckConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuardrailCheckConfig {
+    /// Agent ids or names the check applies to. Empty = every agent.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub agents: Vec<String>,
+    #[serde(flatten)]
+    pub kind: GuardrailKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GuardrailKind {
+    /// The answer must be JSON (optionally in a code fence) valid against
+    /// `schema`.
+    JsonSchema { schema: serde_json::Value },
+    /// The answer must match `pattern` (or must not, with
+    /// `must_match: false`). `message` is the error shown to the model.
+    Regex {
+        pattern: String,
+        #[serde(default = "default_true")]
+        must_match: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+}
+
+fn default_guardrail_retries() -> u32 {
+    2
+}
+
+fn all_moderation_stages() -> Vec<ModerationStage> {
+    vec![ModerationStage::Input, ModerationStage::Output]
+}
+
+fn default_moderation_endpoint() -> String {
+    "https://api.openai.com/v1/moderations".to_string()
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AgentConfig {
     pub system_prompt: Option<String>,
+    /// Variables available to system prompt templates.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub prompt_vars: HashMap<String, String>,
+    /// Provider-side tool definitions, e.g.
+    /// `{"type": "web_search_20250305", "name": "web_search", "max_uses": 5}`.
+    /// Sent untouched, and only to Anthropic providers.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub server_tools: Vec<serde_json::Value>,
+    /// `<environment>` block added to each turn's `<state_update>`.
+    #[serde(default)]
+    pub environment: EnvironmentConfig,
+    /// When a request still exceeds the context window after emergency
+    /// pruning, drop the oldest exchanges instead of failing the turn.
+    #[serde(default)]
+    pub hard_context_cap: bool,
+    /// After this many tool errors in a row, ask the model to reflect on
+    /// what it tried before continuing. Off when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reflect_after_errors: Option<u32>,
+    /// Tool names (or `prefix*` patterns) listed first in requests, in
+    /// this order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_priority: Vec<String>,
+}
+
+/// What the per-turn `<environment>` block describes. Off by default.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvironmentConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Lines to include, in order.
+    #[serde(default = "EnvironmentField::all")]
+    pub fields: Vec<EnvironmentField>,
+}
+
+impl Default for EnvironmentConfig {
+    fn default() -> Self {
+        Self { enabled: false, fields: EnvironmentField::all() }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnvironmentField {
+    /// Operating system and architecture.
+    Os,
+    /// The working directory the tools start from.
+    Cwd,
+    /// Today's date.
+    Date,
+    /// Groups of tools offered this turn, e.g. "filesystem (6)".
+    Tools,
+    /// Directories the filesystem tools can reach, per named workspace.
+    Workspace,
+}
+
+impl EnvironmentField {
+    pub fn all() -> Vec<Self> {
+        vec![Self::Os, Self::Cwd, Self::Date, Self::Tools, Self::Workspace]
+    }
 }
 
 
@@ -178,12 +518,19 @@ fn default_model() -> String {
 fn default_max_tokens() -> u32 {
     8192
 }
+fn default_true() -> bool {
+    true
+}
+
 fn default_host() -> String {
     "127.0.0.1".to_string()
 }
 fn default_port() -> u16 {
     9600
 }
+fn default_shutdown_grace_secs() -> u64 {
+    20
+}
 
 impl Default for ApiConfig {
     fn default() -> Self {
@@ -199,6 +546,7 @@ impl Default for ServerConfig {
         Self {
             host: default_host(),
             port: default_port(),
+            shutdown_grace_secs: default_shutdown_grace_secs(),
         }
     }
 }
@@ -224,6 +572,7 @@ impl NexusConfig {
         FilesystemConfig {
             enabled: self.filesystem.enabled,
             allowed_directories: dirs,
+            workspaces: self.filesystem.workspaces.clone(),
         }
     }
 
@@ -255,6 +604,10 @@ impl NexusConfig {
 
             let config: NexusConfig = serde_json::from_str(&content)
                 .with_context(|| format!("Failed to parse config at {}", path.display()))?;
+            config
+                .conversations
+                .ttl()
+                .with_context(|| format!("Invalid config at {}", path.display()))?;
             Ok(config)
         } else {
             let config = Self::default();
@@ -457,6 +810,34 @@ impl NexusConfig {
 mod tests {
     use super::*;
 
+    #[test]
+    fn conversation_config_defaults_to_auto_title() {
+        let config: NexusConfig = serde_json::from_str(r#"{"conversations":{}}"#).unwrap();
+        assert!(config.conversations.auto_title);
+        assert!(NexusConfig::default().conversations.auto_title);
+
+        let config: NexusConfig =
+            serde_json::from_str(r#"{"conversations":{"auto_title":false}}"#).unwrap();
+        assert!(!config.conversations.auto_title);
+        assert_eq!(config.conversations.turn_summary, TurnSummaryMode::Off);
+
+        let config: NexusConfig =
+            serde_json::from_str(r#"{"conversations":{"turn_summary":"heuristic"}}"#).unwrap();
+        assert_eq!(config.conversations.turn_summary, TurnSummaryMode::Heuristic);
+    }
+
+    #[test]
+    fn ttl_days_beyond_the_representable_range_are_rejected() {
+        let config: NexusConfig =
+            serde_json::from_str(r#"{"conversations":{"ttl_days":30}}"#).unwrap();
+        assert_eq!(config.conversations.ttl().unwrap(), Some(chrono::Duration::days(30)));
+        assert_eq!(NexusConfig::default().conversations.ttl().unwrap(), None);
+
+        let config: NexusConfig =
+            serde_json::from_str(&format!(r#"{{"conversations":{{"ttl_days":{}}}}}"#, u32::MAX)).unwrap();
+        assert!(config.conversations.ttl().is_err());
+    }
+
     #[test]
     fn effective_fs_merges_projects_and_base_dirs() {
         let config = NexusConfig {
@@ -482,6 +863,7 @@ mod tests {
                     "/home/user/project-a".into(), // duplicate of project
                     "/tmp/scratch".into(),
                 ],
+                ..Default::default()
             },
             ..Default::default()
         };
@@ -501,6 +883,7 @@ mod tests {
             filesystem: FilesystemConfig {
                 enabled: true,
                 allowed_directories: vec!["/Volumes/work".into()],
+                ..Default::default()
             },
             ..Default::default()
         };
@@ -521,6 +904,7 @@ mod tests {
             filesystem: FilesystemConfig {
                 enabled: false,
                 allowed_directories: vec![],
+                ..Default::default()
             },
             ..Default::default()
         };
@@ -529,4 +913,19 @@ mod tests {
         // Still merges paths even when disabled (tool_definitions will return empty)
         assert_eq!(effective.allowed_directories.len(), 1);
     }
+
+    #[test]
+    fn effective_fs_keeps_named_workspaces() {
+        let config: NexusConfig = serde_json::from_value(serde_json::json!({
+            "filesystem": {
+                "allowed_directories": ["/tmp/scratch"],
+                "workspaces": [{ "name": "frontend", "allowed_directories": ["/repo/web"] }]
+            }
+        }))
+        .unwrap();
+        let effective = config.effective_filesystem_config();
+        assert_eq!(effective.workspaces.len(), 1);
+        assert_eq!(effective.workspaces[0].name, "frontend");
+        assert_eq!(effective.workspaces[0].allowed_directories, vec!["/repo/web"]);
+    }
 }
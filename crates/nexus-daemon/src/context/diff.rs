@@ -0,0 +1,240 @@
+//! Comparing two context snapshots.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use serde::Serialize;
+
+use super::{ContextSnapshot, MessageFingerprint};
+
+/// A message whose tool result was pruned between the two snapshots.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrunedMessage {
+    pub message: MessageFingerprint,
+    pub tokens_before: u32,
+}
+
+/// Token estimates of one snapshot, by part.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct TokenCounts {
+    pub system: u32,
+    pub tools: u32,
+    pub messages: u32,
+    pub total: u32,
+}
+
+impl TokenCounts {
+    fn of(snapshot: &ContextSnapshot) -> Self {
+        Self {
+            system: snapshot.system_tokens,
+            tools: snapshot.tool_tokens,
+            messages: snapshot.message_tokens(),
+            total: snapshot.total_tokens(),
+        }
+    }
+}
+
+/// What changed in the model's context between two turns.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextDiff {
+    pub from_turn: u32,
+    pub to_turn: u32,
+    /// Messages in the later context only (new turns, compaction summaries).
+    pub added: Vec<MessageFingerprint>,
+    /// Messages whose tool results became pruned stubs.
+    pub pruned: Vec<PrunedMessage>,
+    /// Messages dropped when a new compaction summary replaced them.
+    pub compacted: Vec<MessageFingerprint>,
+    /// Messages dropped without a new summary (e.g. an edit or branch switch).
+    pub removed: Vec<MessageFingerprint>,
+    pub tokens_before: TokenCounts,
+    pub tokens_after: TokenCounts,
+}
+
+/// Compare snapshot `a` with a later snapshot `b`.
+///
+/// Messages are matched by key; repeated keys (identical text) are matched
+/// in order of occurrence.
+pub fn diff(a: &ContextSnapshot, b: &ContextSnapshot) -> ContextDiff {
+    let a_keys = occurrence_keys(&a.messages);
+    let b_keys = occurrence_keys(&b.messages);
+    let in_a: HashMap<&str, &MessageFingerprint> =
+        a_keys.iter().map(String::as_str).zip(&a.messages).collect();
+    let in_b: HashSet<&str> = b_keys.iter().map(String::as_str).collect();
+
+    let mut added = Vec::new();
+    let mut pruned = Vec::new();
+    for (key, message) in b_keys.iter().zip(&b.messages) {
+        match in_a.get(key.as_str()) {
+            None => added.push(message.clone()),
+            Some(before) if message.pruned && !before.pruned => pruned.push(PrunedMessage {
+                message: message.clone(),
+                tokens_before: before.tokens,
+            }),
+            Some(_) => {}
+        }
+    }
+
+    let dropped: Vec<MessageFingerprint> = a_keys
+        .iter()
+        .zip(&a.messages)
+        .filter(|(key, _)| !in_b.contains(key.as_str()))
+        .map(|(_, message)| message.clone())
+        .collect();
+    let (compacted, removed) = if added.iter().any(|m| m.summary) {
+        // Summaries themselves are dropped when spans are re-summarized.
+        (dropped, Vec::new())
+    } else {
+        (Vec::new(), dropped)
+    };
+
+    ContextDiff {
+        from_turn: a.turn,
+        to_turn: b.turn,
+        added,
+        pruned,
+        compacted,
+        removed,
+        tokens_before: TokenCounts::of(a),
+        tokens_after: TokenCounts::of(b),
+    }
+}
+
+/// `key#n` for the n-th message with that key.
+fn occurrence_keys(messages: &[MessageFingerprint]) -> Vec<String> {
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    messages
+        .iter()
+        .map(|m| {
+            let n = seen.entry(m.key.as_str()).or_default();
+            *n += 1;
+            format!("{}#{}", m.key, *n - 1)
+        })
+        .collect()
+}
+
+fn delta(before: u32, after: u32) -> String {
+    format!("{before} → {after} ({:+})", i64::from(after) - i64::from(before))
+}
+
+fn line(f: &mut fmt::Formatter<'_>, mark: char, m: &MessageFingerprint, tokens: String) -> fmt::Result {
+    writeln!(f, "  {mark} {:<9} {tokens:>14}  {}", super::role_name(m.role), m.preview)
+}
+
+impl fmt::Display for ContextDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (a, b) = (self.tokens_before, self.tokens_after);
+        writeln!(f, "Context diff: turn {} → turn {}", self.from_turn, self.to_turn)?;
+        writeln!(f, "Tokens: {}", delta(a.total, b.total))?;
+        writeln!(f, "  system   {}", delta(a.system, b.system))?;
+        writeln!(f, "  tools    {}", delta(a.tools, b.tools))?;
+        writeln!(f, "  messages {}", delta(a.messages, b.messages))?;
+
+        let sections: [(&str, char, &[MessageFingerprint]); 3] = [
+            ("Added", '+', &self.added),
+            ("Compacted", '-', &self.compacted),
+            ("Removed", '-', &self.removed),
+        ];
+        for (title, mark, messages) in sections {
+            if messages.is_empty() {
+                continue;
+            }
+            writeln!(f, "{title} ({}):", messages.len())?;
+            for m in messages {
+                line(f, mark, m, format!("{} tok", m.tokens))?;
+            }
+        }
+        if !self.pruned.is_empty() {
+            writeln!(f, "Pruned ({}):", self.pruned.len())?;
+            for p in &self.pruned {
+                line(f, '~', &p.message, format!("{} → {} tok", p.tokens_before, p.message.tokens))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nexus_provider::types::{ContentBlock, Message, Role};
+
+    use super::*;
+
+    fn text(role: Role, text: &str) -> Message {
+        Message { role, content: vec![ContentBlock::Text { text: text.into() }] }
+    }
+
+    fn tool_round(id: &str, result: &str) -> Vec<Message> {
+        vec![
+            Message {
+                role: Role::Assistant,
+                content: vec![ContentBlock::ToolUse {
+                    id: id.into(),
+                    name: "read_file".into(),
+                    input: serde_json::json!({"path": "a.rs"}),
+                }],
+            },
+            Message {
+                role: Role::User,
+                content: vec![ContentBlock::ToolResult {
+                    tool_use_id: id.into(),
+                    content: result.into(),
+                    is_error: None,
+                }],
+            },
+        ]
+    }
+
+    fn snapshot(turn: u32, messages: &[Message]) -> ContextSnapshot {
+        ContextSnapshot::capture(turn, "run", messages, "system prompt", &[])
+    }
+
+    #[test]
+    fn reports_added_and_pruned_messages() {
+        let mut first = vec![text(Role::User, "read a.rs")];
+        first.extend(tool_round("t1", &"x".repeat(3000)));
+        first.push(text(Role::Assistant, "done"));
+
+        let mut second = first.clone();
+        second[2] = tool_round("t1", "[read_file: 3000 chars, pruned, id=t1]").remove(1);
+        second.push(text(Role::User, "thanks"));
+
+        let d = diff(&snapshot(1, &first), &snapshot(2, &second));
+        assert_eq!(d.added.len(), 1);
+        assert_eq!(d.added[0].preview, "thanks");
+        assert_eq!(d.pruned.len(), 1);
+        assert_eq!(d.pruned[0].tokens_before, 1000);
+        assert!(d.compacted.is_empty() && d.removed.is_empty());
+        assert!(d.tokens_after.messages < d.tokens_before.messages);
+
+        let report = d.to_string();
+        assert!(report.starts_with("Context diff: turn 1 → turn 2"));
+        assert!(report.contains("Pruned (1):"));
+        assert!(report.contains("1000 → "));
+    }
+
+    #[test]
+    fn dropped_messages_are_compacted_when_a_summary_appears() {
+        let first = vec![
+            text(Role::User, "hi"),
+            text(Role::Assistant, "hello"),
+            text(Role::User, "hi"),
+        ];
+        let second = vec![
+            text(Role::User, "[Previous conversation context]\n\ngreetings"),
+            text(Role::Assistant, "Understood, I have the previous context."),
+            text(Role::User, "hi"),
+        ];
+        let d = diff(&snapshot(1, &first), &snapshot(2, &second));
+        assert_eq!(d.added.len(), 2);
+        // The repeated "hi" matches its first occurrence; the second is dropped.
+        assert_eq!(d.compacted.len(), 2);
+        assert!(d.removed.is_empty());
+
+        let d = diff(&snapshot(1, &first), &snapshot(2, &first[..1]));
+        assert!(d.compacted.is_empty());
+        assert_eq!(d.removed.len(), 2);
+    }
+}
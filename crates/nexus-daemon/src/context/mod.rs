@@ -0,0 +1,267 @@
+//! Context snapshots: what the model was sent at the start of each turn.
+//!
+//! After compaction, each turn records a [`ContextSnapshot`] of its API
+//! messages: one [`MessageFingerprint`] per message (role, size, a preview,
+//! and whether it is a pruned stub or a compaction summary) plus the system
+//! prompt and tool sizes. [`diff`] compares two snapshots, so "why did the
+//! agent forget X between turn 5 and 9" becomes a list of messages that were
+//! pruned or compacted away.
+//!
+//! Snapshots are in-memory only; turn numbers count turns since the daemon
+//! started.
+
+mod diff;
+
+pub use diff::diff;
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use nexus_provider::types::{ContentBlock, Message, Role, Tool};
+
+/// Snapshots kept per conversation; older ones are dropped first.
+const MAX_SNAPSHOTS: usize = 100;
+
+const PREVIEW_CHARS: usize = 80;
+
+/// Marker in pruned tool result stubs (see `nexus_compaction::pruning`).
+const PRUNED_MARKER: &str = ", pruned, id=";
+
+/// One API message, reduced to what a diff needs.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageFingerprint {
+    pub role: Role,
+    /// Identity across snapshots: tool ids when the message has any (their
+    /// content changes when pruned), otherwise a hash of the content.
+    pub key: String,
+    pub preview: String,
+    pub tokens: u32,
+    /// Contains a tool result replaced by a pruned stub.
+    pub pruned: bool,
+    /// A compaction summary standing in for earlier messages.
+    pub summary: bool,
+}
+
+impl MessageFingerprint {
+    fn new(message: &Message) -> Self {
+        let mut tool_ids = Vec::new();
+        let mut text = String::new();
+        let mut pruned = false;
+        for block in &message.content {
+            match block {
+                ContentBlock::Text { text: t } => text.push_str(t),
+                ContentBlock::ToolUse { id, name, .. } => {
+                    tool_ids.push(id.as_str());
+                    text.push_str(&format!("[calls {name}]"));
+                }
+                ContentBlock::ToolResult { tool_use_id, content, .. } => {
+                    tool_ids.push(tool_use_id.as_str());
+                    let content = content.text();
+                    pruned |= content.contains(PRUNED_MARKER);
+                    text.push_str(&content);
+                }
+                ContentBlock::ServerToolUse { name, .. } => {
+                    text.push_str(&format!("[calls {name}]"));
+                }
+                ContentBlock::WebSearchToolResult { content, .. }
+                | ContentBlock::CodeExecutionToolResult { content, .. } => {
+                    text.push_str(&content.to_string());
+                }
+                ContentBlock::Thinking { .. } | ContentBlock::RedactedThinking { .. } => {}
+            }
+        }
+
+        let role = role_name(message.role);
+        let key = if tool_ids.is_empty() {
+            let mut hasher = DefaultHasher::new();
+            text.hash(&mut hasher);
+            format!("{role}:{:016x}", hasher.finish())
+        } else {
+            format!("{role}:{}", tool_ids.join(","))
+        };
+
+        let flat = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let mut preview: String = flat.chars().take(PREVIEW_CHARS).collect();
+        if flat.chars().count() > PREVIEW_CHARS {
+            preview.push('…');
+        }
+
+        Self {
+            role: message.role,
+            key,
+            preview,
+            tokens: nexus_compaction::estimate_tokens(std::slice::from_ref(message), None, &[]),
+            pruned,
+            summary: text.starts_with(nexus_compaction::SUMMARY_PREFIX),
+        }
+    }
+}
+
+fn role_name(role: Role) -> &'static str {
+    match role {
+        Role::User => "user",
+        Role::Assistant => "assistant",
+    }
+}
+
+/// The context sent to the model at the start of one turn.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextSnapshot {
+    pub turn: u32,
+    pub run_id: String,
+    pub taken_at: DateTime<Utc>,
+    pub system_tokens: u32,
+    pub tool_tokens: u32,
+    pub messages: Vec<MessageFingerprint>,
+}
+
+impl ContextSnapshot {
+    pub fn capture(turn: u32, run_id: &str, messages: &[Message], system: &str, tools: &[Tool]) -> Self {
+        Self {
+            turn,
+            run_id: run_id.to_string(),
+            taken_at: Utc::now(),
+            system_tokens: nexus_compaction::estimate_tokens(&[], Some(system), &[]),
+            tool_tokens: nexus_compaction::estimate_tokens(&[], None, tools),
+            messages: messages.iter().map(MessageFingerprint::new).collect(),
+        }
+    }
+
+    pub fn message_tokens(&self) -> u32 {
+        self.messages.iter().map(|m| m.tokens).sum()
+    }
+
+    pub fn total_tokens(&self) -> u32 {
+        self.system_tokens + self.tool_tokens + self.message_tokens()
+    }
+}
+
+/// Summary row for listing a conversation's snapshots.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotInfo {
+    pub turn: u32,
+    pub run_id: String,
+    pub taken_at: DateTime<Utc>,
+    pub messages: usize,
+    pub total_tokens: u32,
+}
+
+#[derive(Default)]
+struct Conversation {
+    turns: u32,
+    snapshots: VecDeque<ContextSnapshot>,
+}
+
+/// Recent context snapshots, keyed by conversation.
+#[derive(Default)]
+pub struct ContextSnapshotStore {
+    inner: Mutex<HashMap<String, Conversation>>,
+}
+
+impl ContextSnapshotStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Snapshot the context of the conversation's next turn.
+    pub fn record(&self, conversation_id: &str, run_id: &str, messages: &[Message], system: &str, tools: &[Tool]) {
+        let mut inner = self.inner.lock().unwrap();
+        let conv = inner.entry(conversation_id.to_string()).or_default();
+        conv.turns += 1;
+        conv.snapshots
+            .push_back(ContextSnapshot::capture(conv.turns, run_id, messages, system, tools));
+        if conv.snapshots.len() > MAX_SNAPSHOTS {
+            conv.snapshots.pop_front();
+        }
+    }
+
+    pub fn list(&self, conversation_id: &str) -> Vec<SnapshotInfo> {
+        let inner = self.inner.lock().unwrap();
+        let Some(conv) = inner.get(conversation_id) else {
+            return Vec::new();
+        };
+        conv.snapshots
+            .iter()
+            .map(|s| SnapshotInfo {
+                turn: s.turn,
+                run_id: s.run_id.clone(),
+                taken_at: s.taken_at,
+                messages: s.messages.len(),
+                total_tokens: s.total_tokens(),
+            })
+            .collect()
+    }
+
+    pub fn get(&self, conversation_id: &str, turn: u32) -> Option<ContextSnapshot> {
+        self.inner
+            .lock()
+            .unwrap()
+            .get(conversation_id)?
+            .snapshots
+            .iter()
+            .find(|s| s.turn == turn)
+            .cloned()
+    }
+
+    /// Drop all snapshots for a conversation (on delete).
+    pub fn remove_conversation(&self, conversation_id: &str) {
+        self.inner.lock().unwrap().remove(conversation_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(role: Role, text: &str) -> Message {
+        Message { role, content: vec![ContentBlock::Text { text: text.into() }] }
+    }
+
+    #[test]
+    fn fingerprints_flag_pruned_stubs_and_summaries() {
+        let stub = Message {
+            role: Role::User,
+            content: vec![ContentBlock::ToolResult {
+                tool_use_id: "t1".into(),
+                content: "[read_file: 5000 chars, pruned, id=t1]".into(),
+                is_error: None,
+            }],
+        };
+        let fp = MessageFingerprint::new(&stub);
+        assert!(fp.pruned);
+        assert_eq!(fp.key, "user:t1");
+
+        let fp = MessageFingerprint::new(&text(Role::User, "[Previous conversation context]\n\nearlier"));
+        assert!(fp.summary);
+        assert!(!fp.pruned);
+
+        let long = "word ".repeat(100);
+        let fp = MessageFingerprint::new(&text(Role::Assistant, &long));
+        assert!(fp.preview.ends_with('…'));
+        assert_eq!(fp.preview.chars().count(), PREVIEW_CHARS + 1);
+    }
+
+    #[test]
+    fn store_numbers_turns_and_caps_history() {
+        let store = ContextSnapshotStore::new();
+        for i in 0..MAX_SNAPSHOTS + 5 {
+            store.record("c", &format!("run-{i}"), &[text(Role::User, "hi")], "system", &[]);
+        }
+        let list = store.list("c");
+        assert_eq!(list.len(), MAX_SNAPSHOTS);
+        assert_eq!(list[0].turn, 6);
+        assert!(store.get("c", 1).is_none());
+        assert_eq!(store.get("c", 6).unwrap().run_id, "run-5");
+
+        store.remove_conversation("c");
+        assert!(store.list("c").is_empty());
+    }
+}
@@ -146,6 +146,7 @@ fn agents_def() -> Tool {
                 "system_prompt": { "type": "string", "description": "Custom system prompt override" },
                 "temperature": { "type": "number", "description": "Sampling temperature (0.0-1.0)" },
                 "max_tokens": { "type": "integer", "description": "Max output tokens per response" },
+                "thinking_budget": { "type": "integer", "description": "Extended thinking budget in tokens" },
                 "mcp_server_ids": {
                     "type": "array",
                     "items": { "type": "string" },
@@ -518,6 +519,7 @@ async fn exec_agents(args_json: &str, conversation_id: &str, deps: &ControlPlane
                 system_prompt: get_str(&args.rest, "system_prompt"),
                 temperature: args.rest.get("temperature").and_then(|v| v.as_f64()).map(|f| f as f32),
                 max_tokens: args.rest.get("max_tokens").and_then(|v| v.as_u64()).map(|n| n as u32),
+                thinking_budget: args.rest.get("thinking_budget").and_then(|v| v.as_u64()).map(|n| n as u32),
                 mcp_server_ids: args
                     .rest
                     .get("mcp_server_ids")
@@ -618,6 +620,9 @@ async fn exec_providers(args_json: &str, deps: &ControlPlaneDeps) -> (String, bo
                         if obj.contains_key("api_key") {
                             obj.insert("api_key".to_string(), serde_json::json!("***"));
                         }
+                        if obj.contains_key("api_keys") {
+                            obj.insert("api_keys".to_string(), serde_json::json!("***"));
+                        }
                     }
                     v
                 })
@@ -655,6 +660,9 @@ async fn exec_providers(args_json: &str, deps: &ControlPlaneDeps) -> (String, bo
                         if obj.contains_key("api_key") {
                             obj.insert("api_key".to_string(), serde_json::json!("***"));
                         }
+                        if obj.contains_key("api_keys") {
+                            obj.insert("api_keys".to_string(), serde_json::json!("***"));
+                        }
                     }
                     (format_json(&v), false)
                 }
@@ -682,6 +690,9 @@ async fn exec_providers(args_json: &str, deps: &ControlPlaneDeps) -> (String, bo
                         if obj.contains_key("api_key") {
                             obj.insert("api_key".to_string(), serde_json::json!("***"));
                         }
+                        if obj.contains_key("api_keys") {
+                            obj.insert("api_keys".to_string(), serde_json::json!("***"));
+                        }
                     }
                     (format_json(&v), false)
                 }
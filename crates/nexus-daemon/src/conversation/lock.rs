@@ -0,0 +1,328 @@
+//! Lease-based lock on the conversation store directory.
+//!
+//! Two daemons pointed at the same `~/.nexus/conversations` would each keep
+//! their own in-memory index and overwrite each other's files. The first
+//! daemon writes a `.lock` lease (owner, pid, expiry) and renews it in the
+//! background; a second daemon refuses to start with [`StoreBusy`] unless
+//! the lease has expired, the owning process is gone, or takeover is
+//! configured (`conversations.lock_takeover`).
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+const LOCK_FILE: &str = ".lock";
+
+/// Returned (inside `anyhow::Error`) when another live process holds the lease.
+#[derive(Debug)]
+pub struct StoreBusy {
+    pub pid: u32,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl std::fmt::Display for StoreBusy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "conversation store is in use by pid {} (lease expires {}); stop the other daemon \
+             or set conversations.lock_takeover",
+            self.pid, self.expires_at
+        )
+    }
+}
+
+impl std::error::Error for StoreBusy {}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Lease {
+    owner: String,
+    pid: u32,
+    expires_at: DateTime<Utc>,
+}
+
+pub struct StoreLock {
+    path: PathBuf,
+    owner: String,
+    lease: chrono::Duration,
+}
+
+/// Returned (inside `anyhow::Error`) by [`StoreLock::renew`] when another
+/// process now holds the lease. Unlike an I/O error, retrying won't help.
+#[derive(Debug)]
+pub struct LockLost {
+    pub pid: u32,
+}
+
+impl std::fmt::Display for LockLost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "conversation store lock was taken over by pid {}", self.pid)
+    }
+}
+
+impl std::error::Error for LockLost {}
+
+/// How many times `acquire` retries when the lock changes hands under it.
+const ACQUIRE_ATTEMPTS: usize = 8;
+
+impl StoreLock {
+    /// Acquire the lease on `dir`, failing with [`StoreBusy`] if a live
+    /// process holds an unexpired lease and `takeover` is false.
+    ///
+    /// Safe against concurrent starts: the lock file only ever appears via
+    /// an exclusive create, and a stale lease is moved aside with a rename
+    /// before anyone re-creates it, so exactly one contender wins each round.
+    pub fn acquire(dir: &Path, lease: chrono::Duration, takeover: bool) -> Result<Self> {
+        let lock = Self {
+            path: dir.join(LOCK_FILE),
+            owner: Uuid::new_v4().to_string(),
+            lease,
+        };
+
+        for _ in 0..ACQUIRE_ATTEMPTS {
+            match lock.create_exclusive() {
+                Ok(()) => return Ok(lock),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(e).context("failed to create conversation store lock"),
+            }
+
+            let current = read_lease(&lock.path);
+            if let Some(current) = &current {
+                let live = current.expires_at > Utc::now() && process_alive(current.pid);
+                if live && !takeover {
+                    return Err(StoreBusy {
+                        pid: current.pid,
+                        expires_at: current.expires_at,
+                    }
+                    .into());
+                }
+                if live {
+                    tracing::warn!(pid = current.pid, "Taking over conversation store lock");
+                }
+            }
+
+            // Stale, unreadable, or being taken over: move it aside. Only one
+            // contender's rename can succeed; the rest see NotFound and race
+            // on the exclusive create again.
+            let aside = lock.scratch_path("stale");
+            match fs::rename(&lock.path, &aside) {
+                Ok(()) => {
+                    // If the lease changed between our read and the rename we
+                    // just moved someone's fresh lock — put it back.
+                    let moved = read_lease(&aside).map(|l| l.owner);
+                    if moved != current.map(|l| l.owner) {
+                        let _ = fs::hard_link(&aside, &lock.path);
+                    }
+                    let _ = fs::remove_file(&aside);
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e).context("failed to replace conversation store lock"),
+            }
+        }
+
+        anyhow::bail!("could not acquire conversation store lock: it kept changing hands")
+    }
+
+    /// Extend the lease. Fails with [`LockLost`] if another process has
+    /// taken the lock over; any other error is worth retrying.
+    pub fn renew(&self) -> Result<()> {
+        match read_lease(&self.path) {
+            Some(current) if current.owner == self.owner => self.write(),
+            Some(current) => Err(LockLost { pid: current.pid }.into()),
+            // Missing (or mid-replace): re-create it, unless someone beat us to it
+            None => match self.create_exclusive() {
+                Ok(()) => Ok(()),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => match read_lease(&self.path) {
+                    Some(current) if current.owner != self.owner => Err(LockLost { pid: current.pid }.into()),
+                    _ => self.write(),
+                },
+                Err(e) => Err(e).context("failed to re-create conversation store lock"),
+            },
+        }
+    }
+
+    /// How often [`renew`](Self::renew) should run to keep the lease alive.
+    pub fn renew_interval(&self) -> std::time::Duration {
+        (self.lease / 3).to_std().unwrap_or(std::time::Duration::from_secs(1))
+    }
+
+    /// Full lease length — how long renewals can fail before others may take over.
+    pub fn lease(&self) -> std::time::Duration {
+        self.lease.to_std().unwrap_or_default()
+    }
+
+    /// Remove the lock file if we still own it.
+    pub fn release(&self) {
+        if read_lease(&self.path).is_some_and(|l| l.owner == self.owner) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+
+    /// Overwrite the lock file with a fresh lease. Only for when we own it.
+    fn write(&self) -> Result<()> {
+        let tmp = self.write_scratch().context("failed to write conversation store lock")?;
+        fs::rename(&tmp, &self.path)
+            .inspect_err(|_| {
+                let _ = fs::remove_file(&tmp);
+            })
+            .context("failed to write conversation store lock")
+    }
+
+    /// Publish a fresh lease only if no lock file exists. The lease is
+    /// written to a scratch file first and hard-linked into place, so the
+    /// create is exclusive (like `O_EXCL`) and readers never see a
+    /// half-written lease.
+    fn create_exclusive(&self) -> std::io::Result<()> {
+        let tmp = self.write_scratch()?;
+        let linked = fs::hard_link(&tmp, &self.path);
+        let _ = fs::remove_file(&tmp);
+        linked
+    }
+
+    fn write_scratch(&self) -> std::io::Result<PathBuf> {
+        let lease = Lease {
+            owner: self.owner.clone(),
+            pid: std::process::id(),
+            expires_at: Utc::now() + self.lease,
+        };
+        let tmp = self.scratch_path("tmp");
+        fs::write(&tmp, serde_json::to_vec(&lease)?)?;
+        Ok(tmp)
+    }
+
+    fn scratch_path(&self, kind: &str) -> PathBuf {
+        self.path.with_extension(format!("{}.{kind}", self.owner))
+    }
+}
+
+impl Drop for StoreLock {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+fn read_lease(path: &Path) -> Option<Lease> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Whether `pid` refers to a running process. Unknown platforms assume yes
+/// and rely on lease expiry alone.
+pub(crate) fn process_alive(pid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        // Signal 0 performs the permission/existence check without sending anything.
+        let rc = unsafe { libc::kill(pid as libc::pid_t, 0) };
+        rc == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = pid;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("nexus-lock-test-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn second_acquire_is_busy_until_released() {
+        let dir = temp_dir();
+        let lease = chrono::Duration::seconds(30);
+
+        let first = StoreLock::acquire(&dir, lease, false).unwrap();
+        let err = StoreLock::acquire(&dir, lease, false).err().unwrap();
+        assert!(err.downcast_ref::<StoreBusy>().is_some(), "{err}");
+
+        drop(first);
+        assert!(StoreLock::acquire(&dir, lease, false).is_ok());
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn takeover_invalidates_previous_holder() {
+        let dir = temp_dir();
+        let lease = chrono::Duration::seconds(30);
+
+        let first = StoreLock::acquire(&dir, lease, false).unwrap();
+        let second = StoreLock::acquire(&dir, lease, true).unwrap();
+
+        let err = first.renew().err().unwrap();
+        assert!(err.downcast_ref::<LockLost>().is_some(), "{err}");
+        assert!(second.renew().is_ok());
+
+        // The old holder must not delete the new holder's lock on drop
+        drop(first);
+        assert!(dir.join(LOCK_FILE).exists());
+
+        drop(second);
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn expired_lease_can_be_acquired() {
+        let dir = temp_dir();
+
+        let stale = StoreLock::acquire(&dir, chrono::Duration::seconds(-1), false).unwrap();
+        assert!(StoreLock::acquire(&dir, chrono::Duration::seconds(30), false).is_ok());
+
+        drop(stale);
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn concurrent_acquires_have_one_winner() {
+        for stale in [false, true] {
+            let dir = temp_dir();
+            if stale {
+                std::mem::forget(StoreLock::acquire(&dir, chrono::Duration::seconds(-1), false).unwrap());
+            }
+
+            let barrier = std::sync::Arc::new(std::sync::Barrier::new(8));
+            let handles: Vec<_> = (0..8)
+                .map(|_| {
+                    let dir = dir.clone();
+                    let barrier = std::sync::Arc::clone(&barrier);
+                    std::thread::spawn(move || {
+                        barrier.wait();
+                        StoreLock::acquire(&dir, chrono::Duration::seconds(30), false)
+                    })
+                })
+                .collect();
+            let results: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+            let winners = results.iter().filter(|r| r.is_ok()).count();
+            assert_eq!(winners, 1, "stale={stale}");
+            for err in results.iter().filter_map(|r| r.as_ref().err()) {
+                assert!(err.downcast_ref::<StoreBusy>().is_some(), "{err}");
+            }
+
+            drop(results);
+            fs::remove_dir_all(dir).ok();
+        }
+    }
+
+    #[test]
+    fn renew_recreates_a_missing_lock() {
+        let dir = temp_dir();
+        let lock = StoreLock::acquire(&dir, chrono::Duration::seconds(30), false).unwrap();
+
+        fs::remove_file(dir.join(LOCK_FILE)).unwrap();
+        assert!(lock.renew().is_ok());
+        let err = StoreLock::acquire(&dir, chrono::Duration::seconds(30), false).err().unwrap();
+        assert!(err.downcast_ref::<StoreBusy>().is_some(), "{err}");
+
+        drop(lock);
+        fs::remove_dir_all(dir).ok();
+    }
+}
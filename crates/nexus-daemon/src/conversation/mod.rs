@@ -1,16 +1,80 @@
+pub mod lock;
 pub mod types;
 
 use anyhow::Result;
 use chrono::Utc;
+use serde::de::DeserializeOwned;
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
+use crate::config::{ConversationStorageConfig, StorageCompression};
+
 pub use types::*;
 
+/// First two bytes of every gzip stream.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Longest accepted conversation id, in bytes.
+const MAX_ID_LEN: usize = 128;
+
+/// Device names Windows reserves regardless of extension (`CON.json` still
+/// opens the console).
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// A fresh conversation id.
+pub fn new_id() -> String {
+    Uuid::new_v4().to_string()
+}
+
+/// Whether `id` is safe to use as a file name on every platform: ASCII
+/// letters, digits, `-` and `_` only, at most [`MAX_ID_LEN`] bytes, and not a
+/// reserved Windows device name. Ids become `{id}.json` in the store, so
+/// anything else could escape the directory or fail to open.
+pub fn is_valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_ID_LEN
+        && id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+        && !RESERVED_NAMES.iter().any(|name| name.eq_ignore_ascii_case(id))
+}
+
+/// Returned (inside `anyhow::Error`) for an id that fails [`is_valid_id`],
+/// so the HTTP layer can answer 400 rather than 404 or 500.
+#[derive(Debug)]
+pub struct InvalidId {
+    pub id: String,
+}
+
+impl std::fmt::Display for InvalidId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid conversation id {:?}", self.id)
+    }
+}
+
+impl std::error::Error for InvalidId {}
+
+fn check_id(id: &str) -> Result<()> {
+    if is_valid_id(id) {
+        Ok(())
+    } else {
+        Err(InvalidId { id: id.to_string() }.into())
+    }
+}
+
+/// Whether a legacy id names a file directly inside the store, so its files
+/// can be read and renamed without leaving the directory.
+fn is_confined_id(id: &str) -> bool {
+    !id.is_empty() && id != "." && id != ".." && !id.contains(['/', '\\', '\0'])
+}
+
 pub struct ConversationStore {
     base_dir: PathBuf,
     index: Vec<ConversationMeta>,
+    storage: ConversationStorageConfig,
 }
 
 impl ConversationStore {
@@ -18,14 +82,110 @@ impl ConversationStore {
         fs::create_dir_all(&base_dir)?;
 
         let index_path = base_dir.join("index.json");
-        let index = if index_path.exists() {
-            let content = fs::read_to_string(&index_path)?;
-            serde_json::from_str(&content)?
-        } else {
-            Vec::new()
+        let index = read_json_with_backup(&index_path)?.unwrap_or_default();
+
+        let mut store = Self {
+            base_dir,
+            index,
+            storage: ConversationStorageConfig::default(),
         };
+        store.migrate_legacy_ids();
+        store.backfill_index();
+        Ok(store)
+    }
+
+    /// Conversations created before ids were validated can have ids that
+    /// [`is_valid_id`] rejects, which would leave them unreadable. Give each
+    /// a fresh id and move its files over. Ids that point outside the store
+    /// are left alone, with a warning.
+    fn migrate_legacy_ids(&mut self) {
+        let mut changed = false;
+        for i in 0..self.index.len() {
+            let old_id = self.index[i].id.clone();
+            if is_valid_id(&old_id) {
+                continue;
+            }
+            if !is_confined_id(&old_id) {
+                tracing::warn!(conversation_id = %old_id, "Conversation id points outside the store; it can't be opened or deleted through the API");
+                continue;
+            }
+            let new_id = new_id();
+            match self.move_conversation(&old_id, &new_id) {
+                Ok(()) => {
+                    tracing::warn!(old_id = %old_id, new_id = %new_id, "Migrated conversation with an invalid id");
+                    self.index[i].id = new_id;
+                    changed = true;
+                }
+                Err(e) => {
+                    tracing::warn!(conversation_id = %old_id, "Failed to migrate conversation with an invalid id: {}", e);
+                }
+            }
+        }
+        if changed {
+            if let Err(e) = self.save_index() {
+                tracing::warn!("Failed to save migrated conversation index: {}", e);
+            }
+        }
+    }
+
+    /// Rewrite a conversation under a new id, then remove its old files.
+    fn move_conversation(&self, old_id: &str, new_id: &str) -> Result<()> {
+        let old_path = self.conv_path(old_id);
+        if let Some(mut conv) = read_json_with_backup::<Conversation>(&old_path)? {
+            conv.id = new_id.to_string();
+            self.write_conversation(&conv)?;
+        }
+        let old_events = self.events_path(old_id);
+        if old_events.exists() {
+            fs::rename(&old_events, self.events_path(new_id))?;
+        }
+        for path in [backup_path(&old_path), old_path] {
+            if path.exists() {
+                fs::remove_file(&path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Index entries written before `turn_count` and `usage` were tracked
+    /// load as 0 / `None`. Fill them in from the conversation files.
+    fn backfill_index(&mut self) {
+        let mut changed = false;
+        for i in 0..self.index.len() {
+            if self.index[i].turn_count > 0 || self.index[i].message_count == 0 {
+                continue;
+            }
+            let id = self.index[i].id.clone();
+            let conv = match self.get(&id) {
+                Ok(Some(conv)) => conv,
+                Ok(None) => continue,
+                Err(e) => {
+                    tracing::warn!(conversation_id = %id, "Failed to read conversation for index backfill: {}", e);
+                    continue;
+                }
+            };
+            let meta = &mut self.index[i];
+            let turn_count = conv.turn_count();
+            if turn_count != meta.turn_count {
+                meta.turn_count = turn_count;
+                changed = true;
+            }
+            if meta.usage.is_none() && conv.usage.is_some() {
+                meta.usage = conv.usage;
+                changed = true;
+            }
+        }
+        if changed {
+            if let Err(e) = self.save_index() {
+                tracing::warn!("Failed to save backfilled conversation index: {}", e);
+            }
+        }
+    }
 
-        Ok(Self { base_dir, index })
+    /// Set compression and size limits for conversation files.
+    pub fn with_storage(mut self, storage: ConversationStorageConfig) -> Self {
+        self.storage = storage;
+        self
     }
 
     pub fn list(&self) -> &[ConversationMeta] {
@@ -33,7 +193,8 @@ impl ConversationStore {
     }
 
     pub fn create(&mut self, client_id: Option<String>, workspace_id: Option<String>, agent_id: Option<String>) -> Result<ConversationMeta> {
-        let id = client_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+        let id = client_id.unwrap_or_else(new_id);
+        check_id(&id)?;
         let now = Utc::now();
         let meta = ConversationMeta {
             id: id.clone(),
@@ -41,8 +202,11 @@ impl ConversationStore {
             created_at: now,
             updated_at: now,
             message_count: 0,
+            turn_count: 0,
             workspace_id: workspace_id.clone(),
             agent_id: agent_id.clone(),
+            usage: None,
+            origin: None,
         };
 
         let conv = Conversation {
@@ -56,6 +220,7 @@ impl ConversationStore {
             agent_id,
             workspace_id,
             spans: Vec::new(),
+            checkpoints: Vec::new(),
         };
 
         self.write_conversation(&conv)?;
@@ -66,13 +231,8 @@ impl ConversationStore {
     }
 
     pub fn get(&self, id: &str) -> Result<Option<Conversation>> {
-        let path = self.conv_path(id);
-        if !path.exists() {
-            return Ok(None);
-        }
-        let content = fs::read_to_string(&path)?;
-        let conv: Conversation = serde_json::from_str(&content)?;
-        Ok(Some(conv))
+        check_id(id)?;
+        read_json_with_backup(&self.conv_path(id))
     }
 
     pub fn save(&mut self, conv: &Conversation) -> Result<()> {
@@ -83,6 +243,8 @@ impl ConversationStore {
             meta.title = conv.title.clone();
             meta.updated_at = conv.updated_at;
             meta.message_count = conv.messages.len();
+            meta.turn_count = conv.turn_count();
+            meta.usage = conv.usage.clone();
             meta.workspace_id = conv.workspace_id.clone();
             meta.agent_id = conv.agent_id.clone();
         }
@@ -91,10 +253,19 @@ impl ConversationStore {
     }
 
     pub fn delete(&mut self, id: &str) -> Result<()> {
+        check_id(id)?;
         let path = self.conv_path(id);
         if path.exists() {
             fs::remove_file(&path)?;
         }
+        let backup = backup_path(&path);
+        if backup.exists() {
+            fs::remove_file(&backup)?;
+        }
+        let events = self.events_path(id);
+        if events.exists() {
+            fs::remove_file(&events)?;
+        }
         self.index.retain(|m| m.id != id);
         self.save_index()?;
         Ok(())
@@ -130,6 +301,15 @@ impl ConversationStore {
         Ok(())
     }
 
+    /// Tag a conversation with the API client that opened it.
+    pub fn set_origin(&mut self, id: &str, origin: &str) -> Result<()> {
+        if let Some(meta) = self.index.iter_mut().find(|m| m.id == id) {
+            meta.origin = Some(origin.to_string());
+            self.save_index()?;
+        }
+        Ok(())
+    }
+
     pub fn rename(&mut self, id: &str, title: &str) -> Result<()> {
         if let Some(mut conv) = self.get(id)? {
             conv.title = title.to_string();
@@ -145,21 +325,396 @@ impl ConversationStore {
         Ok(())
     }
 
+    /// Append serialized events to the conversation's JSONL event log.
+    /// Events for unknown (e.g. just-deleted) conversations are dropped.
+    pub fn append_events(&self, id: &str, lines: &[String]) -> Result<()> {
+        if lines.is_empty() || !is_valid_id(id) || !self.index.iter().any(|m| m.id == id) {
+            return Ok(());
+        }
+        let mut buf = Vec::new();
+        for line in lines {
+            buf.extend_from_slice(line.as_bytes());
+            buf.push(b'\n');
+        }
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.events_path(id))?;
+        file.write_all(&buf)?;
+        Ok(())
+    }
+
+    /// Read the conversation's event log, oldest first. A truncated trailing
+    /// line (crash mid-append) is skipped.
+    pub fn read_events(&self, id: &str) -> Result<Vec<serde_json::Value>> {
+        check_id(id)?;
+        let path = self.events_path(id);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(&path)?;
+        Ok(content
+            .lines()
+            .filter_map(|line| match serde_json::from_str(line) {
+                Ok(v) => Some(v),
+                Err(e) => {
+                    tracing::warn!(conversation_id = %id, "Skipping malformed event log line: {}", e);
+                    None
+                }
+            })
+            .collect())
+    }
+
     fn conv_path(&self, id: &str) -> PathBuf {
         self.base_dir.join(format!("{}.json", id))
     }
 
+    fn events_path(&self, id: &str) -> PathBuf {
+        self.base_dir.join(format!("{}.events.jsonl", id))
+    }
+
     fn write_conversation(&self, conv: &Conversation) -> Result<()> {
         let path = self.conv_path(&conv.id);
-        let content = serde_json::to_string_pretty(conv)?;
-        fs::write(&path, content)?;
-        Ok(())
+        let content = match self.storage.compression {
+            StorageCompression::None => serde_json::to_vec_pretty(conv)?,
+            StorageCompression::Gzip => gzip(&serde_json::to_vec(conv)?)?,
+        };
+
+        if let Some(max) = self.storage.max_file_bytes {
+            if content.len() as u64 > max {
+                anyhow::bail!(
+                    "conversation {} is {} bytes on disk, over the {}-byte limit \
+                     (conversations.max_file_bytes); compact it, start a new conversation, \
+                     or raise the limit",
+                    conv.id,
+                    content.len(),
+                    max,
+                );
+            }
+        }
+
+        write_atomic(&path, &content)
     }
 
     fn save_index(&self) -> Result<()> {
         let path = self.base_dir.join("index.json");
         let content = serde_json::to_string_pretty(&self.index)?;
-        fs::write(&path, content)?;
-        Ok(())
+        write_atomic(&path, content.as_bytes())
+    }
+}
+
+// ── Crash-safe file I/O ──
+
+fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    path.with_file_name(name)
+}
+
+fn gzip(data: &[u8]) -> Result<Vec<u8>> {
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+
+    let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+/// Read a file as UTF-8 text, transparently gunzipping it if it starts with
+/// the gzip header.
+fn read_text(path: &Path) -> Result<String> {
+    use std::io::Read;
+
+    let bytes = fs::read(path)?;
+    if bytes.starts_with(&GZIP_MAGIC) {
+        let mut text = String::new();
+        flate2::read::GzDecoder::new(bytes.as_slice()).read_to_string(&mut text)?;
+        Ok(text)
+    } else {
+        Ok(String::from_utf8(bytes)?)
+    }
+}
+
+/// Write `content` to `path` without ever leaving a truncated file behind.
+///
+/// Writes and fsyncs a sibling temp file, keeps the previous version as
+/// `<file>.bak`, then renames the temp file over the original. A crash at
+/// any point leaves either the old or the new file intact.
+fn write_atomic(path: &Path, content: &[u8]) -> Result<()> {
+    use std::io::Write;
+
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp = path.with_file_name(tmp_name);
+
+    {
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(content)?;
+        file.sync_all()?;
+    }
+
+    if path.exists() {
+        fs::copy(path, backup_path(path))?;
+    }
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Read and parse a JSON file, falling back to `<file>.bak` when the primary
+/// is missing or fails to parse. Returns `None` if neither exists.
+fn read_json_with_backup<T: DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+    let backup = backup_path(path);
+
+    let primary_err = if path.exists() {
+        match read_text(path).and_then(|c| serde_json::from_str(&c).map_err(anyhow::Error::from))
+        {
+            Ok(value) => return Ok(Some(value)),
+            Err(e) => e,
+        }
+    } else if backup.exists() {
+        anyhow::anyhow!("{} is missing", path.display())
+    } else {
+        return Ok(None);
+    };
+
+    if !backup.exists() {
+        return Err(primary_err);
+    }
+
+    tracing::warn!(
+        path = %path.display(),
+        error = %primary_err,
+        "Failed to read file, falling back to backup"
+    );
+    let content = read_text(&backup)?;
+    Ok(Some(serde_json::from_str(&content)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store() -> (PathBuf, ConversationStore) {
+        let dir = std::env::temp_dir().join(format!("nexus-conv-test-{}", Uuid::new_v4()));
+        let store = ConversationStore::load(dir.clone()).unwrap();
+        (dir, store)
+    }
+
+    #[test]
+    fn rejects_ids_unsafe_as_file_names() {
+        assert!(is_valid_id(&new_id()));
+        assert!(is_valid_id("chat_2024-01"));
+        for bad in ["", "../escape", "a/b", "a\\b", "c:d", "x.json", "con", "LPT1", &"a".repeat(129)] {
+            assert!(!is_valid_id(bad), "{bad:?} should be rejected");
+        }
+
+        let (dir, mut store) = temp_store();
+        assert!(store.create(Some("../escape".into()), None, None).is_err());
+        assert!(store.list().is_empty());
+        assert!(!dir.parent().unwrap().join("escape.json").exists());
+        for err in [
+            store.get("../index").unwrap_err(),
+            store.read_events("../x").unwrap_err(),
+            store.delete("a/b").unwrap_err(),
+        ] {
+            assert!(err.downcast_ref::<InvalidId>().is_some(), "{err}");
+        }
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn save_keeps_backup_of_previous_version() {
+        let (dir, mut store) = temp_store();
+        let meta = store.create(Some("c1".into()), None, None).unwrap();
+        store.rename(&meta.id, "Renamed").unwrap();
+
+        let backup: Conversation =
+            serde_json::from_str(&fs::read_to_string(dir.join("c1.json.bak")).unwrap()).unwrap();
+        assert_eq!(backup.title, "New Chat");
+        assert!(!dir.join("c1.json.tmp").exists());
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn get_falls_back_to_backup_on_corrupt_primary() {
+        let (dir, mut store) = temp_store();
+        store.create(Some("c1".into()), None, None).unwrap();
+        store.rename("c1", "Renamed").unwrap();
+
+        // Simulate a crash that truncated the primary file.
+        fs::write(dir.join("c1.json"), "{\"id\": \"c1\", \"tit").unwrap();
+
+        let conv = store.get("c1").unwrap().unwrap();
+        assert_eq!(conv.id, "c1");
+        assert_eq!(conv.title, "New Chat");
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn load_recovers_index_from_backup() {
+        let (dir, mut store) = temp_store();
+        store.create(Some("c1".into()), None, None).unwrap();
+        store.create(Some("c2".into()), None, None).unwrap();
+        drop(store);
+
+        fs::write(dir.join("index.json"), "").unwrap();
+
+        let store = ConversationStore::load(dir.clone()).unwrap();
+        assert_eq!(store.list().len(), 1);
+        assert_eq!(store.list()[0].id, "c1");
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn gzip_round_trips_and_reads_plain_files() {
+        let (dir, mut store) = temp_store();
+        store.create(Some("plain".into()), None, None).unwrap();
+
+        let mut store = store.with_storage(ConversationStorageConfig {
+            compression: StorageCompression::Gzip,
+            max_file_bytes: None,
+            ..Default::default()
+        });
+        store.create(Some("packed".into()), None, None).unwrap();
+
+        assert!(fs::read(dir.join("packed.json")).unwrap().starts_with(&GZIP_MAGIC));
+        assert_eq!(store.get("packed").unwrap().unwrap().id, "packed");
+        assert_eq!(store.get("plain").unwrap().unwrap().id, "plain");
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn oversized_conversation_is_rejected() {
+        let (dir, store) = temp_store();
+        let mut store = store.with_storage(ConversationStorageConfig {
+            compression: StorageCompression::None,
+            max_file_bytes: Some(64),
+            ..Default::default()
+        });
+
+        let err = store.create(Some("c1".into()), None, None).unwrap_err();
+        assert!(err.to_string().contains("max_file_bytes"), "{err}");
+        assert!(!dir.join("c1.json").exists());
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn event_log_appends_and_skips_truncated_lines() {
+        let (dir, mut store) = temp_store();
+        let meta = store.create(None, None, None).unwrap();
+
+        store
+            .append_events(&meta.id, &[r#"{"type":"RUN_STARTED"}"#.to_string()])
+            .unwrap();
+        store
+            .append_events(&meta.id, &[r#"{"type":"RUN_FINISHED"}"#.to_string()])
+            .unwrap();
+        let mut file = fs::OpenOptions::new()
+            .append(true)
+            .open(dir.join(format!("{}.events.jsonl", meta.id)))
+            .unwrap();
+        file.write_all(br#"{"type":"TEXT_"#).unwrap();
+
+        let events = store.read_events(&meta.id).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1]["type"], "RUN_FINISHED");
+
+        store.delete(&meta.id).unwrap();
+        assert!(!dir.join(format!("{}.events.jsonl", meta.id)).exists());
+        store
+            .append_events(&meta.id, &[r#"{"type":"CUSTOM"}"#.to_string()])
+            .unwrap();
+        assert!(store.read_events(&meta.id).unwrap().is_empty());
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn delete_removes_backup() {
+        let (dir, mut store) = temp_store();
+        store.create(Some("c1".into()), None, None).unwrap();
+        store.rename("c1", "Renamed").unwrap();
+        store.delete("c1").unwrap();
+
+        assert!(!dir.join("c1.json").exists());
+        assert!(!dir.join("c1.json.bak").exists());
+        assert!(store.get("c1").unwrap().is_none());
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn load_backfills_turn_count_missing_from_old_index() {
+        let (dir, mut store) = temp_store();
+        let meta = store.create(None, None, None).unwrap();
+        let mut conv = store.get(&meta.id).unwrap().unwrap();
+        for (id, role, text) in [("m1", MessageRole::User, "hi"), ("m2", MessageRole::Assistant, "hello")] {
+            conv.messages.push(ChatMessage {
+                id: id.into(),
+                role,
+                parts: vec![MessagePart::Text { text: text.into() }],
+                timestamp: Utc::now(),
+                parent_id: None,
+                source: None,
+                metadata: None,
+            });
+            conv.active_path.push(id.into());
+        }
+        store.save(&conv).unwrap();
+
+        // An index written before turn_count existed
+        let index_path = dir.join("index.json");
+        let mut index: serde_json::Value = serde_json::from_slice(&fs::read(&index_path).unwrap()).unwrap();
+        index[0].as_object_mut().unwrap().remove("turn_count");
+        fs::write(&index_path, index.to_string()).unwrap();
+
+        let store = ConversationStore::load(dir.clone()).unwrap();
+        assert_eq!(store.list()[0].turn_count, 1);
+        let saved: serde_json::Value = serde_json::from_slice(&fs::read(&index_path).unwrap()).unwrap();
+        assert_eq!(saved[0]["turn_count"], 1);
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn load_migrates_legacy_ids() {
+        let (dir, mut store) = temp_store();
+        let meta = store.create(None, None, None).unwrap();
+        let mut conv = store.get(&meta.id).unwrap().unwrap();
+        store.append_events(&meta.id, &["{}".into()]).unwrap();
+
+        // Written before ids were validated
+        let legacy = "chat 1.old";
+        fs::rename(dir.join(format!("{}.json", meta.id)), dir.join(format!("{legacy}.json"))).unwrap();
+        fs::rename(
+            dir.join(format!("{}.events.jsonl", meta.id)),
+            dir.join(format!("{legacy}.events.jsonl")),
+        )
+        .unwrap();
+        conv.id = legacy.into();
+        fs::write(dir.join(format!("{legacy}.json")), serde_json::to_vec(&conv).unwrap()).unwrap();
+        let mut escaping = store.list()[0].clone();
+        escaping.id = "../outside".into();
+        store.index[0].id = legacy.into();
+        store.index.push(escaping);
+        store.save_index().unwrap();
+
+        let store = ConversationStore::load(dir.clone()).unwrap();
+        let migrated = &store.list()[0].id;
+        assert!(is_valid_id(migrated));
+        assert_eq!(store.get(migrated).unwrap().unwrap().id, *migrated);
+        assert_eq!(store.read_events(migrated).unwrap().len(), 1);
+        assert!(!dir.join(format!("{legacy}.json")).exists());
+        // Left alone, and still refused
+        assert_eq!(store.list()[1].id, "../outside");
+        assert!(store.get("../outside").is_err());
+
+        fs::remove_dir_all(dir).ok();
     }
 }
@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use nexus_provider::types::{ContentBlock, Message, Role};
+use nexus_provider::types::{ContentBlock, ImageSource, Message, Role, ToolResultContent};
 use crate::system_prompt::{fence_tool_result, fence_user_message};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,10 +13,42 @@ pub struct ConversationMeta {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub message_count: usize,
+    /// User prompts across sealed spans and the active path.
+    #[serde(default)]
+    pub turn_count: usize,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub workspace_id: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub agent_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<ConversationUsage>,
+    /// Set for conversations opened by an API client (e.g. `openai`) rather
+    /// than the UI; those are left out of listings unless asked for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
+}
+
+/// Filter for listing conversations. `None` fields match everything, except
+/// `origin`: unset lists only conversations without one.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ConversationFilter {
+    pub workspace_id: Option<String>,
+    pub agent_id: Option<String>,
+    pub origin: Option<String>,
+}
+
+impl ConversationFilter {
+    pub fn matches(&self, meta: &ConversationMeta) -> bool {
+        let workspace_ok = self
+            .workspace_id
+            .as_ref()
+            .is_none_or(|ws| meta.workspace_id.as_ref() == Some(ws));
+        let agent_ok = self
+            .agent_id
+            .as_ref()
+            .is_none_or(|a| meta.agent_id.as_ref() == Some(a));
+        workspace_ok && agent_ok && self.origin == meta.origin
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,6 +85,23 @@ pub struct Span {
     pub sealed_at: Option<DateTime<Utc>>,
 }
 
+/// A named point in a conversation, e.g. "after plan approved". Tags name a
+/// message rather than a turn number, so compaction doesn't move them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub tag: String,
+    pub message_id: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Why a rollback could not happen.
+#[derive(Debug, PartialEq, Eq)]
+pub enum RollbackError {
+    UnknownTag,
+    /// The tagged message was compacted into a sealed span.
+    Sealed,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Conversation {
     pub id: String,
@@ -72,6 +121,8 @@ pub struct Conversation {
     /// Conversation spans — sealed segments behind compaction boundaries.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub spans: Vec<Span>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checkpoints: Vec<Checkpoint>,
 }
 
 impl Conversation {
@@ -182,6 +233,49 @@ impl Conversation {
             .collect()
     }
 
+    /// Number of user prompts (user messages with text) in the conversation,
+    /// counting compacted spans as well as the active path.
+    pub fn turn_count(&self) -> usize {
+        let by_id: HashMap<&str, &ChatMessage> =
+            self.messages.iter().map(|m| (m.id.as_str(), m)).collect();
+        self.spans
+            .iter()
+            .filter(|s| s.sealed_at.is_some())
+            .flat_map(|s| s.message_ids.iter())
+            .chain(self.active_path.iter())
+            .filter_map(|id| by_id.get(id.as_str()))
+            .filter(|m| {
+                m.role == MessageRole::User
+                    && m.parts.iter().any(|p| matches!(p, MessagePart::Text { .. }))
+            })
+            .count()
+    }
+
+    /// Text of the assistant messages on the active path after
+    /// `message_id`, up to the next user prompt: the stored reply to that
+    /// prompt. `None` if the message isn't on the active path or nothing was
+    /// said after it.
+    pub fn reply_to(&self, message_id: &str) -> Option<String> {
+        let start = self.active_path.iter().position(|id| id == message_id)?;
+        let by_id: HashMap<&str, &ChatMessage> =
+            self.messages.iter().map(|m| (m.id.as_str(), m)).collect();
+        let text: String = self.active_path[start + 1..]
+            .iter()
+            .filter_map(|id| by_id.get(id.as_str()))
+            .take_while(|m| {
+                m.role != MessageRole::User
+                    || !m.parts.iter().any(|p| matches!(p, MessagePart::Text { .. }))
+            })
+            .filter(|m| m.role == MessageRole::Assistant)
+            .flat_map(|m| m.parts.iter())
+            .filter_map(|p| match p {
+                MessagePart::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        (!text.is_empty()).then_some(text)
+    }
+
     /// Whether a message ID belongs to a sealed span.
     pub fn is_in_sealed_span(&self, message_id: &str) -> bool {
         self.spans
@@ -190,6 +284,44 @@ impl Conversation {
             .any(|s| s.message_ids.iter().any(|id| id == message_id))
     }
 
+    // ── Checkpoints ──
+
+    /// Tag `message_id`, or the end of the active path when `None`,
+    /// replacing any checkpoint with the same tag. Returns `None` if there
+    /// is no such message.
+    pub fn set_checkpoint(&mut self, tag: &str, message_id: Option<&str>) -> Option<&Checkpoint> {
+        let message_id = match message_id {
+            Some(id) => self.messages.iter().find(|m| m.id == id)?.id.clone(),
+            None => self.active_path.last()?.clone(),
+        };
+        self.checkpoints.retain(|c| c.tag != tag);
+        self.checkpoints.push(Checkpoint {
+            tag: tag.to_string(),
+            message_id,
+            created_at: Utc::now(),
+        });
+        self.checkpoints.last()
+    }
+
+    /// Make the tagged message the end of the active path. Later messages
+    /// stay in the tree as a branch; the next message continues from the tag.
+    pub fn rollback_to_tag(&mut self, tag: &str) -> Result<(), RollbackError> {
+        let checkpoint = self
+            .checkpoints
+            .iter()
+            .find(|c| c.tag == tag)
+            .ok_or(RollbackError::UnknownTag)?;
+        if self.is_in_sealed_span(&checkpoint.message_id) {
+            return Err(RollbackError::Sealed);
+        }
+        let path = self.path_to_only(&checkpoint.message_id);
+        if path.is_empty() {
+            return Err(RollbackError::UnknownTag);
+        }
+        self.active_path = path;
+        Ok(())
+    }
+
     // ── API message building ──
 
     /// Build Anthropic API messages from span summaries + current active path.
@@ -205,7 +337,7 @@ impl Conversation {
             result.push(Message {
                 role: Role::User,
                 content: vec![ContentBlock::Text {
-                    text: format!("[Previous conversation context]\n\n{}", summary),
+                    text: format!("{}\n\n{}", nexus_compaction::SUMMARY_PREFIX, summary),
                 }],
             });
             result.push(Message {
@@ -278,7 +410,7 @@ pub fn build_api_messages_from_parts(messages: &[&ChatMessage]) -> Vec<Message>
                             ..
                         } => Some(ContentBlock::ToolResult {
                             tool_use_id: tool_call_id.clone(),
-                            content: fence_tool_result(res),
+                            content: fence_tool_result(res).into(),
                             is_error: Some(*is_error),
                         }),
                         _ => None,
@@ -307,10 +439,14 @@ pub fn build_api_messages_from_parts(messages: &[&ChatMessage]) -> Vec<Message>
                             tool_call_id,
                             result,
                             is_error,
+                            images,
                         } => {
                             tool_result_blocks.push(ContentBlock::ToolResult {
                                 tool_use_id: tool_call_id.clone(),
-                                content: fence_tool_result(result),
+                                content: ToolResultContent::with_images(
+                                    fence_tool_result(result),
+                                    images.clone(),
+                                ),
                                 is_error: Some(*is_error),
                             });
                         }
@@ -408,6 +544,9 @@ pub enum MessagePart {
         result: String,
         #[serde(default)]
         is_error: bool,
+        /// Images the tool returned alongside `result` (e.g. screenshots).
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        images: Vec<ImageSource>,
     },
 }
 
@@ -431,7 +570,7 @@ mod tests {
 
     #[test]
     fn text_only_conversation() {
-        let msgs = vec![
+        let msgs = [
             make_chat_msg("1", MessageRole::User, vec![MessagePart::Text { text: "hi".into() }]),
             make_chat_msg("2", MessageRole::Assistant, vec![MessagePart::Text { text: "hello".into() }]),
         ];
@@ -446,7 +585,7 @@ mod tests {
     #[test]
     fn tool_call_new_format_produces_correct_pairing() {
         // New format: ToolCall on assistant (no inline result), ToolResult on user
-        let msgs = vec![
+        let msgs = [
             make_chat_msg("1", MessageRole::User, vec![MessagePart::Text { text: "do it".into() }]),
             make_chat_msg(
                 "2",
@@ -466,6 +605,7 @@ mod tests {
                     tool_call_id: "tc1".into(),
                     result: "file.txt".into(),
                     is_error: false,
+                    images: Vec::new(),
                 }],
             ),
         ];
@@ -490,7 +630,7 @@ mod tests {
     #[test]
     fn tool_call_legacy_inline_result_produces_separate_user_message() {
         // Old format: ToolCall with inline result
-        let msgs = vec![
+        let msgs = [
             make_chat_msg("1", MessageRole::User, vec![MessagePart::Text { text: "do it".into() }]),
             make_chat_msg(
                 "2",
@@ -519,7 +659,7 @@ mod tests {
     #[test]
     fn user_message_tool_results_before_text() {
         // User message with both text and tool results — results come first
-        let msgs = vec![make_chat_msg(
+        let msgs = [make_chat_msg(
             "1",
             MessageRole::User,
             vec![
@@ -528,6 +668,7 @@ mod tests {
                     tool_call_id: "tc1".into(),
                     result: "output".into(),
                     is_error: false,
+                    images: Vec::new(),
                 },
             ],
         )];
@@ -540,9 +681,32 @@ mod tests {
         assert!(matches!(&api[1].content[0], ContentBlock::Text { .. }));
     }
 
+    #[test]
+    fn tool_result_images_become_content_blocks() {
+        let msgs = [make_chat_msg(
+            "1",
+            MessageRole::User,
+            vec![MessagePart::ToolResult {
+                tool_call_id: "tc1".into(),
+                result: "screenshot taken".into(),
+                is_error: false,
+                images: vec![ImageSource::base64("image/png", "iVBOR")],
+            }],
+        )];
+        let refs: Vec<&ChatMessage> = msgs.iter().collect();
+        let api = build_api_messages_from_parts(&refs);
+
+        let ContentBlock::ToolResult { content, .. } = &api[0].content[0] else {
+            panic!("expected a tool result");
+        };
+        assert!(matches!(content, ToolResultContent::Blocks(blocks) if blocks.len() == 2));
+        assert!(content.text().contains("screenshot taken"));
+        assert_eq!(content.images().next().unwrap().media_type, "image/png");
+    }
+
     #[test]
     fn thinking_blocks_stripped_from_api_output() {
-        let msgs = vec![make_chat_msg(
+        let msgs = [make_chat_msg(
             "1",
             MessageRole::Assistant,
             vec![
@@ -561,7 +725,7 @@ mod tests {
 
     #[test]
     fn empty_assistant_message_skipped() {
-        let msgs = vec![make_chat_msg(
+        let msgs = [make_chat_msg(
             "1",
             MessageRole::Assistant,
             vec![MessagePart::Thinking { thinking: "hmm".into() }],
@@ -592,6 +756,7 @@ mod tests {
             agent_id: None,
             workspace_id: None,
             spans: vec![],
+            checkpoints: vec![],
         };
 
         let active = conv.active_messages();
@@ -600,6 +765,37 @@ mod tests {
         assert_eq!(active[1].id, "c");
     }
 
+    #[test]
+    fn reply_to_joins_assistant_text_after_the_prompt() {
+        let text = |t: &str| vec![MessagePart::Text { text: t.into() }];
+        let conv = Conversation {
+            id: "c1".into(),
+            title: "test".into(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            messages: vec![
+                make_chat_msg("a", MessageRole::User, text("first")),
+                make_chat_msg("b", MessageRole::Assistant, text("old")),
+                make_chat_msg("c", MessageRole::User, text("second")),
+                make_chat_msg("d", MessageRole::Assistant, text("Let me check. ")),
+                make_chat_msg("e", MessageRole::User, vec![]),
+                make_chat_msg("f", MessageRole::Assistant, text("Done.")),
+                make_chat_msg("g", MessageRole::User, text("third")),
+                make_chat_msg("h", MessageRole::Assistant, text("Later.")),
+            ],
+            active_path: ["a", "b", "c", "d", "e", "f", "g", "h"].map(String::from).to_vec(),
+            usage: None,
+            agent_id: None,
+            workspace_id: None,
+            spans: vec![],
+            checkpoints: vec![],
+        };
+
+        assert_eq!(conv.reply_to("c").as_deref(), Some("Let me check. Done."));
+        assert_eq!(conv.reply_to("h"), None);
+        assert_eq!(conv.reply_to("missing"), None);
+    }
+
     #[test]
     fn span_summaries_returns_sealed_only() {
         let conv = Conversation {
@@ -612,6 +808,7 @@ mod tests {
             usage: None,
             agent_id: None,
             workspace_id: None,
+            checkpoints: vec![],
             spans: vec![
                 Span {
                     index: 0,
@@ -632,6 +829,51 @@ mod tests {
         assert_eq!(summaries, vec!["summary 1"]);
     }
 
+    #[test]
+    fn rollback_to_tag_truncates_the_active_path_at_the_tagged_message() {
+        let mut messages: Vec<ChatMessage> = ["a", "b", "c", "d"]
+            .iter()
+            .map(|id| make_chat_msg(id, MessageRole::User, vec![]))
+            .collect();
+        for i in 1..messages.len() {
+            messages[i].parent_id = Some(messages[i - 1].id.clone());
+        }
+        let mut conv = Conversation {
+            id: "c1".into(),
+            title: "test".into(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            messages,
+            active_path: vec!["a".into(), "b".into()],
+            usage: None,
+            agent_id: None,
+            workspace_id: None,
+            checkpoints: vec![],
+            spans: vec![Span {
+                index: 0,
+                message_ids: vec!["a".into()],
+                summary: Some("old".into()),
+                sealed_at: Some(Utc::now()),
+            }],
+        };
+
+        assert_eq!(conv.set_checkpoint("plan approved", None).unwrap().message_id, "b");
+        conv.active_path = vec!["a".into(), "b".into(), "c".into(), "d".into()];
+        assert!(conv.set_checkpoint("start", Some("a")).is_some());
+        assert!(conv.set_checkpoint("missing", Some("zz")).is_none());
+
+        conv.rollback_to_tag("plan approved").unwrap();
+        assert_eq!(conv.active_path, ["a", "b"]);
+        assert_eq!(conv.rollback_to_tag("start"), Err(RollbackError::Sealed));
+        assert_eq!(conv.rollback_to_tag("nope"), Err(RollbackError::UnknownTag));
+
+        // Re-tagging moves the tag rather than adding a second one.
+        conv.set_checkpoint("plan approved", Some("c"));
+        assert_eq!(conv.checkpoints.len(), 2);
+        conv.rollback_to_tag("plan approved").unwrap();
+        assert_eq!(conv.active_path, ["a", "b", "c"]);
+    }
+
     #[test]
     fn build_api_messages_includes_span_summaries() {
         let conv = Conversation {
@@ -648,6 +890,7 @@ mod tests {
             usage: None,
             agent_id: None,
             workspace_id: None,
+            checkpoints: vec![],
             spans: vec![Span {
                 index: 0,
                 message_ids: vec!["old".into()],
@@ -663,4 +906,68 @@ mod tests {
         assert_eq!(api[1].role, Role::Assistant); // ack
         assert_eq!(api[2].role, Role::User); // latest
     }
+
+    #[test]
+    fn turn_count_includes_sealed_spans_and_skips_tool_results() {
+        let conv = Conversation {
+            id: "c1".into(),
+            title: "test".into(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            messages: vec![
+                make_chat_msg("old", MessageRole::User, vec![MessagePart::Text { text: "first".into() }]),
+                make_chat_msg("m1", MessageRole::User, vec![MessagePart::Text { text: "second".into() }]),
+                make_chat_msg(
+                    "m2",
+                    MessageRole::User,
+                    vec![MessagePart::ToolResult {
+                        tool_call_id: "tc1".into(),
+                        result: "ok".into(),
+                        is_error: false,
+                        images: Vec::new(),
+                    }],
+                ),
+                make_chat_msg("m3", MessageRole::Assistant, vec![MessagePart::Text { text: "done".into() }]),
+            ],
+            active_path: vec!["m1".into(), "m2".into(), "m3".into()],
+            usage: None,
+            agent_id: None,
+            workspace_id: None,
+            checkpoints: vec![],
+            spans: vec![Span {
+                index: 0,
+                message_ids: vec!["old".into()],
+                summary: Some("old context".into()),
+                sealed_at: Some(Utc::now()),
+            }],
+        };
+
+        assert_eq!(conv.turn_count(), 2);
+    }
+
+    #[test]
+    fn filter_matches_workspace_and_agent() {
+        let meta = ConversationMeta {
+            id: "c1".into(),
+            title: "t".into(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            message_count: 0,
+            turn_count: 0,
+            workspace_id: Some("ws".into()),
+            agent_id: None,
+            usage: None,
+            origin: None,
+        };
+
+        let workspace = |ws: &str| ConversationFilter { workspace_id: Some(ws.into()), ..Default::default() };
+        assert!(ConversationFilter::default().matches(&meta));
+        assert!(workspace("ws").matches(&meta));
+        assert!(!workspace("other").matches(&meta));
+        assert!(!ConversationFilter { agent_id: Some("a".into()), ..Default::default() }.matches(&meta));
+
+        let api = ConversationMeta { origin: Some("openai".into()), ..meta };
+        assert!(!ConversationFilter::default().matches(&api));
+        assert!(ConversationFilter { origin: Some("openai".into()), ..Default::default() }.matches(&api));
+    }
 }
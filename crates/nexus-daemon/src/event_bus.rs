@@ -1,6 +1,10 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
 use tokio::sync::broadcast;
 
 use crate::agent::events::{AgUiEvent, EventEnvelope};
+use crate::event_sink::EventSink;
 
 /// Shared event bus for all services.
 ///
@@ -13,19 +17,33 @@ use crate::agent::events::{AgUiEvent, EventEnvelope};
 #[derive(Clone)]
 pub struct EventBus {
     tx: broadcast::Sender<EventEnvelope>,
+    /// Events lost by lagging sinks (shared with AgentEventBridge).
+    dropped: Arc<AtomicU64>,
 }
 
 #[allow(dead_code)] // core API surface: new, sender, subscribe used across services
 impl EventBus {
     pub fn new() -> Self {
         let (tx, _) = broadcast::channel(4096);
-        Self { tx }
+        Self {
+            tx,
+            dropped: Arc::default(),
+        }
     }
 
     /// Wrap an existing sender (e.g. from AgentEventBridge) so both share
     /// the same underlying channel.
     pub fn from_sender(tx: broadcast::Sender<EventEnvelope>) -> Self {
-        Self { tx }
+        Self {
+            tx,
+            dropped: Arc::default(),
+        }
+    }
+
+    /// Count sink lag into an existing counter (e.g. the bridge's).
+    pub fn with_drop_counter(mut self, dropped: Arc<AtomicU64>) -> Self {
+        self.dropped = dropped;
+        self
     }
 
     /// Emit an event envelope.
@@ -69,6 +87,32 @@ impl EventBus {
     pub fn subscribe(&self) -> broadcast::Receiver<EventEnvelope> {
         self.tx.subscribe()
     }
+
+    /// Run `sink` on its own task, fed from this bus. Whatever is queued
+    /// when the sink is ready is handed over as one batch.
+    pub fn attach_sink(&self, sink: Arc<dyn EventSink>) {
+        let mut rx = self.tx.subscribe();
+        let dropped = Arc::clone(&self.dropped);
+        tokio::spawn(async move {
+            loop {
+                let first = match rx.recv().await {
+                    Ok(envelope) => envelope,
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        dropped.fetch_add(n, Ordering::Relaxed);
+                        tracing::warn!(skipped = n, "Event sink lagged — {} events dropped", n);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+
+                let mut batch = vec![first];
+                while let Ok(envelope) = rx.try_recv() {
+                    batch.push(envelope);
+                }
+                sink.emit_batch(&batch).await;
+            }
+        });
+    }
 }
 
 #[cfg(test)]
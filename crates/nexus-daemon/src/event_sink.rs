@@ -0,0 +1,167 @@
+//! Pluggable consumers for the event bus.
+//!
+//! The SSE bridge is the primary consumer of [`EventBus`](crate::event_bus::EventBus)
+//! traffic. Anything else that wants the stream — a JSONL log, tracing
+//! output — implements [`EventSink`] and is attached with
+//! `EventBus::attach_sink`, which runs it on its own task so a slow sink
+//! never blocks emitters.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::agent::events::{AgUiEvent, EventEnvelope};
+use crate::thread::ThreadService;
+
+#[async_trait]
+pub trait EventSink: Send + Sync {
+    /// Handle a single event.
+    async fn emit(&self, envelope: &EventEnvelope);
+
+    /// Handle a batch of events drained from the bus in one go. Override
+    /// when per-call overhead matters (e.g. file appends).
+    async fn emit_batch(&self, batch: &[EventEnvelope]) {
+        for envelope in batch {
+            self.emit(envelope).await;
+        }
+    }
+}
+
+/// Forwards every event to each inner sink, in order.
+pub struct FanoutSink {
+    sinks: Vec<Arc<dyn EventSink>>,
+}
+
+impl FanoutSink {
+    pub fn new(sinks: Vec<Arc<dyn EventSink>>) -> Self {
+        Self { sinks }
+    }
+}
+
+#[async_trait]
+impl EventSink for FanoutSink {
+    async fn emit(&self, envelope: &EventEnvelope) {
+        for sink in &self.sinks {
+            sink.emit(envelope).await;
+        }
+    }
+
+    async fn emit_batch(&self, batch: &[EventEnvelope]) {
+        for sink in &self.sinks {
+            sink.emit_batch(batch).await;
+        }
+    }
+}
+
+/// Logs each event at TRACE under the `nexus::events` target.
+pub struct TracingSink;
+
+#[async_trait]
+impl EventSink for TracingSink {
+    async fn emit(&self, envelope: &EventEnvelope) {
+        let name = match &envelope.event {
+            AgUiEvent::Custom { name, .. } => Some(name.as_str()),
+            _ => None,
+        };
+        tracing::trace!(
+            target: "nexus::events",
+            thread_id = envelope.thread_id.as_deref(),
+            run_id = envelope.run_id.as_deref(),
+            name,
+            "{}",
+            serde_json::to_string(&envelope.event).unwrap_or_default()
+        );
+    }
+}
+
+/// Appends thread-scoped events to the conversation's JSONL event log.
+/// Global events are skipped.
+pub struct ConversationLogSink {
+    threads: Arc<ThreadService>,
+}
+
+impl ConversationLogSink {
+    pub fn new(threads: Arc<ThreadService>) -> Self {
+        Self { threads }
+    }
+}
+
+#[async_trait]
+impl EventSink for ConversationLogSink {
+    async fn emit(&self, envelope: &EventEnvelope) {
+        self.emit_batch(std::slice::from_ref(envelope)).await;
+    }
+
+    /// One append per conversation per batch, so a burst of streaming
+    /// deltas doesn't cost a file open per event.
+    async fn emit_batch(&self, batch: &[EventEnvelope]) {
+        let mut lines: HashMap<&str, Vec<String>> = HashMap::new();
+        for envelope in batch {
+            if let Some(tid) = envelope.thread_id() {
+                if let Ok(json) = serde_json::to_string(envelope) {
+                    lines.entry(tid).or_default().push(json);
+                }
+            }
+        }
+
+        for (id, lines) in lines {
+            if let Err(e) = self.threads.append_events(id, &lines).await {
+                tracing::warn!(conversation_id = %id, "Failed to append event log: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_bus::EventBus;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingSink {
+        seen: Mutex<Vec<String>>,
+        batches: Mutex<usize>,
+    }
+
+    #[async_trait]
+    impl EventSink for RecordingSink {
+        async fn emit(&self, envelope: &EventEnvelope) {
+            if let AgUiEvent::Custom { name, .. } = &envelope.event {
+                self.seen.lock().unwrap().push(name.clone());
+            }
+        }
+
+        async fn emit_batch(&self, batch: &[EventEnvelope]) {
+            *self.batches.lock().unwrap() += 1;
+            for envelope in batch {
+                self.emit(envelope).await;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn attached_fanout_delivers_to_every_sink_in_order() {
+        let bus = EventBus::new();
+        let a = Arc::new(RecordingSink::default());
+        let b = Arc::new(RecordingSink::default());
+        bus.attach_sink(Arc::new(FanoutSink::new(vec![
+            a.clone() as Arc<dyn EventSink>,
+            b.clone() as Arc<dyn EventSink>,
+        ])));
+
+        bus.emit_global("first", serde_json::json!({}));
+        bus.emit_data("t1", "second", serde_json::json!({}));
+
+        for _ in 0..100 {
+            if b.seen.lock().unwrap().len() == 2 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        assert_eq!(*a.seen.lock().unwrap(), vec!["first", "second"]);
+        assert_eq!(*b.seen.lock().unwrap(), vec!["first", "second"]);
+        assert!(*a.batches.lock().unwrap() >= 1);
+    }
+}
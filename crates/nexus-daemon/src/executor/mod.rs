@@ -0,0 +1,222 @@
+//! Bulk executor: runs many independent jobs with a concurrency limit,
+//! a shared rate limit, progress tracking and a failure policy.
+//!
+//! Built for batches of agent runs (`server/batches.rs`), but generic over
+//! the job: each item is handed to a closure that returns its output text or
+//! an error message.
+
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+/// Upper bound on jobs running at once.
+pub const MAX_CONCURRENCY: usize = 32;
+
+/// What to do with the remaining items when one fails.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailurePolicy {
+    /// Keep going; the failure is recorded on its item.
+    #[default]
+    Continue,
+    /// Cancel running items and skip those not yet started.
+    FailFast,
+}
+
+/// Spaces job starts evenly to stay under a requests-per-minute budget.
+/// Share one limiter (via `Arc`) between executors that hit the same
+/// provider.
+pub struct RateLimiter {
+    interval: Duration,
+    next: tokio::sync::Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub fn per_minute(requests: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(60) / requests.max(1),
+            next: tokio::sync::Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Wait for the next free slot.
+    pub async fn acquire(&self) {
+        let mut next = self.next.lock().await;
+        let now = Instant::now();
+        if *next > now {
+            tokio::time::sleep_until(*next).await;
+        }
+        *next = (*next).max(now) + self.interval;
+    }
+}
+
+#[derive(Clone)]
+pub struct ExecutorConfig {
+    pub concurrency: usize,
+    pub rate_limit: Option<Arc<RateLimiter>>,
+    pub policy: FailurePolicy,
+}
+
+/// Result of one item.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ItemOutcome {
+    Completed { output: String },
+    Failed { error: String },
+    /// Not run: the batch was cancelled or failed fast first.
+    Skipped,
+}
+
+/// Running totals, updated as items finish.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Progress {
+    pub total: usize,
+    pub running: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+}
+
+pub struct Executor {
+    config: ExecutorConfig,
+    progress: Arc<Mutex<Progress>>,
+}
+
+impl Executor {
+    pub fn new(config: ExecutorConfig) -> Self {
+        Self { config, progress: Arc::default() }
+    }
+
+    /// Live progress, readable while `run` is in flight.
+    pub fn progress(&self) -> Arc<Mutex<Progress>> {
+        Arc::clone(&self.progress)
+    }
+
+    /// Run `job` for every item and return the outcomes in item order.
+    /// `on_item` is called as each item starts (`None`) and finishes.
+    /// Cancelling `cancel` stops the batch: running jobs see their token
+    /// cancelled and unstarted items are skipped.
+    pub async fn run<I, F, Fut>(
+        &self,
+        items: Vec<I>,
+        cancel: CancellationToken,
+        on_item: impl Fn(usize, Option<&ItemOutcome>),
+        job: F,
+    ) -> Vec<ItemOutcome>
+    where
+        F: Fn(usize, I, CancellationToken) -> Fut,
+        Fut: Future<Output = Result<String, String>>,
+    {
+        let total = items.len();
+        *self.progress.lock().unwrap() = Progress { total, ..Default::default() };
+        let concurrency = self.config.concurrency.clamp(1, MAX_CONCURRENCY);
+        let (job, on_item, cancel) = (&job, &on_item, &cancel);
+
+        let mut outcomes = vec![ItemOutcome::Skipped; total];
+        let mut finished = futures::stream::iter(items.into_iter().enumerate())
+            .map(|(index, item)| async move {
+                if let Some(ref limiter) = self.config.rate_limit {
+                    tokio::select! {
+                        _ = limiter.acquire() => {}
+                        _ = cancel.cancelled() => {}
+                    }
+                }
+                if cancel.is_cancelled() {
+                    return (index, ItemOutcome::Skipped);
+                }
+                self.progress.lock().unwrap().running += 1;
+                on_item(index, None);
+                let outcome = match job(index, item, cancel.child_token()).await {
+                    Ok(output) => ItemOutcome::Completed { output },
+                    Err(error) => ItemOutcome::Failed { error },
+                };
+                self.progress.lock().unwrap().running -= 1;
+                (index, outcome)
+            })
+            .buffer_unordered(concurrency);
+
+        while let Some((index, outcome)) = finished.next().await {
+            {
+                let mut progress = self.progress.lock().unwrap();
+                match outcome {
+                    ItemOutcome::Completed { .. } => progress.completed += 1,
+                    ItemOutcome::Failed { .. } => progress.failed += 1,
+                    ItemOutcome::Skipped => progress.skipped += 1,
+                }
+            }
+            if matches!(outcome, ItemOutcome::Failed { .. })
+                && self.config.policy == FailurePolicy::FailFast
+            {
+                cancel.cancel();
+            }
+            on_item(index, Some(&outcome));
+            outcomes[index] = outcome;
+        }
+        outcomes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    fn executor(concurrency: usize, policy: FailurePolicy) -> Executor {
+        Executor::new(ExecutorConfig { concurrency, rate_limit: None, policy })
+    }
+
+    #[tokio::test]
+    async fn respects_concurrency_and_keeps_item_order() {
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let exec = executor(3, FailurePolicy::Continue);
+        let outcomes = exec
+            .run((0..10).collect(), CancellationToken::new(), |_, _| {}, |_, n: u64, _| {
+                let (running, peak) = (&running, &peak);
+                async move {
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(10 - n)).await;
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    if n == 4 { Err("bad item".to_string()) } else { Ok(n.to_string()) }
+                }
+            })
+            .await;
+
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+        assert_eq!(outcomes[0], ItemOutcome::Completed { output: "0".into() });
+        assert_eq!(outcomes[4], ItemOutcome::Failed { error: "bad item".into() });
+        assert_eq!(outcomes[9], ItemOutcome::Completed { output: "9".into() });
+        let progress = exec.progress().lock().unwrap().clone();
+        assert_eq!(progress, Progress { total: 10, running: 0, completed: 9, failed: 1, skipped: 0 });
+    }
+
+    #[tokio::test]
+    async fn fail_fast_skips_the_rest() {
+        let exec = executor(1, FailurePolicy::FailFast);
+        let outcomes = exec
+            .run(vec![true, false, true, true], CancellationToken::new(), |_, _| {}, |_, ok, _| async move {
+                if ok { Ok(String::new()) } else { Err("boom".to_string()) }
+            })
+            .await;
+        assert!(matches!(outcomes[1], ItemOutcome::Failed { .. }));
+        assert_eq!(outcomes[2..], [ItemOutcome::Skipped, ItemOutcome::Skipped]);
+    }
+
+    #[tokio::test]
+    async fn rate_limiter_spaces_starts() {
+        // 1200/min: one start every 50ms.
+        let limiter = RateLimiter::per_minute(1200);
+        let start = Instant::now();
+        for _ in 0..3 {
+            limiter.acquire().await;
+        }
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+}
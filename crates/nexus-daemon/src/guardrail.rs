@@ -0,0 +1,283 @@
+//! Output guardrails with auto-repair.
+//!
+//! A [`Guardrail`] validates the agent's final answer for a turn. When a
+//! check fails, the agent loop (`agent/run.rs`) feeds the errors back as a
+//! user message and runs another round, up to `max_retries` times per turn;
+//! after that the last answer is kept and an `agent_warning` is emitted.
+//!
+//! Built-in checks, configured under `guardrails` in `nexus.json`:
+//! [`SchemaGuardrail`] and [`RegexGuardrail`]. Any other [`Guardrail`]
+//! impl can join a [`GuardrailSet`]; the tests use a closure-backed one.
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use regex::Regex;
+use serde_json::Value;
+
+use crate::config::{GuardrailKind, GuardrailsConfig};
+
+pub trait Guardrail: Send + Sync {
+    /// Name reported in warnings.
+    fn name(&self) -> &str;
+
+    /// Check the final answer. Errors are shown to the model verbatim.
+    fn validate(&self, text: &str) -> Result<(), Vec<String>>;
+}
+
+/// The answer must be a JSON value matching a JSON Schema. A surrounding
+/// Markdown code fence is ignored.
+pub struct SchemaGuardrail {
+    validator: jsonschema::Validator,
+}
+
+impl SchemaGuardrail {
+    pub fn new(schema: &Value) -> Result<Self> {
+        let validator =
+            jsonschema::validator_for(schema).map_err(|e| anyhow!("invalid JSON schema: {e}"))?;
+        Ok(Self { validator })
+    }
+}
+
+impl Guardrail for SchemaGuardrail {
+    fn name(&self) -> &str {
+        "json_schema"
+    }
+
+    fn validate(&self, text: &str) -> Result<(), Vec<String>> {
+        let value: Value = serde_json::from_str(strip_code_fence(text))
+            .map_err(|e| vec![format!("response is not valid JSON: {e}")])?;
+        let errors: Vec<String> = self
+            .validator
+            .iter_errors(&value)
+            .map(|e| match e.instance_path.to_string() {
+                path if path.is_empty() => e.to_string(),
+                path => format!("{path}: {e}"),
+            })
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+pub struct RegexGuardrail {
+    regex: Regex,
+    must_match: bool,
+    message: String,
+}
+
+impl RegexGuardrail {
+    pub fn new(pattern: &str, must_match: bool, message: Option<String>) -> Result<Self> {
+        let regex = Regex::new(pattern)
+            .with_context(|| format!("invalid guardrail pattern {pattern:?}"))?;
+        let message = message.unwrap_or_else(|| {
+            if must_match {
+                format!("response must match the pattern {pattern}")
+            } else {
+                format!("response must not match the pattern {pattern}")
+            }
+        });
+        Ok(Self { regex, must_match, message })
+    }
+}
+
+impl Guardrail for RegexGuardrail {
+    fn name(&self) -> &str {
+        "regex"
+    }
+
+    fn validate(&self, text: &str) -> Result<(), Vec<String>> {
+        if self.regex.is_match(text) == self.must_match {
+            Ok(())
+        } else {
+            Err(vec![self.message.clone()])
+        }
+    }
+}
+
+/// The guardrails that apply to one turn.
+#[derive(Clone)]
+pub struct GuardrailSet {
+    pub checks: Vec<Arc<dyn Guardrail>>,
+    pub max_retries: u32,
+}
+
+impl GuardrailSet {
+    /// Run every check; returns all errors, prefixed with the check name.
+    pub fn validate(&self, text: &str) -> Vec<String> {
+        self.checks
+            .iter()
+            .filter_map(|g| g.validate(text).err().map(|errs| (g.name(), errs)))
+            .flat_map(|(name, errs)| errs.into_iter().map(move |e| format!("{name}: {e}")))
+            .collect()
+    }
+}
+
+/// Every configured guardrail, with the agents it is scoped to.
+#[derive(Default)]
+pub struct Guardrails {
+    checks: Vec<(Vec<String>, Arc<dyn Guardrail>)>,
+    max_retries: u32,
+}
+
+impl Guardrails {
+    pub fn from_config(config: Option<&GuardrailsConfig>) -> Result<Self> {
+        let Some(config) = config else {
+            return Ok(Self::default());
+        };
+        let checks = config
+            .checks
+            .iter()
+            .map(|check| {
+                let guardrail: Arc<dyn Guardrail> = match &check.kind {
+                    GuardrailKind::JsonSchema { schema } => Arc::new(SchemaGuardrail::new(schema)?),
+                    GuardrailKind::Regex { pattern, must_match, message } => {
+                        Arc::new(RegexGuardrail::new(pattern, *must_match, message.clone())?)
+                    }
+                };
+                Ok((check.agents.clone(), guardrail))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { checks, max_retries: config.max_retries })
+    }
+
+    /// The checks that apply to the agent with this id and name, if any.
+    pub fn for_agent(&self, agent_id: &str, agent_name: &str) -> Option<GuardrailSet> {
+        let checks: Vec<_> = self
+            .checks
+            .iter()
+            .filter(|(agents, _)| {
+                agents.is_empty() || agents.iter().any(|a| a == agent_id || a == agent_name)
+            })
+            .map(|(_, g)| Arc::clone(g))
+            .collect();
+        if checks.is_empty() {
+            return None;
+        }
+        Some(GuardrailSet { checks, max_retries: self.max_retries })
+    }
+}
+
+/// The user message asking the model to fix a failed answer.
+pub fn repair_prompt(errors: &[String]) -> String {
+    let list = errors
+        .iter()
+        .map(|e| format!("- {e}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!(
+        "Your previous response failed validation:\n{list}\n\n\
+         Reply again with the complete corrected response only."
+    )
+}
+
+fn strip_code_fence(text: &str) -> &str {
+    let trimmed = text.trim();
+    let Some(rest) = trimmed.strip_prefix("```") else {
+        return trimmed;
+    };
+    let body = rest.split_once('\n').map_or("", |(_, body)| body);
+    body.trim_end().strip_suffix("```").unwrap_or(body).trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// A guardrail backed by a closure, for checks defined in code.
+    struct PredicateGuardrail<F> {
+        name: String,
+        check: F,
+    }
+
+    impl<F> PredicateGuardrail<F>
+    where
+        F: Fn(&str) -> Result<(), String> + Send + Sync,
+    {
+        fn new(name: impl Into<String>, check: F) -> Self {
+            Self { name: name.into(), check }
+        }
+    }
+
+    impl<F> Guardrail for PredicateGuardrail<F>
+    where
+        F: Fn(&str) -> Result<(), String> + Send + Sync,
+    {
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        fn validate(&self, text: &str) -> Result<(), Vec<String>> {
+            (self.check)(text).map_err(|e| vec![e])
+        }
+    }
+
+    #[test]
+    fn schema_guardrail_reports_parse_and_schema_errors() {
+        let guardrail = SchemaGuardrail::new(&json!({
+            "type": "object",
+            "required": ["name"],
+            "properties": { "name": { "type": "string" }, "age": { "type": "integer" } },
+        }))
+        .unwrap();
+
+        assert!(guardrail.validate(r#"{"name": "Ada"}"#).is_ok());
+        assert!(guardrail.validate("```json\n{\"name\": \"Ada\"}\n```").is_ok());
+
+        let errors = guardrail.validate("Sure! Here it is").unwrap_err();
+        assert!(errors[0].starts_with("response is not valid JSON"));
+
+        let errors = guardrail.validate(r#"{"age": "old"}"#).unwrap_err();
+        assert_eq!(errors.len(), 2, "{errors:?}");
+        assert!(errors.iter().any(|e| e.contains("\"name\" is a required property")));
+        assert!(errors.iter().any(|e| e.starts_with("/age:")));
+
+        assert!(SchemaGuardrail::new(&json!({ "type": 12 })).is_err());
+    }
+
+    #[test]
+    fn sets_are_scoped_per_agent_and_collect_errors() {
+        let config: GuardrailsConfig = serde_json::from_value(json!({
+            "checks": [
+                { "type": "regex", "pattern": "^Answer:", "message": "start with Answer:" },
+                { "type": "regex", "pattern": "(?i)lorem", "must_match": false, "agents": ["writer"] },
+            ],
+        }))
+        .unwrap();
+        let guardrails = Guardrails::from_config(Some(&config)).unwrap();
+
+        let set = guardrails.for_agent("a1", "Coder").unwrap();
+        assert_eq!(set.max_retries, 2);
+        assert_eq!(set.checks.len(), 1);
+
+        let set = guardrails.for_agent("a2", "writer").unwrap();
+        assert_eq!(set.validate("Answer: 42"), Vec::<String>::new());
+        assert_eq!(
+            set.validate("Lorem ipsum"),
+            vec![
+                "regex: start with Answer:".to_string(),
+                "regex: response must not match the pattern (?i)lorem".to_string(),
+            ]
+        );
+
+        assert!(Guardrails::default().for_agent("a1", "Coder").is_none());
+
+        let set = GuardrailSet {
+            checks: vec![Arc::new(PredicateGuardrail::new("short", |t: &str| {
+                if t.len() < 5 { Ok(()) } else { Err("too long".into()) }
+            }))],
+            max_retries: 1,
+        };
+        assert_eq!(set.validate("too long text"), vec!["short: too long".to_string()]);
+    }
+
+    #[test]
+    fn repair_prompt_lists_errors() {
+        let prompt = repair_prompt(&["a".into(), "b".into()]);
+        assert!(prompt.contains("- a\n- b"));
+    }
+}
@@ -23,6 +23,8 @@ pub struct HookRecord {
 pub struct HookProbeState {
     pub records: Vec<HookRecord>,
     pub deny_tools: HashSet<String>,
+    /// Tag pushed at every turn_end.
+    pub checkpoint_tag: Option<String>,
 }
 
 pub struct HookProbe {
@@ -36,6 +38,7 @@ impl HookProbe {
             state: Mutex::new(HookProbeState {
                 records: Vec::new(),
                 deny_tools: HashSet::new(),
+                checkpoint_tag: None,
             }),
             force_continue_count: AtomicU32::new(0),
         }
@@ -63,6 +66,7 @@ impl HookProbe {
         let mut state = self.state.lock().unwrap();
         state.records.clear();
         state.deny_tools.clear();
+        state.checkpoint_tag = None;
         self.force_continue_count.store(0, Ordering::Relaxed);
     }
 
@@ -70,6 +74,10 @@ impl HookProbe {
         self.state.lock().unwrap().deny_tools.insert(tool_name);
     }
 
+    pub fn set_checkpoint_tag(&self, tag: String) {
+        self.state.lock().unwrap().checkpoint_tag = Some(tag);
+    }
+
     pub fn set_force_continue(&self, count: u32) {
         self.force_continue_count.store(count, Ordering::Relaxed);
     }
@@ -139,13 +147,24 @@ impl DaemonModule for HookProbe {
         }
     }
 
-    async fn turn_end(&self, event: &TurnEndEvent<'_>) {
+    async fn turn_end(&self, event: &mut TurnEndEvent<'_>) {
         self.record("turn_end", event.conversation_id, serde_json::json!({
             "run_id": event.run_id,
             "round_count": event.round_count,
             "turn_cost": event.turn_cost,
             "error": event.error,
         }));
+        if let Some(tag) = self.state.lock().unwrap().checkpoint_tag.clone() {
+            event.checkpoint_tags.push(tag);
+        }
+    }
+
+    async fn post_compact(&self, event: &PostCompactEvent<'_>) {
+        self.record("post_compact", event.conversation_id, serde_json::json!({
+            "span_index": event.span_index,
+            "summary": event.summary,
+            "consumed_count": event.consumed_message_ids.len(),
+        }));
     }
 
     async fn on_startup(&self) -> anyhow::Result<()> {
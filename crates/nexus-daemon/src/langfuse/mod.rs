@@ -0,0 +1,310 @@
+//! Langfuse trace export.
+//!
+//! An [`EventSink`] that turns each agent run into a Langfuse trace: one
+//! trace per run (grouped by conversation as the Langfuse session), a
+//! generation per provider call (from `inference_usage`) and a span per tool
+//! call. Events for a run are collected in memory and pushed to the
+//! ingestion API in one batch when the run finishes. Pushes run on their own
+//! tasks, so a slow Langfuse host never holds up the event stream, and runs
+//! that never finish are dropped after [`RUN_TTL`].
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::agent::events::{AgUiEvent, EventEnvelope};
+use crate::config::LangfuseConfig;
+use crate::event_sink::EventSink;
+
+/// A run still open this long after it started is assumed lost (e.g. its
+/// RUN_FINISHED was dropped) and its trace is discarded.
+const RUN_TTL: Duration = Duration::from_secs(6 * 3600);
+
+const PUSH_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub struct LangfuseSink {
+    client: reqwest::Client,
+    config: Arc<LangfuseConfig>,
+    traces: Mutex<TraceBuilder>,
+}
+
+impl LangfuseSink {
+    pub fn new(config: LangfuseConfig) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            config: Arc::new(config),
+            traces: Mutex::new(TraceBuilder::default()),
+        }
+    }
+
+    /// Send a finished run's batch in the background.
+    fn push(&self, batch: Vec<Value>) {
+        let client = self.client.clone();
+        let config = Arc::clone(&self.config);
+        tokio::spawn(async move {
+            let url = format!("{}/api/public/ingestion", config.host.trim_end_matches('/'));
+            let result = client
+                .post(&url)
+                .basic_auth(&config.public_key, Some(&config.secret_key))
+                .timeout(PUSH_TIMEOUT)
+                .json(&json!({ "batch": batch }))
+                .send()
+                .await
+                .and_then(|r| r.error_for_status());
+            if let Err(e) = result {
+                tracing::warn!("Langfuse export failed: {}", e);
+            }
+        });
+    }
+}
+
+#[async_trait]
+impl EventSink for LangfuseSink {
+    async fn emit(&self, envelope: &EventEnvelope) {
+        let finished = self.traces.lock().await.observe(envelope);
+        if let Some(batch) = finished {
+            self.push(batch);
+        }
+    }
+}
+
+/// In-progress run: ingestion events so far plus what's needed to build
+/// the remaining ones.
+struct RunTrace {
+    started: Instant,
+    batch: Vec<Value>,
+    output: String,
+    tools: HashMap<String, ToolCall>,
+}
+
+struct ToolCall {
+    name: String,
+    args: String,
+    start_time: String,
+}
+
+/// Maps the event stream to Langfuse ingestion events, keyed by run id.
+#[derive(Default)]
+struct TraceBuilder {
+    runs: HashMap<String, RunTrace>,
+}
+
+impl TraceBuilder {
+    /// Feed one event. Returns the run's full ingestion batch once the run
+    /// finishes or errors.
+    fn observe(&mut self, envelope: &EventEnvelope) -> Option<Vec<Value>> {
+        let run_id = envelope.run_id.as_deref()?;
+        let now = Utc::now().to_rfc3339();
+
+        if let AgUiEvent::RunStarted = envelope.event {
+            self.evict_stale(Instant::now());
+            let trace = ingestion(
+                "trace-create",
+                &now,
+                json!({
+                    "id": run_id,
+                    "name": "nexus-turn",
+                    "sessionId": envelope.thread_id,
+                    "timestamp": now,
+                }),
+            );
+            self.runs.insert(
+                run_id.to_string(),
+                RunTrace {
+                    started: Instant::now(),
+                    batch: vec![trace],
+                    output: String::new(),
+                    tools: HashMap::new(),
+                },
+            );
+            return None;
+        }
+
+        let run = self.runs.get_mut(run_id)?;
+        match &envelope.event {
+            AgUiEvent::TextMessageContent { delta, .. } => run.output.push_str(delta),
+            AgUiEvent::ToolCallStart { tool_call_id, tool_call_name } => {
+                run.tools.insert(
+                    tool_call_id.clone(),
+                    ToolCall {
+                        name: tool_call_name.clone(),
+                        args: String::new(),
+                        start_time: now,
+                    },
+                );
+            }
+            AgUiEvent::ToolCallArgs { tool_call_id, delta } => {
+                if let Some(tool) = run.tools.get_mut(tool_call_id) {
+                    tool.args.push_str(delta);
+                }
+            }
+            AgUiEvent::ToolCallResult { tool_call_id, content, is_error } => {
+                let tool = run.tools.remove(tool_call_id);
+                let (name, args, start_time) = match tool {
+                    Some(t) => (t.name, t.args, t.start_time),
+                    None => ("tool".to_string(), String::new(), now.clone()),
+                };
+                let input = serde_json::from_str::<Value>(&args).unwrap_or(Value::String(args));
+                run.batch.push(ingestion(
+                    "span-create",
+                    &now,
+                    json!({
+                        "id": Uuid::new_v4().to_string(),
+                        "traceId": run_id,
+                        "name": name,
+                        "startTime": start_time,
+                        "endTime": now,
+                        "input": input,
+                        "output": content,
+                        "level": if *is_error { "ERROR" } else { "DEFAULT" },
+                        "metadata": { "toolCallId": tool_call_id },
+                    }),
+                ));
+            }
+            AgUiEvent::Custom { name, value } if name == "inference_usage" => {
+                run.batch.push(ingestion(
+                    "generation-create",
+                    &now,
+                    json!({
+                        "id": Uuid::new_v4().to_string(),
+                        "traceId": run_id,
+                        "name": value["source"],
+                        "model": value["model"],
+                        "endTime": now,
+                        "usageDetails": {
+                            "input": value["inputTokens"],
+                            "output": value["outputTokens"],
+                            "cache_read_input_tokens": value["cacheReadInputTokens"],
+                            "cache_creation_input_tokens": value["cacheCreationInputTokens"],
+                        },
+                        "costDetails": { "total": value["cost"] },
+                        "metadata": { "round": value["round"] },
+                    }),
+                ));
+            }
+            AgUiEvent::RunFinished { .. } | AgUiEvent::RunError { .. } => {
+                let mut run = self.runs.remove(run_id)?;
+                let mut body = json!({
+                    "id": run_id,
+                    "output": run.output,
+                });
+                if let AgUiEvent::RunError { message, .. } = &envelope.event {
+                    body["metadata"] = json!({ "error": message });
+                    body["tags"] = json!(["error"]);
+                }
+                // trace-create with an existing id updates the trace
+                run.batch.push(ingestion("trace-create", &now, body));
+                return Some(run.batch);
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// Drop runs that started more than [`RUN_TTL`] before `now`.
+    fn evict_stale(&mut self, now: Instant) {
+        self.runs.retain(|run_id, run| {
+            let live = now.saturating_duration_since(run.started) < RUN_TTL;
+            if !live {
+                tracing::debug!(run_id = %run_id, "Dropping Langfuse trace for a run that never finished");
+            }
+            live
+        });
+    }
+}
+
+fn ingestion(kind: &str, timestamp: &str, body: Value) -> Value {
+    json!({
+        "id": Uuid::new_v4().to_string(),
+        "type": kind,
+        "timestamp": timestamp,
+        "body": body,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(event: AgUiEvent) -> EventEnvelope {
+        EventEnvelope {
+            thread_id: Some("conv1".into()),
+            run_id: Some("run1".into()),
+            event,
+        }
+    }
+
+    #[test]
+    fn run_becomes_trace_with_generation_and_tool_span() {
+        let mut builder = TraceBuilder::default();
+
+        assert!(builder.observe(&env(AgUiEvent::RunStarted)).is_none());
+        builder.observe(&env(AgUiEvent::Custom {
+            name: "inference_usage".into(),
+            value: json!({
+                "source": "turn", "round": 0, "model": "claude-test",
+                "inputTokens": 100, "outputTokens": 20,
+                "cacheReadInputTokens": 0, "cacheCreationInputTokens": 0, "cost": 0.01
+            }),
+        }));
+        builder.observe(&env(AgUiEvent::ToolCallStart {
+            tool_call_id: "tc1".into(),
+            tool_call_name: "bash".into(),
+        }));
+        builder.observe(&env(AgUiEvent::ToolCallArgs {
+            tool_call_id: "tc1".into(),
+            delta: r#"{"command":"ls"}"#.into(),
+        }));
+        builder.observe(&env(AgUiEvent::ToolCallResult {
+            tool_call_id: "tc1".into(),
+            content: "file.txt".into(),
+            is_error: false,
+        }));
+        builder.observe(&env(AgUiEvent::TextMessageContent {
+            message_id: "m1".into(),
+            delta: "Done".into(),
+        }));
+        let batch = builder
+            .observe(&env(AgUiEvent::RunFinished { has_running_processes: false }))
+            .expect("batch on finish");
+
+        let types: Vec<&str> = batch.iter().map(|e| e["type"].as_str().unwrap()).collect();
+        assert_eq!(
+            types,
+            ["trace-create", "generation-create", "span-create", "trace-create"]
+        );
+        assert_eq!(batch[0]["body"]["sessionId"], "conv1");
+        assert_eq!(batch[1]["body"]["traceId"], "run1");
+        assert_eq!(batch[1]["body"]["usageDetails"]["input"], 100);
+        assert_eq!(batch[2]["body"]["name"], "bash");
+        assert_eq!(batch[2]["body"]["input"]["command"], "ls");
+        assert_eq!(batch[3]["body"]["output"], "Done");
+        assert!(builder.runs.is_empty());
+    }
+
+    #[test]
+    fn runs_that_never_finish_are_evicted() {
+        let mut builder = TraceBuilder::default();
+        builder.observe(&env(AgUiEvent::RunStarted));
+        builder.evict_stale(Instant::now());
+        assert_eq!(builder.runs.len(), 1);
+
+        builder.evict_stale(Instant::now() + RUN_TTL);
+        assert!(builder.runs.is_empty());
+    }
+
+    #[test]
+    fn events_without_a_started_run_are_ignored() {
+        let mut builder = TraceBuilder::default();
+        assert!(builder
+            .observe(&env(AgUiEvent::RunFinished { has_running_processes: false }))
+            .is_none());
+        assert!(builder.runs.is_empty());
+    }
+}
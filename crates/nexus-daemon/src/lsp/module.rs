@@ -70,7 +70,9 @@ impl DaemonModule for LspModule {
     }
 
     async fn post_tool_use(&self, event: &mut PostToolUseEvent<'_>) {
-        let tool_name = event.tool_name;
+        // Workspace-prefixed fs tools (`backend__edit_file`) get the same treatment.
+        let tool_name = nexus_tools::filesystem::split_namespaced(event.tool_name)
+            .map_or(event.tool_name, |(_, tool)| tool);
         let is_write = WRITE_TOOLS.contains(&tool_name);
         let is_read = READ_TOOLS.contains(&tool_name);
         if !is_write && !is_read {
@@ -2,36 +2,51 @@ mod agent;
 mod agent_config;
 mod auto_title;
 mod bg_process;
+#[cfg(feature = "cli")]
+mod cli;
 mod compaction;
 mod config;
+mod context;
 mod control_plane;
 mod conversation;
 mod conversation_context;
 mod event_bus;
+mod event_sink;
+mod executor;
+mod guardrail;
 #[cfg(debug_assertions)]
 mod hook_probe;
+mod langfuse;
 mod lsp;
 mod mcp;
 mod mcp_resources;
+mod moderation;
+#[cfg(feature = "otel")]
+mod otel;
 pub mod module;
 mod provider;
+mod pruned_results;
 mod retry;
 mod server;
+mod skills;
 mod system_prompt;
 mod task_context;
 mod tasks;
 mod thread;
 mod tool_filter;
 mod tool_spill;
+mod turn_summary;
 mod project;
 mod workspace;
 
 use anyhow::Result;
 use std::sync::Arc;
+use tracing_subscriber::prelude::*;
 
 use crate::agent_config::{AgentService, AgentStore};
 use crate::agent_config::store::CreateAgentParams;
 use crate::config::NexusConfig;
+use crate::conversation::lock::{LockLost, StoreLock};
 use crate::conversation::ConversationStore;
 use crate::event_bus::EventBus;
 use crate::mcp::store::McpServerStore;
@@ -53,12 +68,25 @@ async fn main() -> Result<()> {
         let _ = dotenvy::from_filename(path);
     }
 
-    tracing_subscriber::fmt()
-        .with_env_filter(
+    #[cfg(feature = "cli")]
+    if let Some(cli) = cli::Cli::parse_command() {
+        return cli::run(cli).await;
+    }
+
+    let subscriber = tracing_subscriber::registry()
+        .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| "nexus=info".into()),
         )
-        .init();
+        .with(tracing_subscriber::fmt::layer());
+    #[cfg(feature = "otel")]
+    let otel_guard = {
+        let (otel_layer, guard) = otel::layer()?;
+        subscriber.with(otel_layer).init();
+        guard
+    };
+    #[cfg(not(feature = "otel"))]
+    subscriber.init();
 
     let config = NexusConfig::load()?;
     let mcp_servers = NexusConfig::load_mcp_servers()?;
@@ -79,6 +107,11 @@ async fn main() -> Result<()> {
         config.agents.clone(),
         config.active_agent_id.clone(),
     );
+    agent_store.add_file_agents(agent_config::file::load_agent_files(
+        &NexusConfig::nexus_dir().join("agents"),
+        provider_store.list(),
+        &mcp_servers,
+    ));
 
     // Backward compat: seed default provider + agent from ANTHROPIC_API_KEY if none exist
     if provider_store.list().is_empty() {
@@ -101,6 +134,7 @@ async fn main() -> Result<()> {
                     system_prompt: config.agent.system_prompt.clone(),
                     temperature: None,
                     max_tokens: Some(config.api.max_tokens),
+                    thinking_budget: None,
                     mcp_server_ids: None,
                 })?;
                 agent_store.set_active(Some(agent.id))?;
@@ -117,12 +151,28 @@ async fn main() -> Result<()> {
     let nexus_dir = NexusConfig::nexus_dir();
     let conversations_dir = nexus_dir.join("conversations");
 
-    let event_bridge = AgentEventBridge::new();
-    // EventBus shares the same broadcast channel as AgentEventBridge
-    let event_bus = EventBus::from_sender(event_bridge.agent_tx());
+    let event_bridge = AgentEventBridge::new().with_lag_policy(config.events.lag_policy);
+    // EventBus shares the same broadcast channel (and drop counter) as AgentEventBridge
+    let event_bus = EventBus::from_sender(event_bridge.agent_tx())
+        .with_drop_counter(event_bridge.dropped_counter());
+    // Refuse to share the conversation directory with another running daemon
+    std::fs::create_dir_all(&conversations_dir)?;
+    let store_lock = Arc::new(StoreLock::acquire(
+        &conversations_dir,
+        chrono::Duration::seconds(STORE_LOCK_LEASE_SECS),
+        config.conversations.lock_takeover,
+    )?);
+    let lock_lost = spawn_lock_renewal(Arc::clone(&store_lock));
     // ThreadService owns the ConversationStore — all conversation CRUD goes through it
-    let conversations = ConversationStore::load(conversations_dir)?;
+    let conversations = ConversationStore::load(conversations_dir)?
+        .with_storage(config.conversations.clone());
     let threads = Arc::new(ThreadService::new(conversations, event_bus.clone()));
+    if config.conversations.event_log && config.conversations.recover_interrupted {
+        let recovered = threads.recover_interrupted_runs().await;
+        if recovered > 0 {
+            tracing::info!(conversations = recovered, "Recovered interrupted runs from event logs");
+        }
+    }
 
     // Projects + workspaces + effective filesystem config
     let project_store = ProjectStore::new(config.projects.clone());
@@ -160,6 +210,7 @@ async fn main() -> Result<()> {
         event_bridge.agent_tx(),
         message_queue.clone(),
     ));
+    process_manager.reattach().await;
 
     let turns = Arc::new(TurnManager::new(
         event_bridge,
@@ -169,7 +220,13 @@ async fn main() -> Result<()> {
     ));
 
     let agents_svc = Arc::new(AgentService::new(agent_store, event_bus.clone()));
-    let providers_svc = Arc::new(ProviderService::new(provider_store, event_bus.clone()));
+    let response_cache = config.response_cache.as_ref().map(|c| {
+        Arc::new(nexus_provider::cache::ResponseCache::new(
+            std::time::Duration::from_secs(c.ttl_secs),
+            c.max_entries,
+        ))
+    });
+    let providers_svc = Arc::new(ProviderService::new(provider_store, event_bus.clone(), response_cache));
     let task_svc = Arc::new(tasks::TaskService::new(nexus_dir.join("tasks"), event_bus.clone()));
 
     let mcp_svc = Arc::new(McpService {
@@ -228,13 +285,28 @@ async fn main() -> Result<()> {
     module_registry.register(Arc::new(tool_spill::ToolSpillModule) as Arc<dyn crate::module::DaemonModule>);
 
     // Auto-title — generates conversation titles after each turn
-    let auto_title_module = Arc::new(auto_title::AutoTitleModule {
-        threads: Arc::clone(&threads),
-        agents: Arc::clone(&agents_svc),
-        providers: Arc::clone(&providers_svc),
-        model_tiers: config.model_tiers.clone(),
-    });
-    module_registry.register(auto_title_module as Arc<dyn crate::module::DaemonModule>);
+    if config.conversations.auto_title {
+        let auto_title_module = Arc::new(auto_title::AutoTitleModule {
+            threads: Arc::clone(&threads),
+            agents: Arc::clone(&agents_svc),
+            providers: Arc::clone(&providers_svc),
+            model_tiers: config.model_tiers.clone(),
+        });
+        module_registry.register(auto_title_module as Arc<dyn crate::module::DaemonModule>);
+    }
+
+    // Turn summaries — one-line breadcrumbs after each turn
+    if config.conversations.turn_summary != config::TurnSummaryMode::Off {
+        let turn_summary_module = Arc::new(turn_summary::TurnSummaryModule {
+            mode: config.conversations.turn_summary,
+            threads: Arc::clone(&threads),
+            agents: Arc::clone(&agents_svc),
+            providers: Arc::clone(&providers_svc),
+            model_tiers: config.model_tiers.clone(),
+            events: event_bus.sender(),
+        });
+        module_registry.register(turn_summary_module as Arc<dyn crate::module::DaemonModule>);
+    }
 
     // Conversation context — injects workspace/project/cost into status message
     let conversation_context_module = Arc::new(conversation_context::ConversationContextModule {
@@ -251,6 +323,9 @@ async fn main() -> Result<()> {
     });
     module_registry.register(task_context_module as Arc<dyn crate::module::DaemonModule>);
 
+    let moderation = moderation::Moderation::from_config(config.moderation.as_ref())?;
+    let guardrails = guardrail::Guardrails::from_config(config.guardrails.as_ref())?;
+
     let state = AppState {
         base_filesystem_config: config.filesystem.clone(),
         effective_fs_config: effective_fs_lock,
@@ -266,6 +341,10 @@ async fn main() -> Result<()> {
         event_bus,
         lsp: lsp_svc,
         modules: Arc::new(module_registry),
+        a2a: Arc::default(),
+        batches: Arc::default(),
+        moderation: Arc::new(moderation),
+        guardrails: Arc::new(guardrails),
         #[cfg(debug_assertions)]
         hook_probe,
     };
@@ -276,6 +355,8 @@ async fn main() -> Result<()> {
     }
 
     let modules_for_shutdown = Arc::clone(&state.modules);
+    let turns_for_shutdown = Arc::clone(&state.turns);
+    let shutdown_grace = std::time::Duration::from_secs(config.server.shutdown_grace_secs);
     let router = server::build_router(state, queue_rx, "ui/dist");
 
     let addr = format!("{}:{}", config.server.host, config.server.port);
@@ -291,11 +372,31 @@ async fn main() -> Result<()> {
     // axum's graceful shutdown waits for ALL connections to drain, but SSE
     // streams are long-lived and never close on their own, so we'd hang forever.
     tokio::spawn(async move {
-        shutdown_signal().await;
+        let store_lost = tokio::select! {
+            _ = shutdown_signal() => false,
+            _ = lock_lost.notified() => true,
+        };
+
+        if store_lost {
+            // Saving interrupted turns would write into a store another
+            // daemon now owns.
+            let dropped = turns_for_shutdown.abandon_all().await;
+            if dropped > 0 {
+                tracing::warn!(turns = dropped, "Store lock lost; cancelled running turns without saving their progress");
+            }
+        } else {
+            let unfinished = turns_for_shutdown.interrupt_all(shutdown_grace).await;
+            if unfinished > 0 {
+                tracing::warn!(turns = unfinished, "Turns still running at shutdown; their progress is not saved");
+            }
+        }
 
         // HOOK: Shutdown — let modules clean up (includes LSP via LspModule)
         tracing::info!("Shutting down modules...");
         modules_for_shutdown.shutdown().await;
+        store_lock.release();
+        #[cfg(feature = "otel")]
+        otel_guard.shutdown();
 
         tracing::info!("Cleanup complete, exiting");
         std::process::exit(0);
@@ -306,6 +407,42 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Conversation store lease length. Renewed every third of this.
+const STORE_LOCK_LEASE_SECS: i64 = 30;
+
+/// Keep the store lease alive. Failed renewals are retried until the lease
+/// would have expired; after that, or as soon as another daemon takes the
+/// lock over, the returned `Notify` fires so the daemon shuts down without
+/// saving running turns — two writers on one conversation directory would
+/// clobber each other's files.
+fn spawn_lock_renewal(lock: Arc<StoreLock>) -> Arc<tokio::sync::Notify> {
+    let lost = Arc::new(tokio::sync::Notify::new());
+    let notify = Arc::clone(&lost);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(lock.renew_interval());
+        interval.tick().await;
+        let mut renewed_at = std::time::Instant::now();
+        loop {
+            interval.tick().await;
+            let attempt = std::time::Instant::now();
+            match lock.renew() {
+                Ok(()) => renewed_at = attempt,
+                Err(e) if e.downcast_ref::<LockLost>().is_some() => {
+                    tracing::error!("{}; shutting down", e);
+                    break;
+                }
+                Err(e) if renewed_at.elapsed() >= lock.lease() => {
+                    tracing::error!("Conversation store lease expired ({:#}); shutting down", e);
+                    break;
+                }
+                Err(e) => tracing::warn!("Failed to renew conversation store lock, retrying: {:#}", e),
+            }
+        }
+        notify.notify_one();
+    });
+    lost
+}
+
 async fn shutdown_signal() {
     let ctrl_c = tokio::signal::ctrl_c();
     #[cfg(unix)]
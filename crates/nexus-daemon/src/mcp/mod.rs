@@ -8,6 +8,7 @@ use std::collections::HashSet;
 use serde::Serialize;
 use nexus_provider::types::Tool as AnthropicTool;
 use crate::config::McpServerConfig;
+use crate::module::{ToolImage, ToolResult};
 pub use handler::ClientHandlerState;
 use server::McpServer;
 
@@ -154,12 +155,12 @@ impl McpManager {
         &self,
         name: &str,
         args_json: &str,
-    ) -> (String, bool) {
+    ) -> ToolResult {
         let Some((server_idx, original_name)) = self.tool_routing.get(name) else {
-            return (
-                format!("Unknown tool '{}'. No MCP server provides this tool.", name),
-                true,
-            );
+            return ToolResult::error(format!(
+                "Unknown tool '{}'. No MCP server provides this tool.",
+                name
+            ));
         };
 
         let server = &self.servers[*server_idx];
@@ -179,9 +180,18 @@ impl McpManager {
                     .filter_map(|c| c.as_text().map(|t| t.text.clone()))
                     .collect::<Vec<_>>()
                     .join("\n");
-                (text, is_error)
+                let images = result
+                    .content
+                    .iter()
+                    .filter_map(|c| c.as_image())
+                    .map(|image| ToolImage {
+                        media_type: image.mime_type.clone(),
+                        data: image.data.clone(),
+                    })
+                    .collect();
+                ToolResult { content: text, is_error, injected_messages: Vec::new(), images }
             }
-            Err(e) => (format!("Tool call failed: {}", e), true),
+            Err(e) => ToolResult::error(format!("Tool call failed: {}", e)),
         }
     }
 
@@ -191,7 +191,7 @@ impl McpServer {
                     McpRequest::GetPrompt { name, arguments, reply } => {
                         let result = service
                             .get_prompt(GetPromptRequestParams {
-                                name: name.into(),
+                                name,
                                 arguments,
                                 meta: None,
                             })
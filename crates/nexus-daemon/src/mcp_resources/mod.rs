@@ -127,12 +127,12 @@ async fn execute_read(args_json: &str, mcp: &McpManager) -> (String, bool) {
             let text = result
                 .contents
                 .iter()
-                .filter_map(|c| match c {
+                .map(|c| match c {
                     rmcp::model::ResourceContents::TextResourceContents { text, .. } => {
-                        Some(text.clone())
+                        text.clone()
                     }
                     rmcp::model::ResourceContents::BlobResourceContents { blob, .. } => {
-                        Some(format!("[Binary data, {} bytes base64]", blob.len()))
+                        format!("[Binary data, {} bytes base64]", blob.len())
                     }
                 })
                 .collect::<Vec<_>>()
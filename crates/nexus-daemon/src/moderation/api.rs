@@ -0,0 +1,110 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::{ModerationStage, ModerationVerdict, Moderator};
+use crate::config::{ModerationAction, ModerationApiConfig};
+
+/// Classifies content with an OpenAI-compatible moderation endpoint
+/// (`POST {endpoint}` with `{ input, model? }`). Flagged content is blocked
+/// or annotated, with the flagged categories as the reason.
+pub struct ApiModerator {
+    client: reqwest::Client,
+    config: ModerationApiConfig,
+}
+
+impl ApiModerator {
+    pub fn new(config: ModerationApiConfig) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            config,
+        }
+    }
+}
+
+#[async_trait]
+impl Moderator for ApiModerator {
+    fn name(&self) -> &str {
+        "api"
+    }
+
+    async fn moderate(&self, stage: ModerationStage, text: &str) -> Result<ModerationVerdict> {
+        if !self.config.stages.contains(&stage) || text.trim().is_empty() {
+            return Ok(ModerationVerdict::Allow);
+        }
+        let mut body = json!({ "input": text });
+        if let Some(ref model) = self.config.model {
+            body["model"] = json!(model);
+        }
+        let response: Value = self
+            .client
+            .post(&self.config.endpoint)
+            .bearer_auth(&self.config.api_key)
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        verdict_from_response(&response, self.config.action)
+    }
+}
+
+fn verdict_from_response(response: &Value, action: ModerationAction) -> Result<ModerationVerdict> {
+    let results = response["results"]
+        .as_array()
+        .ok_or_else(|| anyhow!("moderation response has no results"))?;
+    let flagged: Vec<&Value> = results
+        .iter()
+        .filter(|r| r["flagged"].as_bool() == Some(true))
+        .collect();
+    if flagged.is_empty() {
+        return Ok(ModerationVerdict::Allow);
+    }
+    let mut categories: Vec<&str> = flagged
+        .iter()
+        .filter_map(|r| r["categories"].as_object())
+        .flat_map(|c| c.iter().filter(|(_, v)| v.as_bool() == Some(true)).map(|(k, _)| k.as_str()))
+        .collect();
+    categories.sort_unstable();
+    categories.dedup();
+    let reason = if categories.is_empty() {
+        "flagged by moderation API".to_string()
+    } else {
+        format!("flagged by moderation API: {}", categories.join(", "))
+    };
+    Ok(match action {
+        ModerationAction::Annotate => ModerationVerdict::Annotate { reason },
+        ModerationAction::Block | ModerationAction::Rewrite => ModerationVerdict::Block { reason },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flagged_results_map_to_verdicts() {
+        let flagged = json!({ "results": [{
+            "flagged": true,
+            "categories": { "violence": true, "harassment": false, "hate": true },
+        }] });
+        assert_eq!(
+            verdict_from_response(&flagged, ModerationAction::Block).unwrap(),
+            ModerationVerdict::Block {
+                reason: "flagged by moderation API: hate, violence".into()
+            }
+        );
+        assert!(matches!(
+            verdict_from_response(&flagged, ModerationAction::Annotate).unwrap(),
+            ModerationVerdict::Annotate { .. }
+        ));
+
+        let clean = json!({ "results": [{ "flagged": false, "categories": {} }] });
+        assert_eq!(
+            verdict_from_response(&clean, ModerationAction::Block).unwrap(),
+            ModerationVerdict::Allow
+        );
+        assert!(verdict_from_response(&json!({}), ModerationAction::Block).is_err());
+    }
+}
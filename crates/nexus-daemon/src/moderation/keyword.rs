@@ -0,0 +1,131 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use regex::Regex;
+
+use super::{ModerationStage, ModerationVerdict, Moderator};
+use crate::config::{ModerationAction, ModerationRule};
+
+const DEFAULT_REPLACEMENT: &str = "[redacted]";
+
+struct CompiledRule {
+    regex: Regex,
+    action: ModerationAction,
+    replacement: String,
+    reason: String,
+    stages: Vec<ModerationStage>,
+}
+
+/// Regex/keyword rules from `moderation.rules`. The first blocking match
+/// wins; rewrites and annotations accumulate across rules.
+pub struct KeywordModerator {
+    rules: Vec<CompiledRule>,
+}
+
+impl KeywordModerator {
+    pub fn new(rules: &[ModerationRule]) -> Result<Self> {
+        let rules = rules
+            .iter()
+            .map(|rule| {
+                Ok(CompiledRule {
+                    regex: Regex::new(&rule.pattern)
+                        .with_context(|| format!("invalid moderation pattern {:?}", rule.pattern))?,
+                    action: rule.action,
+                    replacement: rule
+                        .replacement
+                        .clone()
+                        .unwrap_or_else(|| DEFAULT_REPLACEMENT.to_string()),
+                    reason: rule.reason.clone().unwrap_or_else(|| rule.pattern.clone()),
+                    stages: rule.stages.clone(),
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { rules })
+    }
+}
+
+#[async_trait]
+impl Moderator for KeywordModerator {
+    fn name(&self) -> &str {
+        "keyword"
+    }
+
+    async fn moderate(&self, stage: ModerationStage, text: &str) -> Result<ModerationVerdict> {
+        let mut current = text.to_string();
+        let mut rewrites = Vec::new();
+        let mut annotations = Vec::new();
+
+        for rule in self.rules.iter().filter(|r| r.stages.contains(&stage)) {
+            if !rule.regex.is_match(&current) {
+                continue;
+            }
+            match rule.action {
+                ModerationAction::Block => {
+                    return Ok(ModerationVerdict::Block { reason: rule.reason.clone() });
+                }
+                ModerationAction::Rewrite => {
+                    current = rule
+                        .regex
+                        .replace_all(&current, rule.replacement.as_str())
+                        .into_owned();
+                    rewrites.push(rule.reason.as_str());
+                }
+                ModerationAction::Annotate => annotations.push(rule.reason.as_str()),
+            }
+        }
+
+        Ok(if !rewrites.is_empty() {
+            rewrites.extend(annotations);
+            ModerationVerdict::Rewrite { text: current, reason: rewrites.join(", ") }
+        } else if !annotations.is_empty() {
+            ModerationVerdict::Annotate { reason: annotations.join(", ") }
+        } else {
+            ModerationVerdict::Allow
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(pattern: &str, action: ModerationAction, stages: &[ModerationStage]) -> ModerationRule {
+        ModerationRule {
+            pattern: pattern.into(),
+            action,
+            replacement: None,
+            reason: None,
+            stages: stages.to_vec(),
+        }
+    }
+
+    #[tokio::test]
+    async fn rules_block_rewrite_and_annotate_per_stage() {
+        let both = [ModerationStage::Input, ModerationStage::Output];
+        let moderator = KeywordModerator::new(&[
+            rule(r"\b\d{3}-\d{2}-\d{4}\b", ModerationAction::Rewrite, &both),
+            rule("(?i)refund", ModerationAction::Annotate, &both),
+            rule("(?i)internal only", ModerationAction::Block, &[ModerationStage::Output]),
+        ])
+        .unwrap();
+
+        let verdict = moderator
+            .moderate(ModerationStage::Input, "My SSN is 123-45-6789, refund please")
+            .await
+            .unwrap();
+        let ModerationVerdict::Rewrite { text, .. } = verdict else {
+            panic!("expected rewrite, got {verdict:?}");
+        };
+        assert_eq!(text, "My SSN is [redacted], refund please");
+
+        let verdict = moderator.moderate(ModerationStage::Input, "REFUND").await.unwrap();
+        assert!(matches!(verdict, ModerationVerdict::Annotate { .. }));
+
+        // Output-only rule does not apply to input.
+        let verdict = moderator.moderate(ModerationStage::Input, "internal only").await.unwrap();
+        assert_eq!(verdict, ModerationVerdict::Allow);
+        let verdict = moderator.moderate(ModerationStage::Output, "Internal only").await.unwrap();
+        assert_eq!(verdict, ModerationVerdict::Block { reason: "(?i)internal only".into() });
+
+        assert!(KeywordModerator::new(&[rule("(", ModerationAction::Block, &both)]).is_err());
+    }
+}
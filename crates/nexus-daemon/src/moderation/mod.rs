@@ -0,0 +1,212 @@
+//! Prompt and response moderation.
+//!
+//! A [`Moderator`] screens text at two points of a turn: the user's prompt
+//! before inference (`turn.rs`) and the model's text after each inference
+//! round (`agent/run.rs`). Moderators run in order; a rewrite feeds the next
+//! moderator, a block ends the chain. Every verdict other than `Allow` is
+//! surfaced as a `moderated` event.
+//!
+//! Built-in moderators, configured under `moderation` in `nexus.json`:
+//! [`KeywordModerator`] (regex rules) and [`ApiModerator`] (an
+//! OpenAI-compatible `/v1/moderations` endpoint).
+
+mod api;
+mod keyword;
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+pub use crate::config::ModerationStage;
+use crate::config::ModerationConfig;
+pub use api::ApiModerator;
+pub use keyword::KeywordModerator;
+
+/// What a moderator decided about a piece of content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModerationVerdict {
+    Allow,
+    /// Stop the prompt, or withhold the model's text.
+    Block { reason: String },
+    /// Replace the content with `text`.
+    Rewrite { text: String, reason: String },
+    /// Let the content through unchanged, but flag it.
+    Annotate { reason: String },
+}
+
+impl ModerationVerdict {
+    pub fn action(&self) -> &'static str {
+        match self {
+            Self::Allow => "allow",
+            Self::Block { .. } => "block",
+            Self::Rewrite { .. } => "rewrite",
+            Self::Annotate { .. } => "annotate",
+        }
+    }
+
+    pub fn reason(&self) -> Option<&str> {
+        match self {
+            Self::Allow => None,
+            Self::Block { reason } | Self::Rewrite { reason, .. } | Self::Annotate { reason } => {
+                Some(reason)
+            }
+        }
+    }
+}
+
+#[async_trait]
+pub trait Moderator: Send + Sync {
+    /// Name reported in `moderated` events.
+    fn name(&self) -> &str;
+
+    async fn moderate(&self, stage: ModerationStage, text: &str) -> Result<ModerationVerdict>;
+}
+
+/// One non-`Allow` verdict, attributed to the moderator that returned it.
+#[derive(Debug, Clone)]
+pub struct ModerationFinding {
+    pub moderator: String,
+    pub verdict: ModerationVerdict,
+}
+
+/// Result of running the whole chain over one piece of content.
+#[derive(Debug, Clone)]
+pub struct ModerationOutcome {
+    /// The content after all rewrites.
+    pub text: String,
+    pub findings: Vec<ModerationFinding>,
+}
+
+impl ModerationOutcome {
+    /// The block reason, if a moderator blocked the content.
+    pub fn blocked(&self) -> Option<&str> {
+        self.findings.iter().find_map(|f| match &f.verdict {
+            ModerationVerdict::Block { reason } => Some(reason.as_str()),
+            _ => None,
+        })
+    }
+
+    pub fn rewritten(&self) -> bool {
+        self.findings
+            .iter()
+            .any(|f| matches!(f.verdict, ModerationVerdict::Rewrite { .. }))
+    }
+}
+
+/// The configured moderator chain. Empty when moderation is disabled.
+#[derive(Default)]
+pub struct Moderation {
+    moderators: Vec<Arc<dyn Moderator>>,
+}
+
+impl Moderation {
+    pub fn new(moderators: Vec<Arc<dyn Moderator>>) -> Self {
+        Self { moderators }
+    }
+
+    /// Build the chain from `nexus.json`: regex rules first, then the API.
+    pub fn from_config(config: Option<&ModerationConfig>) -> Result<Self> {
+        let Some(config) = config else {
+            return Ok(Self::default());
+        };
+        let mut moderators: Vec<Arc<dyn Moderator>> = Vec::new();
+        if !config.rules.is_empty() {
+            moderators.push(Arc::new(KeywordModerator::new(&config.rules)?));
+        }
+        if let Some(ref api) = config.api {
+            moderators.push(Arc::new(ApiModerator::new(api.clone())));
+        }
+        Ok(Self::new(moderators))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moderators.is_empty()
+    }
+
+    /// Run every moderator over `text`. A moderator that fails is skipped
+    /// with a warning, so an unreachable moderation API does not stop turns.
+    pub async fn check(&self, stage: ModerationStage, text: &str) -> ModerationOutcome {
+        let mut outcome = ModerationOutcome {
+            text: text.to_string(),
+            findings: Vec::new(),
+        };
+        for moderator in &self.moderators {
+            let verdict = match moderator.moderate(stage, &outcome.text).await {
+                Ok(verdict) => verdict,
+                Err(e) => {
+                    tracing::warn!(moderator = moderator.name(), "Moderator failed: {:#}", e);
+                    continue;
+                }
+            };
+            if verdict == ModerationVerdict::Allow {
+                continue;
+            }
+            if let ModerationVerdict::Rewrite { ref text, .. } = verdict {
+                outcome.text.clone_from(text);
+            }
+            let blocked = matches!(verdict, ModerationVerdict::Block { .. });
+            outcome.findings.push(ModerationFinding {
+                moderator: moderator.name().to_string(),
+                verdict,
+            });
+            if blocked {
+                break;
+            }
+        }
+        outcome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed(ModerationVerdict);
+
+    #[async_trait]
+    impl Moderator for Fixed {
+        fn name(&self) -> &str {
+            "fixed"
+        }
+
+        async fn moderate(&self, _stage: ModerationStage, _text: &str) -> Result<ModerationVerdict> {
+            Ok(self.0.clone())
+        }
+    }
+
+    struct Failing;
+
+    #[async_trait]
+    impl Moderator for Failing {
+        fn name(&self) -> &str {
+            "failing"
+        }
+
+        async fn moderate(&self, _stage: ModerationStage, _text: &str) -> Result<ModerationVerdict> {
+            anyhow::bail!("unreachable")
+        }
+    }
+
+    #[tokio::test]
+    async fn chain_applies_rewrites_and_stops_at_block() {
+        let moderation = Moderation::new(vec![
+            Arc::new(Failing),
+            Arc::new(Fixed(ModerationVerdict::Rewrite {
+                text: "clean".into(),
+                reason: "r".into(),
+            })),
+            Arc::new(Fixed(ModerationVerdict::Block { reason: "no".into() })),
+            Arc::new(Fixed(ModerationVerdict::Annotate { reason: "never reached".into() })),
+        ]);
+        let outcome = moderation.check(ModerationStage::Input, "dirty").await;
+        assert_eq!(outcome.text, "clean");
+        assert!(outcome.rewritten());
+        assert_eq!(outcome.blocked(), Some("no"));
+        assert_eq!(outcome.findings.len(), 2);
+
+        let outcome = Moderation::default().check(ModerationStage::Output, "hi").await;
+        assert_eq!(outcome.text, "hi");
+        assert!(outcome.findings.is_empty());
+    }
+}
@@ -15,5 +15,8 @@ pub fn stop_reason_from_api(sr: &api::StopReason) -> StopReason {
         api::StopReason::MaxTokens => StopReason::MaxTokens,
         api::StopReason::StopSequence => StopReason::StopSequence,
         api::StopReason::ToolUse => StopReason::ToolUse,
+        api::StopReason::Refusal => StopReason::Refusal,
+        api::StopReason::PauseTurn => StopReason::Other("pause_turn".to_string()),
+        api::StopReason::Other(s) => StopReason::Other(s.clone()),
     }
 }
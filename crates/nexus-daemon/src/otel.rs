@@ -0,0 +1,49 @@
+//! OpenTelemetry export for tracing spans (`--features otel`).
+//!
+//! The daemon always creates spans named after the GenAI semantic
+//! conventions — `invoke_agent` per turn, `chat` per provider call (agent
+//! rounds and compaction), `execute_tool` per tool call — with token and
+//! cost attributes. This module only adds an OTLP exporter layer so those
+//! spans reach a collector (Jaeger, Tempo, Honeycomb, ...). The endpoint and
+//! headers come from the standard `OTEL_EXPORTER_OTLP_*` environment
+//! variables.
+
+use anyhow::Result;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+use tracing::Subscriber;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+/// Keeps the tracer provider alive; call [`shutdown`](Self::shutdown) before
+/// exiting to flush buffered spans.
+pub struct OtelGuard {
+    provider: SdkTracerProvider,
+}
+
+impl OtelGuard {
+    pub fn shutdown(&self) {
+        if let Err(e) = self.provider.shutdown() {
+            tracing::warn!("OpenTelemetry shutdown failed: {}", e);
+        }
+    }
+}
+
+/// Build the OTLP (gRPC) exporter and a tracing layer that feeds it.
+pub fn layer<S>() -> Result<(impl Layer<S>, OtelGuard)>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .build()?;
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name("nexus").build())
+        .build();
+    let tracer = provider.tracer("nexus");
+
+    let layer = tracing_opentelemetry::layer().with_tracer(tracer);
+    Ok((layer, OtelGuard { provider }))
+}
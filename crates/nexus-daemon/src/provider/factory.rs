@@ -1,27 +1,46 @@
 use anyhow::{anyhow, Result};
 use chrono::{DateTime, Utc};
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use tokio::sync::RwLock;
 
-use nexus_anthropic::AnthropicProvider;
+use nexus_anthropic::{AnthropicProvider, KeyStats};
 use nexus_aws_bedrock::BedrockProvider;
-use nexus_provider::provider_config::{Provider, ProviderType};
+use nexus_provider::provider_config::{Provider, ProviderType, ThrottleConfig};
+use nexus_provider::cache::{CachingProvider, ResponseCache};
+use nexus_provider::idempotency::DedupProvider;
+use nexus_provider::middleware::Layered;
 use nexus_provider::InferenceProvider;
 
+use super::result_format::ResultFormatter;
+use super::throttle::{Throttle, ThrottledProvider};
+
 type ProviderCache = HashMap<String, (DateTime<Utc>, Arc<dyn InferenceProvider>)>;
 
 pub struct ProviderFactory {
     cache: RwLock<ProviderCache>,
+    response_cache: Option<Arc<ResponseCache>>,
+    /// By provider id; outlive rebuilt instances so edits keep the budget.
+    throttles: Mutex<HashMap<String, Throttle>>,
+    /// Anthropic clients rotating several keys, by provider id, for their
+    /// per-key stats (the cached instance is wrapped and type-erased).
+    key_rings: Mutex<HashMap<String, Arc<AnthropicProvider>>>,
 }
 
 impl ProviderFactory {
-    pub fn new() -> Self {
+    pub fn new(response_cache: Option<Arc<ResponseCache>>) -> Self {
         Self {
             cache: RwLock::new(HashMap::new()),
+            response_cache,
+            throttles: Mutex::new(HashMap::new()),
+            key_rings: Mutex::new(HashMap::new()),
         }
     }
 
+    pub fn response_cache(&self) -> Option<&ResponseCache> {
+        self.response_cache.as_deref()
+    }
+
     pub async fn get(&self, provider: &Provider) -> Result<Arc<dyn InferenceProvider>> {
         // Check cache
         {
@@ -36,12 +55,34 @@ impl ProviderFactory {
         // Build new instance
         let instance: Arc<dyn InferenceProvider> = match provider.provider_type {
             ProviderType::Anthropic => {
-                let api_key = provider
-                    .api_key
-                    .as_ref()
+                let keys: Vec<String> =
+                    provider.api_key.iter().chain(&provider.api_keys).cloned().collect();
+                let api_key = keys
+                    .first()
                     .ok_or_else(|| anyhow!("Anthropic provider '{}' has no API key", provider.name))?
                     .clone();
-                Arc::new(AnthropicProvider::new(api_key, provider.endpoint.clone()))
+                let mut anthropic = AnthropicProvider::new(api_key, provider.endpoint.clone());
+                let rotating = keys.len() > 1;
+                if rotating {
+                    anthropic = anthropic.with_api_keys(keys);
+                }
+                if let Some(rotation) = provider.key_rotation {
+                    anthropic = anthropic.with_key_rotation(rotation);
+                }
+                if !provider.beta_headers.is_empty() {
+                    anthropic = anthropic.with_beta_headers(provider.beta_headers.clone());
+                }
+                if let Some(ref version) = provider.api_version {
+                    anthropic = anthropic.with_api_version(version.clone());
+                }
+                let anthropic = Arc::new(anthropic);
+                let mut key_rings = self.key_rings.lock().unwrap();
+                if rotating {
+                    key_rings.insert(provider.id.clone(), Arc::clone(&anthropic));
+                } else {
+                    key_rings.remove(&provider.id);
+                }
+                anthropic
             }
             ProviderType::Bedrock => {
                 let region = provider
@@ -55,6 +96,30 @@ impl ProviderFactory {
             }
         };
 
+        let instance: Arc<dyn InferenceProvider> = match provider.tool_results {
+            Some(ref formats) => Arc::new(
+                Layered::new(instance).with(Arc::new(ResultFormatter::new(formats.clone()))),
+            ),
+            None => instance,
+        };
+
+        // Innermost, so deduplicated and cached requests don't use quota.
+        let instance: Arc<dyn InferenceProvider> = match provider.throttle {
+            Some(config) => Arc::new(ThrottledProvider::new(instance, self.throttle(&provider.id, config))),
+            None => instance,
+        };
+
+        // Retries reuse their idempotency key; don't send a duplicate
+        // while the original is in flight or just completed.
+        let mut instance: Arc<dyn InferenceProvider> = Arc::new(DedupProvider::new(instance));
+        if let Some(ref response_cache) = self.response_cache {
+            instance = Arc::new(CachingProvider::new(
+                instance,
+                Arc::clone(response_cache),
+                provider.id.clone(),
+            ));
+        }
+
         // Cache it
         {
             let mut cache = self.cache.write().await;
@@ -67,8 +132,117 @@ impl ProviderFactory {
         Ok(instance)
     }
 
+    fn throttle(&self, provider_id: &str, config: ThrottleConfig) -> Throttle {
+        let mut throttles = self.throttles.lock().unwrap();
+        let throttle = throttles
+            .entry(provider_id.to_string())
+            .or_insert_with(|| Throttle::new(config));
+        throttle.reconfigure(config);
+        throttle.clone()
+    }
+
+    /// Requests and throttles per key for a provider rotating several keys;
+    /// `None` if it has one key or hasn't been built yet.
+    pub fn key_stats(&self, provider_id: &str) -> Option<Vec<KeyStats>> {
+        self.key_rings.lock().unwrap().get(provider_id).map(|p| p.key_stats())
+    }
+
     pub async fn invalidate(&self, provider_id: &str) {
         let mut cache = self.cache.write().await;
         cache.remove(provider_id);
+        self.key_rings.lock().unwrap().remove(provider_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use nexus_provider::InferenceRequest;
+
+    use super::*;
+
+    /// Accept one request, reply 400, and return the request's head.
+    async fn capture_request_head(listener: TcpListener) -> String {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut head = Vec::new();
+        let mut buf = [0u8; 4096];
+        while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+            let n = socket.read(&mut buf).await.unwrap();
+            assert!(n > 0, "connection closed mid-request");
+            head.extend_from_slice(&buf[..n]);
+        }
+        let body = r#"{"type":"error","error":{"type":"invalid_request_error","message":"captured"}}"#;
+        let response = format!(
+            "HTTP/1.1 400 Bad Request\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        socket.write_all(response.as_bytes()).await.unwrap();
+        String::from_utf8_lossy(&head).to_lowercase()
+    }
+
+    #[tokio::test]
+    async fn anthropic_providers_send_configured_betas_and_version() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(capture_request_head(listener));
+
+        let provider = Provider {
+            id: "p1".into(),
+            name: "Anthropic".into(),
+            provider_type: ProviderType::Anthropic,
+            endpoint: Some(endpoint),
+            api_key: Some("sk-test".into()),
+            api_keys: Vec::new(),
+            key_rotation: None,
+            beta_headers: vec!["context-1m-2025-08-07".into()],
+            api_version: Some("2099-01-01".into()),
+            aws_region: None,
+            aws_profile: None,
+            throttle: None,
+            tool_results: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let instance = ProviderFactory::new(None).get(&provider).await.unwrap();
+        let request = InferenceRequest {
+            model: "claude-test".into(),
+            max_tokens: 16,
+            system: None,
+            temperature: None,
+            thinking_budget: None,
+            messages: Vec::new(),
+            tools: Vec::new(),
+            idempotency_key: None,
+        };
+        assert!(instance.create_message_stream(request).await.is_err());
+
+        let head = server.await.unwrap();
+        assert!(head.contains("anthropic-beta: context-1m-2025-08-07\r\n"), "{head}");
+        assert!(head.contains("anthropic-version: 2099-01-01\r\n"), "{head}");
+    }
+
+    #[tokio::test]
+    async fn rotating_providers_report_key_stats() {
+        let provider: Provider = serde_json::from_value(serde_json::json!({
+            "id": "p1",
+            "name": "Anthropic",
+            "type": "anthropic",
+            "api_key": "sk-one-aaaa",
+            "api_keys": ["sk-two-bbbb"],
+            "key_rotation": "least_recently_throttled",
+        }))
+        .unwrap();
+        assert_eq!(provider.key_rotation, Some(nexus_anthropic::KeyRotation::LeastRecentlyThrottled));
+
+        let factory = ProviderFactory::new(None);
+        assert!(factory.key_stats("p1").is_none());
+        factory.get(&provider).await.unwrap();
+        let hints: Vec<_> = factory.key_stats("p1").unwrap().into_iter().map(|s| s.key_hint).collect();
+        assert_eq!(hints, ["aaaa", "bbbb"]);
+
+        factory.invalidate("p1").await;
+        assert!(factory.key_stats("p1").is_none());
     }
 }
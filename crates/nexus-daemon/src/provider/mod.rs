@@ -1,6 +1,8 @@
 pub mod factory;
+pub mod result_format;
 pub mod service;
 pub mod store;
+pub mod throttle;
 
 pub use service::ProviderService;
 pub use store::ProviderStore;
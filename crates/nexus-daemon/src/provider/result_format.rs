@@ -0,0 +1,154 @@
+//! Per-model tool result formatting.
+//!
+//! Tool results are stored and built in the `<tool_response>` form that
+//! Claude handles well. Smaller models often do better with plain text or a
+//! JSON envelope, so a provider with `tool_results` configured re-renders
+//! each fenced result for the request's model just before it is sent.
+
+use anyhow::Result;
+
+use nexus_provider::middleware::{ProviderMiddleware, ResponseObserver};
+use nexus_provider::provider_config::{ResultFormat, ToolResultFormats};
+use nexus_provider::types::{ContentBlock, ToolResultBlock, ToolResultContent};
+use nexus_provider::InferenceRequest;
+
+use crate::system_prompt::{format_tool_result, unfence_tool_result};
+
+pub struct ResultFormatter {
+    formats: ToolResultFormats,
+}
+
+impl ResultFormatter {
+    pub fn new(formats: ToolResultFormats) -> Self {
+        Self { formats }
+    }
+}
+
+impl ProviderMiddleware for ResultFormatter {
+    fn on_request(&self, request: &mut InferenceRequest) -> Result<Option<Box<dyn ResponseObserver>>> {
+        let format = self.formats.for_model(&request.model);
+        if format == ResultFormat::XmlTagged {
+            return Ok(None);
+        }
+        let blocks = request.messages.iter_mut().flat_map(|m| m.content.iter_mut());
+        for block in blocks {
+            let ContentBlock::ToolResult { content, .. } = block else {
+                continue;
+            };
+            match content {
+                ToolResultContent::Text(text) => reformat(text, format),
+                ToolResultContent::Blocks(blocks) => {
+                    for block in blocks {
+                        if let ToolResultBlock::Text { text } = block {
+                            reformat(text, format);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Re-render a fenced result; anything else (e.g. a pruning placeholder)
+/// is left as it is.
+fn reformat(text: &mut String, format: ResultFormat) {
+    if let Some(output) = unfence_tool_result(text) {
+        *text = format_tool_result(output, format);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use nexus_provider::types::{ImageSource, Message, Role};
+
+    use super::*;
+    use crate::system_prompt::fence_tool_result;
+
+    fn request(model: &str, content: ToolResultContent) -> InferenceRequest {
+        InferenceRequest {
+            model: model.into(),
+            max_tokens: 1024,
+            system: None,
+            temperature: None,
+            thinking_budget: None,
+            messages: vec![Message {
+                role: Role::User,
+                content: vec![ContentBlock::ToolResult {
+                    tool_use_id: "t1".into(),
+                    content,
+                    is_error: Some(false),
+                }],
+            }],
+            tools: Vec::new(),
+            idempotency_key: None,
+        }
+    }
+
+    fn result_text(request: &InferenceRequest) -> String {
+        match &request.messages[0].content[0] {
+            ContentBlock::ToolResult { content, .. } => content.text().into_owned(),
+            other => panic!("not a tool result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn results_are_rendered_in_the_model_format() {
+        let formatter = ResultFormatter::new(ToolResultFormats {
+            default: ResultFormat::Plain,
+            models: HashMap::from([
+                ("claude-*".to_string(), ResultFormat::XmlTagged),
+                ("qwen*".to_string(), ResultFormat::JsonEnvelope),
+                ("qwen2.5-coder".to_string(), ResultFormat::Plain),
+            ]),
+        });
+        let fenced = || ToolResultContent::Text(fence_tool_result("line 1\nline 2"));
+
+        let mut claude = request("claude-sonnet-4-5", fenced());
+        formatter.on_request(&mut claude).unwrap();
+        assert_eq!(result_text(&claude), fence_tool_result("line 1\nline 2"));
+
+        let mut llama = request("llama3.1", fenced());
+        formatter.on_request(&mut llama).unwrap();
+        let plain = result_text(&llama);
+        assert!(plain.starts_with("line 1\nline 2\n\n"), "{plain}");
+        assert!(!plain.contains("<tool_response>"));
+
+        let mut qwen = request("qwen3", fenced());
+        formatter.on_request(&mut qwen).unwrap();
+        let envelope: serde_json::Value = serde_json::from_str(&result_text(&qwen)).unwrap();
+        assert_eq!(envelope["tool_response"], "line 1\nline 2");
+        assert!(envelope["note"].as_str().unwrap().contains("reference data"));
+
+        // An exact name beats a pattern.
+        let mut coder = request("qwen2.5-coder", fenced());
+        formatter.on_request(&mut coder).unwrap();
+        assert!(result_text(&coder).starts_with("line 1\nline 2\n\n"));
+    }
+
+    #[test]
+    fn images_are_kept_and_unfenced_text_is_left_alone() {
+        let formatter = ResultFormatter::new(ToolResultFormats {
+            default: ResultFormat::Plain,
+            models: HashMap::new(),
+        });
+        let image = ImageSource::base64("image/png", "iVBORw0KGgo=");
+        let mut with_image = request(
+            "llama3.1",
+            ToolResultContent::with_images(fence_tool_result("shot"), vec![image.clone()]),
+        );
+        formatter.on_request(&mut with_image).unwrap();
+        let ContentBlock::ToolResult { content, .. } = &with_image.messages[0].content[0] else {
+            unreachable!()
+        };
+        assert!(content.text().starts_with("shot\n\n"));
+        assert_eq!(content.images().collect::<Vec<_>>(), [&image]);
+
+        let placeholder = "[Result pruned]".to_string();
+        let mut pruned = request("llama3.1", ToolResultContent::Text(placeholder.clone()));
+        formatter.on_request(&mut pruned).unwrap();
+        assert_eq!(result_text(&pruned), placeholder);
+    }
+}
@@ -4,6 +4,8 @@ use tokio::sync::RwLock;
 
 use super::factory::ProviderFactory;
 use super::store::{CreateProviderParams, ProviderStore, ProviderUpdate};
+use nexus_anthropic::KeyStats;
+use nexus_provider::cache::{CacheStats, ResponseCache};
 use nexus_provider::provider_config::Provider;
 use nexus_provider::InferenceProvider;
 use crate::event_bus::EventBus;
@@ -19,10 +21,10 @@ pub struct ProviderService {
 }
 
 impl ProviderService {
-    pub fn new(store: ProviderStore, event_bus: EventBus) -> Self {
+    pub fn new(store: ProviderStore, event_bus: EventBus, response_cache: Option<Arc<ResponseCache>>) -> Self {
         Self {
             store: RwLock::new(store),
-            factory: ProviderFactory::new(),
+            factory: ProviderFactory::new(response_cache),
             event_bus,
         }
     }
@@ -51,7 +53,6 @@ impl ProviderService {
     }
 
     /// Look up a provider by ID and return a cached inference client.
-    #[allow(dead_code)] // part of service API
     pub async fn get_client_by_id(&self, id: &str) -> Result<Option<Arc<dyn InferenceProvider>>> {
         let provider = {
             let store = self.store.read().await;
@@ -64,6 +65,25 @@ impl ProviderService {
         Ok(Some(client))
     }
 
+    /// Response cache counters; `None` when the cache is disabled.
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.factory.response_cache().map(ResponseCache::stats)
+    }
+
+    /// Requests and throttles per API key; empty for a provider with one
+    /// key. `None` if the provider doesn't exist.
+    pub async fn key_stats(&self, id: &str) -> Result<Option<Vec<KeyStats>>> {
+        if self.get_client_by_id(id).await?.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(self.factory.key_stats(id).unwrap_or_default()))
+    }
+
+    /// Empty the response cache. Returns `false` when it is disabled.
+    pub fn clear_cache(&self) -> bool {
+        self.factory.response_cache().map(ResponseCache::clear).is_some()
+    }
+
     // -- Writes ---------------------------------------------------------------
 
     pub async fn create(&self, params: CreateProviderParams) -> Result<Provider> {
@@ -40,8 +40,14 @@ impl ProviderStore {
             provider_type: params.provider_type,
             endpoint: params.endpoint,
             api_key: params.api_key,
+            api_keys: Vec::new(),
+            key_rotation: None,
+            beta_headers: Vec::new(),
+            api_version: None,
             aws_region: params.aws_region,
             aws_profile: params.aws_profile,
+            throttle: None,
+            tool_results: None,
             created_at: now,
             updated_at: now,
         };
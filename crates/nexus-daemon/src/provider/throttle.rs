@@ -0,0 +1,285 @@
+//! Request and token budgets shared by everything that uses a provider.
+//!
+//! A [`Throttle`] is a pair of token buckets, requests per minute and tokens
+//! per minute, behind a cloneable handle. The factory keeps one per provider
+//! and wraps the provider in a [`ThrottledProvider`], so concurrent turns and
+//! sub-agents wait for a shared quota instead of each hitting the provider
+//! and retrying on 429s.
+//!
+//! A request reserves one request and an estimate of its input tokens before
+//! it is sent. When its stream ends, the reservation is settled against the
+//! usage the provider reported. A rate-limit error carrying `retry-after`
+//! pauses every request through the throttle for that long.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::StreamExt;
+use tokio::time::Instant;
+
+use nexus_provider::error::{ProviderError, ProviderErrorKind};
+use nexus_provider::provider_config::ThrottleConfig;
+use nexus_provider::types::StreamEvent;
+use nexus_provider::{EventStream, InferenceProvider, InferenceRequest};
+
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    level: f64,
+    per_sec: f64,
+}
+
+impl Bucket {
+    fn per_minute(limit: u32) -> Self {
+        let capacity = f64::from(limit.max(1));
+        Self { capacity, level: capacity, per_sec: capacity / 60.0 }
+    }
+
+    fn refill(&mut self, elapsed: Duration) {
+        self.level = (self.level + elapsed.as_secs_f64() * self.per_sec).min(self.capacity);
+    }
+
+    /// Time until `amount` is available. Amounts above capacity only need a
+    /// full bucket, so an oversized request still runs eventually.
+    fn wait_for(&self, amount: f64) -> Duration {
+        let short = amount.min(self.capacity) - self.level;
+        if short <= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(short / self.per_sec)
+        }
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    config: ThrottleConfig,
+    requests: Option<Bucket>,
+    tokens: Option<Bucket>,
+    paused_until: Option<Instant>,
+    refilled_at: Instant,
+}
+
+impl State {
+    fn new(config: ThrottleConfig, now: Instant) -> Self {
+        Self {
+            config,
+            requests: config.requests_per_minute.map(Bucket::per_minute),
+            tokens: config.tokens_per_minute.map(Bucket::per_minute),
+            paused_until: None,
+            refilled_at: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.refilled_at);
+        self.refilled_at = now;
+        for bucket in self.requests.iter_mut().chain(self.tokens.iter_mut()) {
+            bucket.refill(elapsed);
+        }
+    }
+
+    /// Take one request and `tokens` if the budget allows, otherwise return
+    /// how long to wait before trying again.
+    fn try_reserve(&mut self, now: Instant, tokens: u32) -> Result<(), Duration> {
+        self.refill(now);
+        let paused = self
+            .paused_until
+            .map(|until| until.saturating_duration_since(now))
+            .unwrap_or_default();
+        let wait = [
+            paused,
+            self.requests.as_ref().map(|b| b.wait_for(1.0)).unwrap_or_default(),
+            self.tokens.as_ref().map(|b| b.wait_for(f64::from(tokens))).unwrap_or_default(),
+        ]
+        .into_iter()
+        .max()
+        .unwrap_or_default();
+        if !wait.is_zero() {
+            return Err(wait);
+        }
+        if let Some(ref mut bucket) = self.requests {
+            bucket.level -= 1.0;
+        }
+        if let Some(ref mut bucket) = self.tokens {
+            bucket.level -= f64::from(tokens);
+        }
+        Ok(())
+    }
+}
+
+/// Cloneable handle to one shared quota.
+#[derive(Clone)]
+pub struct Throttle {
+    state: Arc<Mutex<State>>,
+}
+
+impl Throttle {
+    pub fn new(config: ThrottleConfig) -> Self {
+        Self { state: Arc::new(Mutex::new(State::new(config, Instant::now()))) }
+    }
+
+    /// Apply new limits. Unchanged limits keep their current budget.
+    pub fn reconfigure(&self, config: ThrottleConfig) {
+        let mut state = self.state.lock().unwrap();
+        if state.config != config {
+            let paused_until = state.paused_until;
+            *state = State::new(config, Instant::now());
+            state.paused_until = paused_until;
+        }
+    }
+
+    /// Wait until one request carrying `tokens` fits the budget, and take it.
+    pub async fn acquire(&self, tokens: u32) {
+        loop {
+            let wait = match self.state.lock().unwrap().try_reserve(Instant::now(), tokens) {
+                Ok(()) => return,
+                Err(wait) => wait,
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Correct a reservation of `reserved` tokens to the `used` count.
+    pub fn settle(&self, reserved: u32, used: u32) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(ref mut bucket) = state.tokens {
+            bucket.level = (bucket.level + f64::from(reserved) - f64::from(used)).min(bucket.capacity);
+        }
+    }
+
+    /// Hold every request for `duration`, e.g. after a 429 with `retry-after`.
+    pub fn pause(&self, duration: Duration) {
+        let mut state = self.state.lock().unwrap();
+        let until = Instant::now() + duration;
+        state.paused_until = Some(state.paused_until.map_or(until, |current| current.max(until)));
+    }
+}
+
+pub struct ThrottledProvider {
+    inner: Arc<dyn InferenceProvider>,
+    throttle: Throttle,
+}
+
+impl ThrottledProvider {
+    pub fn new(inner: Arc<dyn InferenceProvider>, throttle: Throttle) -> Self {
+        Self { inner, throttle }
+    }
+}
+
+#[async_trait]
+impl InferenceProvider for ThrottledProvider {
+    async fn create_message_stream(&self, request: InferenceRequest) -> Result<EventStream> {
+        let reserved = nexus_compaction::estimate_tokens(
+            &request.messages,
+            request.system.as_deref(),
+            &request.tools,
+        );
+        self.throttle.acquire(reserved).await;
+
+        let stream = match self.inner.create_message_stream(request).await {
+            Ok(stream) => stream,
+            Err(e) => {
+                if let Some(retry_after_ms) = e
+                    .downcast_ref::<ProviderError>()
+                    .filter(|pe| pe.kind == ProviderErrorKind::RateLimit)
+                    .and_then(|pe| pe.retry_after_ms)
+                {
+                    self.throttle.pause(Duration::from_millis(retry_after_ms));
+                }
+                self.throttle.settle(reserved, 0);
+                return Err(e);
+            }
+        };
+
+        let mut settlement = Settlement {
+            throttle: self.throttle.clone(),
+            reserved,
+            usage: None,
+        };
+        Ok(Box::pin(stream.inspect(move |event| {
+            if let Ok(event) = event {
+                settlement.observe(event);
+            }
+        })))
+    }
+}
+
+/// Settles a request's reservation when its stream is dropped. Without
+/// reported usage the reservation stands.
+struct Settlement {
+    throttle: Throttle,
+    reserved: u32,
+    /// Input and output tokens reported so far.
+    usage: Option<(u32, u32)>,
+}
+
+impl Settlement {
+    fn observe(&mut self, event: &StreamEvent) {
+        let usage = match event {
+            StreamEvent::MessageStart { usage: Some(usage), .. }
+            | StreamEvent::MessageDelta { usage: Some(usage), .. } => usage,
+            _ => return,
+        };
+        let (input, output) = self.usage.unwrap_or_default();
+        let reported = usage.input_tokens + usage.cache_creation_input_tokens;
+        self.usage = Some((input.max(reported), output.max(usage.output_tokens)));
+    }
+}
+
+impl Drop for Settlement {
+    fn drop(&mut self) {
+        if let Some((input, output)) = self.usage {
+            self.throttle.settle(self.reserved, input + output);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(requests: Option<u32>, tokens: Option<u32>) -> ThrottleConfig {
+        ThrottleConfig { requests_per_minute: requests, tokens_per_minute: tokens }
+    }
+
+    #[test]
+    fn requests_wait_for_the_bucket_to_refill() {
+        let start = Instant::now();
+        let mut state = State::new(config(Some(60), None), start);
+        for _ in 0..60 {
+            state.try_reserve(start, 0).unwrap();
+        }
+        // 60/min refills one request per second.
+        assert_eq!(state.try_reserve(start, 0), Err(Duration::from_secs(1)));
+        assert_eq!(state.try_reserve(start + Duration::from_secs(1), 0), Ok(()));
+    }
+
+    #[test]
+    fn tokens_are_reserved_and_oversized_requests_need_a_full_bucket() {
+        let start = Instant::now();
+        let mut state = State::new(config(None, Some(6000)), start);
+        state.try_reserve(start, 3000).unwrap();
+        // 6000/min is 100 tokens a second; 1000 more are needed.
+        assert_eq!(state.try_reserve(start, 4000), Err(Duration::from_secs(10)));
+        assert_eq!(state.try_reserve(start, 100_000), Err(Duration::from_secs(30)));
+        assert_eq!(state.try_reserve(start + Duration::from_secs(30), 100_000), Ok(()));
+    }
+
+    #[tokio::test]
+    async fn settling_and_pausing_are_shared_by_clones() {
+        let throttle = Throttle::new(config(None, Some(6000)));
+        let other = throttle.clone();
+        throttle.acquire(6000).await;
+        // The request used far less than it reserved; the rest comes back.
+        other.settle(6000, 1000);
+        let now = Instant::now();
+        assert_eq!(throttle.state.lock().unwrap().try_reserve(now, 5000), Ok(()));
+
+        other.pause(Duration::from_secs(5));
+        let wait = throttle.state.lock().unwrap().try_reserve(Instant::now(), 0).unwrap_err();
+        assert!(wait > Duration::from_secs(4), "{wait:?}");
+    }
+}
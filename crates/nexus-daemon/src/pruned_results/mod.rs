@@ -0,0 +1,269 @@
+//! Side-store for tool results removed by context pruning.
+//!
+//! Pruning replaces old tool results with `[tool: N chars, pruned, id=…]`
+//! stubs. The original content is kept here, keyed by conversation and
+//! tool_use_id, so the model can restore it with `fetch_pruned_result`
+//! when it actually needs the old data back.
+//!
+//! The store is in-memory only, bounded by a byte budget across all
+//! conversations; the least recently used results are evicted first.
+//! Turn-start pruning rebuilds API messages from the persisted conversation
+//! (which keeps full results), so entries are repopulated after a daemon
+//! restart or eviction the next time pruning runs.
+//!
+//! Results are stored unfenced; the fetch result is fenced like any other
+//! tool output when it goes back to the model.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use nexus_compaction::PrunedToolResult;
+use nexus_provider::types::Tool;
+
+use crate::system_prompt::unfence_tool_result;
+
+const FETCH_TOOL: &str = "fetch_pruned_result";
+
+/// Total content kept across all conversations before eviction kicks in.
+const DEFAULT_BUDGET_BYTES: usize = 64 * 1024 * 1024;
+
+/// Pruned tool result content, keyed by conversation → tool_use_id.
+pub struct PrunedResultStore {
+    budget_bytes: usize,
+    inner: Mutex<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    entries: HashMap<String, HashMap<String, Entry>>,
+    /// Last-use tick → (conversation, tool_use_id), oldest first.
+    lru: BTreeMap<u64, (String, String)>,
+    tick: u64,
+    bytes: usize,
+}
+
+struct Entry {
+    result: PrunedToolResult,
+    last_used: u64,
+}
+
+impl Default for PrunedResultStore {
+    fn default() -> Self {
+        Self::with_budget(DEFAULT_BUDGET_BYTES)
+    }
+}
+
+impl PrunedResultStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_budget(budget_bytes: usize) -> Self {
+        Self { budget_bytes, inner: Mutex::default() }
+    }
+
+    /// Record pruned results for a conversation. Existing entries are kept.
+    /// Evicts the least recently used results to stay within budget; a
+    /// single result larger than the whole budget is not kept.
+    pub fn insert(&self, conversation_id: &str, pruned: Vec<PrunedToolResult>) {
+        if pruned.is_empty() {
+            return;
+        }
+        let mut inner = self.inner.lock().unwrap();
+        for mut result in pruned {
+            if let Some(output) = unfence_tool_result(&result.content) {
+                result.content = output.to_string();
+            }
+            let size = result.content.len();
+            if size > self.budget_bytes
+                || inner
+                    .entries
+                    .get(conversation_id)
+                    .is_some_and(|e| e.contains_key(&result.tool_use_id))
+            {
+                continue;
+            }
+            while inner.bytes + size > self.budget_bytes && !inner.lru.is_empty() {
+                inner.evict_oldest();
+            }
+
+            inner.tick += 1;
+            let tick = inner.tick;
+            inner.bytes += size;
+            inner.lru.insert(tick, (conversation_id.to_string(), result.tool_use_id.clone()));
+            inner
+                .entries
+                .entry(conversation_id.to_string())
+                .or_default()
+                .insert(result.tool_use_id.clone(), Entry { result, last_used: tick });
+        }
+    }
+
+    pub fn get(&self, conversation_id: &str, tool_use_id: &str) -> Option<PrunedToolResult> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.tick += 1;
+        let tick = inner.tick;
+        let entry = inner.entries.get_mut(conversation_id)?.get_mut(tool_use_id)?;
+        let previous = std::mem::replace(&mut entry.last_used, tick);
+        let result = entry.result.clone();
+        inner.lru.remove(&previous);
+        inner.lru.insert(tick, (conversation_id.to_string(), tool_use_id.to_string()));
+        Some(result)
+    }
+
+    /// Drop all pruned results for a conversation (on delete).
+    pub fn remove_conversation(&self, conversation_id: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(entries) = inner.entries.remove(conversation_id) {
+            for entry in entries.into_values() {
+                inner.bytes -= entry.result.content.len();
+                inner.lru.remove(&entry.last_used);
+            }
+        }
+    }
+}
+
+impl Inner {
+    fn evict_oldest(&mut self) {
+        let Some((_, (conversation_id, tool_use_id))) = self.lru.pop_first() else {
+            return;
+        };
+        if let Some(entries) = self.entries.get_mut(&conversation_id) {
+            if let Some(entry) = entries.remove(&tool_use_id) {
+                self.bytes -= entry.result.content.len();
+            }
+            if entries.is_empty() {
+                self.entries.remove(&conversation_id);
+            }
+        }
+    }
+}
+
+pub fn is_fetch_pruned(name: &str) -> bool {
+    name == FETCH_TOOL
+}
+
+pub fn tool_definition() -> Tool {
+    Tool {
+        name: FETCH_TOOL.to_string(),
+        description: "Restores the full content of an earlier tool result that was pruned \
+            from context to save space. Pruned results appear as stubs like \
+            `[read_file: 5000 chars, pruned, id=toolu_123]`. Only call this when you \
+            actually need the old data again — re-running the original tool may be cheaper \
+            if the data could have changed."
+            .to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "tool_use_id": {
+                    "type": "string",
+                    "description": "The id shown in the pruned stub."
+                }
+            },
+            "required": ["tool_use_id"]
+        }),
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct FetchArgs {
+    tool_use_id: String,
+}
+
+/// Execute a fetch_pruned_result call. Returns (content, is_error).
+pub fn execute(args_json: &str, conversation_id: &str, store: &PrunedResultStore) -> (String, bool) {
+    let args: FetchArgs = match serde_json::from_str(args_json) {
+        Ok(a) => a,
+        Err(e) => return (format!("Invalid arguments: {e}"), true),
+    };
+
+    match store.get(conversation_id, &args.tool_use_id) {
+        Some(result) => (result.content, false),
+        None => (
+            format!(
+                "No pruned result found for tool_use_id '{}'. It may never have been pruned, \
+                 or it was dropped (daemon restart or memory limit) before it was pruned again.",
+                args.tool_use_id
+            ),
+            true,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pruned(id: &str, content: &str) -> PrunedToolResult {
+        PrunedToolResult {
+            tool_use_id: id.to_string(),
+            tool_name: "read_file".to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn fetch_returns_stored_content() {
+        let store = PrunedResultStore::new();
+        store.insert("c1", vec![pruned("t1", "full output")]);
+
+        let (content, is_error) = execute(r#"{"tool_use_id":"t1"}"#, "c1", &store);
+        assert!(!is_error);
+        assert_eq!(content, "full output");
+    }
+
+    #[test]
+    fn fetch_is_scoped_to_conversation() {
+        let store = PrunedResultStore::new();
+        store.insert("c1", vec![pruned("t1", "full output")]);
+
+        let (_, is_error) = execute(r#"{"tool_use_id":"t1"}"#, "c2", &store);
+        assert!(is_error);
+    }
+
+    #[test]
+    fn insert_keeps_first_content() {
+        let store = PrunedResultStore::new();
+        store.insert("c1", vec![pruned("t1", "original")]);
+        store.insert("c1", vec![pruned("t1", "[read_file: 8 chars]")]);
+
+        assert_eq!(store.get("c1", "t1").unwrap().content, "original");
+    }
+
+    #[test]
+    fn remove_conversation_drops_entries() {
+        let store = PrunedResultStore::new();
+        store.insert("c1", vec![pruned("t1", "full output")]);
+        store.remove_conversation("c1");
+
+        assert!(store.get("c1", "t1").is_none());
+    }
+
+    #[test]
+    fn stores_results_unfenced() {
+        let store = PrunedResultStore::new();
+        let fenced = crate::system_prompt::fence_tool_result("full output");
+        store.insert("c1", vec![pruned("t1", &fenced)]);
+
+        let (content, _) = execute(r#"{"tool_use_id":"t1"}"#, "c1", &store);
+        assert_eq!(content, "full output");
+    }
+
+    #[test]
+    fn evicts_least_recently_used_over_budget() {
+        let store = PrunedResultStore::with_budget(10);
+        store.insert("c1", vec![pruned("t1", "aaaa"), pruned("t2", "bbbb")]);
+        // Touch t1 so t2 is the oldest
+        assert!(store.get("c1", "t1").is_some());
+        store.insert("c2", vec![pruned("t3", "cccc")]);
+
+        assert!(store.get("c1", "t2").is_none());
+        assert!(store.get("c1", "t1").is_some());
+        assert!(store.get("c2", "t3").is_some());
+
+        // Larger than the whole budget: not kept, nothing evicted for it
+        store.insert("c1", vec![pruned("t4", "x".repeat(11).as_str())]);
+        assert!(store.get("c1", "t4").is_none());
+        assert!(store.get("c1", "t1").is_some());
+    }
+}
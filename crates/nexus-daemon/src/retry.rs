@@ -4,6 +4,8 @@ const MAX_ATTEMPTS: u32 = 3;
 const INITIAL_DELAY_MS: u64 = 1000;
 const MAX_DELAY_MS: u64 = 30_000;
 const BACKOFF_FACTOR: f64 = 2.0;
+/// Upper bound on a server-requested `retry-after` wait.
+const MAX_RETRY_AFTER_MS: u64 = 60_000;
 
 /// Maximum number of retries for transient errors.
 pub const MAX_RETRIES: u32 = MAX_ATTEMPTS;
@@ -17,6 +19,15 @@ pub fn backoff_delay(attempt: u32) -> u64 {
     (delay as i64 + jitter).max(100) as u64
 }
 
+/// Delay before retry `attempt` (1-indexed): the provider's `retry-after`
+/// when it sent one (capped), otherwise exponential backoff.
+pub fn retry_delay(attempt: u32, retry_after_ms: Option<u64>) -> u64 {
+    match retry_after_ms {
+        Some(ms) => ms.min(MAX_RETRY_AFTER_MS),
+        None => backoff_delay(attempt),
+    }
+}
+
 /// Simple deterministic-ish jitter using the current time's nanoseconds.
 /// Not cryptographic, just enough to spread out retry storms.
 fn rand_jitter() -> f64 {
@@ -27,3 +38,16 @@ fn rand_jitter() -> f64 {
         .subsec_nanos();
     (nanos % 1000) as f64 / 1000.0
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_after_overrides_backoff_up_to_cap() {
+        assert_eq!(retry_delay(1, Some(5_000)), 5_000);
+        assert_eq!(retry_delay(1, Some(600_000)), MAX_RETRY_AFTER_MS);
+        let backoff = retry_delay(2, None);
+        assert!((1_500..=2_500).contains(&backoff), "{backoff}");
+    }
+}
@@ -0,0 +1,592 @@
+//! Agent-to-Agent (A2A) protocol endpoint.
+//!
+//! Exposes the active agent as a remote A2A peer:
+//!
+//! - `GET /.well-known/agent.json` — the Agent Card.
+//! - `POST /a2a` — JSON-RPC 2.0 with `message/send`, `message/stream`,
+//!   `tasks/get` and `tasks/cancel`.
+//!
+//! Every incoming message starts a task backed by one agent turn. The A2A
+//! `contextId` is the conversation id: a message without one opens a new
+//! conversation, a message with one continues it. The task moves through
+//! `submitted` → `working` → `completed` / `failed` / `canceled` as the run's
+//! events arrive, and the reply text becomes the task's `response` artifact.
+//! `message/send` waits for the task to finish unless
+//! `configuration.blocking` is `false`. Tasks are kept in memory only, and
+//! dropped [`TASK_TTL_SECS`] after they end.
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::{header, HeaderMap};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::{broadcast, mpsc, Mutex};
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
+use uuid::Uuid;
+
+use crate::agent::events::{AgUiEvent, EventEnvelope};
+use crate::server::AppState;
+use super::chat::{begin_turn, ChatRequest};
+use super::run_watch::{self, stored_reply, RunEvent, RunWatch};
+
+const PROTOCOL_VERSION: &str = "0.3.0";
+const ARTIFACT_ID: &str = "response";
+
+/// How long an ended task stays queryable.
+pub const TASK_TTL_SECS: i64 = 3600;
+
+// JSON-RPC and A2A error codes.
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+const TASK_NOT_FOUND: i64 = -32001;
+const TASK_NOT_CANCELABLE: i64 = -32002;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TaskState {
+    Submitted,
+    Working,
+    Completed,
+    Canceled,
+    Failed,
+}
+
+impl TaskState {
+    fn is_terminal(self) -> bool {
+        matches!(self, Self::Completed | Self::Canceled | Self::Failed)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskStatus {
+    pub state: TaskState,
+    /// Agent message explaining the state (the reply or the error).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<Value>,
+    pub timestamp: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Task {
+    pub id: String,
+    pub context_id: String,
+    pub status: TaskStatus,
+    pub artifacts: Vec<Value>,
+    pub kind: &'static str,
+}
+
+struct Entry {
+    task: Task,
+    /// When the task reached a terminal state.
+    ended_at: Option<DateTime<Utc>>,
+}
+
+/// In-memory registry of A2A tasks.
+#[derive(Default)]
+pub struct A2aTasks {
+    tasks: Mutex<HashMap<String, Entry>>,
+}
+
+impl A2aTasks {
+    pub async fn get(&self, id: &str) -> Option<Task> {
+        self.tasks.lock().await.get(id).map(|e| e.task.clone())
+    }
+
+    /// Register a task, dropping tasks that ended over [`TASK_TTL_SECS`] ago.
+    async fn insert(&self, task: Task) {
+        let mut tasks = self.tasks.lock().await;
+        let now = Utc::now();
+        tasks.retain(|_, e| e.ended_at.is_none_or(|at| (now - at).num_seconds() < TASK_TTL_SECS));
+        let ended_at = task.status.state.is_terminal().then_some(now);
+        tasks.insert(task.id.clone(), Entry { task, ended_at });
+    }
+
+    /// Move a task to `state`. Terminal states are final: once a task has
+    /// completed, failed or been canceled, later transitions are ignored.
+    /// Returns the updated task, or `None` if nothing changed.
+    async fn transition(
+        &self,
+        id: &str,
+        state: TaskState,
+        message: Option<Value>,
+        artifact_text: Option<&str>,
+    ) -> Option<Task> {
+        let mut tasks = self.tasks.lock().await;
+        let entry = tasks.get_mut(id)?;
+        if entry.task.status.state.is_terminal() {
+            return None;
+        }
+        if state.is_terminal() {
+            entry.ended_at = Some(Utc::now());
+        }
+        let task = &mut entry.task;
+        task.status = TaskStatus {
+            state,
+            message,
+            timestamp: Utc::now().to_rfc3339(),
+        };
+        if let Some(text) = artifact_text {
+            task.artifacts = vec![artifact(text)];
+        }
+        Some(task.clone())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RpcRequest {
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MessageSendParams {
+    message: IncomingMessage,
+    #[serde(default)]
+    configuration: Option<SendConfiguration>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IncomingMessage {
+    #[serde(default)]
+    parts: Vec<Value>,
+    #[serde(default)]
+    context_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SendConfiguration {
+    #[serde(default)]
+    blocking: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TaskIdParams {
+    id: String,
+}
+
+pub async fn agent_card(State(state): State<Arc<AppState>>, headers: HeaderMap) -> Json<Value> {
+    let host = headers
+        .get(header::HOST)
+        .and_then(|h| h.to_str().ok())
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{}:{}", state.config.server.host, state.config.server.port));
+    let name = state
+        .agents
+        .active_agent()
+        .await
+        .map(|a| a.name)
+        .unwrap_or_else(|| "Nexus".to_string());
+
+    Json(json!({
+        "protocolVersion": PROTOCOL_VERSION,
+        "name": name,
+        "description": "Nexus agent with tool use, running on a local Nexus daemon.",
+        "url": format!("http://{host}/a2a"),
+        "preferredTransport": "JSONRPC",
+        "version": env!("CARGO_PKG_VERSION"),
+        "capabilities": { "streaming": true, "pushNotifications": false },
+        "defaultInputModes": ["text/plain"],
+        "defaultOutputModes": ["text/plain"],
+        "skills": [{
+            "id": "chat",
+            "name": "Chat",
+            "description": "Answer a request using the agent's tools; follow-ups share the context.",
+            "tags": ["general"],
+        }],
+    }))
+}
+
+pub async fn rpc(State(state): State<Arc<AppState>>, Json(req): Json<RpcRequest>) -> Response {
+    let id = req.id.clone();
+    let result = match req.method.as_str() {
+        "message/send" => message_send(&state, req.params).await,
+        "message/stream" => return message_stream(&state, id, req.params).await,
+        "tasks/get" => tasks_get(&state, req.params).await,
+        "tasks/cancel" => tasks_cancel(&state, req.params).await,
+        other => Err(RpcError::new(METHOD_NOT_FOUND, format!("method not found: {other}"))),
+    };
+    Json(match result {
+        Ok(result) => rpc_result(&id, result),
+        Err(e) => rpc_error(&id, e),
+    })
+    .into_response()
+}
+
+async fn message_send(state: &Arc<AppState>, params: Value) -> Result<Value, RpcError> {
+    let params: MessageSendParams = parse_params(params)?;
+    let blocking = params
+        .configuration
+        .and_then(|c| c.blocking)
+        .unwrap_or(true);
+
+    let (tx, mut updates) = mpsc::channel(64);
+    let task = start_task(state, params.message, blocking.then_some(tx)).await?;
+    if !blocking {
+        return Ok(to_value(&task));
+    }
+    while updates.recv().await.is_some() {}
+    let task = state.a2a.get(&task.id).await.unwrap_or(task);
+    Ok(to_value(&task))
+}
+
+/// Stream the task as SSE: the task itself, then `status-update` and
+/// `artifact-update` events, each wrapped in a JSON-RPC response. The stream
+/// ends after the final status update.
+async fn message_stream(state: &Arc<AppState>, id: Value, params: Value) -> Response {
+    let message = match parse_params::<MessageSendParams>(params) {
+        Ok(params) => params.message,
+        Err(e) => return Json(rpc_error(&id, e)).into_response(),
+    };
+    let (tx, updates) = mpsc::channel(64);
+    let task = match start_task(state, message, Some(tx)).await {
+        Ok(task) => task,
+        Err(e) => return Json(rpc_error(&id, e)).into_response(),
+    };
+
+    let first = rpc_result(&id, to_value(&task));
+    let rest = ReceiverStream::new(updates).map(move |update| rpc_result(&id, update));
+    let stream = tokio_stream::once(first)
+        .chain(rest)
+        .map(|frame| Ok::<_, Infallible>(Event::default().data(frame.to_string())));
+    Sse::new(stream).keep_alive(KeepAlive::default()).into_response()
+}
+
+async fn tasks_get(state: &AppState, params: Value) -> Result<Value, RpcError> {
+    let params: TaskIdParams = parse_params(params)?;
+    state
+        .a2a
+        .get(&params.id)
+        .await
+        .map(|task| to_value(&task))
+        .ok_or_else(|| RpcError::new(TASK_NOT_FOUND, format!("task {} not found", params.id)))
+}
+
+async fn tasks_cancel(state: &AppState, params: Value) -> Result<Value, RpcError> {
+    let params: TaskIdParams = parse_params(params)?;
+    let task = state
+        .a2a
+        .get(&params.id)
+        .await
+        .ok_or_else(|| RpcError::new(TASK_NOT_FOUND, format!("task {} not found", params.id)))?;
+    if task.status.state.is_terminal() {
+        return Err(RpcError::new(
+            TASK_NOT_CANCELABLE,
+            format!("task {} is already {:?}", task.id, task.status.state).to_lowercase(),
+        ));
+    }
+    state.turns.cancel_turn(&task.context_id).await;
+    let task = state
+        .a2a
+        .transition(&task.id, TaskState::Canceled, None, None)
+        .await
+        .unwrap_or(task);
+    Ok(to_value(&task))
+}
+
+/// Create a task for `message` and start its turn. Status and artifact
+/// updates are sent on `updates` (if given) until the task ends.
+async fn start_task(
+    state: &Arc<AppState>,
+    message: IncomingMessage,
+    updates: Option<mpsc::Sender<Value>>,
+) -> Result<Task, RpcError> {
+    let text = message
+        .parts
+        .iter()
+        .filter(|p| p["kind"] == "text")
+        .filter_map(|p| p["text"].as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    if text.trim().is_empty() {
+        return Err(RpcError::new(INVALID_PARAMS, "message has no text parts"));
+    }
+
+    let context_id = match message.context_id {
+        Some(id) => match state.threads.get(&id).await {
+            Ok(Some(_)) => id,
+            Ok(None) => {
+                return Err(RpcError::new(INVALID_PARAMS, format!("unknown contextId {id}")))
+            }
+            Err(e) if e.downcast_ref::<crate::conversation::InvalidId>().is_some() => {
+                return Err(RpcError::new(INVALID_PARAMS, format!("{e:#}")))
+            }
+            Err(e) => return Err(RpcError::new(INTERNAL_ERROR, e.to_string())),
+        },
+        None => state
+            .threads
+            .create(None, None, None)
+            .await
+            .map_err(|e| RpcError::new(INTERNAL_ERROR, e.to_string()))?
+            .id,
+    };
+
+    let task = Task {
+        id: Uuid::new_v4().to_string(),
+        context_id: context_id.clone(),
+        status: TaskStatus {
+            state: TaskState::Submitted,
+            message: None,
+            timestamp: Utc::now().to_rfc3339(),
+        },
+        artifacts: Vec::new(),
+        kind: "task",
+    };
+    state.a2a.insert(task.clone()).await;
+
+    let rx = run_watch::subscribe(state);
+    let req = ChatRequest {
+        conversation_id: context_id,
+        message: text,
+        user_message_id: None,
+        assistant_message_id: None,
+        thinking_budget: None,
+        best_of: None,
+    };
+    let user_message_id = match begin_turn(Arc::clone(state), req).await {
+        Ok(id) => id,
+        Err(status) => {
+            let reason = format!("turn could not start: {status}");
+            return Ok(state
+                .a2a
+                .transition(&task.id, TaskState::Failed, Some(agent_message(&task, &reason)), None)
+                .await
+                .unwrap_or(task));
+        }
+    };
+
+    tokio::spawn(track_task(Arc::clone(state), task.clone(), user_message_id, rx, updates));
+    Ok(task)
+}
+
+/// Follow the task's run on the event bus and mirror it into the task. A
+/// run whose end was missed is finished from the stored reply to
+/// `user_message_id`.
+async fn track_task(
+    state: Arc<AppState>,
+    task: Task,
+    user_message_id: String,
+    rx: broadcast::Receiver<EventEnvelope>,
+    updates: Option<mpsc::Sender<Value>>,
+) {
+    let send = |update: Value| {
+        let updates = updates.clone();
+        async move {
+            if let Some(tx) = updates {
+                let _ = tx.send(update).await;
+            }
+        }
+    };
+
+    let mut watch = RunWatch::new(rx, &task.context_id, None);
+    let mut text = String::new();
+    while let Some(event) = watch.next(&state).await {
+        let (state_, message, artifact_text) = match &event {
+            RunEvent::Event(AgUiEvent::RunStarted) => (TaskState::Working, None, None),
+            RunEvent::Event(AgUiEvent::TextMessageContent { delta, .. }) => {
+                send(artifact_update(&task, delta, !text.is_empty())).await;
+                text.push_str(delta);
+                continue;
+            }
+            RunEvent::Event(_) => continue,
+            RunEvent::Finished { complete: true } => (
+                TaskState::Completed,
+                Some(agent_message(&task, &text)),
+                Some(text.as_str()),
+            ),
+            RunEvent::Finished { complete: false } => {
+                finish_from_storage(&state, &task, &user_message_id, &send).await;
+                return;
+            }
+            RunEvent::Failed(message) => (TaskState::Failed, Some(agent_message(&task, message)), None),
+        };
+
+        let terminal = state_.is_terminal();
+        if let Some(updated) = state
+            .a2a
+            .transition(&task.id, state_, message, artifact_text)
+            .await
+        {
+            send(status_update(&updated, terminal)).await;
+        }
+        // Stop once the task has ended, including by tasks/cancel.
+        let ended = state
+            .a2a
+            .get(&task.id)
+            .await
+            .is_none_or(|t| t.status.state.is_terminal());
+        if terminal || ended {
+            return;
+        }
+    }
+}
+
+/// End a task whose run is over from the conversation as stored: completed
+/// with the reply, or failed if there is none.
+async fn finish_from_storage<F, Fut>(state: &AppState, task: &Task, user_message_id: &str, send: &F)
+where
+    F: Fn(Value) -> Fut,
+    Fut: std::future::Future<Output = ()>,
+{
+    let reply = stored_reply(state, &task.context_id, user_message_id)
+        .await
+        .ok()
+        .flatten();
+    let updated = match reply {
+        Some(text) => {
+            send(artifact_update(task, &text, false)).await;
+            state
+                .a2a
+                .transition(&task.id, TaskState::Completed, Some(agent_message(task, &text)), Some(&text))
+                .await
+        }
+        None => {
+            let message = agent_message(task, "run ended without a reply");
+            state.a2a.transition(&task.id, TaskState::Failed, Some(message), None).await
+        }
+    };
+    if let Some(updated) = updated {
+        send(status_update(&updated, true)).await;
+    }
+}
+
+fn status_update(task: &Task, last: bool) -> Value {
+    json!({
+        "kind": "status-update",
+        "taskId": task.id,
+        "contextId": task.context_id,
+        "status": task.status,
+        "final": last,
+    })
+}
+
+fn artifact_update(task: &Task, delta: &str, append: bool) -> Value {
+    json!({
+        "kind": "artifact-update",
+        "taskId": task.id,
+        "contextId": task.context_id,
+        "artifact": artifact(delta),
+        "append": append,
+    })
+}
+
+fn artifact(text: &str) -> Value {
+    json!({ "artifactId": ARTIFACT_ID, "parts": [{ "kind": "text", "text": text }] })
+}
+
+fn agent_message(task: &Task, text: &str) -> Value {
+    json!({
+        "kind": "message",
+        "role": "agent",
+        "messageId": Uuid::new_v4().to_string(),
+        "taskId": task.id,
+        "contextId": task.context_id,
+        "parts": [{ "kind": "text", "text": text }],
+    })
+}
+
+fn parse_params<T: serde::de::DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+fn to_value(task: &Task) -> Value {
+    serde_json::to_value(task).unwrap_or_default()
+}
+
+fn rpc_result(id: &Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+fn rpc_error(id: &Value, e: RpcError) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": e.code, "message": e.message } })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task() -> Task {
+        Task {
+            id: "t1".into(),
+            context_id: "c1".into(),
+            status: TaskStatus {
+                state: TaskState::Submitted,
+                message: None,
+                timestamp: Utc::now().to_rfc3339(),
+            },
+            artifacts: Vec::new(),
+            kind: "task",
+        }
+    }
+
+    #[tokio::test]
+    async fn terminal_states_are_final() {
+        let tasks = A2aTasks::default();
+        tasks.insert(task()).await;
+
+        let working = tasks.transition("t1", TaskState::Working, None, None).await.unwrap();
+        assert_eq!(working.status.state, TaskState::Working);
+
+        let done = tasks
+            .transition("t1", TaskState::Completed, None, Some("Hi"))
+            .await
+            .unwrap();
+        assert_eq!(done.artifacts[0]["parts"][0]["text"], "Hi");
+
+        assert!(tasks.transition("t1", TaskState::Failed, None, None).await.is_none());
+        assert_eq!(tasks.get("t1").await.unwrap().status.state, TaskState::Completed);
+        assert!(tasks.transition("missing", TaskState::Working, None, None).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn ended_tasks_expire() {
+        let tasks = A2aTasks::default();
+        tasks.insert(task()).await;
+        tasks.transition("t1", TaskState::Completed, None, None).await.unwrap();
+        tasks.insert(Task { id: "t2".into(), ..task() }).await;
+        tasks.tasks.lock().await.get_mut("t1").unwrap().ended_at =
+            Some(Utc::now() - chrono::Duration::seconds(TASK_TTL_SECS + 1));
+
+        tasks.insert(Task { id: "t3".into(), ..task() }).await;
+        assert!(tasks.get("t1").await.is_none());
+        assert!(tasks.get("t2").await.is_some(), "unfinished tasks are kept");
+    }
+
+    #[test]
+    fn tasks_serialize_in_a2a_shape() {
+        let value = to_value(&task());
+        assert_eq!(value["contextId"], "c1");
+        assert_eq!(value["status"]["state"], "submitted");
+        assert_eq!(value["kind"], "task");
+
+        let update = status_update(&task(), true);
+        assert_eq!(update["kind"], "status-update");
+        assert_eq!(update["final"], true);
+    }
+}
@@ -4,7 +4,8 @@ use axum::Json;
 use serde::Deserialize;
 use std::sync::Arc;
 
-use crate::agent_config::store::{AgentUpdate, CreateAgentParams};
+use crate::agent_config::profiles;
+use crate::agent_config::store::{AgentUpdate, CreateAgentParams, FileDefinedAgent};
 use crate::server::AppState;
 
 pub async fn list(
@@ -32,6 +33,7 @@ pub async fn create(
             system_prompt: body.system_prompt,
             temperature: body.temperature,
             max_tokens: body.max_tokens,
+            thinking_budget: body.thinking_budget,
             mcp_server_ids: body.mcp_server_ids,
         })
         .await
@@ -43,6 +45,33 @@ pub async fn create(
     ))
 }
 
+pub async fn list_profiles() -> Json<serde_json::Value> {
+    Json(serde_json::to_value(profiles::PROFILES).unwrap())
+}
+
+/// POST /api/agents/profiles/{id} — create an agent from a built-in profile.
+pub async fn create_from_profile(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(body): Json<FromProfileRequest>,
+) -> Result<(StatusCode, Json<serde_json::Value>), StatusCode> {
+    let profile = profiles::get(&id).ok_or(StatusCode::NOT_FOUND)?;
+    if !state.providers.exists(&body.provider_id).await {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let agent = state
+        .agents
+        .create(profile.params(body.name, body.provider_id, body.model))
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(serde_json::to_value(&agent).unwrap()),
+    ))
+}
+
 pub async fn get(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
@@ -84,7 +113,7 @@ pub async fn update(
         .agents
         .update(&id, updates)
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .map_err(|e| write_error_status(&e))?
     {
         Some(a) => Ok(Json(serde_json::to_value(&a).unwrap())),
         None => Err(StatusCode::NOT_FOUND),
@@ -98,7 +127,17 @@ pub async fn delete(
     match state.agents.delete(&id).await {
         Ok(true) => StatusCode::NO_CONTENT,
         Ok(false) => StatusCode::NOT_FOUND,
-        Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        Err(e) => write_error_status(&e),
+    }
+}
+
+/// Agents loaded from agent files are edited on disk, so the store rejects
+/// changes to them; that maps to 409 rather than 500.
+fn write_error_status(err: &anyhow::Error) -> StatusCode {
+    if err.downcast_ref::<FileDefinedAgent>().is_some() {
+        StatusCode::CONFLICT
+    } else {
+        StatusCode::INTERNAL_SERVER_ERROR
     }
 }
 
@@ -136,9 +175,19 @@ pub struct CreateAgentRequest {
     pub system_prompt: Option<String>,
     pub temperature: Option<f32>,
     pub max_tokens: Option<u32>,
+    #[serde(default)]
+    pub thinking_budget: Option<u32>,
     pub mcp_server_ids: Option<Vec<String>>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct FromProfileRequest {
+    pub provider_id: String,
+    pub model: String,
+    /// Defaults to the profile's name.
+    pub name: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct UpdateAgentRequest {
     pub name: Option<String>,
@@ -0,0 +1,358 @@
+//! Batch runs: one agent turn per input, for bulk jobs.
+//!
+//! `POST /api/batches` takes a list of inputs and runs each as the first
+//! message of a new conversation with the given (or active) agent, through
+//! the [`executor`](crate::executor) with a concurrency limit, an optional
+//! requests-per-minute limit shared by the batch's workers, and a failure
+//! policy. `GET /api/batches/{id}` reports progress and per-item results
+//! (the reply text, or the error); `POST /api/batches/{id}/cancel` stops it.
+//! Batches are kept in memory only, and dropped [`FINISHED_TTL_SECS`] after
+//! they finish; their conversations are stored as usual.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::Json;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use crate::executor::{Executor, ExecutorConfig, FailurePolicy, ItemOutcome, Progress, RateLimiter};
+use crate::server::AppState;
+use super::chat::{begin_turn, ChatRequest};
+use super::run_watch::{self, await_run_end, stored_reply};
+
+/// Upper bound on inputs per batch.
+pub const MAX_INPUTS: usize = 10_000;
+
+const DEFAULT_CONCURRENCY: usize = 4;
+
+/// How long a finished batch stays queryable.
+pub const FINISHED_TTL_SECS: i64 = 3600;
+
+/// A turn still running after this long is canceled and its item failed.
+const ITEM_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateBatchRequest {
+    pub inputs: Vec<String>,
+    /// Agent to run; defaults to the active agent.
+    pub agent_id: Option<String>,
+    pub concurrency: Option<usize>,
+    pub requests_per_minute: Option<u32>,
+    #[serde(default)]
+    pub policy: FailurePolicy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchStatus {
+    Running,
+    Completed,
+    /// Stopped early by the `fail_fast` policy.
+    Failed,
+    Canceled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchItem {
+    pub input: String,
+    pub status: ItemStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conversation_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Batch {
+    pub id: String,
+    pub status: BatchStatus,
+    pub progress: Progress,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+    pub items: Vec<BatchItem>,
+}
+
+struct Entry {
+    batch: Batch,
+    progress: Arc<Mutex<Progress>>,
+    cancel: CancellationToken,
+}
+
+/// In-memory registry of batches.
+#[derive(Default)]
+pub struct Batches {
+    batches: Mutex<HashMap<String, Entry>>,
+}
+
+impl Batches {
+    /// Register a batch, dropping batches that finished over
+    /// [`FINISHED_TTL_SECS`] ago.
+    fn insert(&self, entry: Entry) {
+        let mut batches = self.batches.lock().unwrap();
+        let now = Utc::now();
+        batches.retain(|_, e| {
+            e.batch
+                .finished_at
+                .is_none_or(|at| (now - at).num_seconds() < FINISHED_TTL_SECS)
+        });
+        batches.insert(entry.batch.id.clone(), entry);
+    }
+
+    pub fn get(&self, id: &str) -> Option<Batch> {
+        let batches = self.batches.lock().unwrap();
+        let entry = batches.get(id)?;
+        let mut batch = entry.batch.clone();
+        batch.progress = entry.progress.lock().unwrap().clone();
+        Some(batch)
+    }
+
+    fn update(&self, id: &str, f: impl FnOnce(&mut Batch)) {
+        if let Some(entry) = self.batches.lock().unwrap().get_mut(id) {
+            f(&mut entry.batch);
+        }
+    }
+
+    fn update_item(&self, id: &str, index: usize, f: impl FnOnce(&mut BatchItem)) {
+        self.update(id, |batch| {
+            if let Some(item) = batch.items.get_mut(index) {
+                f(item);
+            }
+        });
+    }
+}
+
+pub async fn create(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<CreateBatchRequest>,
+) -> Result<(StatusCode, Json<Batch>), StatusCode> {
+    if body.inputs.is_empty() || body.inputs.len() > MAX_INPUTS {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let agent_id = match body.agent_id {
+        Some(id) => {
+            state.agents.get(&id).await.ok_or(StatusCode::NOT_FOUND)?;
+            Some(id)
+        }
+        None => state.agents.active_agent().await.map(|a| a.id),
+    };
+
+    let id = Uuid::new_v4().to_string();
+    let executor = Executor::new(ExecutorConfig {
+        concurrency: body.concurrency.unwrap_or(DEFAULT_CONCURRENCY),
+        rate_limit: body
+            .requests_per_minute
+            .map(|rpm| Arc::new(RateLimiter::per_minute(rpm))),
+        policy: body.policy,
+    });
+    let cancel = CancellationToken::new();
+    let batch = Batch {
+        id: id.clone(),
+        status: BatchStatus::Running,
+        progress: Progress { total: body.inputs.len(), ..Default::default() },
+        created_at: Utc::now(),
+        finished_at: None,
+        items: body
+            .inputs
+            .iter()
+            .map(|input| BatchItem {
+                input: input.clone(),
+                status: ItemStatus::Pending,
+                conversation_id: None,
+                output: None,
+                error: None,
+            })
+            .collect(),
+    };
+    state.batches.insert(Entry {
+        batch: batch.clone(),
+        progress: executor.progress(),
+        cancel: cancel.clone(),
+    });
+
+    tracing::info!(batch = %id, inputs = body.inputs.len(), "Batch started");
+    tokio::spawn(run_batch(state, id, executor, body.inputs, agent_id, body.policy, cancel));
+    Ok((StatusCode::CREATED, Json(batch)))
+}
+
+pub async fn get(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<Batch>, StatusCode> {
+    state.batches.get(&id).map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+pub async fn cancel(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<Batch>, StatusCode> {
+    let token = state
+        .batches
+        .batches
+        .lock()
+        .unwrap()
+        .get(&id)
+        .map(|entry| entry.cancel.clone())
+        .ok_or(StatusCode::NOT_FOUND)?;
+    token.cancel();
+    state.batches.update(&id, |batch| {
+        if batch.status == BatchStatus::Running {
+            batch.status = BatchStatus::Canceled;
+        }
+    });
+    state.batches.get(&id).map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+async fn run_batch(
+    state: Arc<AppState>,
+    id: String,
+    executor: Executor,
+    inputs: Vec<String>,
+    agent_id: Option<String>,
+    policy: FailurePolicy,
+    cancel: CancellationToken,
+) {
+    let batches = &state.batches;
+    let on_item = |index: usize, outcome: Option<&ItemOutcome>| {
+        batches.update_item(&id, index, |item| match outcome {
+            None => item.status = ItemStatus::Running,
+            Some(ItemOutcome::Completed { output }) => {
+                item.status = ItemStatus::Completed;
+                item.output = Some(output.clone());
+            }
+            Some(ItemOutcome::Failed { error }) => {
+                item.status = ItemStatus::Failed;
+                item.error = Some(error.clone());
+            }
+            Some(ItemOutcome::Skipped) => item.status = ItemStatus::Skipped,
+        });
+    };
+    let job = |index: usize, input: String, cancel: CancellationToken| {
+        let (state, id, agent_id) = (&state, &id, agent_id.clone());
+        async move {
+            let conversation_id = state
+                .threads
+                .create(None, None, agent_id)
+                .await
+                .map_err(|e| e.to_string())?
+                .id;
+            state.batches.update_item(id, index, |item| {
+                item.conversation_id = Some(conversation_id.clone());
+            });
+            run_input(state, &conversation_id, input, cancel).await
+        }
+    };
+    executor.run(inputs, cancel.clone(), on_item, job).await;
+
+    let progress = executor.progress().lock().unwrap().clone();
+    tracing::info!(
+        batch = %id,
+        completed = progress.completed,
+        failed = progress.failed,
+        skipped = progress.skipped,
+        "Batch finished"
+    );
+    state.batches.update(&id, |batch| {
+        if batch.status == BatchStatus::Running {
+            batch.status = if policy == FailurePolicy::FailFast && progress.failed > 0 {
+                BatchStatus::Failed
+            } else {
+                BatchStatus::Completed
+            };
+        }
+        batch.finished_at = Some(Utc::now());
+    });
+}
+
+/// Start a turn for `input` and wait for its reply text.
+async fn run_input(
+    state: &Arc<AppState>,
+    conversation_id: &str,
+    input: String,
+    cancel: CancellationToken,
+) -> Result<String, String> {
+    let rx = run_watch::subscribe(state);
+    let req = ChatRequest {
+        conversation_id: conversation_id.to_string(),
+        message: input,
+        user_message_id: None,
+        assistant_message_id: None,
+        thinking_budget: None,
+        best_of: None,
+    };
+    let user_message_id = begin_turn(Arc::clone(state), req)
+        .await
+        .map_err(|status| format!("turn could not start: {status}"))?;
+
+    tokio::select! {
+        result = await_run_end(state, rx, conversation_id, None) => result?,
+        _ = cancel.cancelled() => {
+            state.turns.cancel_turn(conversation_id).await;
+            return Err("canceled".to_string());
+        }
+        _ = tokio::time::sleep(ITEM_TIMEOUT) => {
+            state.turns.cancel_turn(conversation_id).await;
+            return Err(format!("timed out after {}s", ITEM_TIMEOUT.as_secs()));
+        }
+    }
+
+    // The stored conversation has the whole reply even if events were dropped.
+    stored_reply(state, conversation_id, &user_message_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "run ended without a reply".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, finished_secs_ago: Option<i64>) -> Entry {
+        Entry {
+            batch: Batch {
+                id: id.into(),
+                status: BatchStatus::Completed,
+                progress: Progress::default(),
+                created_at: Utc::now(),
+                finished_at: finished_secs_ago.map(|s| Utc::now() - chrono::Duration::seconds(s)),
+                items: Vec::new(),
+            },
+            progress: Arc::default(),
+            cancel: CancellationToken::new(),
+        }
+    }
+
+    #[test]
+    fn finished_batches_expire() {
+        let batches = Batches::default();
+        batches.insert(entry("old", Some(FINISHED_TTL_SECS + 1)));
+        batches.insert(entry("recent", Some(1)));
+        batches.insert(entry("running", None));
+        batches.insert(entry("new", None));
+
+        assert!(batches.get("old").is_none());
+        assert!(batches.get("recent").is_some());
+        assert!(batches.get("running").is_some());
+    }
+}
@@ -6,6 +6,7 @@ use serde::Deserialize;
 use std::sync::Arc;
 use uuid::Uuid;
 
+use crate::agent::best_of::{self, BestOf};
 use crate::conversation::types::{ChatMessage, MessagePart, MessageRole, MessageSource};
 use crate::server::AppState;
 use super::turn::{spawn_agent_turn, TurnRequest};
@@ -21,6 +22,35 @@ pub struct ChatRequest {
     /// Client-generated Snowflake ID for the assistant response
     #[serde(rename = "assistantMessageId")]
     pub assistant_message_id: Option<String>,
+    /// Thinking budget for this turn only, overriding the agent's.
+    /// `0` disables thinking for the turn.
+    #[serde(rename = "thinkingBudget")]
+    pub thinking_budget: Option<u32>,
+    /// Sample the first round several times and keep the best answer.
+    #[serde(rename = "bestOf")]
+    pub best_of: Option<BestOfRequest>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BestOfRequest {
+    /// Number of candidates, 1–8.
+    pub n: u32,
+    /// What the judge should look for, on top of correctness and helpfulness.
+    pub criteria: Option<String>,
+}
+
+impl BestOfRequest {
+    fn resolve(self) -> Result<Option<BestOf>, StatusCode> {
+        match self.n {
+            0 => Err(StatusCode::BAD_REQUEST),
+            1 => Ok(None),
+            n if n > best_of::MAX_CANDIDATES => Err(StatusCode::BAD_REQUEST),
+            n => Ok(Some(BestOf {
+                n,
+                judge: Arc::new(best_of::LlmJudge { criteria: self.criteria }),
+            })),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -44,6 +74,26 @@ pub async fn start_turn(
     Json(body): Json<ChatRequest>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
     let conversation_id = body.conversation_id.clone();
+    let user_msg_id = begin_turn(state, body).await?;
+
+    Ok(Json(
+        serde_json::json!({
+            "ok": true,
+            "conversationId": conversation_id,
+            "messageId": user_msg_id,
+        }),
+    ))
+}
+
+/// Append the user message to the conversation and spawn a turn for it.
+/// Returns the user message id. Shared by `/api/chat` and the WebSocket
+/// endpoint.
+pub(crate) async fn begin_turn(
+    state: Arc<AppState>,
+    body: ChatRequest,
+) -> Result<String, StatusCode> {
+    let conversation_id = body.conversation_id.clone();
+    let best_of = body.best_of.map(BestOfRequest::resolve).transpose()?.flatten();
 
     let (cancel, run_id) = state.turns.register_turn(&conversation_id).await;
 
@@ -82,6 +132,8 @@ pub async fn start_turn(
             assistant_message_id: body.assistant_message_id,
             last_active_id: conv.active_path.last().cloned(),
             prior_cost: conv.usage.as_ref().map(|u| u.total_cost).unwrap_or(0.0),
+            thinking_budget: body.thinking_budget,
+            best_of,
         };
 
         state.threads.commit(conv).await
@@ -102,13 +154,7 @@ pub async fn start_turn(
 
     spawn_agent_turn(state, req);
 
-    Ok(Json(
-        serde_json::json!({
-            "ok": true,
-            "conversationId": body.conversation_id,
-            "messageId": user_msg_id,
-        }),
-    ))
+    Ok(user_msg_id)
 }
 
 pub async fn branch_turn(
@@ -178,6 +224,8 @@ pub async fn branch_turn(
             assistant_message_id: body.assistant_message_id,
             last_active_id: conv.active_path.last().cloned(),
             prior_cost: conv.usage.as_ref().map(|u| u.total_cost).unwrap_or(0.0),
+            thinking_budget: None,
+            best_of: None,
         };
 
         state.threads.commit(conv).await
@@ -248,6 +296,8 @@ pub async fn regenerate_turn(
             assistant_message_id: body.assistant_message_id,
             last_active_id: conv.active_path.last().cloned(),
             prior_cost: conv.usage.as_ref().map(|u| u.total_cost).unwrap_or(0.0),
+            thinking_budget: None,
+            best_of: None,
         };
 
         state.threads.commit(conv).await
@@ -350,6 +400,8 @@ pub async fn tool_invoke(
             assistant_message_id: body.assistant_message_id,
             last_active_id: conv.active_path.last().cloned(),
             prior_cost: conv.usage.as_ref().map(|u| u.total_cost).unwrap_or(0.0),
+            thinking_budget: None,
+            best_of: None,
         };
 
         state.threads.commit(conv).await
@@ -400,33 +452,44 @@ pub async fn answer_question(
     State(state): State<Arc<AppState>>,
     Json(body): Json<AnswerRequest>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
+    let answer = nexus_tools::ask_user::UserAnswer {
+        value: body.value,
+        dismissed: false,
+    };
+    resolve_question(&state, &body.conversation_id, &body.question_id, answer).await?;
+
+    Ok(Json(serde_json::json!({
+        "ok": true,
+        "questionId": body.question_id,
+    })))
+}
+
+/// Hand an answer to a pending `ask_user` question. The question must
+/// belong to `conversation_id`.
+pub(crate) async fn resolve_question(
+    state: &AppState,
+    conversation_id: &str,
+    question_id: &str,
+    answer: nexus_tools::ask_user::UserAnswer,
+) -> Result<(), StatusCode> {
     let question = {
         let mut store = state.turns.pending_questions.write().await;
-        store.remove(&body.question_id)
+        store.remove(question_id)
     };
 
     let question = question.ok_or(StatusCode::NOT_FOUND)?;
 
-    if question.conversation_id != body.conversation_id {
+    if question.conversation_id != conversation_id {
         return Err(StatusCode::BAD_REQUEST);
     }
 
-    let answer = nexus_tools::ask_user::UserAnswer {
-        value: body.value.clone(),
-        dismissed: false,
-    };
-
     // Send answer through the oneshot channel — this resumes the agent turn
     let _ = question.response_tx.send(answer);
-
-    Ok(Json(serde_json::json!({
-        "ok": true,
-        "questionId": body.question_id,
-    })))
+    Ok(())
 }
 
 /// Resolve MCP tools filtered by the active agent's mcp_server_ids.
-async fn resolve_mcp_tools(state: &AppState) -> Vec<nexus_provider::types::Tool> {
+pub(crate) async fn resolve_mcp_tools(state: &AppState) -> Vec<nexus_provider::types::Tool> {
     let mcp = state.mcp.mcp.read().await;
     let agent = state.agents.active_agent().await;
     match agent.and_then(|a| a.mcp_server_ids).as_ref() {
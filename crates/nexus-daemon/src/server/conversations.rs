@@ -1,16 +1,21 @@
-use axum::extract::{Path, State};
+use axum::extract::{Path, Query, State};
 use axum::http::StatusCode;
 use axum::Json;
 use chrono::Utc;
 use serde::Deserialize;
 use std::sync::Arc;
 
+use crate::agent::events::replay::{self, Transcript};
+use crate::context::{self, SnapshotInfo};
+use crate::conversation::InvalidId;
+use crate::conversation::types::{Checkpoint, Conversation, ConversationFilter, RollbackError};
 use crate::server::AppState;
 
 pub async fn list(
     State(state): State<Arc<AppState>>,
+    Query(filter): Query<ConversationFilter>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
-    let threads = state.threads.list().await;
+    let threads = state.threads.list_filtered(&filter).await;
     Ok(Json(serde_json::to_value(&threads).unwrap()))
 }
 
@@ -19,13 +24,16 @@ pub async fn create(
     body: Option<Json<CreateRequest>>,
 ) -> Result<(StatusCode, Json<serde_json::Value>), StatusCode> {
     let client_id = body.and_then(|b| b.id.clone());
+    if client_id.as_deref().is_some_and(|id| !crate::conversation::is_valid_id(id)) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
     // Stamp with the global default agent
     let default_agent_id = state.agents.active_agent().await.map(|a| a.id);
     let meta = state
         .threads
         .create(client_id, None, default_agent_id)
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(|e| store_error_status(&e))?;
     Ok((StatusCode::CREATED, Json(serde_json::to_value(&meta).unwrap())))
 }
 
@@ -37,7 +45,7 @@ pub async fn get(
         .threads
         .get(&id)
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .map_err(|e| store_error_status(&e))?
         .ok_or(StatusCode::NOT_FOUND)?;
     let mut value = serde_json::to_value(&conv).unwrap();
     // Include task state (triggers lazy disk load via get_or_default)
@@ -54,6 +62,8 @@ pub async fn delete(
 ) -> StatusCode {
     // Cancel running background processes and clean up output files
     state.turns.process_manager.cleanup_conversation(&id).await;
+    state.turns.pruned_results.remove_conversation(&id);
+    state.turns.context_snapshots.remove_conversation(&id);
 
     match state.threads.delete(&id).await {
         Ok(()) => {
@@ -61,8 +71,116 @@ pub async fn delete(
             state.tasks.remove(&id).await;
             StatusCode::NO_CONTENT
         }
-        Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        Err(e) => store_error_status(&e),
+    }
+}
+
+/// Ids that aren't safe file names are rejected by the store; that maps to
+/// 400 rather than 500.
+fn store_error_status(err: &anyhow::Error) -> StatusCode {
+    if err.downcast_ref::<InvalidId>().is_some() {
+        StatusCode::BAD_REQUEST
+    } else {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
+/// Persisted event log for a conversation (empty unless
+/// `conversations.event_log` is enabled).
+pub async fn events(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<serde_json::Value>>, StatusCode> {
+    state
+        .threads
+        .get(&id)
+        .await
+        .map_err(|e| store_error_status(&e))?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let events = state
+        .threads
+        .events(&id)
+        .await
+        .map_err(|e| store_error_status(&e))?;
+    Ok(Json(events))
+}
+
+/// Transcript rebuilt from the persisted event log, for post-mortems when
+/// the conversation file itself is missing messages.
+pub async fn replay(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<Transcript>, StatusCode> {
+    let Json(events) = events(State(state), Path(id)).await?;
+    Ok(Json(replay::replay(&events)))
+}
+
+/// Context snapshots recorded for a conversation's turns since the daemon
+/// started, oldest first.
+pub async fn context_snapshots(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Json<Vec<SnapshotInfo>> {
+    Json(state.turns.context_snapshots.list(&id))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ContextDiffQuery {
+    pub from: u32,
+    pub to: u32,
+}
+
+/// What changed in the model's context between two turns, as data and as a
+/// readable report.
+pub async fn context_diff(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(query): Query<ContextDiffQuery>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let snapshots = &state.turns.context_snapshots;
+    let from = snapshots.get(&id, query.from).ok_or(StatusCode::NOT_FOUND)?;
+    let to = snapshots.get(&id, query.to).ok_or(StatusCode::NOT_FOUND)?;
+    let diff = context::diff(&from, &to);
+    Ok(Json(serde_json::json!({
+        "report": diff.to_string(),
+        "diff": diff,
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PurgeRequest {
+    pub older_than_secs: u64,
+}
+
+pub async fn purge(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<PurgeRequest>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    // Ages past the earliest representable cutoff would wrap or panic.
+    let max_age = i64::try_from(body.older_than_secs)
+        .ok()
+        .and_then(chrono::Duration::try_seconds)
+        .filter(|age| chrono::Utc::now().checked_sub_signed(*age).is_some())
+        .ok_or(StatusCode::BAD_REQUEST)?;
+    let purged = purge_stale(&state, max_age)
+        .await
+        .map_err(|e| store_error_status(&e))?;
+    Ok(Json(serde_json::json!({ "purged": purged })))
+}
+
+/// Purge conversations idle for longer than `max_age`, skipping any with a
+/// running turn, and clean up their processes, task state, pruned results,
+/// and context snapshots.
+pub async fn purge_stale(state: &AppState, max_age: chrono::Duration) -> anyhow::Result<Vec<String>> {
+    let active = state.turns.active_conversation_ids().await;
+    let purged = state.threads.purge_older_than(max_age, &active).await?;
+    for id in &purged {
+        state.turns.process_manager.cleanup_conversation(id).await;
+        state.turns.pruned_results.remove_conversation(id);
+        state.turns.context_snapshots.remove_conversation(id);
+        state.tasks.remove(id).await;
     }
+    Ok(purged)
 }
 
 pub async fn update(
@@ -71,20 +189,20 @@ pub async fn update(
     Json(body): Json<UpdateRequest>,
 ) -> StatusCode {
     if let Some(ref title) = body.title {
-        if let Err(_) = state.threads.rename(&id, title).await {
-            return StatusCode::INTERNAL_SERVER_ERROR;
+        if let Err(e) = state.threads.rename(&id, title).await {
+            return store_error_status(&e);
         }
     }
     if let Some(ref workspace_id) = body.workspace_id {
         let ws = if workspace_id.is_empty() { None } else { Some(workspace_id.clone()) };
-        if let Err(_) = state.threads.set_workspace(&id, ws).await {
-            return StatusCode::INTERNAL_SERVER_ERROR;
+        if let Err(e) = state.threads.set_workspace(&id, ws).await {
+            return store_error_status(&e);
         }
     }
     if let Some(ref agent_id) = body.agent_id {
         let agent = if agent_id.is_empty() { None } else { Some(agent_id.clone()) };
-        if let Err(_) = state.threads.set_agent(&id, agent).await {
-            return StatusCode::INTERNAL_SERVER_ERROR;
+        if let Err(e) = state.threads.set_agent(&id, agent).await {
+            return store_error_status(&e);
         }
     }
     StatusCode::OK
@@ -119,7 +237,7 @@ pub async fn switch_path(
         .threads
         .checkout(&id)
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .map_err(|e| store_error_status(&e))?
         .ok_or(StatusCode::NOT_FOUND)?;
 
     // Cannot switch to a path inside a sealed span
@@ -133,13 +251,23 @@ pub async fn switch_path(
     }
 
     conv.active_path = new_path;
+    commit_with_task_state(&state, conv).await
+}
+
+/// Save a conversation whose active path moved, returning it with its task
+/// state (if any) like `get`.
+async fn commit_with_task_state(
+    state: &AppState,
+    mut conv: Conversation,
+) -> Result<Json<serde_json::Value>, StatusCode> {
     conv.updated_at = Utc::now();
+    let id = conv.id.clone();
     let mut value = serde_json::to_value(&conv).unwrap();
     state
         .threads
         .commit(conv)
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(|e| store_error_status(&e))?;
     // Include task state if it exists
     let task_state = state.tasks.get_or_default(&id).await;
     if task_state.plan.is_some() {
@@ -147,3 +275,67 @@ pub async fn switch_path(
     }
     Ok(Json(value))
 }
+
+#[derive(Debug, Deserialize)]
+pub struct CheckpointRequest {
+    pub tag: String,
+    /// Message to tag; defaults to the end of the active path.
+    #[serde(rename = "messageId")]
+    pub message_id: Option<String>,
+}
+
+pub async fn list_checkpoints(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<Checkpoint>>, StatusCode> {
+    let conv = state
+        .threads
+        .get(&id)
+        .await
+        .map_err(|e| store_error_status(&e))?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(conv.checkpoints))
+}
+
+pub async fn create_checkpoint(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(body): Json<CheckpointRequest>,
+) -> Result<(StatusCode, Json<Checkpoint>), StatusCode> {
+    if body.tag.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let mut conv = state
+        .threads
+        .checkout(&id)
+        .await
+        .map_err(|e| store_error_status(&e))?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let checkpoint = conv
+        .set_checkpoint(&body.tag, body.message_id.as_deref())
+        .cloned()
+        .ok_or(StatusCode::BAD_REQUEST)?;
+    state
+        .threads
+        .commit(conv)
+        .await
+        .map_err(|e| store_error_status(&e))?;
+    Ok((StatusCode::CREATED, Json(checkpoint)))
+}
+
+pub async fn rollback_to_checkpoint(
+    State(state): State<Arc<AppState>>,
+    Path((id, tag)): Path<(String, String)>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let mut conv = state
+        .threads
+        .checkout(&id)
+        .await
+        .map_err(|e| store_error_status(&e))?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    match conv.rollback_to_tag(&tag) {
+        Ok(()) => commit_with_task_state(&state, conv).await,
+        Err(RollbackError::UnknownTag) => Err(StatusCode::NOT_FOUND),
+        Err(RollbackError::Sealed) => Err(StatusCode::CONFLICT),
+    }
+}
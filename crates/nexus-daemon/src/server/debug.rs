@@ -11,6 +11,7 @@ use serde::Deserialize;
 use std::sync::Arc;
 use uuid::Uuid;
 
+use crate::agent::emitter::{Compaction, CompactionTrigger};
 use crate::agent::events::{AgUiEvent, EventEnvelope};
 use crate::conversation::types::Span;
 use crate::server::AppState;
@@ -55,7 +56,7 @@ pub async fn force_compact(
         conv.spans.push(Span {
             index: 0,
             message_ids: consumed_ids.clone(),
-            summary: Some(summary_text),
+            summary: Some(summary_text.clone()),
             sealed_at: Some(Utc::now()),
         });
         conv.spans.push(Span {
@@ -65,7 +66,7 @@ pub async fn force_compact(
             sealed_at: None,
         });
     } else {
-        conv.seal_current_span(&consumed_ids, summary_text);
+        conv.seal_current_span(&consumed_ids, summary_text.clone());
         conv.open_new_span();
     }
 
@@ -74,6 +75,7 @@ pub async fn force_compact(
     conv.updated_at = Utc::now();
 
     let sealed_index = conv.spans.len() - 2;
+    let full = conv.active_path.is_empty();
 
     state.threads.commit(conv).await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -84,10 +86,15 @@ pub async fn force_compact(
         run_id: None,
         event: AgUiEvent::Custom {
             name: "compaction".to_string(),
-            value: serde_json::json!({
-                "sealed_span_index": sealed_index,
-                "consumed_count": consumed_ids.len(),
-            }),
+            value: serde_json::to_value(Compaction {
+                sealed_span_index: sealed_index,
+                consumed_count: consumed_ids.len(),
+                summary: &summary_text,
+                compaction_number: sealed_index + 1,
+                full,
+                trigger: CompactionTrigger::Manual,
+            })
+            .unwrap_or_default(),
         },
     });
 
@@ -403,6 +410,17 @@ pub async fn deny_tool(
     Json(serde_json::json!({ "ok": true }))
 }
 
+/// POST /api/debug/hooks/checkpoint — tag the conversation at every turn end.
+pub async fn checkpoint_tag(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<CheckpointTagRequest>,
+) -> Json<serde_json::Value> {
+    if let Some(probe) = &state.hook_probe {
+        probe.set_checkpoint_tag(body.tag);
+    }
+    Json(serde_json::json!({ "ok": true }))
+}
+
 /// POST /api/debug/hooks/force-continue — set force-continue count.
 pub async fn force_continue(
     State(state): State<Arc<AppState>>,
@@ -419,6 +437,11 @@ pub struct DenyToolRequest {
     pub tool_name: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct CheckpointTagRequest {
+    pub tag: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ForceContinueRequest {
     pub count: u32,
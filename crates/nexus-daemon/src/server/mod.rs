@@ -1,4 +1,6 @@
+pub mod a2a;
 pub mod agent_api;
+pub mod batches;
 pub mod browse;
 pub mod chat;
 pub mod conversations;
@@ -8,13 +10,17 @@ pub mod introspect;
 pub mod lsp_api;
 pub mod mcp_api;
 pub mod message_queue;
+#[cfg(feature = "openai-api")]
+pub mod openai;
 pub mod providers;
+pub mod run_watch;
 pub mod services;
 pub mod sse;
 pub mod turn;
 pub mod project_api;
 pub mod settings_api;
 pub mod workspace_api;
+pub mod ws;
 
 use axum::extract::{Path, State};
 use axum::http::StatusCode;
@@ -27,6 +33,8 @@ use tower_http::services::ServeDir;
 use crate::agent_config::AgentService;
 use crate::config::{FilesystemConfig, NexusConfig};
 use crate::event_bus::EventBus;
+use crate::langfuse::LangfuseSink;
+use crate::event_sink::{ConversationLogSink, EventSink, FanoutSink, TracingSink};
 use crate::module::ModuleRegistry;
 use crate::provider::ProviderService;
 use crate::tasks::TaskService;
@@ -56,6 +64,14 @@ pub struct AppState {
     pub lsp: Arc<nexus_lsp::LspService>,
     /// Module registry — hook system for extending daemon behavior.
     pub modules: Arc<ModuleRegistry>,
+    /// Tasks created through the A2A endpoint.
+    pub a2a: Arc<a2a::A2aTasks>,
+    /// Bulk agent runs started through `/api/batches`.
+    pub batches: Arc<batches::Batches>,
+    /// Prompt/response moderators from `moderation` in nexus.json.
+    pub moderation: Arc<crate::moderation::Moderation>,
+    /// Output validators from `guardrails` in nexus.json.
+    pub guardrails: Arc<crate::guardrail::Guardrails>,
     /// Hook probe for debug/test introspection (debug builds only).
     #[cfg(debug_assertions)]
     pub hook_probe: Option<Arc<crate::hook_probe::HookProbe>>,
@@ -66,6 +82,8 @@ pub fn build_router(state: AppState, queue_rx: tokio::sync::mpsc::UnboundedRecei
 
     // Start event-driven queue watcher for idle conversations
     start_queue_watcher(queue_rx, Arc::clone(&state));
+    start_conversation_purge(Arc::clone(&state));
+    start_event_sinks(Arc::clone(&state));
 
     let mut router = Router::new()
         // Chat
@@ -79,16 +97,44 @@ pub fn build_router(state: AppState, queue_rx: tokio::sync::mpsc::UnboundedRecei
             "/api/conversations",
             get(conversations::list).post(conversations::create),
         )
+        .route(
+            "/api/conversations/purge",
+            post(conversations::purge),
+        )
         .route(
             "/api/conversations/{id}",
             get(conversations::get)
                 .delete(conversations::delete)
                 .patch(conversations::update),
         )
+        .route(
+            "/api/conversations/{id}/events",
+            get(conversations::events),
+        )
+        .route(
+            "/api/conversations/{id}/replay",
+            get(conversations::replay),
+        )
+        .route(
+            "/api/conversations/{id}/context/snapshots",
+            get(conversations::context_snapshots),
+        )
+        .route(
+            "/api/conversations/{id}/context/diff",
+            get(conversations::context_diff),
+        )
         .route(
             "/api/conversations/{id}/path",
             patch(conversations::switch_path),
         )
+        .route(
+            "/api/conversations/{id}/checkpoints",
+            get(conversations::list_checkpoints).post(conversations::create_checkpoint),
+        )
+        .route(
+            "/api/conversations/{id}/checkpoints/{tag}/rollback",
+            post(conversations::rollback_to_checkpoint),
+        )
         // Providers
         .route(
             "/api/providers",
@@ -104,10 +150,18 @@ pub fn build_router(state: AppState, queue_rx: tokio::sync::mpsc::UnboundedRecei
             "/api/providers/test",
             post(providers::test_inline),
         )
+        .route(
+            "/api/providers/cache",
+            get(providers::cache_stats).delete(providers::clear_cache),
+        )
         .route(
             "/api/providers/{id}/test",
             post(providers::test_connection),
         )
+        .route(
+            "/api/providers/{id}/keys",
+            get(providers::key_stats),
+        )
         .route(
             "/api/providers/{id}/models",
             get(providers::list_models),
@@ -121,6 +175,11 @@ pub fn build_router(state: AppState, queue_rx: tokio::sync::mpsc::UnboundedRecei
             "/api/agents",
             get(agent_api::list).post(agent_api::create),
         )
+        .route("/api/agents/profiles", get(agent_api::list_profiles))
+        .route(
+            "/api/agents/profiles/{id}",
+            post(agent_api::create_from_profile),
+        )
         .route(
             "/api/agents/{id}",
             get(agent_api::get)
@@ -215,6 +274,10 @@ pub fn build_router(state: AppState, queue_rx: tokio::sync::mpsc::UnboundedRecei
             "/api/processes/{processId}/stop",
             post(stop_process),
         )
+        // Batches (bulk agent runs)
+        .route("/api/batches", post(batches::create))
+        .route("/api/batches/{id}", get(batches::get))
+        .route("/api/batches/{id}/cancel", post(batches::cancel))
         // Folder browser (for workspace picker)
         .route("/api/browse", get(browse::browse))
         // Ask-user answer endpoint
@@ -223,6 +286,11 @@ pub fn build_router(state: AppState, queue_rx: tokio::sync::mpsc::UnboundedRecei
         .route("/api/tools", get(list_tools))
         // SSE events (global multiplexed stream)
         .route("/api/events", get(events_stream))
+        // WebSocket sessions (prompt/cancel/answer/steer + event stream)
+        .route("/api/ws", get(ws::upgrade))
+        // Agent-to-Agent protocol (Agent Card + JSON-RPC)
+        .route("/.well-known/agent.json", get(a2a::agent_card))
+        .route("/a2a", post(a2a::rpc))
         // Status
         .route("/api/status", get(health));
 
@@ -242,9 +310,15 @@ pub fn build_router(state: AppState, queue_rx: tokio::sync::mpsc::UnboundedRecei
             .route("/api/debug/hooks", get(debug::get_hook_records))
             .route("/api/debug/hooks/clear", post(debug::clear_hooks))
             .route("/api/debug/hooks/deny-tool", post(debug::deny_tool))
+            .route("/api/debug/hooks/checkpoint", post(debug::checkpoint_tag))
             .route("/api/debug/hooks/force-continue", post(debug::force_continue));
     }
 
+    #[cfg(feature = "openai-api")]
+    {
+        router = router.route("/v1/chat/completions", post(openai::chat_completions));
+    }
+
     // Introspection MCP server — exposes daemon state to MCP clients
     let introspect_state = Arc::clone(&state);
     let mcp_service = introspect::IntrospectService::new(
@@ -269,8 +343,11 @@ pub fn build_router(state: AppState, queue_rx: tokio::sync::mpsc::UnboundedRecei
         .with_state(state)
 }
 
-async fn health() -> Json<serde_json::Value> {
-    Json(serde_json::json!({ "status": "ok" }))
+async fn health(State(state): State<Arc<AppState>>) -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "status": "ok",
+        "dropped_events": state.turns.event_bridge.dropped_events(),
+    }))
 }
 
 async fn list_tools(
@@ -307,6 +384,46 @@ async fn stop_process(
     }
 }
 
+/// Periodically delete conversations older than `conversations.ttl_days`.
+/// No-op when no TTL is configured.
+fn start_conversation_purge(state: Arc<AppState>) {
+    // Validated at config load.
+    let Ok(Some(max_age)) = state.config.conversations.ttl() else {
+        return;
+    };
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
+        loop {
+            interval.tick().await;
+            if let Err(e) = conversations::purge_stale(&state, max_age).await {
+                tracing::warn!("Conversation purge failed: {}", e);
+            }
+        }
+    });
+}
+
+/// Attach the configured event sinks: the per-conversation JSONL log when
+/// `conversations.event_log` is enabled, Langfuse export when `langfuse` is
+/// configured, and a tracing sink when `nexus::events=trace` logging is on.
+/// Langfuse gets its own subscription so a slow export host can't make the
+/// audit log fall behind.
+fn start_event_sinks(state: Arc<AppState>) {
+    if let Some(ref langfuse) = state.config.langfuse {
+        state.event_bus.attach_sink(Arc::new(LangfuseSink::new(langfuse.clone())));
+    }
+    let mut sinks: Vec<Arc<dyn EventSink>> = Vec::new();
+    if state.config.conversations.event_log {
+        sinks.push(Arc::new(ConversationLogSink::new(Arc::clone(&state.threads))));
+    }
+    if tracing::enabled!(target: "nexus::events", tracing::Level::TRACE) {
+        sinks.push(Arc::new(TracingSink));
+    }
+    if !sinks.is_empty() {
+        state.event_bus.attach_sink(Arc::new(FanoutSink::new(sinks)));
+    }
+}
+
 /// Event-driven queue watcher. Receives conversation IDs when messages are
 /// enqueued. If no turn is active for that conversation, drains the queue
 /// and spawns a follow-up turn.
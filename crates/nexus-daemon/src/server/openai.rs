@@ -0,0 +1,394 @@
+//! OpenAI-compatible chat completions endpoint.
+//!
+//! `POST /v1/chat/completions` runs a normal agent turn — with the active
+//! agent's system prompt, tools and MCP servers — and returns the reply in
+//! OpenAI's response shape, so any OpenAI client or chat UI can drive the
+//! daemon. Each request becomes a stored conversation seeded with the
+//! request's `user`/`assistant` messages and tagged with origin `openai`,
+//! which keeps it out of the UI's conversation list; `system` messages are
+//! ignored in favour of the agent's own prompt. Tool calls run inside the
+//! daemon and are not surfaced to the client. A client that disconnects
+//! before the reply is complete cancels the turn.
+
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
+use uuid::Uuid;
+
+use crate::agent::events::{AgUiEvent, EventEnvelope};
+use crate::conversation::types::{ChatMessage, MessagePart, MessageRole, MessageSource};
+use crate::server::AppState;
+use super::chat::resolve_mcp_tools;
+use super::run_watch::{self, stored_reply, RunEvent, RunWatch};
+use super::turn::{spawn_agent_turn, TurnRequest};
+
+/// Origin tag on conversations opened through this endpoint.
+const ORIGIN: &str = "openai";
+
+#[derive(Debug, Deserialize)]
+pub struct ChatCompletionRequest {
+    #[serde(default)]
+    pub model: Option<String>,
+    pub messages: Vec<CompletionMessage>,
+    #[serde(default)]
+    pub stream: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompletionMessage {
+    pub role: String,
+    #[serde(default)]
+    pub content: Option<CompletionContent>,
+}
+
+/// Message content: a plain string or an array of content parts, of which
+/// only `text` parts are used.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum CompletionContent {
+    Text(String),
+    Parts(Vec<Value>),
+}
+
+impl CompletionContent {
+    fn text(&self) -> String {
+        match self {
+            Self::Text(text) => text.clone(),
+            Self::Parts(parts) => parts
+                .iter()
+                .filter(|p| p["type"] == "text")
+                .filter_map(|p| p["text"].as_str())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+}
+
+pub async fn chat_completions(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<ChatCompletionRequest>,
+) -> Result<Response, (StatusCode, Json<Value>)> {
+    if body.messages.last().map(|m| m.role.as_str()) != Some("user") {
+        return Err(invalid_request("the last message must have role \"user\""));
+    }
+
+    let meta = state.threads.create(None, None, None).await.map_err(internal)?;
+    let conversation_id = meta.id;
+    state.threads.set_origin(&conversation_id, ORIGIN).await.map_err(internal)?;
+    let (cancel, run_id) = state.turns.register_turn(&conversation_id).await;
+
+    let req = {
+        let mut conv = state
+            .threads
+            .checkout(&conversation_id)
+            .await
+            .map_err(internal)?
+            .ok_or_else(|| internal("conversation vanished"))?;
+
+        for msg in &body.messages {
+            let (role, source) = match msg.role.as_str() {
+                "user" => (MessageRole::User, Some(MessageSource::Human)),
+                "assistant" => (MessageRole::Assistant, None),
+                _ => continue,
+            };
+            let text = msg.content.as_ref().map(CompletionContent::text).unwrap_or_default();
+            let chat_msg = ChatMessage {
+                id: Uuid::new_v4().to_string(),
+                role,
+                parts: vec![MessagePart::Text { text }],
+                timestamp: Utc::now(),
+                parent_id: conv.active_path.last().cloned(),
+                source,
+                metadata: None,
+            };
+            conv.active_path.push(chat_msg.id.clone());
+            conv.messages.push(chat_msg);
+        }
+        conv.updated_at = Utc::now();
+
+        let req = TurnRequest {
+            conversation_id: conversation_id.clone(),
+            api_messages: conv.build_api_messages(),
+            tools: resolve_mcp_tools(&state).await,
+            cancel: cancel.clone(),
+            run_id: run_id.clone(),
+            assistant_message_id: None,
+            last_active_id: conv.active_path.last().cloned(),
+            prior_cost: 0.0,
+            thinking_budget: None,
+            best_of: None,
+        };
+        state.threads.commit(conv).await.map_err(internal)?;
+        req
+    };
+
+    let rx = run_watch::subscribe(&state);
+    let prompt_id = req.last_active_id.clone();
+    spawn_agent_turn(Arc::clone(&state), req);
+
+    let completion = Completion {
+        id: format!("chatcmpl-{run_id}"),
+        model: body.model.unwrap_or_else(|| "nexus".to_string()),
+        created: Utc::now().timestamp(),
+    };
+    let (tx, mut chunks) = mpsc::channel::<Outcome>(64);
+    tokio::spawn(forward_run(state, rx, conversation_id, run_id, prompt_id, tx));
+
+    if body.stream {
+        let (frames_tx, frames_rx) = mpsc::channel::<String>(64);
+        tokio::spawn(stream_chunks(completion, chunks, frames_tx, cancel));
+        let stream = ReceiverStream::new(frames_rx)
+            .map(|data| Ok::<_, Infallible>(Event::default().data(data)));
+        return Ok(Sse::new(stream).keep_alive(KeepAlive::default()).into_response());
+    }
+
+    // Axum drops this future when the client disconnects; cancel the turn
+    // with it rather than running it to completion for nobody.
+    let cancel_on_drop = cancel.drop_guard();
+    let mut content = String::new();
+    let mut usage = Usage::default();
+    while let Some(outcome) = chunks.recv().await {
+        match outcome {
+            Outcome::Content(delta) => content.push_str(&delta),
+            Outcome::Usage(u) => usage.add(&u),
+            Outcome::Finished => break,
+            Outcome::Error(message) => {
+                cancel_on_drop.disarm();
+                return Err((
+                    StatusCode::BAD_GATEWAY,
+                    Json(json!({ "error": { "message": message, "type": "server_error" } })),
+                ));
+            }
+        }
+    }
+    cancel_on_drop.disarm();
+    Ok(Json(completion.full(&content, &usage)).into_response())
+}
+
+/// What the client needs to know about the run, in order.
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Content(String),
+    Usage(Value),
+    Finished,
+    Error(String),
+}
+
+impl Outcome {
+    /// The outcome for an event within the run; its end comes from the
+    /// [`RunWatch`].
+    fn from_event(event: &AgUiEvent) -> Option<Self> {
+        match event {
+            AgUiEvent::TextMessageContent { delta, .. } => Some(Self::Content(delta.clone())),
+            AgUiEvent::Custom { name, value } if name == "inference_usage" => {
+                Some(Self::Usage(value.clone()))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Relay one run's events from the bus until it ends or the client goes
+/// away. A run whose end was missed is finished from the stored reply to
+/// `prompt_id`, sending whatever the client has not seen yet.
+async fn forward_run(
+    state: Arc<AppState>,
+    rx: broadcast::Receiver<EventEnvelope>,
+    conversation_id: String,
+    run_id: String,
+    prompt_id: Option<String>,
+    tx: mpsc::Sender<Outcome>,
+) {
+    let mut watch = RunWatch::new(rx, &conversation_id, Some(run_id));
+    let mut streamed = String::new();
+    while let Some(event) = watch.next(&state).await {
+        let outcome = match event {
+            RunEvent::Event(event) => match Outcome::from_event(&event) {
+                Some(outcome) => outcome,
+                None => continue,
+            },
+            RunEvent::Finished { complete: true } => Outcome::Finished,
+            RunEvent::Finished { complete: false } => {
+                let reply = match &prompt_id {
+                    Some(id) => stored_reply(&state, &conversation_id, id).await.ok().flatten(),
+                    None => None,
+                };
+                let Some(reply) = reply else {
+                    let _ = tx.send(Outcome::Error("run ended without a reply".to_string())).await;
+                    return;
+                };
+                if let Some(rest) = reply.strip_prefix(streamed.as_str()).filter(|r| !r.is_empty()) {
+                    if tx.send(Outcome::Content(rest.to_string())).await.is_err() {
+                        return;
+                    }
+                }
+                Outcome::Finished
+            }
+            RunEvent::Failed(message) => Outcome::Error(message),
+        };
+        if let Outcome::Content(delta) = &outcome {
+            streamed.push_str(delta);
+        }
+        let terminal = matches!(outcome, Outcome::Finished | Outcome::Error(_));
+        if tx.send(outcome).await.is_err() || terminal {
+            return;
+        }
+    }
+}
+
+/// Write `chat.completion.chunk` frames, ending with `[DONE]`. A client
+/// that disconnects mid-run cancels the turn.
+async fn stream_chunks(
+    completion: Completion,
+    mut outcomes: mpsc::Receiver<Outcome>,
+    frames: mpsc::Sender<String>,
+    cancel: tokio_util::sync::CancellationToken,
+) {
+    let first = completion.chunk(json!({ "role": "assistant", "content": "" }), None);
+    if frames.send(first.to_string()).await.is_err() {
+        cancel.cancel();
+        return;
+    }
+    while let Some(outcome) = outcomes.recv().await {
+        let out = match outcome {
+            Outcome::Content(delta) => vec![completion.chunk(json!({ "content": delta }), None).to_string()],
+            Outcome::Usage(_) => continue,
+            Outcome::Finished => vec![
+                completion.chunk(json!({}), Some("stop")).to_string(),
+                "[DONE]".to_string(),
+            ],
+            Outcome::Error(message) => {
+                vec![json!({ "error": { "message": message, "type": "server_error" } }).to_string()]
+            }
+        };
+        for frame in out {
+            if frames.send(frame).await.is_err() {
+                cancel.cancel();
+                return;
+            }
+        }
+    }
+}
+
+struct Completion {
+    id: String,
+    model: String,
+    created: i64,
+}
+
+impl Completion {
+    fn chunk(&self, delta: Value, finish_reason: Option<&str>) -> Value {
+        json!({
+            "id": self.id,
+            "object": "chat.completion.chunk",
+            "created": self.created,
+            "model": self.model,
+            "choices": [{ "index": 0, "delta": delta, "finish_reason": finish_reason }],
+        })
+    }
+
+    fn full(&self, content: &str, usage: &Usage) -> Value {
+        json!({
+            "id": self.id,
+            "object": "chat.completion",
+            "created": self.created,
+            "model": self.model,
+            "choices": [{
+                "index": 0,
+                "message": { "role": "assistant", "content": content },
+                "finish_reason": "stop",
+            }],
+            "usage": {
+                "prompt_tokens": usage.prompt_tokens,
+                "completion_tokens": usage.completion_tokens,
+                "total_tokens": usage.prompt_tokens + usage.completion_tokens,
+            },
+        })
+    }
+}
+
+/// Token totals across every provider call of the run.
+#[derive(Default)]
+struct Usage {
+    prompt_tokens: u64,
+    completion_tokens: u64,
+}
+
+impl Usage {
+    fn add(&mut self, value: &Value) {
+        self.prompt_tokens += ["inputTokens", "cacheReadInputTokens", "cacheCreationInputTokens"]
+            .iter()
+            .filter_map(|k| value[k].as_u64())
+            .sum::<u64>();
+        self.completion_tokens += value["outputTokens"].as_u64().unwrap_or(0);
+    }
+}
+
+fn invalid_request(message: &str) -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(json!({ "error": { "message": message, "type": "invalid_request_error" } })),
+    )
+}
+
+fn internal(e: impl std::fmt::Display) -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(json!({ "error": { "message": e.to_string(), "type": "server_error" } })),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_parts_keep_only_text() {
+        let content: CompletionContent = serde_json::from_value(json!([
+            { "type": "text", "text": "hello" },
+            { "type": "image_url", "image_url": { "url": "data:..." } },
+            { "type": "text", "text": "world" },
+        ]))
+        .unwrap();
+        assert_eq!(content.text(), "hello\nworld");
+    }
+
+    #[test]
+    fn events_map_to_chunks_and_usage() {
+        let completion = Completion {
+            id: "chatcmpl-run1".into(),
+            model: "nexus".into(),
+            created: 0,
+        };
+        let outcome = Outcome::from_event(&AgUiEvent::TextMessageContent {
+            message_id: "m1".into(),
+            delta: "Hi".into(),
+        });
+        assert_eq!(outcome, Some(Outcome::Content("Hi".into())));
+        assert_eq!(Outcome::from_event(&AgUiEvent::RunStarted), None);
+
+        let chunk = completion.chunk(json!({ "content": "Hi" }), None);
+        assert_eq!(chunk["object"], "chat.completion.chunk");
+        assert_eq!(chunk["choices"][0]["delta"]["content"], "Hi");
+        assert!(chunk["choices"][0]["finish_reason"].is_null());
+
+        let mut usage = Usage::default();
+        usage.add(&json!({ "inputTokens": 10, "cacheReadInputTokens": 5, "outputTokens": 3 }));
+        usage.add(&json!({ "inputTokens": 20, "outputTokens": 4 }));
+        let full = completion.full("Hi", &usage);
+        assert_eq!(full["usage"]["prompt_tokens"], 35);
+        assert_eq!(full["usage"]["completion_tokens"], 7);
+        assert_eq!(full["usage"]["total_tokens"], 42);
+    }
+}
@@ -115,6 +115,7 @@ async fn reload_effective_config(state: &Arc<AppState>) {
     let effective = crate::config::FilesystemConfig {
         enabled: state.base_filesystem_config.enabled,
         allowed_directories: dirs,
+        workspaces: state.base_filesystem_config.workspaces.clone(),
     };
 
     let mut fs_config = state.effective_fs_config.write().await;
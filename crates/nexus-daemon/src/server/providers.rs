@@ -17,6 +17,26 @@ pub async fn list(
     Ok(Json(serde_json::to_value(public).unwrap()))
 }
 
+/// Response cache counters (`enabled: false` when the cache is off).
+pub async fn cache_stats(State(state): State<Arc<AppState>>) -> Json<serde_json::Value> {
+    match state.providers.cache_stats() {
+        Some(stats) => {
+            let mut value = serde_json::to_value(stats).unwrap_or_default();
+            value["enabled"] = serde_json::json!(true);
+            Json(value)
+        }
+        None => Json(serde_json::json!({ "enabled": false })),
+    }
+}
+
+pub async fn clear_cache(State(state): State<Arc<AppState>>) -> StatusCode {
+    if state.providers.clear_cache() {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
 pub async fn create(
     State(state): State<Arc<AppState>>,
     Json(body): Json<CreateProviderRequest>,
@@ -54,6 +74,21 @@ pub async fn get(
     }
 }
 
+/// Requests and rate-limit throttles per API key (last four characters
+/// only); empty unless the provider rotates several keys.
+pub async fn key_stats(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let stats = state
+        .providers
+        .key_stats(&id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(serde_json::to_value(stats).unwrap()))
+}
+
 pub async fn update(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
@@ -122,6 +157,7 @@ pub async fn test_connection(
                     thinking_budget: None,
                     messages,
                     tools: vec![],
+                    idempotency_key: None,
                 })
                 .await
             {
@@ -147,8 +183,14 @@ pub async fn test_inline(
         provider_type: body.provider_type,
         endpoint: body.endpoint,
         api_key: body.api_key,
+        api_keys: Vec::new(),
+        key_rotation: None,
+        beta_headers: Vec::new(),
+        api_version: None,
         aws_region: body.aws_region,
         aws_profile: body.aws_profile,
+        throttle: None,
+        tool_results: None,
         created_at: chrono::Utc::now(),
         updated_at: chrono::Utc::now(),
     };
@@ -181,6 +223,7 @@ pub async fn test_inline(
             thinking_budget: None,
             messages,
             tools: vec![],
+            idempotency_key: None,
         })
         .await
     {
@@ -0,0 +1,183 @@
+//! Following one agent run on the shared event bus.
+//!
+//! The bus is a broadcast channel, so a slow subscriber can miss events —
+//! including the run's `RUN_FINISHED` or `RUN_ERROR` — and some turns end
+//! without either (e.g. a prompt blocked by moderation). The turn registry
+//! has the last word: a turn is unregistered only after its messages are
+//! stored, so once the conversation has no active turn the run is over and
+//! its reply can be read from storage.
+
+use std::time::Duration;
+
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+use tokio::time::Interval;
+
+use crate::agent::events::{AgUiEvent, EventEnvelope};
+use crate::server::AppState;
+
+/// How often a watcher re-checks turn state, in case the event that ends
+/// its run was missed.
+const TURN_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Subscribe to the agent event bus. Call this before starting the turn so
+/// no event of the run can be missed.
+pub fn subscribe(state: &AppState) -> broadcast::Receiver<EventEnvelope> {
+    state.turns.event_bridge.agent_tx().subscribe()
+}
+
+/// What a [`RunWatch`] saw of the run.
+#[derive(Debug)]
+pub enum RunEvent {
+    /// An event of the run other than its end.
+    Event(AgUiEvent),
+    /// The run finished. `complete` is false if events were dropped or the
+    /// run ended without `RUN_FINISHED`; the streamed text is then partial,
+    /// and the stored reply (already written) has all of it.
+    Finished { complete: bool },
+    /// The run failed with this message.
+    Failed(String),
+}
+
+/// Watches one run of a conversation.
+pub struct RunWatch {
+    rx: broadcast::Receiver<EventEnvelope>,
+    conversation_id: String,
+    run_id: Option<String>,
+    /// Set once events were dropped.
+    lagged: bool,
+    /// Set once the turn is unregistered; only queued events are left.
+    turn_over: bool,
+    ended: bool,
+    poll: Interval,
+}
+
+impl RunWatch {
+    /// Watch the run on `conversation_id`. Without a `run_id`, the first
+    /// `RUN_STARTED` of the conversation pins it, so the end of a run that
+    /// this turn replaced is not mistaken for ours; a `RUN_ERROR` before
+    /// that still counts, since some errors (e.g. no agent configured) come
+    /// before the run starts.
+    pub fn new(rx: broadcast::Receiver<EventEnvelope>, conversation_id: &str, run_id: Option<String>) -> Self {
+        Self {
+            rx,
+            conversation_id: conversation_id.to_string(),
+            run_id,
+            lagged: false,
+            turn_over: false,
+            ended: false,
+            poll: tokio::time::interval(TURN_POLL_INTERVAL),
+        }
+    }
+
+    /// The next event of the run. Ends with `Finished` or `Failed`, after
+    /// which it returns `None`, as it does if the bus closes.
+    pub async fn next(&mut self, state: &AppState) -> Option<RunEvent> {
+        while !self.ended {
+            let envelope = if self.turn_over {
+                // Some paths unregister the turn just before emitting
+                // RUN_ERROR, so drain what is queued before giving up.
+                match self.rx.try_recv() {
+                    Ok(envelope) => envelope,
+                    Err(TryRecvError::Lagged(_)) => {
+                        self.lagged = true;
+                        continue;
+                    }
+                    Err(_) => {
+                        self.ended = true;
+                        return Some(RunEvent::Finished { complete: false });
+                    }
+                }
+            } else {
+                let received = tokio::select! {
+                    received = self.rx.recv() => match received {
+                        Ok(envelope) => Some(envelope),
+                        Err(RecvError::Lagged(n)) => {
+                            tracing::warn!(skipped = n, conversation_id = %self.conversation_id, "Run watcher lagged; checking turn state");
+                            self.lagged = true;
+                            None
+                        }
+                        Err(RecvError::Closed) => {
+                            self.ended = true;
+                            return None;
+                        }
+                    },
+                    _ = self.poll.tick() => None,
+                };
+                match received {
+                    Some(envelope) => envelope,
+                    None => {
+                        self.turn_over = !state.turns.is_active(&self.conversation_id).await;
+                        continue;
+                    }
+                }
+            };
+            if let Some(event) = self.accept(envelope) {
+                return Some(event);
+            }
+        }
+        None
+    }
+
+    fn accept(&mut self, envelope: EventEnvelope) -> Option<RunEvent> {
+        if envelope.thread_id.as_deref() != Some(self.conversation_id.as_str()) {
+            return None;
+        }
+        match (&self.run_id, &envelope.event) {
+            (None, AgUiEvent::RunStarted) => self.run_id = envelope.run_id.clone(),
+            (None, AgUiEvent::RunError { .. }) => {}
+            (None, _) => return None,
+            (Some(run_id), _) if envelope.run_id.as_ref() != Some(run_id) => return None,
+            _ => {}
+        }
+        match envelope.event {
+            // With events dropped, the reply is only whole once stored,
+            // which the turn registry tells us.
+            AgUiEvent::RunFinished { .. } if self.lagged => None,
+            AgUiEvent::RunFinished { .. } => {
+                self.ended = true;
+                Some(RunEvent::Finished { complete: true })
+            }
+            AgUiEvent::RunError { message, .. } => {
+                self.ended = true;
+                Some(RunEvent::Failed(message))
+            }
+            event => Some(RunEvent::Event(event)),
+        }
+    }
+}
+
+/// Wait for the conversation's run to end and its results to be stored.
+/// A `RUN_ERROR` is returned as the error.
+pub async fn await_run_end(
+    state: &AppState,
+    rx: broadcast::Receiver<EventEnvelope>,
+    conversation_id: &str,
+    run_id: Option<String>,
+) -> Result<(), String> {
+    let mut watch = RunWatch::new(rx, conversation_id, run_id);
+    loop {
+        match watch.next(state).await {
+            Some(RunEvent::Event(_)) => {}
+            Some(RunEvent::Finished { complete: true }) => break,
+            Some(RunEvent::Finished { complete: false }) => return Ok(()),
+            Some(RunEvent::Failed(message)) => return Err(message),
+            None => return Err("event bus closed".to_string()),
+        }
+    }
+    // RUN_FINISHED comes before the turn's messages are written.
+    let mut poll = tokio::time::interval(TURN_POLL_INTERVAL);
+    while state.turns.is_active(conversation_id).await {
+        poll.tick().await;
+    }
+    Ok(())
+}
+
+/// The stored reply to `user_message_id`, once the run is over.
+pub async fn stored_reply(state: &AppState, conversation_id: &str, user_message_id: &str) -> anyhow::Result<Option<String>> {
+    Ok(state
+        .threads
+        .get(conversation_id)
+        .await?
+        .and_then(|conv| conv.reply_to(user_message_id)))
+}
@@ -1,12 +1,16 @@
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 use tokio::sync::{Mutex, RwLock};
 
 use nexus_tools::ask_user::PendingQuestionStore;
 use crate::bg_process::ProcessManager;
+use crate::context::ContextSnapshotStore;
 use crate::mcp::store::McpServerStore;
 use crate::mcp::McpManager;
+use crate::pruned_results::PrunedResultStore;
 use super::message_queue::MessageQueue;
 use super::sse::AgentEventBridge;
 
@@ -17,15 +21,25 @@ pub struct ActiveTurn {
 }
 
 /// Turn lifecycle manager: active turn tracking, cancellation, events,
-/// pending questions, background processes, and message queue.
+/// pending questions, background processes, message queue, pruned
+/// tool results, and context snapshots.
 ///
 /// Conversation CRUD → `ThreadService`. Task state → `TaskService`.
 pub struct TurnManager {
     active_turns: Mutex<HashMap<String, ActiveTurn>>,
+    /// Set by [`interrupt_all`](Self::interrupt_all) and
+    /// [`abandon_all`](Self::abandon_all); turns registered
+    /// afterwards start cancelled.
+    shutting_down: AtomicBool,
+    /// Set by [`abandon_all`](Self::abandon_all): the conversation store
+    /// belongs to another daemon now, so nothing may be written to it.
+    store_lost: AtomicBool,
     pub event_bridge: AgentEventBridge,
     pub pending_questions: RwLock<PendingQuestionStore>,
     pub process_manager: Arc<ProcessManager>,
     pub message_queue: Arc<MessageQueue>,
+    pub pruned_results: PrunedResultStore,
+    pub context_snapshots: ContextSnapshotStore,
 }
 
 impl TurnManager {
@@ -37,10 +51,14 @@ impl TurnManager {
     ) -> Self {
         Self {
             active_turns: Mutex::new(HashMap::new()),
+            shutting_down: AtomicBool::new(false),
+            store_lost: AtomicBool::new(false),
             event_bridge,
             pending_questions: RwLock::new(pending_questions),
             process_manager,
             message_queue,
+            pruned_results: PrunedResultStore::new(),
+            context_snapshots: ContextSnapshotStore::new(),
         }
     }
 
@@ -52,6 +70,9 @@ impl TurnManager {
     ) -> (tokio_util::sync::CancellationToken, String) {
         let run_id = uuid::Uuid::new_v4().to_string();
         let cancel = tokio_util::sync::CancellationToken::new();
+        if self.is_shutting_down() {
+            cancel.cancel();
+        }
         let mut active = self.active_turns.lock().await;
         if let Some(prev) = active.remove(conversation_id) {
             prev.cancel.cancel();
@@ -78,11 +99,58 @@ impl TurnManager {
         }
     }
 
+    /// Cancel every active turn for shutdown and wait up to `grace` for them
+    /// to end. A cancelled turn finishes its current tool call, saves what it
+    /// has, and stops. Returns how many were still running at the deadline.
+    pub async fn interrupt_all(&self, grace: Duration) -> usize {
+        self.shutting_down.store(true, Ordering::SeqCst);
+        for turn in self.active_turns.lock().await.values() {
+            turn.cancel.cancel();
+        }
+        let deadline = tokio::time::Instant::now() + grace;
+        loop {
+            let remaining = self.active_turns.lock().await.len();
+            if remaining == 0 || tokio::time::Instant::now() >= deadline {
+                return remaining;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+
+    /// Whether [`interrupt_all`](Self::interrupt_all) has been called.
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::SeqCst)
+    }
+
+    /// Cancel every active turn after the store lock was lost. Unlike
+    /// [`interrupt_all`](Self::interrupt_all), turns don't save what they
+    /// have: the store is another daemon's now. Returns how many were
+    /// cancelled.
+    pub async fn abandon_all(&self) -> usize {
+        self.store_lost.store(true, Ordering::SeqCst);
+        self.shutting_down.store(true, Ordering::SeqCst);
+        let active = self.active_turns.lock().await;
+        for turn in active.values() {
+            turn.cancel.cancel();
+        }
+        active.len()
+    }
+
+    /// Whether [`abandon_all`](Self::abandon_all) has been called.
+    pub fn is_store_lost(&self) -> bool {
+        self.store_lost.load(Ordering::SeqCst)
+    }
+
     /// Check if a turn is active for a conversation.
     pub async fn is_active(&self, conversation_id: &str) -> bool {
         self.active_turns.lock().await.contains_key(conversation_id)
     }
 
+    /// Get IDs of all conversations with an active turn.
+    pub async fn active_conversation_ids(&self) -> Vec<String> {
+        self.active_turns.lock().await.keys().cloned().collect()
+    }
+
     /// Get all active run IDs (for SSE subscriber replay).
     pub async fn active_run_ids(&self) -> Vec<String> {
         self.active_turns
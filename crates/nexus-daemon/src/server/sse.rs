@@ -2,12 +2,15 @@ use axum::response::sse::{Event, KeepAlive, Sse};
 use futures::stream::Stream;
 use std::collections::HashMap;
 use std::convert::Infallible;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::sync::{broadcast, Mutex};
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
 use tokio_stream::wrappers::BroadcastStream;
 use tokio_stream::StreamExt;
 
 use crate::agent::events::{AgUiEvent, EventEnvelope};
+use crate::config::LagPolicy;
 
 /// Bridge between the agent loop and the global SSE stream.
 ///
@@ -21,11 +24,19 @@ pub struct AgentEventBridge {
     /// Key = conversation_id, Value = serialized JSON events.
     /// Created on RUN_STARTED, cleared on RUN_FINISHED/RUN_ERROR.
     turn_buffers: Arc<Mutex<HashMap<String, Vec<String>>>>,
+    /// Events lost by any bus consumer that fell too far behind.
+    dropped: Arc<AtomicU64>,
+    lag_policy: LagPolicy,
 }
 
 impl AgentEventBridge {
     pub fn new() -> Self {
-        let (tx, _) = broadcast::channel(4096);
+        Self::with_capacity(4096)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(capacity);
+        let dropped = Arc::new(AtomicU64::new(0));
         let turn_buffers: Arc<Mutex<HashMap<String, Vec<String>>>> =
             Arc::new(Mutex::new(HashMap::new()));
 
@@ -33,6 +44,7 @@ impl AgentEventBridge {
         // per-turn event buffers for replay on reconnect.
         let mut rx: broadcast::Receiver<EventEnvelope> = tx.subscribe();
         let buffers = Arc::clone(&turn_buffers);
+        let capture_dropped = Arc::clone(&dropped);
         tokio::spawn(async move {
             loop {
                 match rx.recv().await {
@@ -62,6 +74,7 @@ impl AgentEventBridge {
                         }
                     }
                     Err(broadcast::error::RecvError::Lagged(n)) => {
+                        capture_dropped.fetch_add(n, Ordering::Relaxed);
                         tracing::warn!(
                             skipped = n,
                             "SSE buffer-capture lagged — {} events dropped",
@@ -74,7 +87,38 @@ impl AgentEventBridge {
             }
         });
 
-        Self { tx, turn_buffers }
+        Self {
+            tx,
+            turn_buffers,
+            dropped,
+            lag_policy: LagPolicy::default(),
+        }
+    }
+
+    /// Set what happens to an SSE client that falls behind the channel.
+    pub fn with_lag_policy(mut self, lag_policy: LagPolicy) -> Self {
+        self.lag_policy = lag_policy;
+        self
+    }
+
+    /// Shared counter of events dropped by lagging consumers. Other bus
+    /// consumers (event sinks) add to it so `/api/status` reports one total.
+    pub fn dropped_counter(&self) -> Arc<AtomicU64> {
+        Arc::clone(&self.dropped)
+    }
+
+    pub fn dropped_events(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// What happens to a client that falls behind the channel.
+    pub fn lag_policy(&self) -> LagPolicy {
+        self.lag_policy
+    }
+
+    /// Record `n` events lost by a lagging client.
+    pub fn record_dropped(&self, n: u64) {
+        self.dropped.fetch_add(n, Ordering::Relaxed);
     }
 
     /// Get the sender that the agent loop uses to emit events.
@@ -109,7 +153,7 @@ impl AgentEventBridge {
         let sync_envelope = EventEnvelope {
             thread_id: None,
             run_id: None,
-            event: AgUiEvent::Sync { active_runs },
+            event: AgUiEvent::sync(active_runs),
         };
         let sync_json = serde_json::to_string(&sync_envelope).unwrap_or_default();
 
@@ -122,18 +166,53 @@ impl AgentEventBridge {
             replay.into_iter().map(|json| Ok(Event::default().data(json))),
         );
 
-        let live_stream = BroadcastStream::new(rx)
-            .filter_map(|msg| msg.ok())
-            .map(|envelope| {
-                let json = serde_json::to_string(&envelope).unwrap_or_default();
-                Ok(Event::default().data(json))
-            });
+        let live_stream = live_json_stream(rx, Arc::clone(&self.dropped), self.lag_policy)
+            .map(|json| Ok(Event::default().data(json)));
 
         let stream = sync_stream.chain(replay_stream).chain(live_stream);
         Sse::new(stream).keep_alive(KeepAlive::default())
     }
 }
 
+/// Live events for one SSE client, serialized. When the client lags behind
+/// the channel the lost count is recorded, then depending on `policy` the
+/// client either gets an `events_dropped` notice (and should reload state)
+/// or the stream ends so the client reconnects and replays via SYNC.
+fn live_json_stream(
+    rx: broadcast::Receiver<EventEnvelope>,
+    dropped: Arc<AtomicU64>,
+    policy: LagPolicy,
+) -> impl Stream<Item = String> {
+    BroadcastStream::new(rx)
+        .map(move |msg| match msg {
+            Ok(envelope) => Some(serde_json::to_string(&envelope).unwrap_or_default()),
+            Err(BroadcastStreamRecvError::Lagged(n)) => {
+                dropped.fetch_add(n, Ordering::Relaxed);
+                tracing::warn!(skipped = n, ?policy, "SSE client lagged — {} events dropped", n);
+                match policy {
+                    LagPolicy::Notify => {
+                        Some(serde_json::to_string(&events_dropped(n)).unwrap_or_default())
+                    }
+                    LagPolicy::Disconnect => None,
+                }
+            }
+        })
+        .take_while(|json| json.is_some())
+        .map(|json| json.unwrap_or_default())
+}
+
+/// The `events_dropped` notice for a client that lost `count` events.
+pub fn events_dropped(count: u64) -> EventEnvelope {
+    EventEnvelope {
+        thread_id: None,
+        run_id: None,
+        event: AgUiEvent::Custom {
+            name: "events_dropped".to_string(),
+            value: serde_json::json!({ "count": count }),
+        },
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -143,9 +222,7 @@ mod tests {
         let envelope = EventEnvelope {
             thread_id: None,
             run_id: None,
-            event: AgUiEvent::Sync {
-                active_runs: vec!["conv1".into(), "conv2".into()],
-            },
+            event: AgUiEvent::sync(vec!["conv1".into(), "conv2".into()]),
         };
         let json = serde_json::to_value(&envelope).unwrap();
         assert_eq!(json["type"], "SYNC");
@@ -199,4 +276,50 @@ mod tests {
             assert!(!bufs.contains_key("conv1"));
         }
     }
+
+    fn text_event(delta: &str) -> EventEnvelope {
+        EventEnvelope {
+            thread_id: Some("conv1".into()),
+            run_id: Some("run1".into()),
+            event: AgUiEvent::TextMessageContent {
+                message_id: "m1".into(),
+                delta: delta.into(),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn lagging_client_is_notified_and_counted() {
+        let (tx, rx) = broadcast::channel(2);
+        let dropped = Arc::new(AtomicU64::new(0));
+        let stream = live_json_stream(rx, Arc::clone(&dropped), LagPolicy::Notify);
+        tokio::pin!(stream);
+
+        for i in 0..5 {
+            tx.send(text_event(&i.to_string())).unwrap();
+        }
+
+        let first: serde_json::Value = serde_json::from_str(&stream.next().await.unwrap()).unwrap();
+        assert_eq!(first["name"], "events_dropped");
+        assert_eq!(first["value"]["count"], 3);
+        assert_eq!(dropped.load(Ordering::Relaxed), 3);
+
+        let next: serde_json::Value = serde_json::from_str(&stream.next().await.unwrap()).unwrap();
+        assert_eq!(next["delta"], "3");
+    }
+
+    #[tokio::test]
+    async fn lagging_client_is_disconnected_under_disconnect_policy() {
+        let (tx, rx) = broadcast::channel(2);
+        let dropped = Arc::new(AtomicU64::new(0));
+        let stream = live_json_stream(rx, Arc::clone(&dropped), LagPolicy::Disconnect);
+        tokio::pin!(stream);
+
+        for i in 0..5 {
+            tx.send(text_event(&i.to_string())).unwrap();
+        }
+
+        assert!(stream.next().await.is_none());
+        assert_eq!(dropped.load(Ordering::Relaxed), 3);
+    }
 }
@@ -1,20 +1,22 @@
 use std::sync::Arc;
 
 use chrono::Utc;
+use tracing::Instrument;
 use uuid::Uuid;
 
 use crate::agent;
-use crate::agent::emitter::TurnEmitter;
+use crate::agent::emitter::{CallUsage, Compaction, CompactionTrigger, TurnEmitter};
 use crate::agent::{AgentTurnResult, TimingSpan};
-use nexus_provider::types::{ContentBlock, Message, Role};
+use nexus_provider::types::{ContentBlock, Message, Role, StopReason};
 use crate::conversation::types::{
     ChatMessage, ConversationUsage, MessagePart, MessageRole, MessageSource, Span,
 };
 use crate::config::{ModelTier, ModelTierConfig};
 use nexus_provider::InferenceProvider;
 use nexus_provider::provider_config::ProviderType;
+use nexus_provider::racing::{Racer, RacingProvider};
 use crate::server::AppState;
-use crate::system_prompt::{SystemPromptBuilder, SystemPromptContext};
+use crate::system_prompt::{unfence_tool_result, SystemPromptBuilder, SystemPromptContext};
 use nexus_core::tasks::AgentMode;
 
 /// Everything needed to launch an agent turn. Assembled by the caller,
@@ -28,12 +30,20 @@ pub struct TurnRequest {
     pub assistant_message_id: Option<String>,
     pub last_active_id: Option<String>,
     pub prior_cost: f64,
+    /// Overrides the agent's thinking budget for this turn; `Some(0)`
+    /// disables thinking.
+    pub thinking_budget: Option<u32>,
+    /// Best-of-N sampling for the first round.
+    pub best_of: Option<agent::best_of::BestOf>,
 }
 
 /// Resolved agent configuration from AppState.
 struct ResolvedAgent {
     provider: Arc<dyn InferenceProvider>,
     provider_type: ProviderType,
+    /// Whether every provider that may serve the turn runs Anthropic
+    /// server tools (a race against another provider type rules them out).
+    server_tools: bool,
     model: String,
     max_tokens: u32,
     system_prompt: Option<String>,
@@ -59,8 +69,18 @@ pub fn spawn_agent_turn(state: Arc<AppState>, req: TurnRequest) {
         assistant_message_id,
         last_active_id,
         prior_cost,
+        thinking_budget,
+        best_of,
     } = req;
 
+    let turn_span = tracing::info_span!(
+        "invoke_agent",
+        otel.name = "invoke_agent",
+        gen_ai.operation.name = "invoke_agent",
+        gen_ai.conversation.id = %conversation_id,
+        nexus.run_id = %run_id,
+    );
+
     tokio::spawn(async move {
         let setup_start = std::time::Instant::now();
 
@@ -76,6 +96,22 @@ pub fn spawn_agent_turn(state: Arc<AppState>, req: TurnRequest) {
             None => return,
         };
 
+        // 1b. Moderate the user's prompt before any inference.
+        let mut api_messages = api_messages;
+        if let Err(reason) = moderate_prompt(
+            &state_clone,
+            &conversation_id,
+            last_active_id.as_deref(),
+            &mut api_messages,
+            &emitter,
+        )
+        .await
+        {
+            state_clone.turns.finish_turn(&conversation_id, &run_id).await;
+            emitter.run_error(format!("Message blocked by moderation: {reason}"), None);
+            return;
+        }
+
         // 2. Assemble tools (MCP + built-in + ask_user + sub_agent + fetch + bash + bg + fs)
         let mut tools = tools;
         tools.extend(crate::tasks::tools::definitions());
@@ -87,7 +123,14 @@ pub fn spawn_agent_turn(state: Arc<AppState>, req: TurnRequest) {
         tools.push(nexus_tools::bash::tool_definition());
         tools.extend(crate::bg_process::tools::tool_definitions());
         tools.extend(crate::mcp_resources::tool_definitions());
+        tools.push(crate::pruned_results::tool_definition());
         tools.extend(crate::control_plane::tool_definitions());
+        let skills = Arc::new(crate::skills::SkillLibrary::load(
+            &crate::config::NexusConfig::nexus_dir().join("skills"),
+        ));
+        if !skills.is_empty() {
+            tools.push(crate::skills::tool_definition());
+        }
         let effective_fs = state_clone.effective_fs_config.read().await.clone();
         tools.extend(nexus_tools::filesystem::tool_definitions(&effective_fs));
         nexus_provider::types::inject_tool_description_field(&mut tools);
@@ -110,7 +153,14 @@ pub fn spawn_agent_turn(state: Arc<AppState>, req: TurnRequest) {
             mode: mode_enum,
             plan: plan_snapshot,
         };
-        let tools = crate::tool_filter::ToolFilterChain::default_chain().apply(&filter_ctx, tools);
+        let mut tools = crate::tool_filter::ToolFilterChain::default_chain().apply(&filter_ctx, tools);
+        crate::tool_filter::order_by_priority(&mut tools, &state_clone.config.agent.tool_priority);
+        // Server tools run provider-side, so only Anthropic understands them.
+        if resolved.server_tools {
+            tools.extend(
+                state_clone.config.agent.server_tools.iter().cloned().map(nexus_provider::types::Tool::server),
+            );
+        }
         tracing::debug!(mode = %mode, tool_count = tools.len(), "Tool filter applied");
 
         // 5. HOOK: TurnStart — modules contribute prompt/status sections.
@@ -127,35 +177,28 @@ pub fn spawn_agent_turn(state: Arc<AppState>, req: TurnRequest) {
         // 6. Build system prompt
         let context_window = crate::agent::context_window_for_model(&resolved.model);
         let builder = SystemPromptBuilder::default_builder();
-        let mut prompt_parts = builder.build_parts(&SystemPromptContext {
-            tool_names: tools.iter().map(|t| t.name.clone()).collect(),
-            agent_name: resolved.meta["agent_name"]
-                .as_str()
-                .unwrap_or("Assistant")
-                .to_string(),
-            custom_system_prompt: resolved.system_prompt.clone(),
-            mode,
-        });
-
-        // Merge module-contributed sections into prompt output
-        for section in &prompt_sections {
-            prompt_parts.system.push_str("\n\n");
-            prompt_parts.system.push_str(&section.content);
-        }
-        for section in &status_sections {
-            let state = prompt_parts.state.get_or_insert_with(|| "<state_update>\n</state_update>".to_string());
-            // Insert before closing tag
-            if let Some(pos) = state.rfind("</state_update>") {
-                state.insert_str(pos, &format!("{}\n", section.content));
-            } else {
-                state.push_str(&format!("\n{}", section.content));
-            }
-        }
+        let prompt_parts = builder.build_parts(
+            &SystemPromptContext {
+                tool_names: tools.iter().map(|t| t.name.clone()).collect(),
+                agent_name: resolved.meta["agent_name"]
+                    .as_str()
+                    .unwrap_or("Assistant")
+                    .to_string(),
+                custom_system_prompt: resolved.system_prompt.clone(),
+                prompt_vars: state_clone.config.agent.prompt_vars.clone(),
+                mode,
+                environment: state_clone.config.agent.environment.enabled.then(|| {
+                    environment_context(&state_clone.config.agent.environment, &effective_fs)
+                }),
+                skill_index: skills.index(),
+            },
+            &prompt_sections,
+            &status_sections,
+        );
 
         let mcp_guard = state_clone.mcp.mcp.read().await;
 
         // 6. Context compaction
-        let mut api_messages = api_messages;
         compact_context(
             &mut api_messages,
             &prompt_parts.system,
@@ -166,10 +209,19 @@ pub fn spawn_agent_turn(state: Arc<AppState>, req: TurnRequest) {
             &resolved.provider_type,
             &state_clone.config.model_tiers,
             &state_clone.threads,
+            &state_clone.modules,
+            &state_clone.turns.pruned_results,
             &conversation_id,
             &emitter,
         )
         .await;
+        state_clone.turns.context_snapshots.record(
+            &conversation_id,
+            &run_id,
+            &api_messages,
+            &prompt_parts.system,
+            &tools,
+        );
 
         // 7. Build InferenceConfig, TurnContext, TurnServices
         let bg_sub_agent_deps = Arc::new(agent::sub_agent::BgSubAgentDeps {
@@ -189,9 +241,16 @@ pub fn spawn_agent_turn(state: Arc<AppState>, req: TurnRequest) {
             model: &resolved.model,
             max_tokens: resolved.max_tokens,
             temperature: resolved.temperature,
-            thinking_budget: resolved.thinking_budget,
+            thinking_budget: match thinking_budget {
+                Some(0) => None,
+                Some(budget) => Some(budget),
+                None => resolved.thinking_budget,
+            },
             system_prompt: Some(prompt_parts.system),
             state_update: prompt_parts.state,
+            best_of,
+            hard_context_cap: state_clone.config.agent.hard_context_cap,
+            reflect_after_errors: state_clone.config.agent.reflect_after_errors,
         };
 
         let turn_ctx = agent::TurnContext {
@@ -208,6 +267,7 @@ pub fn spawn_agent_turn(state: Arc<AppState>, req: TurnRequest) {
             filesystem_config: &effective_fs,
             task_store: state_clone.tasks.store(),
             pending_questions: &state_clone.turns.pending_questions,
+            pruned_results: &state_clone.turns.pruned_results,
             process_manager: Some(state_clone.turns.process_manager.clone()),
             bg_sub_agent_deps: Some(bg_sub_agent_deps),
             control_plane: Some(Arc::new(crate::control_plane::ControlPlaneDeps {
@@ -219,7 +279,13 @@ pub fn spawn_agent_turn(state: Arc<AppState>, req: TurnRequest) {
                 mcp_svc: Arc::clone(&state_clone.mcp),
                 event_bus: state_clone.event_bus.clone(),
             })),
+            skills: (!skills.is_empty()).then(|| Arc::clone(&skills)),
             modules: Arc::clone(&state_clone.modules),
+            moderation: Some(Arc::clone(&state_clone.moderation)),
+            guardrails: state_clone.guardrails.for_agent(
+                resolved.meta["agent_id"].as_str().unwrap_or_default(),
+                resolved.meta["agent_name"].as_str().unwrap_or_default(),
+            ),
         };
 
         // 8. Run agent loop
@@ -245,6 +311,7 @@ pub fn spawn_agent_turn(state: Arc<AppState>, req: TurnRequest) {
                 context_window,
                 turn_cost,
                 error: turn_error,
+                stop_reason,
                 ..
             }) => {
                 // 9. Adjust timing spans to include setup phase
@@ -254,7 +321,21 @@ pub fn spawn_agent_turn(state: Arc<AppState>, req: TurnRequest) {
                     tracing::error!("Agent turn failed: {}", err_msg);
                 }
 
-                // 10. Persist turn results
+                // The store lock was lost: another daemon owns the store,
+                // so this turn's progress is dropped rather than written.
+                if state_clone.turns.is_store_lost() {
+                    tracing::warn!(conversation_id = %conversation_id, "Store lock lost; dropping the turn's progress");
+                    state_clone.turns.finish_turn(&conversation_id, &run_id).await;
+                    return;
+                }
+
+                // 10. Persist turn results. A turn cut short by shutdown is
+                // labelled so the UI can show it didn't finish.
+                let persisted_stop = if state_clone.turns.is_shutting_down() {
+                    Some(StopReason::Other("interrupted".into()))
+                } else {
+                    stop_reason.clone()
+                };
                 if !new_messages.is_empty() {
                     let usage = ConversationUsage {
                         input_tokens,
@@ -267,24 +348,31 @@ pub fn spawn_agent_turn(state: Arc<AppState>, req: TurnRequest) {
                     persist_turn_results(
                         &state_clone,
                         &conversation_id,
+                        &run_id,
                         &new_messages,
                         last_active_id.as_deref(),
                         assistant_message_id.as_deref(),
                         &resolved.meta,
                         &timing_spans,
+                        persisted_stop.as_ref(),
                         usage,
                     )
                     .await;
                 }
 
                 // HOOK: TurnEnd — notify modules the turn is done.
-                state_clone.modules.fire_turn_end(&crate::module::TurnEndEvent {
+                let mut checkpoint_tags = Vec::new();
+                state_clone.modules.fire_turn_end(&mut crate::module::TurnEndEvent {
                     conversation_id: &conversation_id,
                     run_id: &run_id,
                     round_count: new_messages.len(),
                     turn_cost,
                     error: turn_error.as_deref(),
+                    checkpoint_tags: &mut checkpoint_tags,
                 }).await;
+                if !checkpoint_tags.is_empty() {
+                    save_checkpoints(&state_clone, &conversation_id, &checkpoint_tags).await;
+                }
 
                 // 12. Cleanup + follow-up
                 let is_mine = state_clone.turns.finish_turn(&conversation_id, &run_id).await;
@@ -309,7 +397,7 @@ pub fn spawn_agent_turn(state: Arc<AppState>, req: TurnRequest) {
                 emitter.run_error(e.to_string(), None);
             }
         }
-    });
+    }.instrument(turn_span));
 }
 
 // ── Extracted helpers ──
@@ -367,7 +455,7 @@ async fn resolve_agent(state: &AppState, conversation_id: &str, emitter: &TurnEm
         }
     };
 
-    let provider = match state.providers.get_client(&provider_record).await {
+    let mut provider = match state.providers.get_client(&provider_record).await {
         Ok(p) => p,
         Err(e) => {
             emitter.run_error(format!("Failed to create provider client: {}", e), None);
@@ -375,9 +463,34 @@ async fn resolve_agent(state: &AppState, conversation_id: &str, emitter: &TurnEm
         }
     };
 
+    // Race the agent's provider against a second one; whichever starts
+    // producing content first serves the request.
+    let mut server_tools = matches!(provider_record.provider_type, ProviderType::Anthropic);
+    if let Some(ref race) = agent.race {
+        let rival = match state.providers.get(&race.provider_id).await {
+            Some(record) => state.providers.get_client(&record).await.map(|client| (record, client)),
+            None => Err(anyhow::anyhow!("provider '{}' not found", race.provider_id)),
+        };
+        match rival {
+            Ok((record, rival)) => {
+                // Both racers get the same tools, so server tools are only
+                // sent when the rival can run them too.
+                if !matches!(record.provider_type, ProviderType::Anthropic) {
+                    server_tools = false;
+                }
+                provider = Arc::new(RacingProvider::new(vec![
+                    Racer { provider, model: None },
+                    Racer { provider: rival, model: Some(race.model.clone()) },
+                ]));
+            }
+            Err(e) => tracing::warn!(agent = %agent.name, "Not racing, rival provider unavailable: {:#}", e),
+        }
+    }
+
     Some(ResolvedAgent {
         provider,
         provider_type: provider_record.provider_type.clone(),
+        server_tools,
         model: agent.model.clone(),
         max_tokens: agent.max_tokens.unwrap_or(8192),
         system_prompt: agent.system_prompt.clone(),
@@ -391,6 +504,95 @@ async fn resolve_agent(state: &AppState, conversation_id: &str, emitter: &TurnEm
     })
 }
 
+/// Run input moderation over the prompt (the trailing user message).
+/// Moderators see the text as the user typed it. Rewrites apply to this
+/// turn's API messages and to the stored message, so later turns resend
+/// the moderated text. Returns the reason when the prompt is blocked.
+async fn moderate_prompt(
+    state: &AppState,
+    conversation_id: &str,
+    user_message_id: Option<&str>,
+    api_messages: &mut [Message],
+    emitter: &TurnEmitter,
+) -> Result<(), String> {
+    use crate::moderation::ModerationStage;
+
+    if state.moderation.is_empty() {
+        return Ok(());
+    }
+    let Some(prompt) = api_messages.last_mut().filter(|m| m.role == Role::User) else {
+        return Ok(());
+    };
+
+    let mut conv = match user_message_id {
+        Some(_) => state.threads.checkout(conversation_id).await.ok().flatten(),
+        None => None,
+    };
+    let stored = conv.as_mut().and_then(|c| {
+        c.messages
+            .iter_mut()
+            .find(|m| Some(m.id.as_str()) == user_message_id && m.role == MessageRole::User)
+    });
+
+    // Without a stored message, moderate the API text directly.
+    let Some(stored) = stored else {
+        for block in prompt.content.iter_mut() {
+            let ContentBlock::Text { text } = block else { continue };
+            let outcome = state.moderation.check(ModerationStage::Input, text).await;
+            for finding in &outcome.findings {
+                emitter.moderated(ModerationStage::Input, finding, None);
+            }
+            if let Some(reason) = outcome.blocked() {
+                return Err(reason.to_string());
+            }
+            *text = outcome.text;
+        }
+        return Ok(());
+    };
+
+    let mut changed = false;
+    let mut blocked = None;
+    for part in stored.parts.iter_mut() {
+        let MessagePart::Text { text: original } = part else { continue };
+        let outcome = state.moderation.check(ModerationStage::Input, original).await;
+        for finding in &outcome.findings {
+            emitter.moderated(ModerationStage::Input, finding, None);
+        }
+        if let Some(reason) = outcome.blocked() {
+            blocked = Some(reason.to_string());
+            break;
+        }
+        if outcome.rewritten() {
+            // The API text wraps the original (e.g. in <user_message> tags).
+            for block in prompt.content.iter_mut() {
+                if let ContentBlock::Text { text } = block {
+                    *text = text.replace(original.as_str(), &outcome.text);
+                }
+            }
+            *original = outcome.text;
+            changed = true;
+        }
+    }
+    if let Some(reason) = blocked {
+        // Take the blocked prompt off the active path so later turns don't
+        // send it to the provider as history.
+        if let Some(mut conv) = conv {
+            conv.active_path.retain(|id| Some(id.as_str()) != user_message_id);
+            conv.updated_at = Utc::now();
+            if let Err(e) = state.threads.commit(conv).await {
+                tracing::warn!("Failed to drop blocked prompt from the active path: {}", e);
+            }
+        }
+        return Err(reason);
+    }
+    if let (true, Some(conv)) = (changed, conv) {
+        if let Err(e) = state.threads.commit(conv).await {
+            tracing::warn!("Failed to store moderated prompt: {}", e);
+        }
+    }
+    Ok(())
+}
+
 /// Derive agent mode and task counts from the task store.
 ///
 /// Returns (mode_string, mode_enum, Option<(task_count, completed_count)>).
@@ -418,6 +620,26 @@ async fn resolve_task_mode(
     }
 }
 
+/// Facts for the `<environment>` block: the working directory and the
+/// directories each filesystem tool suite can reach.
+fn environment_context(
+    config: &crate::config::EnvironmentConfig,
+    fs: &crate::config::FilesystemConfig,
+) -> crate::system_prompt::EnvironmentContext {
+    let mut workspaces = Vec::new();
+    if fs.enabled {
+        workspaces.push((None, fs.allowed_directories.clone()));
+        for ws in nexus_tools::filesystem::active_workspaces(fs) {
+            workspaces.push((Some(ws.name.clone()), ws.allowed_directories.clone()));
+        }
+    }
+    crate::system_prompt::EnvironmentContext {
+        fields: config.fields.clone(),
+        cwd: fs.allowed_directories.first().cloned(),
+        workspaces,
+    }
+}
+
 /// Compact context if approaching the context window limit.
 ///
 /// Layer 1: Mechanical tool result pruning (no LLM call).
@@ -433,6 +655,8 @@ async fn compact_context(
     provider_type: &ProviderType,
     model_tiers: &ModelTierConfig,
     threads: &crate::thread::ThreadService,
+    modules: &crate::module::ModuleRegistry,
+    pruned_results: &crate::pruned_results::PrunedResultStore,
     conversation_id: &str,
     emitter: &TurnEmitter,
 ) {
@@ -443,7 +667,8 @@ async fn compact_context(
     let prune_threshold =
         (context_window as f64 * nexus_compaction::PRUNE_THRESHOLD_PCT) as u32;
     if estimated_tokens > prune_threshold {
-        nexus_compaction::prune_tool_results(api_messages, 3);
+        let pruned = nexus_compaction::prune_tool_results(api_messages, 3);
+        pruned_results.insert(conversation_id, pruned);
     }
 
     // Layer 2: LLM summarization
@@ -468,17 +693,43 @@ async fn compact_context(
         None => return,
     };
 
+    let compact_span = tracing::info_span!(
+        "chat",
+        otel.name = format!("chat {}", compact_model),
+        otel.kind = "client",
+        gen_ai.operation.name = "chat",
+        gen_ai.request.model = %compact_model,
+        nexus.purpose = "compaction",
+        gen_ai.usage.input_tokens = tracing::field::Empty,
+        gen_ai.usage.output_tokens = tracing::field::Empty,
+        nexus.cost_usd = tracing::field::Empty,
+    );
+
     match crate::compaction::summarize_messages(
         provider,
         &compact_model,
         &compact_conv.active_messages(),
         10,
     )
+    .instrument(compact_span.clone())
     .await
     {
         Ok((summary_text, consumed_ids, input_tokens, output_tokens)) => {
             // Track compaction cost
             let cost = nexus_pricing::calculate_cost(&compact_model, input_tokens, output_tokens);
+            compact_span.record("gen_ai.usage.input_tokens", input_tokens);
+            compact_span.record("gen_ai.usage.output_tokens", output_tokens);
+            compact_span.record("nexus.cost_usd", cost);
+            emitter.call_usage(&CallUsage {
+                source: "compaction",
+                round: None,
+                model: &compact_model,
+                input_tokens,
+                output_tokens,
+                cache_read_input_tokens: 0,
+                cache_creation_input_tokens: 0,
+                cost,
+            });
             if cost > 0.0 {
                 if let Err(e) = threads.add_cost(conversation_id, cost).await {
                     tracing::error!("Failed to save compaction cost: {}", e);
@@ -509,18 +760,40 @@ async fn compact_context(
             *api_messages = compact_conv.build_api_messages();
 
             let sealed_span_count = compact_conv.spans.len();
+            let sealed = compact_conv.spans[sealed_span_count - 2].clone();
+            let full = compact_conv.active_path.is_empty();
 
             if let Err(e) = threads.commit(compact_conv).await {
                 tracing::error!("Failed to save compacted conversation: {}", e);
             }
 
-            emitter.compaction(sealed_span_count - 2, consumed_ids.len());
+            // HOOK: PostCompact — modules can ingest the discarded segment.
+            modules.fire_post_compact(&crate::module::PostCompactEvent {
+                conversation_id,
+                span_index: sealed.index,
+                summary: sealed.summary.as_deref().unwrap_or_default(),
+                consumed_message_ids: &consumed_ids,
+            }).await;
+
+            emitter.compaction(&Compaction {
+                sealed_span_index: sealed_span_count - 2,
+                consumed_count: consumed_ids.len(),
+                summary: sealed.summary.as_deref().unwrap_or_default(),
+                compaction_number: sealed_span_count - 1,
+                full,
+                trigger: CompactionTrigger::Threshold,
+            });
         }
         Err(e) => {
             tracing::warn!(
                 "Compaction failed, continuing with full context: {}",
                 e
             );
+            emitter.warning(
+                "compaction_failed",
+                format!("Context compaction failed, continuing with full context: {e}"),
+                serde_json::json!({ "model": compact_model }),
+            );
         }
     }
 }
@@ -566,17 +839,20 @@ fn adjust_timing_spans(timing_spans: &mut Vec<TimingSpan>, setup_duration_ms: u6
 async fn persist_turn_results(
     state: &AppState,
     conversation_id: &str,
+    run_id: &str,
     new_messages: &[Message],
     last_active_id: Option<&str>,
     assistant_message_id: Option<&str>,
     agent_meta: &serde_json::Value,
     timing_spans: &[TimingSpan],
+    stop_reason: Option<&StopReason>,
     usage: ConversationUsage,
 ) {
     let mut chat_messages =
         api_messages_to_chat(new_messages, last_active_id, assistant_message_id);
 
-    // Tag every message with its source
+    // Tag every message with its source, and with the run that produced
+    // it so interrupted-run recovery can tell the turn was already saved
     let agent_source = MessageSource::Agent {
         agent_id: agent_meta["agent_id"]
             .as_str()
@@ -592,6 +868,9 @@ async fn persist_turn_results(
         if msg.source.is_none() {
             msg.source = Some(agent_source.clone());
         }
+        if let Some(obj) = msg.metadata.get_or_insert_with(|| serde_json::json!({})).as_object_mut() {
+            obj.insert("runId".to_string(), serde_json::json!(run_id));
+        }
         // Keep metadata.agent for backward compat
         if msg.role == MessageRole::Assistant {
             let meta = msg
@@ -603,8 +882,11 @@ async fn persist_turn_results(
         }
     }
 
-    // Inject timing spans into the last assistant message
-    if !timing_spans.is_empty() {
+    // Inject timing spans into the last assistant message, and flag turns
+    // that ended on a refusal or unrecognized stop reason
+    let flagged_stop = stop_reason
+        .filter(|sr| matches!(sr, StopReason::Refusal | StopReason::Other(_)));
+    if !timing_spans.is_empty() || flagged_stop.is_some() {
         if let Some(last_assistant) = chat_messages
             .iter_mut()
             .rev()
@@ -614,10 +896,15 @@ async fn persist_turn_results(
                 .metadata
                 .get_or_insert_with(|| serde_json::json!({}));
             if let Some(obj) = meta.as_object_mut() {
-                obj.insert(
-                    "timingSpans".to_string(),
-                    serde_json::to_value(timing_spans).unwrap_or_default(),
-                );
+                if !timing_spans.is_empty() {
+                    obj.insert(
+                        "timingSpans".to_string(),
+                        serde_json::to_value(timing_spans).unwrap_or_default(),
+                    );
+                }
+                if let Some(sr) = flagged_stop {
+                    obj.insert("stopReason".to_string(), serde_json::json!(sr.as_str()));
+                }
             }
         }
     }
@@ -653,6 +940,26 @@ async fn persist_turn_results(
     }
 }
 
+/// Tag the end of the conversation's active path with each of `tags`.
+async fn save_checkpoints(state: &AppState, conversation_id: &str, tags: &[String]) {
+    let mut conv = match state.threads.checkout(conversation_id).await {
+        Ok(Some(conv)) => conv,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::error!("Failed to reload conversation for checkpoints: {}", e);
+            return;
+        }
+    };
+    for tag in tags {
+        if conv.set_checkpoint(tag, None).is_none() {
+            return;
+        }
+    }
+    if let Err(e) = state.threads.commit(conv).await {
+        tracing::error!("Failed to save checkpoints: {}", e);
+    }
+}
+
 // ── Queue + follow-up ──
 
 /// Drain queued messages into a conversation and spawn a follow-up agent turn.
@@ -724,23 +1031,14 @@ pub async fn drain_queue_and_follow_up(
             assistant_message_id: None,
             last_active_id,
             prior_cost,
+            thinking_budget: None,
+            best_of: None,
         },
     );
 }
 
 // ── Message conversion ──
 
-/// Strip `<tool_response>` fencing from tool results.
-fn unfence_tool_result(content: &str) -> String {
-    let trimmed = content.trim();
-    if let Some(rest) = trimmed.strip_prefix("<tool_response>") {
-        if let Some(inner) = rest.split("</tool_response>").next() {
-            return inner.trim().to_string();
-        }
-    }
-    content.to_string()
-}
-
 /// Convert API Messages back to ChatMessages with parent_id chaining.
 ///
 /// Stores messages in API-native format: assistant messages have ToolCall parts
@@ -764,29 +1062,41 @@ fn api_messages_to_chat(
             let parts: Vec<MessagePart> = msg
                 .content
                 .iter()
-                .map(|block| match block {
+                .filter_map(|block| match block {
                     ContentBlock::Text { text } => {
-                        MessagePart::Text { text: text.clone() }
+                        Some(MessagePart::Text { text: text.clone() })
                     }
-                    ContentBlock::ToolUse { id, name, input } => MessagePart::ToolCall {
+                    ContentBlock::ToolUse { id, name, input } => Some(MessagePart::ToolCall {
                         tool_call_id: id.clone(),
                         tool_name: name.clone(),
                         args: input.clone(),
                         result: None,
                         is_error: false,
-                    },
+                    }),
                     ContentBlock::ToolResult {
                         tool_use_id,
                         content,
                         is_error,
-                    } => MessagePart::ToolResult {
+                    } => Some(MessagePart::ToolResult {
                         tool_call_id: tool_use_id.clone(),
-                        result: unfence_tool_result(content),
+                        result: {
+                            let text = content.text();
+                            unfence_tool_result(&text).unwrap_or(&text).to_string()
+                        },
                         is_error: is_error.unwrap_or(false),
-                    },
-                    ContentBlock::Thinking { thinking } => MessagePart::Thinking {
+                        images: content.images().cloned().collect(),
+                    }),
+                    // Signatures and redacted blocks only matter within the
+                    // turn; thinking is stripped when history is rebuilt.
+                    ContentBlock::Thinking { thinking, .. } => Some(MessagePart::Thinking {
                         thinking: thinking.clone(),
-                    },
+                    }),
+                    // Server tool calls and results ran provider-side and
+                    // are only replayed within the turn.
+                    ContentBlock::RedactedThinking { .. }
+                    | ContentBlock::ServerToolUse { .. }
+                    | ContentBlock::WebSearchToolResult { .. }
+                    | ContentBlock::CodeExecutionToolResult { .. } => None,
                 })
                 .collect();
 
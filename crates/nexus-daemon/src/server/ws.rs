@@ -0,0 +1,216 @@
+//! WebSocket transport for agent sessions (`GET /api/ws`).
+//!
+//! One socket drives one conversation at a time. Client frames are JSON
+//! objects tagged by `type`:
+//!
+//! - `session { conversationId? }` — attach to a conversation, creating one
+//!   when no id is given. Answered with `session_opened { conversationId }`.
+//! - `prompt { message, thinkingBudget? }` — start a turn, as `/api/chat`.
+//! - `cancel` — abort the active turn.
+//! - `answer { questionId, value, dismissed? }` — resolve a pending
+//!   `ask_user` question (confirmations, choices, free text).
+//! - `steer { message }` — queue a message that is injected as soon as the
+//!   current turn ends.
+//!
+//! The server sends every event envelope for the attached conversation in
+//! the same JSON shape as `/api/events`. Rejected frames get
+//! `error { message }`; the socket stays open. A client that falls behind
+//! the event channel is handled per `events.lag_policy`, as on `/api/events`:
+//! it gets an `events_dropped { count }` notice, or the socket is closed.
+
+use std::sync::Arc;
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::Response;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::sync::broadcast;
+
+use crate::config::LagPolicy;
+use crate::server::AppState;
+use super::chat::{begin_turn, resolve_question, ChatRequest};
+use super::message_queue::QueuedMessage;
+use super::sse::events_dropped;
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientFrame {
+    Session {
+        #[serde(rename = "conversationId", default)]
+        conversation_id: Option<String>,
+    },
+    Prompt {
+        message: String,
+        #[serde(rename = "thinkingBudget", default)]
+        thinking_budget: Option<u32>,
+    },
+    Cancel,
+    Answer {
+        #[serde(rename = "questionId")]
+        question_id: String,
+        #[serde(default)]
+        value: Value,
+        #[serde(default)]
+        dismissed: bool,
+    },
+    Steer {
+        message: String,
+    },
+}
+
+pub async fn upgrade(ws: WebSocketUpgrade, State(state): State<Arc<AppState>>) -> Response {
+    ws.on_upgrade(move |socket| run_session(socket, state))
+}
+
+async fn run_session(mut socket: WebSocket, state: Arc<AppState>) {
+    let mut events = state.turns.event_bridge.agent_tx().subscribe();
+    let mut session: Option<String> = None;
+
+    loop {
+        tokio::select! {
+            msg = socket.recv() => {
+                let text = match msg {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None | Some(Err(_)) => return,
+                    Some(Ok(_)) => continue,
+                };
+                let reply = match serde_json::from_str::<ClientFrame>(&text) {
+                    Ok(frame) => handle_frame(&state, &mut session, frame).await,
+                    Err(e) => Some(error_frame(format!("invalid frame: {e}"))),
+                };
+                if let Some(reply) = reply {
+                    if socket.send(Message::Text(reply.to_string().into())).await.is_err() {
+                        return;
+                    }
+                }
+            }
+            event = events.recv() => {
+                let envelope = match event {
+                    Ok(envelope) if session.is_some() && envelope.thread_id == session => envelope,
+                    Ok(_) => continue,
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        let bridge = &state.turns.event_bridge;
+                        let policy = bridge.lag_policy();
+                        bridge.record_dropped(n);
+                        tracing::warn!(skipped = n, ?policy, "WebSocket client lagged — {} events dropped", n);
+                        match policy {
+                            LagPolicy::Notify => events_dropped(n),
+                            LagPolicy::Disconnect => {
+                                let _ = socket.send(Message::Close(None)).await;
+                                return;
+                            }
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return,
+                };
+                let json = serde_json::to_string(&envelope).unwrap_or_default();
+                if socket.send(Message::Text(json.into())).await.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Apply one client frame. Returns the frame to send back, if any.
+async fn handle_frame(
+    state: &Arc<AppState>,
+    session: &mut Option<String>,
+    frame: ClientFrame,
+) -> Option<Value> {
+    if let ClientFrame::Session { conversation_id } = frame {
+        let id = match conversation_id {
+            Some(id) => match state.threads.get(&id).await {
+                Ok(Some(_)) => id,
+                Ok(None) => return Some(error_frame(format!("conversation {id} not found"))),
+                Err(e) => return Some(error_frame(e.to_string())),
+            },
+            None => match state.threads.create(None, None, None).await {
+                Ok(meta) => meta.id,
+                Err(e) => return Some(error_frame(e.to_string())),
+            },
+        };
+        *session = Some(id.clone());
+        return Some(json!({ "type": "session_opened", "conversationId": id }));
+    }
+
+    let Some(conversation_id) = session.clone() else {
+        return Some(error_frame("no session: send a session frame first".into()));
+    };
+
+    match frame {
+        ClientFrame::Session { .. } => unreachable!("handled above"),
+        ClientFrame::Prompt { message, thinking_budget } => {
+            let req = ChatRequest {
+                conversation_id,
+                message,
+                user_message_id: None,
+                assistant_message_id: None,
+                thinking_budget,
+                best_of: None,
+            };
+            begin_turn(Arc::clone(state), req)
+                .await
+                .err()
+                .map(|status| error_frame(format!("prompt rejected: {status}")))
+        }
+        ClientFrame::Cancel => {
+            state.turns.cancel_turn(&conversation_id).await;
+            None
+        }
+        ClientFrame::Answer { question_id, value, dismissed } => {
+            let answer = nexus_tools::ask_user::UserAnswer { value, dismissed };
+            match resolve_question(state, &conversation_id, &question_id, answer).await {
+                Ok(()) => None,
+                Err(StatusCode::NOT_FOUND) => {
+                    Some(error_frame(format!("no pending question {question_id}")))
+                }
+                Err(status) => Some(error_frame(format!("answer rejected: {status}"))),
+            }
+        }
+        ClientFrame::Steer { message } => {
+            state
+                .turns
+                .message_queue
+                .enqueue(&conversation_id, QueuedMessage {
+                    text: message,
+                    metadata: Value::Null,
+                })
+                .await;
+            None
+        }
+    }
+}
+
+fn error_frame(message: String) -> Value {
+    json!({ "type": "error", "message": message })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn client_frames_deserialize() {
+        let frame: ClientFrame = serde_json::from_str(r#"{"type":"session"}"#).unwrap();
+        assert!(matches!(frame, ClientFrame::Session { conversation_id: None }));
+
+        let frame: ClientFrame =
+            serde_json::from_str(r#"{"type":"prompt","message":"hi","thinkingBudget":0}"#).unwrap();
+        assert!(matches!(
+            frame,
+            ClientFrame::Prompt { ref message, thinking_budget: Some(0) } if message == "hi"
+        ));
+
+        let frame: ClientFrame =
+            serde_json::from_str(r#"{"type":"answer","questionId":"q1","value":true}"#).unwrap();
+        assert!(matches!(
+            frame,
+            ClientFrame::Answer { ref question_id, dismissed: false, .. } if question_id == "q1"
+        ));
+
+        assert!(serde_json::from_str::<ClientFrame>(r#"{"type":"nope"}"#).is_err());
+    }
+}
@@ -0,0 +1,216 @@
+//! Instruction packs ("skills") loaded from `~/.nexus/skills`.
+//!
+//! A skill is a markdown file, `<name>.md` or `<name>/SKILL.md`, with
+//! optional YAML frontmatter setting `name` and `description`. Only the
+//! index — names and one-line descriptions — goes into the system prompt;
+//! the model reads a skill's full text with `load_skill` when a task calls
+//! for it. The directory is re-read every turn, so new or edited skills
+//! apply without a restart.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use nexus_provider::types::Tool;
+use serde::Deserialize;
+
+const LOAD_TOOL: &str = "load_skill";
+
+/// Longest description shown in the index, in characters.
+const MAX_DESCRIPTION_CHARS: usize = 160;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Skill {
+    pub name: String,
+    pub description: String,
+    /// Instructions, without the frontmatter.
+    pub body: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Frontmatter {
+    name: Option<String>,
+    description: Option<String>,
+}
+
+/// The skills available to a turn.
+#[derive(Debug, Default)]
+pub struct SkillLibrary {
+    skills: Vec<Skill>,
+}
+
+impl SkillLibrary {
+    /// Load every skill in `dir`. A missing directory yields no skills; a
+    /// file that can't be read, or repeats an earlier skill's name, is
+    /// skipped with a warning.
+    pub fn load(dir: &Path) -> Self {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return Self::default();
+        };
+        let mut paths: Vec<(String, PathBuf)> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter_map(|path| {
+                let stem = path.file_stem()?.to_str()?.to_string();
+                if path.is_dir() {
+                    let file = path.join("SKILL.md");
+                    file.is_file().then_some((stem, file))
+                } else {
+                    (path.extension().and_then(|e| e.to_str()) == Some("md")).then_some((stem, path))
+                }
+            })
+            .collect();
+        paths.sort();
+
+        let mut skills: Vec<Skill> = Vec::new();
+        for (stem, path) in paths {
+            let skill = match fs::read_to_string(&path) {
+                Ok(text) => parse(&stem, &text),
+                Err(e) => {
+                    tracing::warn!("Skipping skill {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            if skills.iter().any(|s| s.name == skill.name) {
+                tracing::warn!("Skipping skill {}: duplicate name '{}'", path.display(), skill.name);
+                continue;
+            }
+            skills.push(skill);
+        }
+        Self { skills }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.skills.is_empty()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Skill> {
+        self.skills.iter().find(|s| s.name == name)
+    }
+
+    /// The `<skills>` index for the system prompt; `None` without skills.
+    pub fn index(&self) -> Option<String> {
+        if self.skills.is_empty() {
+            return None;
+        }
+        let lines: Vec<String> = self
+            .skills
+            .iter()
+            .map(|s| format!("- {}: {}", s.name, s.description))
+            .collect();
+        Some(format!(
+            "<skills>\nInstruction packs for specific tasks. When one applies, call \
+             `{LOAD_TOOL}` with its name and follow the instructions it returns.\n{}\n</skills>",
+            lines.join("\n")
+        ))
+    }
+}
+
+/// Split optional `---` frontmatter from the body. A description defaults
+/// to the body's first line, a name to the file stem.
+fn parse(stem: &str, text: &str) -> Skill {
+    let (front, body) = text
+        .strip_prefix("---\n")
+        .and_then(|rest| rest.split_once("\n---\n"))
+        .map(|(front, body)| (serde_yaml::from_str::<Frontmatter>(front).unwrap_or_default(), body))
+        .unwrap_or_else(|| (Frontmatter::default(), text));
+    let body = body.trim().to_string();
+    let description = front.description.unwrap_or_else(|| {
+        body.lines()
+            .map(|l| l.trim_start_matches('#').trim())
+            .find(|l| !l.is_empty())
+            .unwrap_or_default()
+            .to_string()
+    });
+    let mut description: String = description.split_whitespace().collect::<Vec<_>>().join(" ");
+    if description.chars().count() > MAX_DESCRIPTION_CHARS {
+        description = description.chars().take(MAX_DESCRIPTION_CHARS).collect();
+        description.push('…');
+    }
+    Skill {
+        name: front.name.unwrap_or_else(|| stem.to_string()),
+        description,
+        body,
+    }
+}
+
+pub fn is_load_skill(name: &str) -> bool {
+    name == LOAD_TOOL
+}
+
+pub fn tool_definition() -> Tool {
+    Tool {
+        name: LOAD_TOOL.to_string(),
+        description: "Loads the full instructions of a skill listed in <skills>. Call it \
+            before starting a task a skill covers, then follow what it says."
+            .to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "The skill's name as shown in <skills>."
+                }
+            },
+            "required": ["name"]
+        }),
+    }
+}
+
+#[derive(Deserialize)]
+struct LoadArgs {
+    name: String,
+}
+
+/// Execute a load_skill call. Returns (content, is_error).
+pub fn execute(args_json: &str, library: &SkillLibrary) -> (String, bool) {
+    let args: LoadArgs = match serde_json::from_str(args_json) {
+        Ok(a) => a,
+        Err(e) => return (format!("Invalid arguments: {e}"), true),
+    };
+    match library.get(&args.name) {
+        Some(skill) => (skill.body.clone(), false),
+        None => {
+            let names: Vec<&str> = library.skills.iter().map(|s| s.name.as_str()).collect();
+            (
+                format!("No skill named '{}'. Available: {}", args.name, names.join(", ")),
+                true,
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loads_files_and_directories_with_optional_frontmatter() {
+        let dir = std::env::temp_dir().join(format!("nexus-skills-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("release.md"),
+            "---\ndescription: Cut and tag a release\n---\n\n1. Bump the version.\n",
+        )
+        .unwrap();
+        fs::create_dir(dir.join("triage")).unwrap();
+        fs::write(dir.join("triage/SKILL.md"), "# Sort incoming bug reports\n\nLabel each issue.").unwrap();
+        fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let library = SkillLibrary::load(&dir);
+        assert_eq!(
+            library.index().unwrap(),
+            "<skills>\nInstruction packs for specific tasks. When one applies, call `load_skill` \
+             with its name and follow the instructions it returns.\n\
+             - release: Cut and tag a release\n\
+             - triage: Sort incoming bug reports\n</skills>"
+        );
+
+        assert_eq!(execute(r#"{"name":"release"}"#, &library), ("1. Bump the version.".into(), false));
+        let (message, is_error) = execute(r#"{"name":"deploy"}"#, &library);
+        assert!(is_error);
+        assert!(message.ends_with("Available: release, triage"));
+
+        assert!(SkillLibrary::load(&dir.join("missing")).index().is_none());
+
+        fs::remove_dir_all(dir).ok();
+    }
+}
@@ -0,0 +1,157 @@
+use chrono::Local;
+
+use crate::config::EnvironmentField;
+
+use super::{SystemPromptContext, SystemPromptProvider};
+
+/// Per-turn facts for the `<environment>` block, gathered by the turn
+/// setup when `agent.environment.enabled` is set.
+#[derive(Debug, Clone, Default)]
+pub struct EnvironmentContext {
+    pub fields: Vec<EnvironmentField>,
+    /// Where the tools start from; the process directory if unset.
+    pub cwd: Option<String>,
+    /// Filesystem directories by workspace name (`None` for the default
+    /// tool suite).
+    pub workspaces: Vec<(Option<String>, Vec<String>)>,
+}
+
+/// Describes the machine and the turn's tools in the `<state_update>`, so
+/// it stays current without touching the cached system prompt.
+pub struct EnvironmentProvider;
+
+impl SystemPromptProvider for EnvironmentProvider {
+    fn name(&self) -> &str {
+        "environment"
+    }
+
+    fn cacheable(&self) -> bool {
+        false
+    }
+
+    fn provide(&self, ctx: &SystemPromptContext) -> Option<String> {
+        let env = ctx.environment.as_ref()?;
+        let mut lines = Vec::new();
+        for field in &env.fields {
+            match field {
+                EnvironmentField::Os => {
+                    lines.push(format!("OS: {} ({})", std::env::consts::OS, std::env::consts::ARCH));
+                }
+                EnvironmentField::Cwd => {
+                    let cwd = env.cwd.clone().or_else(|| {
+                        std::env::current_dir().ok().map(|d| d.display().to_string())
+                    });
+                    if let Some(cwd) = cwd {
+                        lines.push(format!("Working directory: {cwd}"));
+                    }
+                }
+                EnvironmentField::Date => {
+                    lines.push(format!("Date: {}", Local::now().format("%Y-%m-%d (%A)")));
+                }
+                EnvironmentField::Tools => {
+                    let groups = tool_groups(&ctx.tool_names);
+                    if !groups.is_empty() {
+                        let groups: Vec<String> =
+                            groups.iter().map(|(group, n)| format!("{group} ({n})")).collect();
+                        lines.push(format!("Tools: {}", groups.join(", ")));
+                    }
+                }
+                EnvironmentField::Workspace => {
+                    for (name, dirs) in &env.workspaces {
+                        if dirs.is_empty() {
+                            continue;
+                        }
+                        let label = match name {
+                            Some(name) => format!("Workspace \"{name}\" ({name}__* tools)"),
+                            None => "Workspace".to_string(),
+                        };
+                        lines.push(format!("{label}: {}", dirs.join(", ")));
+                    }
+                }
+            }
+        }
+        if lines.is_empty() {
+            return None;
+        }
+        Some(format!("<environment>\n{}\n</environment>", lines.join("\n")))
+    }
+}
+
+/// Which built-in toolset a tool belongs to.
+fn tool_group(name: &str) -> &'static str {
+    if nexus_tools::filesystem::is_filesystem_tool(name)
+        || nexus_tools::filesystem::split_namespaced(name).is_some()
+    {
+        "filesystem"
+    } else if nexus_tools::bash::is_bash(name) || nexus_tools::bg_process::is_bg_process_tool(name) {
+        "shell"
+    } else if nexus_tools::fetch::is_fetch(name) || crate::pruned_results::is_fetch_pruned(name) {
+        "web"
+    } else if crate::tasks::tools::is_builtin(name) || nexus_tools::ask_user::is_ask_user(name) {
+        "planning"
+    } else if crate::agent::sub_agent::is_sub_agent(name) {
+        "sub-agents"
+    } else if crate::control_plane::is_control_plane(name) {
+        "nexus settings"
+    } else if crate::mcp_resources::is_resource_tool(name) || name.starts_with("mcp_") {
+        "mcp"
+    } else {
+        "other"
+    }
+}
+
+/// Tool groups in first-seen order with their tool counts.
+fn tool_groups(tool_names: &[String]) -> Vec<(&'static str, usize)> {
+    let mut groups: Vec<(&'static str, usize)> = Vec::new();
+    for name in tool_names {
+        let group = tool_group(name);
+        match groups.iter_mut().find(|(g, _)| *g == group) {
+            Some((_, n)) => *n += 1,
+            None => groups.push((group, 1)),
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx(environment: Option<EnvironmentContext>) -> SystemPromptContext {
+        SystemPromptContext {
+            tool_names: ["read_file", "frontend__read_file", "bash", "task_create", "mcp_github__search"]
+                .iter()
+                .map(|t| t.to_string())
+                .collect(),
+            agent_name: "Nexus".into(),
+            custom_system_prompt: None,
+            prompt_vars: Default::default(),
+            mode: "general".into(),
+            environment,
+            skill_index: None,
+        }
+    }
+
+    #[test]
+    fn renders_configured_fields_in_order() {
+        assert!(EnvironmentProvider.provide(&ctx(None)).is_none());
+
+        let env = EnvironmentContext {
+            fields: vec![EnvironmentField::Tools, EnvironmentField::Cwd, EnvironmentField::Workspace],
+            cwd: Some("/repo".into()),
+            workspaces: vec![
+                (None, vec!["/repo".into()]),
+                (Some("frontend".into()), vec!["/web".into(), "/assets".into()]),
+            ],
+        };
+        assert_eq!(
+            EnvironmentProvider.provide(&ctx(Some(env))).unwrap(),
+            "<environment>\n\
+             Tools: filesystem (2), shell (1), planning (1), mcp (1)\n\
+             Working directory: /repo\n\
+             Workspace: /repo\n\
+             Workspace \"frontend\" (frontend__* tools): /web, /assets\n\
+             </environment>"
+        );
+    }
+}
@@ -1,15 +1,30 @@
+mod environment;
 mod fence;
 mod providers;
+mod template;
 
+use std::collections::HashMap;
+
+use crate::module::PromptSection;
+
+pub use environment::{EnvironmentContext, EnvironmentProvider};
 pub use fence::*;
 pub use providers::*;
+pub use template::render_system_prompt;
 
 /// Context passed to each provider so it can decide what to emit.
 pub struct SystemPromptContext {
     pub tool_names: Vec<String>,
     pub agent_name: String,
+    /// Rendered as a template; see [`render_system_prompt`].
     pub custom_system_prompt: Option<String>,
+    /// Extra template variables (`agent.prompt_vars` in `nexus.json`).
+    pub prompt_vars: HashMap<String, String>,
     pub mode: String,
+    /// Facts for the `<environment>` block; `None` when it is disabled.
+    pub environment: Option<EnvironmentContext>,
+    /// The `<skills>` index; `None` when no skills are installed.
+    pub skill_index: Option<String>,
 }
 
 /// A composable section of the system prompt.
@@ -66,22 +81,21 @@ impl SystemPromptBuilder {
     /// Build split parts: static system prompt (cached) and dynamic state
     /// (injected as a user message). This enables prompt caching by keeping
     /// the system prompt invariant across rounds and turns.
-    pub fn build_parts(&self, ctx: &SystemPromptContext) -> SystemPromptParts {
-        let system = self
-            .providers
-            .iter()
-            .filter(|p| p.cacheable())
-            .filter_map(|p| p.provide(ctx))
-            .collect::<Vec<_>>()
-            .join("\n\n");
-
-        let state_parts: Vec<String> = self
-            .providers
-            .iter()
-            .filter(|p| !p.cacheable())
-            .filter_map(|p| p.provide(ctx))
-            .collect();
+    ///
+    /// `system_sections` and `status_sections` are contributed by modules at
+    /// `turn_start`. A section named after a provider replaces that
+    /// provider's output in place (empty content removes it); the rest are
+    /// appended in order. `system_sections` can only override cacheable
+    /// providers, `status_sections` only dynamic ones.
+    pub fn build_parts(
+        &self,
+        ctx: &SystemPromptContext,
+        system_sections: &[PromptSection],
+        status_sections: &[PromptSection],
+    ) -> SystemPromptParts {
+        let system = self.assemble(ctx, true, system_sections).join("\n\n");
 
+        let state_parts = self.assemble(ctx, false, status_sections);
         let state = if state_parts.is_empty() {
             None
         } else {
@@ -94,6 +108,26 @@ impl SystemPromptBuilder {
         SystemPromptParts { system, state }
     }
 
+    fn assemble(&self, ctx: &SystemPromptContext, cacheable: bool, sections: &[PromptSection]) -> Vec<String> {
+        let providers: Vec<_> = self.providers.iter().filter(|p| p.cacheable() == cacheable).collect();
+        let override_for = |name: &str| sections.iter().rfind(|s| s.name == name);
+        let mut parts: Vec<String> = providers
+            .iter()
+            .filter_map(|p| match override_for(p.name()) {
+                Some(section) => Some(section.content.clone()),
+                None => p.provide(ctx),
+            })
+            .collect();
+        parts.extend(
+            sections
+                .iter()
+                .filter(|s| !providers.iter().any(|p| p.name() == s.name))
+                .map(|s| s.content.clone()),
+        );
+        parts.retain(|part| !part.is_empty());
+        parts
+    }
+
     /// Default builder with all standard providers registered.
     ///
     /// Static providers (cacheable) come first, followed by the state protocol
@@ -108,10 +142,67 @@ impl SystemPromptBuilder {
             .register(ModeProvider)
             .register(WorkflowProvider)
             .register(CorePromptProvider)
+            .register(SkillsProvider)
             .register(StateProtocolProvider)
             // Dynamic (not cacheable) providers — injected as <state_update>
             .register(DatetimeProvider)
+            .register(EnvironmentProvider)
             // Note: TaskContextProvider and ConversationContextProvider are now
             // DaemonModules that contribute via the turn_start hook.
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed(&'static str, &'static str, bool);
+
+    impl SystemPromptProvider for Fixed {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn provide(&self, _ctx: &SystemPromptContext) -> Option<String> {
+            Some(self.1.to_string())
+        }
+
+        fn cacheable(&self) -> bool {
+            self.2
+        }
+    }
+
+    fn section(name: &str, content: &str) -> PromptSection {
+        PromptSection { name: name.into(), content: content.into() }
+    }
+
+    #[test]
+    fn module_sections_replace_remove_or_append_by_name() {
+        let builder = SystemPromptBuilder::new()
+            .register(Fixed("identity", "I am Nexus.", true))
+            .register(Fixed("workflow", "Plan first.", true))
+            .register(Fixed("datetime", "<datetime/>", false));
+        let ctx = SystemPromptContext {
+            tool_names: Vec::new(),
+            agent_name: "Nexus".into(),
+            custom_system_prompt: None,
+            prompt_vars: HashMap::new(),
+            mode: "general".into(),
+            environment: None,
+            skill_index: None,
+        };
+
+        let parts = builder.build_parts(
+            &ctx,
+            &[section("safety", "Be careful."), section("identity", "I am Ops."), section("workflow", "")],
+            &[section("datetime", "<clock/>"), section("tasks", "<tasks/>")],
+        );
+        assert_eq!(parts.system, "I am Ops.\n\nBe careful.");
+        assert_eq!(parts.state.unwrap(), "<state_update>\n<clock/>\n<tasks/>\n</state_update>");
+
+        // A status section can't override a cacheable provider.
+        let parts = builder.build_parts(&ctx, &[], &[section("identity", "x")]);
+        assert_eq!(parts.system, "I am Nexus.\n\nPlan first.");
+        assert_eq!(parts.state.unwrap(), "<state_update>\n<datetime/>\nx\n</state_update>");
+    }
+}
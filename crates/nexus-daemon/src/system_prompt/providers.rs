@@ -62,7 +62,9 @@ impl SystemPromptProvider for CorePromptProvider {
     }
 
     fn provide(&self, ctx: &SystemPromptContext) -> Option<String> {
-        let prompt = ctx.custom_system_prompt.as_deref()?.trim();
+        let prompts_dir = crate::config::NexusConfig::nexus_dir().join("prompts");
+        let prompt = super::render_system_prompt(ctx.custom_system_prompt.as_deref()?, ctx, &prompts_dir);
+        let prompt = prompt.trim();
         if prompt.is_empty() {
             return None;
         }
@@ -369,3 +371,17 @@ impl SystemPromptProvider for SystemInfoProvider {
     }
 }
 
+
+// ── 11. Skills ──
+
+pub struct SkillsProvider;
+
+impl SystemPromptProvider for SkillsProvider {
+    fn name(&self) -> &str {
+        "skills"
+    }
+
+    fn provide(&self, ctx: &SystemPromptContext) -> Option<String> {
+        ctx.skill_index.clone()
+    }
+}
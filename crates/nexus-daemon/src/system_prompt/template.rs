@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use minijinja::{context, path_loader, Environment, UndefinedBehavior};
+
+use super::SystemPromptContext;
+
+/// Render an agent's custom system prompt as a minijinja template.
+///
+/// Available variables: `agent_name`, `tools` (list of tool names),
+/// `has_tools`, plus `agent.prompt_vars` from `nexus.json`. `{% include %}`
+/// resolves against `prompts_dir` (`~/.nexus/prompts`). Only inputs that
+/// are stable across a conversation are exposed, so the rendered prompt
+/// stays cacheable.
+///
+/// Prompts without template syntax render unchanged. A template that fails
+/// to render is used verbatim, with a warning.
+pub fn render_system_prompt(source: &str, ctx: &SystemPromptContext, prompts_dir: &Path) -> String {
+    match try_render(source, ctx, prompts_dir) {
+        Ok(rendered) => rendered,
+        Err(e) => {
+            tracing::warn!("System prompt template failed to render, using it verbatim: {:#}", e);
+            source.to_string()
+        }
+    }
+}
+
+fn try_render(
+    source: &str,
+    ctx: &SystemPromptContext,
+    prompts_dir: &Path,
+) -> Result<String, minijinja::Error> {
+    let mut env = Environment::new();
+    env.set_loader(path_loader(prompts_dir));
+    env.set_undefined_behavior(UndefinedBehavior::Strict);
+    env.set_trim_blocks(true);
+    env.set_lstrip_blocks(true);
+
+    let vars: &HashMap<String, String> = &ctx.prompt_vars;
+    env.render_str(
+        source,
+        context! {
+            agent_name => &ctx.agent_name,
+            tools => &ctx.tool_names,
+            has_tools => !ctx.tool_names.is_empty(),
+            ..minijinja::Value::from_serialize(vars)
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx(tools: &[&str]) -> SystemPromptContext {
+        SystemPromptContext {
+            tool_names: tools.iter().map(|t| t.to_string()).collect(),
+            agent_name: "Nexus".into(),
+            custom_system_prompt: None,
+            prompt_vars: HashMap::from([("team".to_string(), "Platform".to_string())]),
+            mode: "general".into(),
+            environment: None,
+            skill_index: None,
+        }
+    }
+
+    #[test]
+    fn renders_variables_conditionals_and_includes() {
+        let dir = std::env::temp_dir().join(format!("nexus-prompts-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("tools.md"), "Use tools sparingly.").unwrap();
+
+        let source = "You are {{ agent_name }} for {{ team }}.\n\
+                      {% if has_tools %}\n\
+                      {% include \"tools.md\" %}\n\
+                      {% endif %}";
+        assert_eq!(
+            render_system_prompt(source, &ctx(&["bash"]), &dir),
+            "You are Nexus for Platform.\nUse tools sparingly."
+        );
+        assert_eq!(
+            render_system_prompt(source, &ctx(&[]), &dir),
+            "You are Nexus for Platform.\n"
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn broken_templates_fall_back_to_source() {
+        let dir = Path::new("/nonexistent");
+        assert_eq!(render_system_prompt("Plain prompt.", &ctx(&[]), dir), "Plain prompt.");
+        assert_eq!(render_system_prompt("Hi {{ unknown }}", &ctx(&[]), dir), "Hi {{ unknown }}");
+        assert_eq!(render_system_prompt("Unclosed {% if", &ctx(&[]), dir), "Unclosed {% if");
+    }
+}
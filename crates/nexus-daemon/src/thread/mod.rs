@@ -5,7 +5,11 @@ use anyhow::{Context, Result};
 use chrono::Utc;
 use tokio::sync::RwLock;
 
-use crate::conversation::types::{ChatMessage, Conversation, ConversationMeta, ConversationUsage};
+use crate::agent::events::{replay, AgUiEvent, EventEnvelope};
+use crate::conversation::types::{
+    ChatMessage, Conversation, ConversationFilter, ConversationMeta, ConversationUsage,
+    MessagePart, MessageRole,
+};
 use crate::conversation::ConversationStore;
 use crate::event_bus::EventBus;
 
@@ -44,6 +48,17 @@ impl ThreadService {
         store.list().to_vec()
     }
 
+    /// List conversations matching a filter, in index order.
+    pub async fn list_filtered(&self, filter: &ConversationFilter) -> Vec<ConversationMeta> {
+        let store = self.store.read().await;
+        store
+            .list()
+            .iter()
+            .filter(|m| filter.matches(m))
+            .cloned()
+            .collect()
+    }
+
     /// Get a conversation by ID. Cache-first, falls through to disk.
     pub async fn get(&self, id: &str) -> Result<Option<Conversation>> {
         // Check cache first
@@ -63,6 +78,12 @@ impl ThreadService {
         Ok(conv)
     }
 
+    /// Read the persisted event log for a conversation, oldest first.
+    pub async fn events(&self, id: &str) -> Result<Vec<serde_json::Value>> {
+        let store = self.store.read().await;
+        store.read_events(id).context("failed to read event log")
+    }
+
     /// Build Anthropic API messages for a conversation.
     #[allow(dead_code)] // part of service API
     pub async fn build_api_messages(
@@ -107,6 +128,157 @@ impl ThreadService {
         Ok(())
     }
 
+    /// Delete every conversation whose `updated_at` is older than `max_age`,
+    /// skipping any ID in `exclude` (e.g. conversations with a running turn).
+    ///
+    /// Emits `thread_deleted` per conversation and a single global
+    /// `threads_purged` summary. Returns the purged IDs.
+    pub async fn purge_older_than(
+        &self,
+        max_age: chrono::Duration,
+        exclude: &[String],
+    ) -> Result<Vec<String>> {
+        let Some(cutoff) = Utc::now().checked_sub_signed(max_age) else {
+            anyhow::bail!("purge age {max_age} is out of range");
+        };
+        let stale: Vec<String> = self
+            .list()
+            .await
+            .into_iter()
+            .filter(|m| m.updated_at < cutoff && !exclude.contains(&m.id))
+            .map(|m| m.id)
+            .collect();
+
+        let mut purged = Vec::with_capacity(stale.len());
+        for id in stale {
+            match self.delete(&id).await {
+                Ok(()) => purged.push(id),
+                Err(e) => tracing::warn!(conversation_id = %id, "Failed to purge conversation: {}", e),
+            }
+        }
+
+        tracing::info!(count = purged.len(), "Purged stale conversations");
+        self.event_bus.emit_global(
+            "threads_purged",
+            serde_json::json!({ "count": purged.len(), "ids": &purged }),
+        );
+
+        Ok(purged)
+    }
+
+    /// Fold runs the daemon stopped in the middle of back into their
+    /// conversations, from the event log. Complete tool rounds are appended
+    /// to the active path, and the run is closed with a `RUN_ERROR` in the
+    /// log so it is recovered only once. Returns the number of conversations
+    /// that got messages back.
+    pub async fn recover_interrupted_runs(&self) -> usize {
+        let mut recovered = 0;
+        for meta in self.list().await {
+            let events = match self.events(&meta.id).await {
+                Ok(events) => events,
+                Err(e) => {
+                    tracing::warn!(conversation_id = %meta.id, "Failed to read event log: {}", e);
+                    continue;
+                }
+            };
+            let Some((run_id, messages)) = replay::interrupted_run(&events) else {
+                continue;
+            };
+            match self.append_recovered(&meta.id, &run_id, messages).await {
+                Ok(0) => {}
+                Ok(count) => {
+                    tracing::info!(conversation_id = %meta.id, run_id = %run_id, count, "Recovered interrupted run");
+                    recovered += 1;
+                }
+                Err(e) => {
+                    tracing::warn!(conversation_id = %meta.id, "Failed to recover interrupted run: {}", e)
+                }
+            }
+        }
+        recovered
+    }
+
+    async fn append_recovered(&self, id: &str, run_id: &str, messages: Vec<ChatMessage>) -> Result<usize> {
+        let mut store = self.store.write().await;
+        let Some(mut conv) = store.get(id).context("failed to load conversation")? else {
+            return Ok(0);
+        };
+
+        // The turn may have been persisted before the daemon stopped. Saved
+        // messages carry their runId; older saves only match on tool calls.
+        let saved = conv.messages.iter().any(|m| {
+            m.metadata.as_ref().and_then(|meta| meta["runId"].as_str()) == Some(run_id)
+        });
+        let call_ids = |m: &ChatMessage| -> Vec<String> {
+            m.parts
+                .iter()
+                .filter_map(|p| match p {
+                    MessagePart::ToolCall { tool_call_id, .. } => Some(tool_call_id.clone()),
+                    _ => None,
+                })
+                .collect()
+        };
+        let persisted: Vec<String> = conv.messages.iter().flat_map(call_ids).collect();
+        let messages = if saved || messages.iter().flat_map(call_ids).any(|id| persisted.contains(&id)) {
+            Vec::new()
+        } else {
+            messages
+        };
+
+        let count = messages.len();
+        if count > 0 {
+            let mut parent_id = conv.active_path.last().cloned();
+            for mut msg in messages {
+                msg.parent_id = parent_id.replace(msg.id.clone());
+                if let Some(obj) = msg.metadata.get_or_insert_with(|| serde_json::json!({})).as_object_mut() {
+                    obj.insert("recovered".into(), serde_json::json!(true));
+                }
+                conv.active_path.push(msg.id.clone());
+                conv.messages.push(msg);
+            }
+            // Ending on tool results would leave two user turns in a row
+            // once the user sends the next prompt.
+            if conv.messages.last().is_some_and(|m| m.role == MessageRole::User) {
+                let note = ChatMessage {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    role: MessageRole::Assistant,
+                    parts: vec![MessagePart::Text {
+                        text: "(Interrupted: the daemon stopped before this turn finished.)".into(),
+                    }],
+                    timestamp: Utc::now(),
+                    parent_id,
+                    source: None,
+                    metadata: Some(serde_json::json!({ "runId": run_id, "recovered": true })),
+                };
+                conv.active_path.push(note.id.clone());
+                conv.messages.push(note);
+            }
+            conv.updated_at = Utc::now();
+            store.save(&conv)?;
+        }
+
+        let closing = EventEnvelope {
+            thread_id: Some(id.to_string()),
+            run_id: Some(run_id.to_string()),
+            event: AgUiEvent::RunError {
+                message: "Interrupted: the daemon stopped before the run finished".into(),
+                details: Some(serde_json::json!({ "recoveredMessages": count })),
+            },
+        };
+        store.append_events(id, &[serde_json::to_string(&closing)?])?;
+        drop(store);
+
+        self.cache.invalidate(id).await;
+        Ok(count)
+    }
+
+    /// Append serialized event envelopes to a conversation's event log.
+    /// Only takes the read lock — appends don't touch the index.
+    pub async fn append_events(&self, id: &str, lines: &[String]) -> Result<()> {
+        let store = self.store.read().await;
+        store.append_events(id, lines)
+    }
+
     pub async fn set_workspace(&self, id: &str, workspace_id: Option<String>) -> Result<()> {
         let mut store = self.store.write().await;
         store.set_workspace(id, workspace_id.clone())?;
@@ -139,6 +311,13 @@ impl ThreadService {
         Ok(())
     }
 
+    /// Tag a conversation with the API client that opened it, which hides
+    /// it from the default listing.
+    #[cfg_attr(not(feature = "openai-api"), allow(dead_code))]
+    pub async fn set_origin(&self, id: &str, origin: &str) -> Result<()> {
+        self.store.write().await.set_origin(id, origin)
+    }
+
     pub async fn rename(&self, id: &str, title: &str) -> Result<()> {
         let mut store = self.store.write().await;
         store.rename(id, title)?;
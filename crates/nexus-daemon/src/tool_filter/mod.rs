@@ -57,6 +57,25 @@ impl ToolFilterChain {
     }
 }
 
+/// Move tools matching `priority` to the front, in pattern order, so the
+/// important ones aren't buried at the end of a long list. A pattern is a
+/// tool name or a `prefix*`. The rest keep their relative order.
+pub fn order_by_priority(tools: &mut [Tool], priority: &[String]) {
+    if priority.is_empty() {
+        return;
+    }
+    let rank = |name: &str| {
+        priority
+            .iter()
+            .position(|pattern| match pattern.strip_suffix('*') {
+                Some(prefix) => name.starts_with(prefix),
+                None => name == pattern,
+            })
+            .unwrap_or(priority.len())
+    };
+    tools.sort_by_cached_key(|t| rank(&t.name));
+}
+
 // ── Filter 1: Client-Only ──
 
 /// Excludes tools marked as client-only (MCP Apps visibility pattern).
@@ -146,6 +165,22 @@ mod tests {
         ]
     }
 
+    #[test]
+    fn priority_patterns_move_tools_to_the_front() {
+        let mut tools = all_tools();
+        order_by_priority(&mut tools, &["read_text_file".into(), "mcp_*".into()]);
+        let names: Vec<&str> = tools.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(
+            names[..5],
+            ["read_text_file", "mcp_read_file", "mcp_write_file", "mcp_run_tests", "task_create_plan"]
+        );
+        assert_eq!(names.len(), 14);
+
+        let mut unchanged = all_tools();
+        order_by_priority(&mut unchanged, &[]);
+        assert_eq!(unchanged[0].name, "task_create_plan");
+    }
+
     #[test]
     fn general_mode_allows_all() {
         let result = apply(AgentMode::General, all_tools());
@@ -0,0 +1,307 @@
+//! Post-turn one-line summaries as a DaemonModule.
+//!
+//! After each turn, emits a `turn_summary` event so long-running agent UIs
+//! can show a readable timeline ("Fixed the failing test · read_text_file ×3,
+//! edit_file") instead of raw tool noise. The text is built from the turn's
+//! tool calls and final answer, or written by the fast model tier when
+//! `conversations.turn_summary` is `"model"`.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::broadcast;
+
+use crate::agent::emitter::TurnEmitter;
+use crate::agent::events::EventEnvelope;
+use crate::agent_config::AgentService;
+use crate::auto_title;
+use crate::config::{ModelTierConfig, TurnSummaryMode};
+use crate::conversation::types::{ChatMessage, MessagePart, MessageRole};
+use crate::module::{DaemonModule, DoctorCheck, DoctorReport, DoctorStatus, TurnEndEvent};
+use crate::provider::ProviderService;
+use crate::thread::ThreadService;
+
+const SUMMARY_PROMPT: &str = "\
+Summarize what the assistant did in the turn below in one line of at most 12 words, \
+past tense, no preamble. Respond with ONLY the summary — no quotes.";
+
+/// Longest summary text, in characters.
+const MAX_SUMMARY_CHARS: usize = 120;
+
+pub struct TurnSummaryModule {
+    pub mode: TurnSummaryMode,
+    pub threads: Arc<ThreadService>,
+    pub agents: Arc<AgentService>,
+    pub providers: Arc<ProviderService>,
+    pub model_tiers: ModelTierConfig,
+    pub events: broadcast::Sender<EventEnvelope>,
+}
+
+#[async_trait]
+impl DaemonModule for TurnSummaryModule {
+    fn name(&self) -> &str {
+        "turn_summary"
+    }
+
+    async fn turn_end(&self, event: &mut TurnEndEvent<'_>) {
+        if event.error.is_some() || self.mode == TurnSummaryMode::Off {
+            return;
+        }
+        let Some(conv) = self.threads.get(event.conversation_id).await.ok().flatten() else {
+            return;
+        };
+        let active = conv.active_messages();
+        let Some(turn) = last_turn(&active) else {
+            return;
+        };
+
+        let heuristic = heuristic_summary(&turn);
+        if heuristic.is_empty() {
+            return;
+        }
+        let (text, source) = match self.mode {
+            TurnSummaryMode::Model => match self.model_summary(event.conversation_id, &turn).await {
+                Some(text) => (text, "model"),
+                None => (heuristic, "heuristic"),
+            },
+            _ => (heuristic, "heuristic"),
+        };
+
+        let emitter = TurnEmitter::new(
+            self.events.clone(),
+            event.conversation_id.to_string(),
+            event.run_id.to_string(),
+        );
+        emitter.turn_summary(turn.number, &text, source);
+    }
+
+    async fn doctor(&self) -> DoctorReport {
+        // Only the model mode depends on anything outside this module.
+        let has_provider = self.mode != TurnSummaryMode::Model
+            || auto_title::resolve_fast_provider(&self.agents, &self.providers, &self.model_tiers)
+                .await
+                .is_some();
+        DoctorReport {
+            module: "turn_summary".into(),
+            status: if has_provider {
+                DoctorStatus::Healthy
+            } else {
+                DoctorStatus::Degraded
+            },
+            checks: vec![DoctorCheck {
+                name: "summary_provider_available".into(),
+                passed: has_provider,
+                message: if has_provider {
+                    "Turn summaries are available".into()
+                } else {
+                    "No active agent/provider — summaries fall back to the heuristic".into()
+                },
+            }],
+        }
+    }
+}
+
+impl TurnSummaryModule {
+    async fn model_summary(&self, conversation_id: &str, turn: &Turn<'_>) -> Option<String> {
+        let (provider, model) =
+            auto_title::resolve_fast_provider(&self.agents, &self.providers, &self.model_tiers)
+                .await?;
+        let prompt = describe_turn(turn);
+        match auto_title::complete(provider.as_ref(), &model, SUMMARY_PROMPT, &prompt, 40).await {
+            Ok((text, cost)) => {
+                if cost > 0.0 {
+                    if let Err(e) = self.threads.add_cost(conversation_id, cost).await {
+                        tracing::error!("turn_summary: failed to save cost: {}", e);
+                    }
+                }
+                let text = clean(&text);
+                (!text.is_empty()).then_some(text)
+            }
+            Err(e) => {
+                tracing::warn!("turn_summary: summary generation failed: {}", e);
+                None
+            }
+        }
+    }
+}
+
+/// The latest turn: the last prompt and everything after it.
+struct Turn<'a> {
+    /// 1-based count of prompts in the active branch.
+    number: usize,
+    prompt: &'a str,
+    replies: &'a [&'a ChatMessage],
+}
+
+/// A user message carrying text, as opposed to one that only returns tool
+/// results to the model.
+fn prompt_text(msg: &ChatMessage) -> Option<&str> {
+    if msg.role != MessageRole::User {
+        return None;
+    }
+    msg.parts.iter().find_map(|p| match p {
+        MessagePart::Text { text } => Some(text.as_str()),
+        _ => None,
+    })
+}
+
+fn last_turn<'a>(messages: &'a [&'a ChatMessage]) -> Option<Turn<'a>> {
+    let number = messages.iter().filter(|m| prompt_text(m).is_some()).count();
+    let start = messages.iter().rposition(|m| prompt_text(m).is_some())?;
+    Some(Turn {
+        number,
+        prompt: prompt_text(messages[start])?,
+        replies: &messages[start + 1..],
+    })
+}
+
+/// Tool names in first-use order with call counts.
+fn tool_counts<'a>(turn: &Turn<'a>) -> Vec<(&'a str, usize)> {
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    let calls = turn.replies.iter().flat_map(|m| &m.parts).filter_map(|p| match p {
+        MessagePart::ToolCall { tool_name, .. } => Some(tool_name.as_str()),
+        _ => None,
+    });
+    for name in calls {
+        match counts.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((name, 1)),
+        }
+    }
+    counts
+}
+
+fn final_answer<'a>(turn: &Turn<'a>) -> Option<&'a str> {
+    turn.replies
+        .iter()
+        .rev()
+        .filter(|m| m.role == MessageRole::Assistant)
+        .flat_map(|m| m.parts.iter().rev())
+        .find_map(|p| match p {
+            MessagePart::Text { text } if !text.trim().is_empty() => Some(text.as_str()),
+            _ => None,
+        })
+}
+
+/// "<first line of the answer> · tool ×n, tool".
+fn heuristic_summary(turn: &Turn<'_>) -> String {
+    let tools = tool_counts(turn)
+        .into_iter()
+        .map(|(name, n)| if n > 1 { format!("{name} ×{n}") } else { name.to_string() })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let answer = final_answer(turn).map(clean).unwrap_or_default();
+    match (answer.is_empty(), tools.is_empty()) {
+        (false, false) => format!("{answer} · {tools}"),
+        (false, true) => answer,
+        (true, false) => format!("Used {tools}"),
+        (true, true) => String::new(),
+    }
+}
+
+/// Prompt for the summary model.
+fn describe_turn(turn: &Turn<'_>) -> String {
+    let mut lines = vec![format!("User: {}", truncate(turn.prompt, 300))];
+    for (name, n) in tool_counts(turn) {
+        lines.push(format!("Tool call: {name} ({n}×)"));
+    }
+    if let Some(answer) = final_answer(turn) {
+        lines.push(format!("Assistant: {}", truncate(answer, 300)));
+    }
+    lines.join("\n")
+}
+
+/// First non-empty line, unquoted, markdown heading marks removed, capped
+/// at [`MAX_SUMMARY_CHARS`].
+fn clean(text: &str) -> String {
+    let line = text.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or_default();
+    let line = line.trim_start_matches('#').trim().trim_matches(|c: char| c == '"' || c == '\'');
+    truncate(line.trim(), MAX_SUMMARY_CHARS)
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() > max_chars {
+        let mut cut: String = text.chars().take(max_chars).collect();
+        cut.push('…');
+        cut
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn msg(role: MessageRole, parts: Vec<MessagePart>) -> ChatMessage {
+        ChatMessage {
+            id: String::new(),
+            role,
+            parts,
+            timestamp: chrono::Utc::now(),
+            parent_id: None,
+            source: None,
+            metadata: None,
+        }
+    }
+
+    fn text(text: &str) -> MessagePart {
+        MessagePart::Text { text: text.into() }
+    }
+
+    fn call(name: &str) -> MessagePart {
+        MessagePart::ToolCall {
+            tool_call_id: String::new(),
+            tool_name: name.into(),
+            args: serde_json::json!({}),
+            result: None,
+            is_error: false,
+        }
+    }
+
+    fn result() -> MessagePart {
+        MessagePart::ToolResult {
+            tool_call_id: String::new(),
+            result: String::new(),
+            is_error: false,
+            images: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn summarizes_the_last_turn_from_tools_and_answer() {
+        let messages = [
+            msg(MessageRole::User, vec![text("hi")]),
+            msg(MessageRole::Assistant, vec![text("Hello!")]),
+            msg(MessageRole::User, vec![text("fix the test")]),
+            msg(MessageRole::Assistant, vec![call("read_text_file"), call("read_text_file")]),
+            msg(MessageRole::User, vec![result(), result()]),
+            msg(MessageRole::Assistant, vec![call("edit_file")]),
+            msg(MessageRole::User, vec![result()]),
+            msg(MessageRole::Assistant, vec![text("\n## Fixed the off-by-one in parse()\n\nDetails…")]),
+        ];
+        let refs: Vec<&ChatMessage> = messages.iter().collect();
+        let turn = last_turn(&refs).unwrap();
+        assert_eq!(turn.number, 2);
+        assert_eq!(turn.prompt, "fix the test");
+        assert_eq!(
+            heuristic_summary(&turn),
+            "Fixed the off-by-one in parse() · read_text_file ×2, edit_file"
+        );
+        assert!(describe_turn(&turn).contains("Tool call: read_text_file (2×)"));
+    }
+
+    #[test]
+    fn summary_without_answer_or_tools() {
+        let messages = [
+            msg(MessageRole::User, vec![text("run it")]),
+            msg(MessageRole::Assistant, vec![call("bash")]),
+        ];
+        let refs: Vec<&ChatMessage> = messages.iter().collect();
+        assert_eq!(heuristic_summary(&last_turn(&refs).unwrap()), "Used bash");
+
+        let refs: Vec<&ChatMessage> = messages[..1].iter().collect();
+        assert_eq!(heuristic_summary(&last_turn(&refs).unwrap()), "");
+        assert_eq!(clean(&"x".repeat(200)).chars().count(), MAX_SUMMARY_CHARS + 1);
+    }
+}
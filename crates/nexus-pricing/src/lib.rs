@@ -118,6 +118,13 @@ pub fn calculate_cost_with_cache(
     uncached_cost + cache_write_cost + cache_read_cost + output_cost
 }
 
+/// Cost in USD of server-side web searches, billed per request on top of tokens.
+///
+/// Reference: https://docs.anthropic.com/en/docs/agents-and-tools/tool-use/web-search-tool#usage-and-pricing
+pub fn web_search_cost(requests: u32) -> f64 {
+    requests as f64 * WEB_SEARCH_PER_1K_REQUESTS / 1_000.0
+}
+
 /// Get the context window for a model.
 pub fn context_window(model: &str) -> u32 {
     lookup(model).context_window
@@ -126,6 +133,8 @@ pub fn context_window(model: &str) -> u32 {
 // ── Pricing constants ──
 // Source: https://docs.anthropic.com/en/docs/about-claude/pricing
 
+const WEB_SEARCH_PER_1K_REQUESTS: f64 = 10.0;
+
 const OPUS_4_6: ModelPricing = ModelPricing {
     input_per_mtok: 5.0,
     output_per_mtok: 25.0,
@@ -288,6 +297,12 @@ mod tests {
         assert!(cost < no_cache, "cached cost should be cheaper");
     }
 
+    #[test]
+    fn web_search_is_billed_per_request() {
+        assert_eq!(web_search_cost(0), 0.0);
+        assert!((web_search_cost(3) - 0.03).abs() < 1e-10);
+    }
+
     #[test]
     fn context_window_returns_correct_value() {
         assert_eq!(context_window("claude-opus-4-6"), 200_000);
@@ -0,0 +1,421 @@
+//! Response cache keyed by a semantic hash of the request.
+//!
+//! [`semantic_key`] hashes what determines the model's answer — model,
+//! sampling parameters, system prompt, tools and messages — after normalizing
+//! away what doesn't: whitespace runs, tool call ids (replaced by their order
+//! of appearance), thinking blocks and the idempotency key. Identical or
+//! near-identical requests, common in eval loops and re-run conversations,
+//! therefore share a key. A suffix on the idempotency key (`{key}-{i}`, set
+//! by best-of sampling and racing on deliberate duplicates) is kept, so
+//! those requests stay apart.
+//!
+//! [`CachingProvider`] serves such requests from a shared [`ResponseCache`]
+//! by replaying the recorded events with their usage zeroed, since a hit
+//! spends no tokens. Only streams that complete with
+//! `MessageStop` and no error are stored; entries expire after the cache's
+//! TTL, and the oldest are evicted beyond its capacity.
+
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use futures::{Stream, StreamExt};
+use serde::Serialize;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::types::{ContentBlock, Message, StreamEvent, Usage};
+use crate::{EventStream, InferenceProvider, InferenceRequest};
+
+/// Cache key for `request` within `scope` (e.g. the provider id).
+pub fn semantic_key(scope: &str, request: &InferenceRequest) -> String {
+    let mut tool_ids = HashMap::new();
+    let messages: Vec<Value> = request
+        .messages
+        .iter()
+        .map(|m| normalize_message(m, &mut tool_ids))
+        .collect();
+    let tools: Vec<Value> = request
+        .tools
+        .iter()
+        .map(|t| json!([t.name, collapse(&t.description), t.input_schema]))
+        .collect();
+    let content = json!({
+        "scope": scope,
+        "model": request.model,
+        "max_tokens": request.max_tokens,
+        "temperature": request.temperature,
+        "thinking_budget": request.thinking_budget,
+        "variant": variant(request),
+        "system": request.system.as_deref().map(collapse),
+        "tools": tools,
+        "messages": messages,
+    });
+    let digest = Sha256::digest(content.to_string().as_bytes());
+    digest.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// The suffix callers add to an idempotency key to tell deliberate
+/// duplicates apart. Derived keys are hex digests, so anything after the
+/// first `-` is such a suffix.
+fn variant(request: &InferenceRequest) -> Option<&str> {
+    request.idempotency_key.as_deref()?.split_once('-').map(|(_, suffix)| suffix)
+}
+
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn normalize_message(message: &Message, tool_ids: &mut HashMap<String, usize>) -> Value {
+    let mut ordinal = |id: &str| {
+        let next = tool_ids.len();
+        *tool_ids.entry(id.to_string()).or_insert(next)
+    };
+    let blocks: Vec<Value> = message
+        .content
+        .iter()
+        .filter_map(|block| match block {
+            ContentBlock::Text { text } => Some(json!(["text", collapse(text)])),
+            ContentBlock::ToolUse { id, name, input } => {
+                Some(json!(["tool_use", ordinal(id), name, input]))
+            }
+            ContentBlock::ToolResult { tool_use_id, content, is_error } => {
+                let images: Vec<_> = content.images().collect();
+                Some(json!([
+                    "tool_result",
+                    ordinal(tool_use_id),
+                    collapse(&content.text()),
+                    images,
+                    is_error.unwrap_or(false),
+                ]))
+            }
+            ContentBlock::ServerToolUse { id, name, input } => {
+                Some(json!(["server_tool_use", ordinal(id), name, input]))
+            }
+            ContentBlock::WebSearchToolResult { tool_use_id, content }
+            | ContentBlock::CodeExecutionToolResult { tool_use_id, content } => {
+                Some(json!(["server_tool_result", ordinal(tool_use_id), content]))
+            }
+            ContentBlock::Thinking { .. } | ContentBlock::RedactedThinking { .. } => None,
+        })
+        .collect();
+    json!([message.role, blocks])
+}
+
+/// Hit/miss counters and size of a [`ResponseCache`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+}
+
+type Events = Arc<Vec<StreamEvent>>;
+
+/// Completed responses by semantic key, shared by the providers that use it.
+pub struct ResponseCache {
+    ttl: Duration,
+    max_entries: usize,
+    entries: Mutex<HashMap<String, (Events, DateTime<Utc>)>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl ResponseCache {
+    pub fn new(ttl: std::time::Duration, max_entries: usize) -> Self {
+        Self {
+            ttl: Duration::from_std(ttl).unwrap_or(Duration::MAX),
+            max_entries: max_entries.max(1),
+            entries: Mutex::default(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.entries.lock().unwrap().len(),
+        }
+    }
+
+    /// Drop every entry; counters are kept.
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    fn get(&self, key: &str) -> Option<Events> {
+        let mut entries = self.entries.lock().unwrap();
+        let hit = match entries.get(key) {
+            Some((events, at)) if Utc::now() - *at < self.ttl => Some(Arc::clone(events)),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        };
+        let counter = if hit.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        hit
+    }
+
+    fn insert(&self, key: String, events: Vec<StreamEvent>) {
+        let mut entries = self.entries.lock().unwrap();
+        let now = Utc::now();
+        entries.retain(|_, (_, at)| now - *at < self.ttl);
+        while entries.len() >= self.max_entries {
+            let Some(oldest) = entries.iter().min_by_key(|(_, (_, at))| *at).map(|(k, _)| k.clone())
+            else {
+                break;
+            };
+            entries.remove(&oldest);
+        }
+        entries.insert(key, (Arc::new(events), now));
+    }
+}
+
+/// Wraps a provider with a [`ResponseCache`]. `scope` keeps providers that
+/// share the cache apart (the same model can answer differently elsewhere).
+pub struct CachingProvider {
+    inner: Arc<dyn InferenceProvider>,
+    cache: Arc<ResponseCache>,
+    scope: String,
+}
+
+impl CachingProvider {
+    pub fn new(inner: Arc<dyn InferenceProvider>, cache: Arc<ResponseCache>, scope: impl Into<String>) -> Self {
+        Self { inner, cache, scope: scope.into() }
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl InferenceProvider for CachingProvider {
+    async fn create_message_stream(&self, request: InferenceRequest) -> Result<EventStream> {
+        let key = semantic_key(&self.scope, &request);
+        if let Some(events) = self.cache.get(&key) {
+            tracing::debug!(key = %key, "Serving response from cache");
+            return Ok(Box::pin(futures::stream::iter(
+                events.iter().cloned().map(unbilled).map(Ok).collect::<Vec<_>>(),
+            )));
+        }
+        let stream = self.inner.create_message_stream(request).await?;
+        Ok(Box::pin(Recorder {
+            inner: stream,
+            events: Some(Vec::new()),
+            key,
+            cache: Arc::clone(&self.cache),
+        }))
+    }
+}
+
+/// A replayed event with its usage zeroed, so cost accounting downstream
+/// doesn't bill a hit for tokens that were spent on the original call.
+fn unbilled(event: StreamEvent) -> StreamEvent {
+    match event {
+        StreamEvent::MessageStart { message_id, model, role, usage } => StreamEvent::MessageStart {
+            message_id,
+            model,
+            role,
+            usage: usage.map(|_| Usage::default()),
+        },
+        StreamEvent::MessageDelta { stop_reason, usage } => StreamEvent::MessageDelta {
+            stop_reason,
+            usage: usage.map(|_| Usage::default()),
+        },
+        event => event,
+    }
+}
+
+/// Passes the stream through while recording it; stores the events on
+/// `MessageStop` unless the stream errored first.
+struct Recorder {
+    inner: EventStream,
+    /// `None` once stored or after an error.
+    events: Option<Vec<StreamEvent>>,
+    key: String,
+    cache: Arc<ResponseCache>,
+}
+
+impl Stream for Recorder {
+    type Item = Result<StreamEvent>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let polled = self.inner.poll_next_unpin(cx);
+        match &polled {
+            Poll::Ready(Some(Ok(StreamEvent::Error { .. }))) | Poll::Ready(Some(Err(_))) => {
+                self.events = None;
+            }
+            Poll::Ready(Some(Ok(event))) => {
+                let done = matches!(event, StreamEvent::MessageStop);
+                if let Some(events) = self.events.as_mut() {
+                    events.push(event.clone());
+                }
+                if done {
+                    if let Some(events) = self.events.take() {
+                        let key = std::mem::take(&mut self.key);
+                        self.cache.insert(key, events);
+                    }
+                }
+            }
+            _ => {}
+        }
+        polled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use super::*;
+    use crate::types::{Role, Tool};
+
+    struct Counting {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl InferenceProvider for Counting {
+        async fn create_message_stream(&self, _request: InferenceRequest) -> Result<EventStream> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(Box::pin(futures::stream::iter(vec![
+                Ok(StreamEvent::MessageStart {
+                    message_id: format!("msg-{call}"),
+                    model: "m".into(),
+                    role: Role::Assistant,
+                    usage: Some(Usage { input_tokens: 100, ..Default::default() }),
+                }),
+                Ok(StreamEvent::MessageDelta {
+                    stop_reason: None,
+                    usage: Some(Usage { output_tokens: 20, ..Default::default() }),
+                }),
+                Ok(StreamEvent::MessageStop),
+            ])))
+        }
+    }
+
+    fn request(text: &str, tool_id: &str) -> InferenceRequest {
+        InferenceRequest {
+            model: "m".into(),
+            max_tokens: 16,
+            system: Some("You are  helpful.\n".into()),
+            temperature: None,
+            thinking_budget: None,
+            messages: vec![
+                Message { role: Role::User, content: vec![ContentBlock::Text { text: text.into() }] },
+                Message {
+                    role: Role::Assistant,
+                    content: vec![
+                        ContentBlock::Thinking { thinking: tool_id.into(), signature: None },
+                        ContentBlock::ToolUse {
+                            id: tool_id.into(),
+                            name: "read_file".into(),
+                            input: json!({"path": "a.rs"}),
+                        },
+                    ],
+                },
+                Message {
+                    role: Role::User,
+                    content: vec![ContentBlock::ToolResult {
+                        tool_use_id: tool_id.into(),
+                        content: "fn main() {}".into(),
+                        is_error: None,
+                    }],
+                },
+            ],
+            tools: vec![Tool {
+                name: "read_file".into(),
+                description: "Read a file".into(),
+                input_schema: json!({"type": "object"}),
+            }],
+            idempotency_key: Some(tool_id.into()),
+        }
+    }
+
+    #[test]
+    fn keys_ignore_whitespace_tool_ids_and_thinking() {
+        let key = semantic_key("p", &request("read  a.rs", "toolu_1"));
+        assert_eq!(key, semantic_key("p", &request(" read a.rs\n", "toolu_2")));
+        assert_ne!(key, semantic_key("p", &request("read b.rs", "toolu_1")));
+        assert_ne!(key, semantic_key("q", &request("read a.rs", "toolu_1")));
+        let mut hotter = request("read a.rs", "toolu_1");
+        hotter.temperature = Some(1.0);
+        assert_ne!(key, semantic_key("p", &hotter));
+    }
+
+    #[test]
+    fn keys_keep_duplicate_suffixes_apart() {
+        let keyed = |key: &str| {
+            let mut request = request("read a.rs", "toolu_1");
+            request.idempotency_key = Some(key.into());
+            semantic_key("p", &request)
+        };
+        assert_eq!(keyed("abc"), keyed("def"));
+        assert_ne!(keyed("abc-0"), keyed("abc-1"));
+        assert_eq!(keyed("abc-1"), keyed("def-1"));
+    }
+
+    #[test]
+    fn hits_report_no_usage() {
+        futures::executor::block_on(async {
+            let inner = Arc::new(Counting { calls: AtomicUsize::new(0) });
+            let cache = Arc::new(ResponseCache::new(std::time::Duration::from_secs(60), 10));
+            let provider = CachingProvider::new(inner, cache, "p");
+
+            let mut billed = Vec::new();
+            for _ in 0..2 {
+                let events: Vec<_> =
+                    provider.create_message_stream(request("read a.rs", "t")).await.unwrap().collect().await;
+                let tokens: u32 = events
+                    .iter()
+                    .filter_map(|e| match e {
+                        Ok(StreamEvent::MessageStart { usage: Some(u), .. })
+                        | Ok(StreamEvent::MessageDelta { usage: Some(u), .. }) => {
+                            Some(u.input_tokens + u.output_tokens)
+                        }
+                        _ => None,
+                    })
+                    .sum();
+                billed.push(tokens);
+            }
+            assert_eq!(billed, [120, 0]);
+        });
+    }
+
+    #[test]
+    fn completed_responses_are_served_until_they_expire() {
+        futures::executor::block_on(async {
+            let inner = Arc::new(Counting { calls: AtomicUsize::new(0) });
+            let cache = Arc::new(ResponseCache::new(std::time::Duration::from_secs(60), 10));
+            let provider = CachingProvider::new(inner.clone(), Arc::clone(&cache), "p");
+
+            for tool_id in ["toolu_1", "toolu_2"] {
+                let events: Vec<_> = provider
+                    .create_message_stream(request("read a.rs", tool_id))
+                    .await
+                    .unwrap()
+                    .collect()
+                    .await;
+                assert!(matches!(&events[0], Ok(StreamEvent::MessageStart { message_id, .. }) if message_id == "msg-0"));
+            }
+            assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+            assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 1, entries: 1 });
+
+            let expired = Arc::new(ResponseCache::new(std::time::Duration::ZERO, 10));
+            let provider = CachingProvider::new(inner.clone(), Arc::clone(&expired), "p");
+            for _ in 0..2 {
+                let _: Vec<_> = provider.create_message_stream(request("x", "t")).await.unwrap().collect().await;
+            }
+            assert_eq!(inner.calls.load(Ordering::SeqCst), 3);
+            assert_eq!(expired.stats().hits, 0);
+        });
+    }
+}
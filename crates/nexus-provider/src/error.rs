@@ -25,6 +25,13 @@ pub struct ProviderError {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub status_code: Option<u16>,
     pub retryable: bool,
+    /// The provider's own error code (e.g. `rate_limit_error`,
+    /// `ThrottlingException`), when it sent one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// Server-requested wait before retrying (`retry-after` header), if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after_ms: Option<u64>,
     pub provider: String,
 }
 
@@ -37,6 +44,22 @@ impl fmt::Display for ProviderError {
 impl std::error::Error for ProviderError {}
 
 impl ProviderError {
+    /// Whether the request can be retried as-is (rate limits, overload,
+    /// transient server errors).
+    pub fn is_retryable(&self) -> bool {
+        self.retryable
+    }
+
+    /// Attach a `retry-after` header value. Accepts delta-seconds (integer
+    /// or fractional); HTTP-date values are ignored.
+    pub fn with_retry_after(mut self, header: Option<&str>) -> Self {
+        self.retry_after_ms = header
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .filter(|secs| secs.is_finite() && *secs >= 0.0)
+            .map(|secs| (secs * 1000.0) as u64);
+        self
+    }
+
     /// User-facing title for this error kind.
     pub fn title(&self) -> &'static str {
         match self.kind {
@@ -56,27 +79,25 @@ impl ProviderError {
     /// Takes the raw HTTP status code as `u16` to avoid coupling to any
     /// specific HTTP client library.
     pub fn from_anthropic_http(status_code: u16, body: &str) -> Self {
+        let parsed = serde_json::from_str::<serde_json::Value>(body).ok();
+        let error_type = parsed
+            .as_ref()
+            .and_then(|v| v["error"]["type"].as_str());
+
         let (kind, retryable) = match status_code {
             401 | 403 => (ProviderErrorKind::Authentication, false),
-            400 => {
-                if body.contains("prompt is too long")
-                    || body.contains("too many tokens")
-                    || body.contains("context length")
-                {
-                    (ProviderErrorKind::ContextLength, false)
-                } else {
-                    (ProviderErrorKind::InvalidRequest, false)
-                }
-            }
+            400 if is_context_length(body) => (ProviderErrorKind::ContextLength, false),
+            400 => (ProviderErrorKind::InvalidRequest, false),
             429 => (ProviderErrorKind::RateLimit, true),
             529 => (ProviderErrorKind::Overloaded, true),
-            500 | 502 | 503 => (ProviderErrorKind::ServerError, true),
-            _ => (ProviderErrorKind::Unknown, false),
+            500 | 502 | 503 | 504 => (ProviderErrorKind::ServerError, true),
+            // Unmapped status: fall back to the typed error in the body
+            _ => anthropic_error_type(error_type),
         };
 
         // Try to extract the human-readable message from the JSON body
-        let message = serde_json::from_str::<serde_json::Value>(body)
-            .ok()
+        let message = parsed
+            .as_ref()
             .and_then(|v| v["error"]["message"].as_str().map(|s| s.to_string()))
             .unwrap_or_else(|| format!("HTTP {}: {}", status_code, body));
 
@@ -85,6 +106,8 @@ impl ProviderError {
             message,
             status_code: Some(status_code),
             retryable,
+            code: error_type.map(str::to_string),
+            retry_after_ms: None,
             provider: "anthropic".to_string(),
         }
     }
@@ -92,13 +115,10 @@ impl ProviderError {
     /// Parse an Anthropic SSE error event.
     pub fn from_anthropic_stream(error_type: Option<&str>, message: &str) -> Self {
         let (kind, retryable) = match error_type {
-            Some("rate_limit_error") => (ProviderErrorKind::RateLimit, true),
-            Some("authentication_error") => (ProviderErrorKind::Authentication, false),
-            Some("permission_error") => (ProviderErrorKind::Authentication, false),
-            Some("invalid_request_error") => (ProviderErrorKind::InvalidRequest, false),
-            Some("overloaded_error") => (ProviderErrorKind::Overloaded, true),
-            Some("api_error") => (ProviderErrorKind::ServerError, true),
-            _ => (ProviderErrorKind::Unknown, false),
+            Some("invalid_request_error") if is_context_length(message) => {
+                (ProviderErrorKind::ContextLength, false)
+            }
+            other => anthropic_error_type(other),
         };
 
         Self {
@@ -106,40 +126,159 @@ impl ProviderError {
             message: message.to_string(),
             status_code: None,
             retryable,
+            code: error_type.map(str::to_string),
+            retry_after_ms: None,
             provider: "anthropic".to_string(),
         }
     }
 
-    /// Parse an AWS Bedrock error.
+    /// Parse an AWS Bedrock error from its formatted text, looking for a
+    /// known exception name. Prefer [`from_bedrock_code`](Self::from_bedrock_code)
+    /// when the SDK exposes the code.
     pub fn from_bedrock(error: &str) -> Self {
-        let (kind, retryable) = if error.contains("ThrottlingException")
-            || error.contains("rate")
-            || error.contains("TooManyRequestsException")
-        {
-            (ProviderErrorKind::RateLimit, true)
-        } else if error.contains("AccessDeniedException")
-            || error.contains("UnrecognizedClientException")
-        {
-            (ProviderErrorKind::Authentication, false)
-        } else if error.contains("ValidationException") {
-            (ProviderErrorKind::InvalidRequest, false)
-        } else if error.contains("ModelStreamErrorException")
-            || error.contains("ServiceUnavailableException")
-            || error.contains("InternalServerException")
-        {
-            (ProviderErrorKind::ServerError, true)
-        } else if error.contains("ModelTimeoutException") {
-            (ProviderErrorKind::Overloaded, true)
-        } else {
-            (ProviderErrorKind::Unknown, false)
-        };
+        let code = BEDROCK_CODES
+            .iter()
+            .map(|(code, _, _)| *code)
+            .find(|code| error.contains(code));
+        let mut err = Self::from_bedrock_code(code, error);
+        if code.is_none() && error.contains("rate") {
+            err.kind = ProviderErrorKind::RateLimit;
+            err.retryable = true;
+        }
+        err
+    }
+
+    /// Build a Bedrock error from the service's error code and message.
+    pub fn from_bedrock_code(code: Option<&str>, message: &str) -> Self {
+        let (kind, retryable) = code
+            .and_then(|c| BEDROCK_CODES.iter().find(|(name, _, _)| *name == c))
+            .map(|(_, kind, retryable)| (*kind, *retryable))
+            .unwrap_or((ProviderErrorKind::Unknown, false));
 
         Self {
             kind,
-            message: error.to_string(),
+            message: message.to_string(),
             status_code: None,
             retryable,
+            code: code.map(str::to_string),
+            retry_after_ms: None,
             provider: "bedrock".to_string(),
         }
     }
 }
+
+/// Bedrock exception names and how they classify.
+const BEDROCK_CODES: &[(&str, ProviderErrorKind, bool)] = &[
+    ("ThrottlingException", ProviderErrorKind::RateLimit, true),
+    ("TooManyRequestsException", ProviderErrorKind::RateLimit, true),
+    ("ServiceQuotaExceededException", ProviderErrorKind::RateLimit, false),
+    ("AccessDeniedException", ProviderErrorKind::Authentication, false),
+    ("UnrecognizedClientException", ProviderErrorKind::Authentication, false),
+    ("ValidationException", ProviderErrorKind::InvalidRequest, false),
+    ("ResourceNotFoundException", ProviderErrorKind::InvalidRequest, false),
+    ("ModelStreamErrorException", ProviderErrorKind::ServerError, true),
+    ("ServiceUnavailableException", ProviderErrorKind::ServerError, true),
+    ("InternalServerException", ProviderErrorKind::ServerError, true),
+    ("ModelTimeoutException", ProviderErrorKind::Overloaded, true),
+    ("ModelNotReadyException", ProviderErrorKind::Overloaded, true),
+];
+
+/// Classify an Anthropic `error.type` string.
+fn anthropic_error_type(error_type: Option<&str>) -> (ProviderErrorKind, bool) {
+    match error_type {
+        Some("rate_limit_error") => (ProviderErrorKind::RateLimit, true),
+        Some("authentication_error") => (ProviderErrorKind::Authentication, false),
+        Some("permission_error") => (ProviderErrorKind::Authentication, false),
+        Some("invalid_request_error") => (ProviderErrorKind::InvalidRequest, false),
+        Some("overloaded_error") => (ProviderErrorKind::Overloaded, true),
+        Some("api_error") => (ProviderErrorKind::ServerError, true),
+        _ => (ProviderErrorKind::Unknown, false),
+    }
+}
+
+fn is_context_length(text: &str) -> bool {
+    text.contains("prompt is too long")
+        || text.contains("too many tokens")
+        || text.contains("context length")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn http_status_classification() {
+        let e = ProviderError::from_anthropic_http(429, r#"{"error":{"type":"rate_limit_error","message":"slow down"}}"#);
+        assert_eq!(e.kind, ProviderErrorKind::RateLimit);
+        assert!(e.is_retryable());
+        assert_eq!(e.message, "slow down");
+
+        let e = ProviderError::from_anthropic_http(400, r#"{"error":{"message":"prompt is too long: 210000 tokens"}}"#);
+        assert_eq!(e.kind, ProviderErrorKind::ContextLength);
+        assert!(!e.is_retryable());
+    }
+
+    #[test]
+    fn unmapped_status_falls_back_to_body_error_type() {
+        let e = ProviderError::from_anthropic_http(
+            418,
+            r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#,
+        );
+        assert_eq!(e.kind, ProviderErrorKind::Overloaded);
+        assert!(e.is_retryable());
+
+        let e = ProviderError::from_anthropic_http(418, "teapot");
+        assert_eq!(e.kind, ProviderErrorKind::Unknown);
+        assert!(!e.is_retryable());
+    }
+
+    #[test]
+    fn stream_context_length_is_not_retryable() {
+        let e = ProviderError::from_anthropic_stream(Some("invalid_request_error"), "prompt is too long");
+        assert_eq!(e.kind, ProviderErrorKind::ContextLength);
+        let e = ProviderError::from_anthropic_stream(Some("overloaded_error"), "Overloaded");
+        assert_eq!(e.kind, ProviderErrorKind::Overloaded);
+        assert!(e.is_retryable());
+    }
+
+    #[test]
+    fn error_code_is_preserved() {
+        let e = ProviderError::from_anthropic_http(
+            402,
+            r#"{"error":{"type":"billing_error","message":"Credit balance too low"}}"#,
+        );
+        assert_eq!(e.code.as_deref(), Some("billing_error"));
+        assert_eq!(e.message, "Credit balance too low");
+
+        let json = serde_json::to_value(ProviderError::from_anthropic_stream(None, "boom")).unwrap();
+        assert!(json.get("code").is_none());
+    }
+
+    #[test]
+    fn bedrock_classifies_by_code() {
+        let e = ProviderError::from_bedrock_code(Some("ThrottlingException"), "Too many requests");
+        assert_eq!(e.kind, ProviderErrorKind::RateLimit);
+        assert!(e.is_retryable());
+        assert_eq!(e.code.as_deref(), Some("ThrottlingException"));
+
+        let e = ProviderError::from_bedrock("ServiceError(ValidationException: messages: at least one message)");
+        assert_eq!(e.kind, ProviderErrorKind::InvalidRequest);
+        assert_eq!(e.code.as_deref(), Some("ValidationException"));
+
+        let e = ProviderError::from_bedrock("dispatch failure: rate exceeded");
+        assert_eq!(e.kind, ProviderErrorKind::RateLimit);
+        assert!(e.code.is_none());
+    }
+
+    #[test]
+    fn retry_after_parses_delta_seconds() {
+        let e = ProviderError::from_anthropic_http(429, "").with_retry_after(Some("7"));
+        assert_eq!(e.retry_after_ms, Some(7000));
+        let e = ProviderError::from_anthropic_http(429, "").with_retry_after(Some("1.5"));
+        assert_eq!(e.retry_after_ms, Some(1500));
+        let e = ProviderError::from_anthropic_http(429, "")
+            .with_retry_after(Some("Wed, 21 Oct 2015 07:28:00 GMT"));
+        assert_eq!(e.retry_after_ms, None);
+        assert!(serde_json::to_value(&e).unwrap().get("retry_after_ms").is_none());
+    }
+}
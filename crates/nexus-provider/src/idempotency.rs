@@ -0,0 +1,320 @@
+//! Idempotency keys and duplicate-request suppression.
+//!
+//! [`idempotency_key`] derives a key from a request's content and the turn it
+//! belongs to, so retrying the same request yields the same key. Providers
+//! that support it forward the key upstream (Anthropic: `Idempotency-Key`).
+//!
+//! [`DedupProvider`] suppresses duplicates on our side: while a keyed request
+//! is in flight, a second request with the same key waits for it and replays
+//! its events instead of calling the provider again, and a completed response
+//! is replayed for [`DEDUP_TTL_SECS`]. A request that fails is forgotten, so
+//! its retry goes upstream.
+
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::channel::oneshot;
+use futures::future::{FutureExt, Shared};
+use futures::{Stream, StreamExt};
+use sha2::{Digest, Sha256};
+
+use crate::types::StreamEvent;
+use crate::{EventStream, InferenceProvider, InferenceRequest};
+
+/// How long a completed response is replayed for its key.
+pub const DEDUP_TTL_SECS: i64 = 300;
+
+/// Key for `request` within `scope` (e.g. the run id). Stable across
+/// retries of the same request; differs when any input changes.
+pub fn idempotency_key(scope: &str, request: &InferenceRequest) -> String {
+    let content = serde_json::json!({
+        "scope": scope,
+        "model": request.model,
+        "max_tokens": request.max_tokens,
+        "system": request.system,
+        "temperature": request.temperature,
+        "thinking_budget": request.thinking_budget,
+        "messages": request.messages,
+        "tools": request.tools,
+    });
+    let digest = Sha256::digest(content.to_string().as_bytes());
+    digest.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+type Events = Arc<Vec<StreamEvent>>;
+
+enum Entry {
+    /// Resolves with the events once the leader's stream completes; the
+    /// sender is dropped if it fails.
+    InFlight(Shared<oneshot::Receiver<Events>>),
+    Done { events: Events, at: DateTime<Utc> },
+}
+
+type Entries = Arc<Mutex<HashMap<String, Entry>>>;
+
+/// Wraps a provider with an in-flight/recent map keyed by idempotency key.
+/// Requests without a key pass straight through.
+pub struct DedupProvider {
+    inner: Arc<dyn InferenceProvider>,
+    entries: Entries,
+}
+
+impl DedupProvider {
+    pub fn new(inner: Arc<dyn InferenceProvider>) -> Self {
+        Self { inner, entries: Arc::default() }
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl InferenceProvider for DedupProvider {
+    async fn create_message_stream(&self, request: InferenceRequest) -> Result<EventStream> {
+        let Some(key) = request.idempotency_key.clone() else {
+            return self.inner.create_message_stream(request).await;
+        };
+
+        // Look up and claim the key in one critical section, so of several
+        // concurrent first requests exactly one goes upstream.
+        let tx = loop {
+            let waiting = {
+                let mut entries = self.entries.lock().unwrap();
+                let now = Utc::now();
+                entries.retain(|_, e| match e {
+                    Entry::Done { at, .. } => (now - *at).num_seconds() < DEDUP_TTL_SECS,
+                    Entry::InFlight(_) => true,
+                });
+                match entries.get(&key) {
+                    Some(Entry::Done { events, .. }) => {
+                        tracing::debug!(key = %key, "Replaying completed request");
+                        return Ok(replay(events));
+                    }
+                    Some(Entry::InFlight(rx)) => rx.clone(),
+                    None => {
+                        let (tx, rx) = oneshot::channel();
+                        entries.insert(key.clone(), Entry::InFlight(rx.shared()));
+                        break tx;
+                    }
+                }
+            };
+            if let Ok(events) = waiting.await {
+                tracing::debug!(key = %key, "Replaying duplicate of in-flight request");
+                return Ok(replay(&events));
+            }
+            // The leader failed and removed its entry; claim the key again.
+        };
+
+        match self.inner.create_message_stream(request).await {
+            Ok(stream) => Ok(Box::pin(Recorder {
+                inner: stream,
+                events: Vec::new(),
+                tx: Some(tx),
+                key,
+                entries: Arc::clone(&self.entries),
+            })),
+            Err(e) => {
+                self.entries.lock().unwrap().remove(&key);
+                Err(e)
+            }
+        }
+    }
+}
+
+fn replay(events: &Events) -> EventStream {
+    Box::pin(futures::stream::iter(events.iter().cloned().map(Ok).collect::<Vec<_>>()))
+}
+
+/// Passes the leader's stream through while recording it. Publishes the
+/// events on `MessageStop`; on an error, or if dropped before completing,
+/// removes the key so a retry goes upstream.
+struct Recorder {
+    inner: EventStream,
+    events: Vec<StreamEvent>,
+    tx: Option<oneshot::Sender<Events>>,
+    key: String,
+    entries: Entries,
+}
+
+impl Recorder {
+    fn complete(&mut self) {
+        let Some(tx) = self.tx.take() else { return };
+        let events: Events = Arc::new(std::mem::take(&mut self.events));
+        self.entries.lock().unwrap().insert(
+            self.key.clone(),
+            Entry::Done { events: Arc::clone(&events), at: Utc::now() },
+        );
+        let _ = tx.send(events);
+    }
+
+    fn abandon(&mut self) {
+        // Remove the entry before dropping the sender, so woken waiters
+        // find the key free.
+        if let Some(tx) = self.tx.take() {
+            self.entries.lock().unwrap().remove(&self.key);
+            drop(tx);
+        }
+    }
+}
+
+impl Stream for Recorder {
+    type Item = Result<StreamEvent>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let polled = self.inner.poll_next_unpin(cx);
+        match &polled {
+            Poll::Ready(Some(Ok(StreamEvent::Error { .. }))) | Poll::Ready(Some(Err(_))) => {
+                self.abandon()
+            }
+            Poll::Ready(Some(Ok(event))) if self.tx.is_some() => {
+                let done = matches!(event, StreamEvent::MessageStop);
+                self.events.push(event.clone());
+                if done {
+                    self.complete();
+                }
+            }
+            Poll::Ready(None) => self.abandon(),
+            _ => {}
+        }
+        polled
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        self.abandon();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::types::Role;
+
+    /// Counts calls; each stream is a message start and stop, or an error.
+    struct Counting {
+        calls: AtomicUsize,
+        fail_first: bool,
+        /// Blocks each call this long before answering.
+        delay: std::time::Duration,
+    }
+
+    #[async_trait]
+    impl InferenceProvider for Counting {
+        async fn create_message_stream(&self, _request: InferenceRequest) -> Result<EventStream> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            std::thread::sleep(self.delay);
+            let end = if self.fail_first && call == 0 {
+                StreamEvent::Error { error_type: Some("overloaded_error".into()), message: "busy".into() }
+            } else {
+                StreamEvent::MessageStop
+            };
+            Ok(Box::pin(futures::stream::iter(vec![
+                Ok(StreamEvent::MessageStart {
+                    message_id: format!("msg-{call}"),
+                    model: "m".into(),
+                    role: Role::Assistant,
+                    usage: None,
+                }),
+                Ok(end),
+            ])))
+        }
+    }
+
+    fn request(key: Option<&str>) -> InferenceRequest {
+        InferenceRequest {
+            model: "m".into(),
+            max_tokens: 16,
+            system: None,
+            temperature: None,
+            thinking_budget: None,
+            messages: Vec::new(),
+            tools: Vec::new(),
+            idempotency_key: key.map(str::to_string),
+        }
+    }
+
+    fn counting(fail_first: bool) -> Arc<Counting> {
+        Arc::new(Counting { calls: AtomicUsize::new(0), fail_first, delay: std::time::Duration::ZERO })
+    }
+
+    async fn drain(provider: &DedupProvider, key: Option<&str>) -> Vec<StreamEvent> {
+        let stream = provider.create_message_stream(request(key)).await.unwrap();
+        stream.filter_map(|e| async { e.ok() }).collect().await
+    }
+
+    #[test]
+    fn keys_are_stable_and_content_sensitive() {
+        let a = idempotency_key("run-1", &request(None));
+        assert_eq!(a, idempotency_key("run-1", &request(None)));
+        assert_eq!(a.len(), 64);
+        assert_ne!(a, idempotency_key("run-2", &request(None)));
+        let mut other = request(None);
+        other.max_tokens = 32;
+        assert_ne!(a, idempotency_key("run-1", &other));
+    }
+
+    #[test]
+    fn duplicates_are_replayed_and_failures_forgotten() {
+        futures::executor::block_on(async {
+            let inner = counting(false);
+            let provider = DedupProvider::new(inner.clone());
+
+            // In flight: the second request waits for the first.
+            let first = provider.create_message_stream(request(Some("k"))).await.unwrap();
+            let (first, second) = futures::join!(
+                first.collect::<Vec<_>>(),
+                drain(&provider, Some("k")),
+            );
+            assert_eq!(first.len(), 2);
+            assert!(matches!(&second[0], StreamEvent::MessageStart { message_id, .. } if message_id == "msg-0"));
+            // Completed: replayed without a call.
+            drain(&provider, Some("k")).await;
+            assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+
+            // No key: always upstream.
+            drain(&provider, None).await;
+            drain(&provider, None).await;
+            assert_eq!(inner.calls.load(Ordering::SeqCst), 3);
+
+            // A failed stream is not replayed.
+            let inner = counting(true);
+            let provider = DedupProvider::new(inner.clone());
+            drain(&provider, Some("k")).await;
+            let retried = drain(&provider, Some("k")).await;
+            assert!(matches!(retried[1], StreamEvent::MessageStop));
+            assert_eq!(inner.calls.load(Ordering::SeqCst), 2);
+        });
+    }
+
+    #[test]
+    fn concurrent_first_requests_go_upstream_once() {
+        let inner = Arc::new(Counting {
+            calls: AtomicUsize::new(0),
+            fail_first: false,
+            delay: std::time::Duration::from_millis(50),
+        });
+        let provider = Arc::new(DedupProvider::new(inner.clone()));
+        let barrier = Arc::new(std::sync::Barrier::new(8));
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let provider = Arc::clone(&provider);
+                let barrier = Arc::clone(&barrier);
+                std::thread::spawn(move || {
+                    barrier.wait();
+                    futures::executor::block_on(drain(&provider, Some("k")))
+                })
+            })
+            .collect();
+        for thread in threads {
+            let events = thread.join().unwrap();
+            assert!(matches!(&events[0], StreamEvent::MessageStart { message_id, .. } if message_id == "msg-0"));
+        }
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+    }
+}
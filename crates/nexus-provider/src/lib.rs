@@ -1,14 +1,22 @@
+pub mod cache;
 pub mod error;
+pub mod idempotency;
+pub mod middleware;
 pub mod provider_config;
+pub mod racing;
 pub mod types;
 
 use anyhow::Result;
 use async_trait::async_trait;
+#[cfg(not(target_arch = "wasm32"))]
 use futures::stream::BoxStream;
+#[cfg(target_arch = "wasm32")]
+use futures::stream::LocalBoxStream;
 
 use types::{Message, StreamEvent, Tool};
 
 /// Parameters for an inference request to an LLM provider.
+#[derive(Clone)]
 pub struct InferenceRequest {
     pub model: String,
     pub max_tokens: u32,
@@ -17,13 +25,22 @@ pub struct InferenceRequest {
     pub thinking_budget: Option<u32>,
     pub messages: Vec<Message>,
     pub tools: Vec<Tool>,
+    /// Identifies this request across retries; see [`idempotency`].
+    pub idempotency_key: Option<String>,
 }
 
+/// Stream of events from one inference request.
+///
+/// On `wasm32` the HTTP backend is the browser's `fetch`, whose futures are
+/// not `Send`, so the stream (and the trait's futures) are local there.
+#[cfg(not(target_arch = "wasm32"))]
+pub type EventStream = BoxStream<'static, Result<StreamEvent>>;
+#[cfg(target_arch = "wasm32")]
+pub type EventStream = LocalBoxStream<'static, Result<StreamEvent>>;
+
 /// Abstraction over LLM providers (Anthropic, Bedrock, etc.)
-#[async_trait]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 pub trait InferenceProvider: Send + Sync {
-    async fn create_message_stream(
-        &self,
-        request: InferenceRequest,
-    ) -> Result<BoxStream<'static, Result<StreamEvent>>>;
+    async fn create_message_stream(&self, request: InferenceRequest) -> Result<EventStream>;
 }
@@ -0,0 +1,182 @@
+//! Request and response hooks around any provider.
+//!
+//! A [`ProviderMiddleware`] may change a request before it is sent, and may
+//! return a [`ResponseObserver`] that sees that request's response: each
+//! streamed event, and any error. [`Layered`] runs a stack of them around an
+//! [`InferenceProvider`], so a logging, metrics or request-rewriting wrapper
+//! implements only its hooks instead of its own provider and stream types.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::StreamExt;
+
+use crate::types::StreamEvent;
+use crate::{EventStream, InferenceProvider, InferenceRequest};
+
+pub trait ProviderMiddleware: Send + Sync {
+    /// Inspect or change `request` before it is sent. An error fails the
+    /// request without reaching the provider. The observer, if any, sees
+    /// this request's response.
+    fn on_request(&self, request: &mut InferenceRequest) -> Result<Option<Box<dyn ResponseObserver>>>;
+}
+
+/// Sees one response. Dropped with the stream, which makes `Drop` the place
+/// to act on a finished (or abandoned) response.
+pub trait ResponseObserver: Send {
+    fn on_event(&mut self, _event: &StreamEvent) {}
+
+    /// The request failed to start, or the stream yielded an error.
+    fn on_error(&mut self, _error: &anyhow::Error) {}
+}
+
+/// A provider with middleware applied. Request hooks run in the order the
+/// middleware was added; observers see each event in the same order.
+pub struct Layered {
+    inner: Arc<dyn InferenceProvider>,
+    middleware: Vec<Arc<dyn ProviderMiddleware>>,
+}
+
+impl Layered {
+    pub fn new(inner: Arc<dyn InferenceProvider>) -> Self {
+        Self { inner, middleware: Vec::new() }
+    }
+
+    pub fn with(mut self, middleware: Arc<dyn ProviderMiddleware>) -> Self {
+        self.middleware.push(middleware);
+        self
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl InferenceProvider for Layered {
+    async fn create_message_stream(&self, mut request: InferenceRequest) -> Result<EventStream> {
+        let mut observers = Vec::new();
+        for middleware in &self.middleware {
+            match middleware.on_request(&mut request) {
+                Ok(observer) => observers.extend(observer),
+                Err(e) => {
+                    observers.iter_mut().for_each(|o| o.on_error(&e));
+                    return Err(e);
+                }
+            }
+        }
+
+        match self.inner.create_message_stream(request).await {
+            Ok(stream) if observers.is_empty() => Ok(stream),
+            Ok(stream) => Ok(Box::pin(stream.inspect(move |item| {
+                for observer in observers.iter_mut() {
+                    match item {
+                        Ok(event) => observer.on_event(event),
+                        Err(e) => observer.on_error(e),
+                    }
+                }
+            }))),
+            Err(e) => {
+                observers.iter_mut().for_each(|o| o.on_error(&e));
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use anyhow::anyhow;
+
+    use super::*;
+    use crate::types::Role;
+
+    /// Records the request it receives and replies with a fixed stream.
+    #[derive(Default)]
+    struct Echo {
+        seen: Mutex<Vec<InferenceRequest>>,
+    }
+
+    #[async_trait]
+    impl InferenceProvider for Echo {
+        async fn create_message_stream(&self, request: InferenceRequest) -> Result<EventStream> {
+            self.seen.lock().unwrap().push(request);
+            Ok(Box::pin(futures::stream::iter(vec![
+                Ok(StreamEvent::MessageStart {
+                    message_id: "msg".into(),
+                    model: "m".into(),
+                    role: Role::Assistant,
+                    usage: None,
+                }),
+                Ok(StreamEvent::MessageStop),
+            ])))
+        }
+    }
+
+    /// Caps max_tokens and logs "name:event" lines for its responses.
+    struct Tag {
+        name: &'static str,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    struct TagObserver {
+        name: &'static str,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl ProviderMiddleware for Tag {
+        fn on_request(&self, request: &mut InferenceRequest) -> Result<Option<Box<dyn ResponseObserver>>> {
+            request.max_tokens = request.max_tokens.min(100);
+            if request.model == "forbidden" {
+                return Err(anyhow!("model not allowed"));
+            }
+            Ok(Some(Box::new(TagObserver { name: self.name, log: Arc::clone(&self.log) })))
+        }
+    }
+
+    impl ResponseObserver for TagObserver {
+        fn on_event(&mut self, event: &StreamEvent) {
+            let kind = if matches!(event, StreamEvent::MessageStop) { "stop" } else { "event" };
+            self.log.lock().unwrap().push(format!("{}:{kind}", self.name));
+        }
+
+        fn on_error(&mut self, error: &anyhow::Error) {
+            self.log.lock().unwrap().push(format!("{}:error {error}", self.name));
+        }
+    }
+
+    fn request(model: &str) -> InferenceRequest {
+        InferenceRequest {
+            model: model.into(),
+            max_tokens: 4096,
+            system: None,
+            temperature: None,
+            thinking_budget: None,
+            messages: Vec::new(),
+            tools: Vec::new(),
+            idempotency_key: None,
+        }
+    }
+
+    #[test]
+    fn middleware_rewrites_requests_and_observes_responses_in_order() {
+        futures::executor::block_on(async {
+            let inner = Arc::new(Echo::default());
+            let log = Arc::new(Mutex::new(Vec::new()));
+            let provider = Layered::new(inner.clone())
+                .with(Arc::new(Tag { name: "a", log: Arc::clone(&log) }))
+                .with(Arc::new(Tag { name: "b", log: Arc::clone(&log) }));
+
+            let events: Vec<_> = provider.create_message_stream(request("m")).await.unwrap().collect().await;
+            assert_eq!(events.len(), 2);
+            assert_eq!(inner.seen.lock().unwrap()[0].max_tokens, 100);
+            assert_eq!(*log.lock().unwrap(), ["a:event", "b:event", "a:stop", "b:stop"]);
+
+            log.lock().unwrap().clear();
+            let err = provider.create_message_stream(request("forbidden")).await.err().unwrap();
+            assert_eq!(err.to_string(), "model not allowed");
+            assert_eq!(inner.seen.lock().unwrap().len(), 1);
+            assert!(log.lock().unwrap().is_empty());
+        });
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -20,18 +22,100 @@ pub struct Provider {
     /// API key for Anthropic
     #[serde(skip_serializing_if = "Option::is_none")]
     pub api_key: Option<String>,
+    /// Further Anthropic API keys; requests rotate across these and
+    /// `api_key`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub api_keys: Vec<String>,
+    /// How rotated keys are picked (round-robin when unset).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_rotation: Option<KeyRotation>,
+    /// Anthropic beta features (e.g. `context-1m-2025-08-07`) sent as
+    /// `anthropic-beta` on every request.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub beta_headers: Vec<String>,
+    /// `anthropic-version` to send instead of the pinned default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_version: Option<String>,
     /// AWS region for Bedrock
     #[serde(skip_serializing_if = "Option::is_none")]
     pub aws_region: Option<String>,
     /// AWS CLI profile name (e.g. "default", "my-bedrock-profile")
     #[serde(skip_serializing_if = "Option::is_none")]
     pub aws_profile: Option<String>,
+    /// Quota shared by every request sent through this provider.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub throttle: Option<ThrottleConfig>,
+    /// How tool results are shown to this provider's models.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_results: Option<ToolResultFormats>,
     #[serde(default = "chrono::Utc::now")]
     pub created_at: DateTime<Utc>,
     #[serde(default = "chrono::Utc::now")]
     pub updated_at: DateTime<Utc>,
 }
 
+/// How requests are spread across a provider's API keys.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyRotation {
+    /// Each key in turn.
+    #[default]
+    RoundRobin,
+    /// A key that was never throttled, else the one throttled longest ago.
+    /// Ties go round-robin.
+    LeastRecentlyThrottled,
+}
+
+/// Per-minute limits for a provider. Unset limits are not enforced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThrottleConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requests_per_minute: Option<u32>,
+    /// Input plus output tokens.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokens_per_minute: Option<u32>,
+}
+
+/// How a tool result's text is presented to the model.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResultFormat {
+    /// `<tool_response>` tags followed by the anti-injection note.
+    #[default]
+    XmlTagged,
+    /// The output, a blank line, then the note.
+    Plain,
+    /// `{"tool_response": ..., "note": ...}`.
+    JsonEnvelope,
+}
+
+/// Tool result formats for a provider: a default, and overrides by model
+/// name or `prefix*` pattern.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolResultFormats {
+    #[serde(default)]
+    pub default: ResultFormat,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub models: HashMap<String, ResultFormat>,
+}
+
+impl ToolResultFormats {
+    /// An exact model match wins, then the longest matching pattern.
+    pub fn for_model(&self, model: &str) -> ResultFormat {
+        if let Some(format) = self.models.get(model) {
+            return *format;
+        }
+        self.models
+            .iter()
+            .filter_map(|(pattern, format)| {
+                let prefix = pattern.strip_suffix('*')?;
+                model.starts_with(prefix).then_some((prefix.len(), *format))
+            })
+            .max_by_key(|(len, _)| *len)
+            .map_or(self.default, |(_, format)| format)
+    }
+}
+
 /// Safe for frontend — no secrets
 #[derive(Debug, Clone, Serialize)]
 pub struct ProviderPublic {
@@ -57,7 +141,7 @@ impl From<&Provider> for ProviderPublic {
             name: p.name.clone(),
             provider_type: p.provider_type.clone(),
             endpoint: p.endpoint.clone(),
-            has_api_key: p.api_key.is_some(),
+            has_api_key: p.api_key.is_some() || !p.api_keys.is_empty(),
             aws_region: p.aws_region.clone(),
             aws_profile: p.aws_profile.clone(),
             created_at: p.created_at,
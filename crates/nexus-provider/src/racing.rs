@@ -0,0 +1,246 @@
+//! Speculative racing across providers.
+//!
+//! [`RacingProvider`] sends the same request to several providers at once and
+//! streams from whichever first produces content (its first content block),
+//! dropping the others, which closes their connections. A racer that fails,
+//! or reports a stream error before any content, drops out of the race.
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::StreamExt;
+
+use crate::error::ProviderError;
+use crate::types::StreamEvent;
+use crate::{EventStream, InferenceProvider, InferenceRequest};
+
+/// One contestant: a provider, and the model to ask it for. `None` keeps
+/// the request's model.
+#[derive(Clone)]
+pub struct Racer {
+    pub provider: Arc<dyn InferenceProvider>,
+    pub model: Option<String>,
+}
+
+pub struct RacingProvider {
+    racers: Vec<Racer>,
+}
+
+impl RacingProvider {
+    pub fn new(racers: Vec<Racer>) -> Self {
+        Self { racers }
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl InferenceProvider for RacingProvider {
+    async fn create_message_stream(&self, request: InferenceRequest) -> Result<EventStream> {
+        let mut pending: Vec<_> = self
+            .racers
+            .iter()
+            .enumerate()
+            .map(|(i, racer)| {
+                let mut request = request.clone();
+                if let Some(ref model) = racer.model {
+                    request.model.clone_from(model);
+                }
+                // Racers can share a provider (and its dedup map), so each
+                // needs its own key or the later ones replay the first.
+                request.idempotency_key = request.idempotency_key.map(|k| format!("{k}-{i}"));
+                Box::pin(until_content(racer.provider.as_ref(), request))
+            })
+            .collect();
+
+        let mut first_error = None;
+        while !pending.is_empty() {
+            let (result, index, rest) = futures::future::select_all(pending).await;
+            match result {
+                Ok((head, tail)) => {
+                    tracing::debug!(racer = index, "Provider race won");
+                    // Dropping the other racers cancels their requests.
+                    drop(rest);
+                    return Ok(Box::pin(futures::stream::iter(head.into_iter().map(Ok)).chain(tail)));
+                }
+                Err(e) => {
+                    tracing::warn!(racer = index, "Racing provider failed: {:#}", e);
+                    first_error.get_or_insert(e);
+                    pending = rest;
+                }
+            }
+        }
+        Err(first_error.unwrap_or_else(|| anyhow!("no providers to race")))
+    }
+}
+
+/// Start a request and read it up to its first content block. Returns the
+/// events read so far and the rest of the stream.
+async fn until_content(
+    provider: &dyn InferenceProvider,
+    request: InferenceRequest,
+) -> Result<(Vec<StreamEvent>, EventStream)> {
+    let mut stream = provider.create_message_stream(request).await?;
+    let mut head = Vec::new();
+    while let Some(event) = stream.next().await {
+        match event? {
+            StreamEvent::Error { error_type, message } => {
+                return Err(ProviderError::from_anthropic_stream(error_type.as_deref(), &message).into());
+            }
+            event @ StreamEvent::ContentBlockStart { .. } => {
+                head.push(event);
+                break;
+            }
+            event => head.push(event),
+        }
+    }
+    Ok((head, stream))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use super::*;
+    use crate::idempotency::DedupProvider;
+    use crate::types::{ContentBlockInfo, Role};
+
+    fn start(model: &str) -> StreamEvent {
+        StreamEvent::MessageStart {
+            message_id: format!("msg-{model}"),
+            model: model.to_string(),
+            role: Role::Assistant,
+            usage: None,
+        }
+    }
+
+    /// Starts a message, then either streams text or stalls forever.
+    struct Scripted {
+        stall: bool,
+        fail: bool,
+        dropped: Arc<AtomicBool>,
+    }
+
+    struct DropFlag(Arc<AtomicBool>);
+
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[async_trait]
+    impl InferenceProvider for Scripted {
+        async fn create_message_stream(&self, request: InferenceRequest) -> Result<EventStream> {
+            if self.fail {
+                anyhow::bail!("unreachable");
+            }
+            let flag = DropFlag(Arc::clone(&self.dropped));
+            let head = futures::stream::iter(vec![Ok(start(&request.model))]);
+            if self.stall {
+                return Ok(Box::pin(head.chain(futures::stream::pending()).map(move |e| {
+                    let _ = &flag;
+                    e
+                })));
+            }
+            Ok(Box::pin(head.chain(futures::stream::iter(vec![
+                Ok(StreamEvent::ContentBlockStart { index: 0, content_block: ContentBlockInfo::Text }),
+                Ok(StreamEvent::MessageStop),
+            ]))))
+        }
+    }
+
+    fn racer(stall: bool, fail: bool, model: &str) -> (Racer, Arc<AtomicBool>) {
+        let dropped = Arc::new(AtomicBool::new(false));
+        let provider = Scripted { stall, fail, dropped: Arc::clone(&dropped) };
+        (Racer { provider: Arc::new(provider), model: Some(model.into()) }, dropped)
+    }
+
+    fn request() -> InferenceRequest {
+        InferenceRequest {
+            model: "default".into(),
+            max_tokens: 16,
+            system: None,
+            temperature: None,
+            thinking_budget: None,
+            messages: Vec::new(),
+            tools: Vec::new(),
+            idempotency_key: None,
+        }
+    }
+
+    #[test]
+    fn first_racer_with_content_wins_and_loser_is_dropped() {
+        let (slow, slow_dropped) = racer(true, false, "slow");
+        let (fast, _) = racer(false, false, "fast");
+        let events: Vec<_> = futures::executor::block_on(async {
+            let stream = RacingProvider::new(vec![slow, fast])
+                .create_message_stream(request())
+                .await
+                .unwrap();
+            stream.collect::<Vec<_>>().await
+        });
+        assert_eq!(events.len(), 3);
+        assert!(matches!(
+            events[0].as_ref().unwrap(),
+            StreamEvent::MessageStart { model, .. } if model == "fast"
+        ));
+        assert!(slow_dropped.load(Ordering::SeqCst));
+    }
+
+    /// Stalls after the message start when asked for the "slow" model.
+    struct ByModel;
+
+    #[async_trait]
+    impl InferenceProvider for ByModel {
+        async fn create_message_stream(&self, request: InferenceRequest) -> Result<EventStream> {
+            let head = futures::stream::iter(vec![Ok(start(&request.model))]);
+            if request.model == "slow" {
+                return Ok(Box::pin(head.chain(futures::stream::pending())));
+            }
+            Ok(Box::pin(head.chain(futures::stream::iter(vec![
+                Ok(StreamEvent::ContentBlockStart { index: 0, content_block: ContentBlockInfo::Text }),
+                Ok(StreamEvent::MessageStop),
+            ]))))
+        }
+    }
+
+    #[test]
+    fn racers_on_one_provider_get_their_own_key() {
+        use futures::FutureExt;
+
+        let provider: Arc<dyn InferenceProvider> = Arc::new(DedupProvider::new(Arc::new(ByModel)));
+        let racers = ["slow", "fast"]
+            .map(|model| Racer { provider: Arc::clone(&provider), model: Some(model.into()) });
+        let mut keyed = request();
+        keyed.idempotency_key = Some("k".into());
+
+        // With a shared key the fast racer would wait on the stalled one.
+        let stream = RacingProvider::new(racers.to_vec())
+            .create_message_stream(keyed)
+            .now_or_never()
+            .expect("race stalled")
+            .unwrap();
+        let events: Vec<_> = futures::executor::block_on(stream.collect::<Vec<_>>());
+        assert!(matches!(
+            events[0].as_ref().unwrap(),
+            StreamEvent::MessageStart { model, .. } if model == "fast"
+        ));
+    }
+
+    #[test]
+    fn failed_racers_drop_out() {
+        let (broken, _) = racer(false, true, "broken");
+        let (ok, _) = racer(false, false, "ok");
+        let result = futures::executor::block_on(
+            RacingProvider::new(vec![broken, ok]).create_message_stream(request()),
+        );
+        assert!(result.is_ok());
+
+        let (broken, _) = racer(false, true, "broken");
+        let result = futures::executor::block_on(
+            RacingProvider::new(vec![broken]).create_message_stream(request()),
+        );
+        assert!(result.is_err());
+    }
+}
@@ -52,22 +52,194 @@ pub enum ContentBlock {
     },
     ToolResult {
         tool_use_id: String,
-        content: String,
+        content: ToolResultContent,
         #[serde(skip_serializing_if = "Option::is_none")]
         is_error: Option<bool>,
     },
+    /// Extended thinking. The signature must be sent back unchanged when the
+    /// block is replayed (e.g. in a tool-use loop), or the API rejects it.
     Thinking {
         thinking: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        signature: Option<String>,
+    },
+    /// Thinking the provider encrypted for safety reasons. Opaque; only
+    /// meaningful when sent back to the API.
+    RedactedThinking {
+        data: String,
+    },
+    /// A call to a tool the provider runs itself (Anthropic `web_search`,
+    /// `code_execution`). Its result arrives in the same response, so the
+    /// agent never executes it.
+    ServerToolUse {
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+    /// Results of a `web_search` server tool call. `content` is kept as
+    /// returned (search results or an error) and replayed unchanged.
+    WebSearchToolResult {
+        tool_use_id: String,
+        content: serde_json::Value,
+    },
+    /// Results of a `code_execution` server tool call, kept as returned.
+    CodeExecutionToolResult {
+        tool_use_id: String,
+        content: serde_json::Value,
     },
 }
 
-#[derive(Debug, Clone, Serialize)]
+impl ContentBlock {
+    /// Server tool calls and their results — produced and consumed by the
+    /// provider, never executed locally.
+    pub fn is_server_tool_block(&self) -> bool {
+        matches!(
+            self,
+            Self::ServerToolUse { .. }
+                | Self::WebSearchToolResult { .. }
+                | Self::CodeExecutionToolResult { .. }
+        )
+    }
+}
+
+/// Content of a tool result: a plain string, or an array of text and image
+/// blocks for tools that return rich output (e.g. screenshots). Serializes
+/// to the Messages API's `tool_result.content`, which both Anthropic and
+/// Bedrock accept in either form.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ToolResultContent {
+    Text(String),
+    Blocks(Vec<ToolResultBlock>),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ToolResultBlock {
+    Text { text: String },
+    Image { source: ImageSource },
+}
+
+/// A base64-encoded image.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImageSource {
+    /// Always `base64`.
+    #[serde(rename = "type")]
+    pub source_type: String,
+    /// e.g. `image/png`.
+    pub media_type: String,
+    pub data: String,
+}
+
+impl ImageSource {
+    pub fn base64(media_type: impl Into<String>, data: impl Into<String>) -> Self {
+        Self {
+            source_type: "base64".to_string(),
+            media_type: media_type.into(),
+            data: data.into(),
+        }
+    }
+}
+
+impl ToolResultContent {
+    /// Text followed by images; plain text when there are no images.
+    pub fn with_images(text: String, images: Vec<ImageSource>) -> Self {
+        if images.is_empty() {
+            return Self::Text(text);
+        }
+        let mut blocks = vec![ToolResultBlock::Text { text }];
+        blocks.extend(images.into_iter().map(|source| ToolResultBlock::Image { source }));
+        Self::Blocks(blocks)
+    }
+
+    /// The text blocks, joined by newlines.
+    pub fn text(&self) -> std::borrow::Cow<'_, str> {
+        match self {
+            Self::Text(text) => text.as_str().into(),
+            Self::Blocks(blocks) => blocks
+                .iter()
+                .filter_map(|b| match b {
+                    ToolResultBlock::Text { text } => Some(text.as_str()),
+                    ToolResultBlock::Image { .. } => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+                .into(),
+        }
+    }
+
+    pub fn images(&self) -> impl Iterator<Item = &ImageSource> {
+        let blocks = match self {
+            Self::Text(_) => &[][..],
+            Self::Blocks(blocks) => blocks.as_slice(),
+        };
+        blocks.iter().filter_map(|b| match b {
+            ToolResultBlock::Image { source } => Some(source),
+            ToolResultBlock::Text { .. } => None,
+        })
+    }
+}
+
+impl From<String> for ToolResultContent {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<&str> for ToolResultContent {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_string())
+    }
+}
+
+/// A tool definition sent to the model.
+///
+/// Server tools (Anthropic `web_search_20250305`, `code_execution_20250522`,
+/// …) are represented with their full API definition as `input_schema`; its
+/// `type` is the versioned tool type rather than `"object"`. They serialize
+/// as that definition, untouched — see [`Tool::server`].
+#[derive(Debug, Clone)]
 pub struct Tool {
     pub name: String,
     pub description: String,
     pub input_schema: serde_json::Value,
 }
 
+impl Tool {
+    /// A provider-run tool from its API definition, e.g.
+    /// `{"type": "web_search_20250305", "name": "web_search", "max_uses": 5}`.
+    pub fn server(definition: serde_json::Value) -> Self {
+        Self {
+            name: definition["name"].as_str().unwrap_or_default().to_string(),
+            description: String::new(),
+            input_schema: definition,
+        }
+    }
+
+    /// Whether this is a server tool (its schema `type` is not `"object"`).
+    pub fn is_server_tool(&self) -> bool {
+        self.input_schema
+            .get("type")
+            .and_then(|t| t.as_str())
+            .is_some_and(|t| t != "object")
+    }
+}
+
+impl Serialize for Tool {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        if self.is_server_tool() {
+            return self.input_schema.serialize(serializer);
+        }
+        let mut tool = serializer.serialize_struct("Tool", 3)?;
+        tool.serialize_field("name", &self.name)?;
+        tool.serialize_field("description", &self.description)?;
+        tool.serialize_field("input_schema", &self.input_schema)?;
+        tool.end()
+    }
+}
+
 /// Inject a required `description` field into every tool's input_schema.
 ///
 /// This forces the model to articulate its reasoning before acting,
@@ -79,7 +251,7 @@ pub fn inject_tool_description_field(tools: &mut [Tool]) {
         "description": "Brief explanation of what you're doing and why (1-2 sentences)."
     });
 
-    for tool in tools.iter_mut() {
+    for tool in tools.iter_mut().filter(|t| !t.is_server_tool()) {
         if let Some(schema) = tool.input_schema.as_object_mut() {
             // Add description to properties
             if let Some(props) = schema.get_mut("properties").and_then(|p| p.as_object_mut()) {
@@ -141,6 +313,11 @@ pub enum ContentBlockInfo {
     Text,
     ToolUse { id: String, name: String },
     Thinking,
+    RedactedThinking { data: String },
+    /// A server tool call; its input streams as `InputJsonDelta`s.
+    ServerToolUse { id: String, name: String },
+    /// A complete server tool result block, delivered at block start.
+    ServerToolResult { block: ContentBlock },
 }
 
 #[derive(Debug, Clone)]
@@ -149,15 +326,58 @@ pub enum Delta {
     TextDelta { text: String },
     InputJsonDelta { partial_json: String },
     ThinkingDelta { thinking: String },
+    SignatureDelta { signature: String },
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
+/// Why the model stopped generating. Unrecognized values (e.g. `pause_turn`)
+/// are kept as [`StopReason::Other`] rather than failing the stream.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
 pub enum StopReason {
     EndTurn,
     ToolUse,
     MaxTokens,
     StopSequence,
+    /// The model declined to continue for safety reasons.
+    Refusal,
+    /// A long server-tool turn was paused; send the response back as the
+    /// last message to let it continue.
+    PauseTurn,
+    Other(String),
+}
+
+impl StopReason {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::EndTurn => "end_turn",
+            Self::ToolUse => "tool_use",
+            Self::MaxTokens => "max_tokens",
+            Self::StopSequence => "stop_sequence",
+            Self::Refusal => "refusal",
+            Self::PauseTurn => "pause_turn",
+            Self::Other(s) => s,
+        }
+    }
+}
+
+impl From<String> for StopReason {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "end_turn" => Self::EndTurn,
+            "tool_use" => Self::ToolUse,
+            "max_tokens" => Self::MaxTokens,
+            "stop_sequence" => Self::StopSequence,
+            "refusal" => Self::Refusal,
+            "pause_turn" => Self::PauseTurn,
+            _ => Self::Other(s),
+        }
+    }
+}
+
+impl From<StopReason> for String {
+    fn from(sr: StopReason) -> Self {
+        sr.as_str().to_string()
+    }
 }
 
 #[derive(Debug, Clone, Default, Deserialize)]
@@ -170,6 +390,16 @@ pub struct Usage {
     pub cache_creation_input_tokens: u32,
     #[serde(default)]
     pub cache_read_input_tokens: u32,
+    /// Server tool invocations billed with this response.
+    #[serde(default)]
+    pub server_tool_use: Option<ServerToolUsage>,
+}
+
+/// Per-request counts of server tool invocations (`usage.server_tool_use`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+pub struct ServerToolUsage {
+    #[serde(default)]
+    pub web_search_requests: u32,
 }
 
 // ── Prompt caching ──
@@ -290,6 +520,10 @@ pub enum RawContentBlock {
     Text { text: String },
     ToolUse { id: String, name: String },
     Thinking { thinking: String },
+    RedactedThinking { data: String },
+    ServerToolUse { id: String, name: String },
+    WebSearchToolResult { tool_use_id: String, content: serde_json::Value },
+    CodeExecutionToolResult { tool_use_id: String, content: serde_json::Value },
 }
 
 #[derive(Debug, Deserialize)]
@@ -305,6 +539,7 @@ pub enum RawDelta {
     TextDelta { text: String },
     InputJsonDelta { partial_json: String },
     ThinkingDelta { thinking: String },
+    SignatureDelta { signature: String },
 }
 
 #[derive(Debug, Deserialize)]
@@ -353,6 +588,95 @@ pub struct RawErrorInner {
 mod tests {
     use super::*;
 
+    #[test]
+    fn thinking_blocks_serialize_with_signature() {
+        let block = ContentBlock::Thinking {
+            thinking: "hmm".into(),
+            signature: Some("sig".into()),
+        };
+        let json = serde_json::to_value(&block).unwrap();
+        assert_eq!(json, serde_json::json!({"type": "thinking", "thinking": "hmm", "signature": "sig"}));
+
+        let redacted = ContentBlock::RedactedThinking { data: "opaque".into() };
+        let json = serde_json::to_value(&redacted).unwrap();
+        assert_eq!(json, serde_json::json!({"type": "redacted_thinking", "data": "opaque"}));
+    }
+
+    #[test]
+    fn tool_results_serialize_as_string_or_blocks() {
+        let plain = ContentBlock::ToolResult {
+            tool_use_id: "t1".into(),
+            content: "ok".into(),
+            is_error: None,
+        };
+        let json = serde_json::to_value(&plain).unwrap();
+        assert_eq!(json, serde_json::json!({"type": "tool_result", "tool_use_id": "t1", "content": "ok"}));
+
+        let content = ToolResultContent::with_images(
+            "a chart".into(),
+            vec![ImageSource::base64("image/png", "iVBOR")],
+        );
+        let json = serde_json::to_value(&content).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {"type": "text", "text": "a chart"},
+                {"type": "image", "source": {"type": "base64", "media_type": "image/png", "data": "iVBOR"}},
+            ])
+        );
+        let back: ToolResultContent = serde_json::from_value(json).unwrap();
+        assert_eq!(back, content);
+        assert_eq!(back.text(), "a chart");
+        assert_eq!(back.images().count(), 1);
+        assert_eq!(ToolResultContent::with_images("x".into(), vec![]), "x".into());
+    }
+
+    #[test]
+    fn server_tools_serialize_untouched() {
+        let definition = serde_json::json!({"type": "web_search_20250305", "name": "web_search", "max_uses": 5});
+        let mut tools = vec![
+            Tool::server(definition.clone()),
+            Tool {
+                name: "fetch".into(),
+                description: "Fetch a URL".into(),
+                input_schema: serde_json::json!({"type": "object", "properties": {}}),
+            },
+        ];
+        inject_tool_description_field(&mut tools);
+        assert_eq!(tools[0].name, "web_search");
+        assert!(tools[0].is_server_tool() && !tools[1].is_server_tool());
+        assert_eq!(serde_json::to_value(&tools[0]).unwrap(), definition);
+        assert_eq!(serde_json::to_value(&tools[1]).unwrap()["input_schema"]["required"], serde_json::json!(["description"]));
+
+        let block = ContentBlock::WebSearchToolResult {
+            tool_use_id: "srvtoolu_1".into(),
+            content: serde_json::json!([{"type": "web_search_result", "url": "https://example.com"}]),
+        };
+        let json = serde_json::to_value(&block).unwrap();
+        assert_eq!(json["type"], "web_search_tool_result");
+        assert_eq!(serde_json::from_value::<ContentBlock>(json).unwrap(), block);
+        assert!(block.is_server_tool_block());
+
+        let usage: Usage = serde_json::from_str(
+            r#"{"input_tokens": 1, "output_tokens": 2, "server_tool_use": {"web_search_requests": 3}}"#,
+        )
+        .unwrap();
+        assert_eq!(usage.server_tool_use, Some(ServerToolUsage { web_search_requests: 3 }));
+    }
+
+    #[test]
+    fn stop_reason_round_trips_known_and_unknown_values() {
+        let sr: StopReason = serde_json::from_str(r#""refusal""#).unwrap();
+        assert_eq!(sr, StopReason::Refusal);
+        let sr: StopReason = serde_json::from_str(r#""pause_turn""#).unwrap();
+        assert_eq!(sr, StopReason::PauseTurn);
+        assert_eq!(serde_json::to_value(&sr).unwrap(), "pause_turn");
+        let sr: StopReason = serde_json::from_str(r#""model_context_window_exceeded""#).unwrap();
+        assert_eq!(sr, StopReason::Other("model_context_window_exceeded".into()));
+        assert_eq!(serde_json::to_value(&sr).unwrap(), "model_context_window_exceeded");
+        assert_eq!(serde_json::to_value(StopReason::ToolUse).unwrap(), "tool_use");
+    }
+
     #[test]
     fn inject_cache_control_converts_system_string_to_array() {
         let mut body = serde_json::json!({
@@ -121,6 +121,34 @@ pub async fn execute(command: &str, timeout_ms: Option<u64>, working_dir: Option
     }
 }
 
+/// Spawn a command to run in the background with no timeout. Stdout and
+/// stderr are appended to `output` as they are written, so the file can be
+/// read while the process runs. On Unix the command leads its own process
+/// group, letting a stop signal reach everything it started.
+pub fn spawn_background(
+    command: &str,
+    working_dir: Option<&str>,
+    output: &std::path::Path,
+) -> std::io::Result<tokio::process::Child> {
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(output)?;
+
+    let mut cmd = tokio::process::Command::new(detect_shell());
+    cmd.arg("-c").arg(command);
+    if let Some(dir) = working_dir {
+        cmd.current_dir(dir);
+    }
+    cmd.stdin(std::process::Stdio::null());
+    cmd.stdout(file.try_clone()?);
+    cmd.stderr(file);
+    #[cfg(unix)]
+    cmd.process_group(0);
+
+    cmd.spawn()
+}
+
 /// Detect the user's preferred shell.
 fn detect_shell() -> String {
     std::env::var("SHELL").unwrap_or_else(|_| "/bin/bash".to_string())
@@ -173,4 +201,14 @@ mod tests {
         // /tmp may resolve to /private/tmp on macOS
         assert!(output.contains("tmp"));
     }
+
+    #[tokio::test]
+    async fn spawn_background_streams_output_to_file() {
+        let path = std::env::temp_dir().join(format!("nexus-bash-test-{}.out", uuid::Uuid::new_v4()));
+        let mut child = spawn_background("echo out; echo err >&2", None, &path).unwrap();
+        assert!(child.wait().await.unwrap().success());
+        let output = std::fs::read_to_string(&path).unwrap();
+        assert!(output.contains("out\n") && output.contains("err\n"), "{output}");
+        std::fs::remove_file(&path).ok();
+    }
 }
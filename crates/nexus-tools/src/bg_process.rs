@@ -18,6 +18,7 @@ pub trait ProcessBackend: Send + Sync {
         process_id: &str,
         tail: Option<usize>,
         head: Option<usize>,
+        offset: Option<u64>,
     ) -> Result<String, String>;
     async fn list_json(&self, conversation_id: &str) -> String;
     async fn cancel(&self, process_id: &str) -> Result<(), String>;
@@ -37,7 +38,8 @@ pub async fn execute(
             };
             let tail = args.get("tail").and_then(|v| v.as_u64()).map(|n| n as usize);
             let head = args.get("head").and_then(|v| v.as_u64()).map(|n| n as usize);
-            match backend.read_output(process_id, tail, head).await {
+            let offset = args.get("offset").and_then(|v| v.as_u64());
+            match backend.read_output(process_id, tail, head, offset).await {
                 Ok(output) => (output, false),
                 Err(e) => (e, true),
             }
@@ -64,7 +66,8 @@ pub fn tool_definitions() -> Vec<Tool> {
         Tool {
             name: PROCESS_OUTPUT.to_string(),
             description: "Read output from a background process. Returns the stdout/stderr \
-                captured to disk. Use tail or head to read specific sections of large output."
+                captured to disk so far; the process may still be running. Use tail or head to \
+                read specific sections of large output, or offset to poll for new output."
                 .to_string(),
             input_schema: serde_json::json!({
                 "type": "object",
@@ -80,6 +83,11 @@ pub fn tool_definitions() -> Vec<Tool> {
                     "head": {
                         "type": "integer",
                         "description": "Read first N lines of output."
+                    },
+                    "offset": {
+                        "type": "integer",
+                        "description": "Read only output after this byte offset. Start with 0 and \
+                            pass the next_offset from each response to get just what is new."
                     }
                 },
                 "required": ["process_id"]
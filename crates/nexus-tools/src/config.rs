@@ -12,6 +12,20 @@ pub struct FilesystemConfig {
     /// are rejected.  Empty vec = no filesystem access.
     #[serde(default)]
     pub allowed_directories: Vec<String>,
+    /// Additional named roots, each exposed as its own copy of the tool suite
+    /// (`frontend__read_file`, `backend__read_file`, …).  They share this
+    /// config's `enabled` switch and path policy.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub workspaces: Vec<WorkspaceConfig>,
+}
+
+/// A named filesystem root for agents working across several repositories.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceConfig {
+    /// Tool name prefix.  Letters, digits, `-` and single `_` only.
+    pub name: String,
+    /// Directories this workspace's tools may access.
+    pub allowed_directories: Vec<String>,
 }
 
 fn default_fs_enabled() -> bool {
@@ -23,6 +37,7 @@ impl Default for FilesystemConfig {
         Self {
             enabled: default_fs_enabled(),
             allowed_directories: Vec::new(),
+            workspaces: Vec::new(),
         }
     }
 }
@@ -190,8 +205,10 @@ mod tests {
 
     #[test]
     fn policy_enabled_none_does_not_override() {
-        let mut config = FetchConfig::default();
-        config.enabled = true;
+        let mut config = FetchConfig {
+            enabled: true,
+            ..Default::default()
+        };
 
         let policy = FetchPolicy {
             enabled: None,
@@ -1,11 +1,13 @@
 pub mod validate;
+pub mod workspace;
 mod ops;
 
 pub use ops::EditOp;
 pub use validate::PathValidator;
+pub use workspace::{GitTreeWorkspace, LocalWorkspace, MemoryWorkspace, Workspace};
 
 use nexus_provider::types::Tool;
-use crate::config::FilesystemConfig;
+use crate::config::{FilesystemConfig, WorkspaceConfig};
 
 // ── Tool names ──
 
@@ -24,6 +26,10 @@ const SEARCH_FILES: &str = "search_files";
 const GET_FILE_INFO: &str = "get_file_info";
 const LIST_ALLOWED_DIRECTORIES: &str = "list_allowed_directories";
 
+/// Joins a workspace name and a tool name (`frontend__read_file`).  Provider
+/// APIs only accept `[a-zA-Z0-9_-]` in tool names, so no `.` or `/`.
+pub const NAMESPACE_SEPARATOR: &str = "__";
+
 /// Default patterns always excluded from directory_tree and search_files.
 const DEFAULT_EXCLUDES: &[&str] = &[
     "node_modules",
@@ -70,14 +76,61 @@ pub fn is_filesystem_tool(name: &str) -> bool {
     ALL_TOOLS.contains(&name)
 }
 
+/// Split a namespaced tool name into workspace and tool
+/// (`frontend__read_file` → `Some(("frontend", "read_file"))`).  Returns
+/// `None` unless the tool part is a filesystem tool.
+pub fn split_namespaced(name: &str) -> Option<(&str, &str)> {
+    let (workspace, tool) = name.split_once(NAMESPACE_SEPARATOR)?;
+    (!workspace.is_empty() && is_filesystem_tool(tool)).then_some((workspace, tool))
+}
+
+/// Whether `name` can prefix tool names: non-empty, `[a-zA-Z0-9_-]`, and no
+/// separator inside.
+pub fn is_valid_workspace_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.contains(NAMESPACE_SEPARATOR)
+        && !name.ends_with('_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Workspaces from `config` that get tools: valid names with at least one
+/// directory, first occurrence of each name wins.
+pub fn active_workspaces(config: &FilesystemConfig) -> Vec<&WorkspaceConfig> {
+    let mut active: Vec<&WorkspaceConfig> = Vec::new();
+    for ws in &config.workspaces {
+        if !is_valid_workspace_name(&ws.name) {
+            tracing::warn!(workspace = %ws.name, "Ignoring filesystem workspace with invalid name");
+        } else if !ws.allowed_directories.is_empty() && !active.iter().any(|a| a.name == ws.name) {
+            active.push(ws);
+        }
+    }
+    active
+}
+
 /// Return tool definitions for the filesystem toolset.
 ///
-/// Returns an empty vec if the config is disabled or has no allowed directories
-/// (the tools are meaningless without at least one allowed directory).
+/// The unprefixed suite is returned when `allowed_directories` is non-empty,
+/// plus one prefixed suite per [active workspace](active_workspaces).
+/// Returns an empty vec if the config is disabled.
 pub fn tool_definitions(config: &FilesystemConfig) -> Vec<Tool> {
-    if !config.enabled || config.allowed_directories.is_empty() {
+    if !config.enabled {
         return Vec::new();
     }
+    let mut tools = Vec::new();
+    if !config.allowed_directories.is_empty() {
+        tools.extend(base_definitions());
+    }
+    for ws in active_workspaces(config) {
+        tools.extend(base_definitions().into_iter().map(|tool| Tool {
+            name: format!("{}{}{}", ws.name, NAMESPACE_SEPARATOR, tool.name),
+            description: format!("[{} workspace] {}", ws.name, tool.description),
+            input_schema: tool.input_schema,
+        }));
+    }
+    tools
+}
+
+fn base_definitions() -> Vec<Tool> {
     vec![
         Tool {
             name: READ_TEXT_FILE.into(),
@@ -141,8 +194,9 @@ pub fn tool_definitions(config: &FilesystemConfig) -> Vec<Tool> {
         },
         Tool {
             name: EDIT_FILE.into(),
-            description: "Make line-based edits to a text file. Each edit replaces an exact \
-                match of oldText with newText. Use dryRun to preview changes without applying."
+            description: "Edit a text file with search/replace blocks. Each oldText must match \
+                exactly once; if any edit fails, nothing is written. Returns a unified diff. \
+                Use dryRun to preview the diff without applying."
                 .into(),
             input_schema: serde_json::json!({
                 "type": "object",
@@ -164,6 +218,11 @@ pub fn tool_definitions(config: &FilesystemConfig) -> Vec<Tool> {
                         "type": "boolean",
                         "default": false,
                         "description": "Preview changes without writing to disk"
+                    },
+                    "backup": {
+                        "type": "boolean",
+                        "default": false,
+                        "description": "Save the original content to <path>.bak before writing"
                     }
                 },
                 "required": ["path", "edits"]
@@ -293,11 +352,12 @@ pub fn tool_definitions(config: &FilesystemConfig) -> Vec<Tool> {
 
 // ── Dispatch ──
 
-/// Execute a filesystem tool by name.  Returns `Ok(output)` or `Err(error_message)`.
+/// Execute a filesystem tool by name against `workspace`.  Returns
+/// `Ok(output)` or `Err(error_message)`.
 pub fn execute(
     name: &str,
     args_json: &str,
-    validator: &PathValidator,
+    workspace: &dyn Workspace,
 ) -> Result<String, String> {
     let raw = if args_json.is_empty() { "{}" } else { args_json };
     let args: serde_json::Value =
@@ -308,11 +368,11 @@ pub fn execute(
             let path = require_str(&args, "path")?;
             let head = args.get("head").and_then(|v| v.as_u64()).map(|n| n as usize);
             let tail = args.get("tail").and_then(|v| v.as_u64()).map(|n| n as usize);
-            ops::read_text_file(validator, path, head, tail)
+            ops::read_text_file(workspace, path, head, tail)
         }
         READ_MEDIA_FILE => {
             let path = require_str(&args, "path")?;
-            ops::read_media_file(validator, path)
+            ops::read_media_file(workspace, path)
         }
         READ_MULTIPLE_FILES => {
             let paths: Vec<String> = args
@@ -325,12 +385,12 @@ pub fn execute(
             if paths.is_empty() {
                 return Err("'paths' must contain at least one path".into());
             }
-            ops::read_multiple_files(validator, &paths)
+            ops::read_multiple_files(workspace, &paths)
         }
         WRITE_FILE => {
             let path = require_str(&args, "path")?;
             let content = require_str(&args, "content")?;
-            ops::write_file(validator, path, content)
+            ops::write_file(workspace, path, content)
         }
         EDIT_FILE => {
             let path = require_str(&args, "path")?;
@@ -347,20 +407,24 @@ pub fn execute(
                 .get("dryRun")
                 .and_then(|v| v.as_bool())
                 .unwrap_or(false);
-            ops::edit_file(validator, path, &edits, dry_run)
+            let backup = args
+                .get("backup")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+            ops::edit_file(workspace, path, &edits, dry_run, backup)
         }
         CREATE_DIRECTORY => {
             let path = require_str(&args, "path")?;
-            ops::create_directory(validator, path)
+            ops::create_directory(workspace, path)
         }
         LIST_DIRECTORY => {
             let path = require_str(&args, "path")?;
-            ops::list_directory(validator, path)
+            ops::list_directory(workspace, path)
         }
         LIST_DIRECTORY_WITH_SIZES => {
             let path = require_str(&args, "path")?;
             let sort_by = args.get("sortBy").and_then(|v| v.as_str());
-            ops::list_directory_with_sizes(validator, path, sort_by)
+            ops::list_directory_with_sizes(workspace, path, sort_by)
         }
         DIRECTORY_TREE => {
             let path = require_str(&args, "path")?;
@@ -374,12 +438,12 @@ pub fn execute(
                     }
                 }
             }
-            ops::directory_tree(validator, path, &exclude)
+            ops::directory_tree(workspace, path, &exclude)
         }
         MOVE_FILE => {
             let source = require_str(&args, "source")?;
             let destination = require_str(&args, "destination")?;
-            ops::move_file(validator, source, destination)
+            ops::move_file(workspace, source, destination)
         }
         SEARCH_FILES => {
             let path = require_str(&args, "path")?;
@@ -394,13 +458,13 @@ pub fn execute(
                     }
                 }
             }
-            ops::search_files(validator, path, pattern, &exclude)
+            ops::search_files(workspace, path, pattern, &exclude)
         }
         GET_FILE_INFO => {
             let path = require_str(&args, "path")?;
-            ops::get_file_info(validator, path)
+            ops::get_file_info(workspace, path)
         }
-        LIST_ALLOWED_DIRECTORIES => Ok(ops::list_allowed_directories(validator)),
+        LIST_ALLOWED_DIRECTORIES => Ok(ops::list_allowed_directories(workspace)),
         _ => Err(format!("Unknown filesystem tool: '{}'", name)),
     }
 }
@@ -434,6 +498,7 @@ mod tests {
         let config = FilesystemConfig {
             enabled: false,
             allowed_directories: vec!["/tmp".into()],
+            ..Default::default()
         };
         assert!(tool_definitions(&config).is_empty());
     }
@@ -443,6 +508,7 @@ mod tests {
         let config = FilesystemConfig {
             enabled: true,
             allowed_directories: vec![],
+            ..Default::default()
         };
         assert!(tool_definitions(&config).is_empty());
     }
@@ -452,9 +518,41 @@ mod tests {
         let config = FilesystemConfig {
             enabled: true,
             allowed_directories: vec!["/tmp".into()],
+            ..Default::default()
         };
         let defs = tool_definitions(&config);
         // 13 tools (read_file alias not in definitions, only handled in dispatch)
         assert_eq!(defs.len(), 13);
     }
+
+    #[test]
+    fn workspaces_get_prefixed_suites() {
+        let ws = |name: &str, dirs: &[&str]| WorkspaceConfig {
+            name: name.into(),
+            allowed_directories: dirs.iter().map(|d| d.to_string()).collect(),
+        };
+        let config = FilesystemConfig {
+            enabled: true,
+            allowed_directories: vec![],
+            workspaces: vec![
+                ws("frontend", &["/repo/web"]),
+                ws("backend", &["/repo/api"]),
+                ws("frontend", &["/elsewhere"]),
+                ws("bad.name", &["/tmp"]),
+                ws("empty", &[]),
+            ],
+        };
+        let names: Vec<String> = tool_definitions(&config).into_iter().map(|t| t.name).collect();
+        assert_eq!(names.len(), 26);
+        assert!(names.contains(&"frontend__read_text_file".to_string()));
+        assert!(names.contains(&"backend__list_allowed_directories".to_string()));
+        assert!(!names.iter().any(|n| n == "read_text_file" || n.starts_with("empty")));
+
+        assert_eq!(split_namespaced("frontend__edit_file"), Some(("frontend", "edit_file")));
+        assert_eq!(split_namespaced("frontend__fetch"), None);
+        assert_eq!(split_namespaced("read_file"), None);
+        assert!(is_valid_workspace_name("api-v2_beta"));
+        assert!(!is_valid_workspace_name("a__b"));
+        assert!(!is_valid_workspace_name("trailing_"));
+    }
 }
@@ -1,7 +1,6 @@
-use std::fs;
 use std::path::Path;
 
-use super::validate::PathValidator;
+use super::workspace::{Entry, Workspace};
 
 // ── Args types ──
 
@@ -16,14 +15,15 @@ pub struct EditOp {
 // ── Read operations ──
 
 pub fn read_text_file(
-    validator: &PathValidator,
+    ws: &dyn Workspace,
     path: &str,
     head: Option<usize>,
     tail: Option<usize>,
 ) -> Result<String, String> {
-    let resolved = validator.validate_existing(path)?;
-    let content =
-        fs::read_to_string(&resolved).map_err(|e| format!("Failed to read '{}': {}", path, e))?;
+    let resolved = ws.resolve_existing(path)?;
+    let content = ws
+        .read_to_string(&resolved)
+        .map_err(|e| format!("Failed to read '{}': {}", path, e))?;
 
     if let Some(n) = head {
         let lines: Vec<&str> = content.lines().take(n).collect();
@@ -38,10 +38,9 @@ pub fn read_text_file(
     Ok(content)
 }
 
-pub fn read_media_file(validator: &PathValidator, path: &str) -> Result<String, String> {
-    let resolved = validator.validate_existing(path)?;
-    let bytes =
-        fs::read(&resolved).map_err(|e| format!("Failed to read '{}': {}", path, e))?;
+pub fn read_media_file(ws: &dyn Workspace, path: &str) -> Result<String, String> {
+    let resolved = ws.resolve_existing(path)?;
+    let bytes = ws.read(&resolved).map_err(|e| format!("Failed to read '{}': {}", path, e))?;
 
     let mime = mime_from_extension(&resolved);
     let encoded = base64_encode(&bytes);
@@ -56,12 +55,12 @@ pub fn read_media_file(validator: &PathValidator, path: &str) -> Result<String,
 }
 
 pub fn read_multiple_files(
-    validator: &PathValidator,
+    ws: &dyn Workspace,
     paths: &[String],
 ) -> Result<String, String> {
     let mut sections = Vec::with_capacity(paths.len());
     for p in paths {
-        match read_text_file(validator, p, None, None) {
+        match read_text_file(ws, p, None, None) {
             Ok(content) => sections.push(format!("--- {} ---\n{}", p, content)),
             Err(e) => sections.push(format!("--- {} ---\nError: {}", p, e)),
         }
@@ -72,136 +71,143 @@ pub fn read_multiple_files(
 // ── Write operations ──
 
 pub fn write_file(
-    validator: &PathValidator,
+    ws: &dyn Workspace,
     path: &str,
     content: &str,
 ) -> Result<String, String> {
-    let resolved = validator.validate(path)?;
+    let resolved = ws.resolve(path)?;
 
     // Ensure parent directory exists
     if let Some(parent) = resolved.parent() {
-        fs::create_dir_all(parent)
+        ws.create_dir_all(parent)
             .map_err(|e| format!("Failed to create parent directories: {}", e))?;
     }
 
-    // Atomic write: temp file → rename (prevents TOCTOU races)
-    let temp_path = resolved.with_extension("nexus-write-tmp");
-    fs::write(&temp_path, content)
+    ws.write(&resolved, content.as_bytes())
         .map_err(|e| format!("Failed to write '{}': {}", path, e))?;
-    fs::rename(&temp_path, &resolved).map_err(|e| {
-        let _ = fs::remove_file(&temp_path);
-        format!("Failed to finalize write to '{}': {}", path, e)
-    })?;
 
     Ok(format!("Successfully wrote to {}", path))
 }
 
+/// Apply search/replace edits to a file. Each `oldText` must match exactly
+/// once in the content left by the edits before it; if any edit fails, the
+/// file is left untouched and every failure is reported. Returns a unified
+/// diff of the change.
 pub fn edit_file(
-    validator: &PathValidator,
+    ws: &dyn Workspace,
     path: &str,
     edits: &[EditOp],
     dry_run: bool,
+    backup: bool,
 ) -> Result<String, String> {
-    let resolved = validator.validate_existing(path)?;
-    let original =
-        fs::read_to_string(&resolved).map_err(|e| format!("Failed to read '{}': {}", path, e))?;
+    let resolved = ws.resolve_existing(path)?;
+    let original = ws
+        .read_to_string(&resolved)
+        .map_err(|e| format!("Failed to read '{}': {}", path, e))?;
 
     let mut content = original.clone();
-    let mut results = Vec::with_capacity(edits.len());
-    let mut any_applied = false;
+    let mut failures = Vec::new();
 
     for (i, edit) in edits.iter().enumerate() {
-        if let Some(pos) = content.find(&edit.old_text) {
-            content = format!(
-                "{}{}{}",
-                &content[..pos],
-                edit.new_text,
-                &content[pos + edit.old_text.len()..]
-            );
-            any_applied = true;
-            results.push(format!(
-                "Edit {}/{}: Applied\n  -{}\n  +{}",
-                i + 1,
-                edits.len(),
-                truncate_display(&edit.old_text, 200),
-                truncate_display(&edit.new_text, 200),
-            ));
+        let matches = if edit.old_text.is_empty() {
+            None
         } else {
-            results.push(format!(
-                "Edit {}/{}: FAILED — oldText not found in file\n  oldText: {}",
-                i + 1,
-                edits.len(),
-                truncate_display(&edit.old_text, 200),
-            ));
-        }
+            Some(content.matches(edit.old_text.as_str()).count())
+        };
+        let reason = match matches {
+            None => "oldText is empty".to_string(),
+            Some(0) => "oldText not found in file".to_string(),
+            Some(1) => {
+                content = content.replacen(&edit.old_text, &edit.new_text, 1);
+                continue;
+            }
+            Some(n) => format!("oldText matches {n} times; include more context to make it unique"),
+        };
+        failures.push(format!(
+            "Edit {}/{}: {}\n  oldText: {}",
+            i + 1,
+            edits.len(),
+            reason,
+            truncate_display(&edit.old_text, 200),
+        ));
+    }
+
+    if !failures.is_empty() {
+        return Err(format!(
+            "No changes made to {}.\n\n{}",
+            path,
+            failures.join("\n\n")
+        ));
+    }
+
+    let diff = similar::TextDiff::from_lines(&original, &content)
+        .unified_diff()
+        .header(path, path)
+        .to_string();
+    if diff.is_empty() {
+        return Ok(format!("No changes: edits leave {} unchanged", path));
     }
 
-    if !dry_run && any_applied {
-        let temp_path = resolved.with_extension("nexus-edit-tmp");
-        fs::write(&temp_path, &content)
-            .map_err(|e| format!("Failed to write '{}': {}", path, e))?;
-        fs::rename(&temp_path, &resolved).map_err(|e| {
-            let _ = fs::remove_file(&temp_path);
-            format!("Failed to finalize edit to '{}': {}", path, e)
-        })?;
+    if dry_run {
+        return Ok(format!("DRY RUN — {}", diff));
     }
 
-    let prefix = if dry_run { "DRY RUN — " } else { "" };
-    Ok(format!("{}File: {}\n\n{}", prefix, path, results.join("\n\n")))
+    if backup {
+        let backup_path = resolved.with_file_name(format!(
+            "{}.bak",
+            resolved.file_name().unwrap_or_default().to_string_lossy()
+        ));
+        ws.write(&backup_path, original.as_bytes())
+            .map_err(|e| format!("Failed to back up '{}': {}", path, e))?;
+    }
+    ws.write(&resolved, content.as_bytes())
+        .map_err(|e| format!("Failed to write '{}': {}", path, e))?;
+
+    Ok(diff)
 }
 
 // ── Directory operations ──
 
-pub fn create_directory(validator: &PathValidator, path: &str) -> Result<String, String> {
-    let resolved = validator.validate(path)?;
-    fs::create_dir_all(&resolved)
+pub fn create_directory(ws: &dyn Workspace, path: &str) -> Result<String, String> {
+    let resolved = ws.resolve(path)?;
+    ws.create_dir_all(&resolved)
         .map_err(|e| format!("Failed to create directory '{}': {}", path, e))?;
     Ok(format!("Successfully created directory {}", path))
 }
 
-pub fn list_directory(validator: &PathValidator, path: &str) -> Result<String, String> {
-    let resolved = validator.validate_existing(path)?;
-    let mut entries: Vec<_> = fs::read_dir(&resolved)
-        .map_err(|e| format!("Failed to read directory '{}': {}", path, e))?
-        .filter_map(|e| e.ok())
-        .collect();
-    entries.sort_by_key(|e| e.file_name());
+pub fn list_directory(ws: &dyn Workspace, path: &str) -> Result<String, String> {
+    let resolved = ws.resolve_existing(path)?;
+    let mut entries = ws
+        .read_dir(&resolved)
+        .map_err(|e| format!("Failed to read directory '{}': {}", path, e))?;
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
 
     let mut lines = Vec::with_capacity(entries.len());
     for entry in &entries {
-        let name = entry.file_name().to_string_lossy().to_string();
-        let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
-        let prefix = if is_dir { "[DIR]  " } else { "[FILE] " };
-        lines.push(format!("{}{}", prefix, name));
+        let prefix = if entry.is_dir { "[DIR]  " } else { "[FILE] " };
+        lines.push(format!("{}{}", prefix, entry.name));
     }
 
     Ok(lines.join("\n"))
 }
 
 pub fn list_directory_with_sizes(
-    validator: &PathValidator,
+    ws: &dyn Workspace,
     path: &str,
     sort_by: Option<&str>,
 ) -> Result<String, String> {
-    let resolved = validator.validate_existing(path)?;
-    let entries: Vec<_> = fs::read_dir(&resolved)
-        .map_err(|e| format!("Failed to read directory '{}': {}", path, e))?
-        .filter_map(|e| e.ok())
-        .collect();
+    let resolved = ws.resolve_existing(path)?;
+    let entries = ws
+        .read_dir(&resolved)
+        .map_err(|e| format!("Failed to read directory '{}': {}", path, e))?;
 
     let mut items: Vec<(String, bool, u64)> = entries
-        .iter()
-        .map(|entry| {
-            let name = entry.file_name().to_string_lossy().to_string();
-            let meta = entry.metadata().ok();
-            let is_dir = meta.as_ref().map(|m| m.is_dir()).unwrap_or(false);
-            let size = meta.as_ref().map(|m| m.len()).unwrap_or(0);
-            (name, is_dir, size)
-        })
+        .into_iter()
+        .map(|entry| (entry.name, entry.is_dir, entry.len))
         .collect();
 
     match sort_by {
-        Some("size") => items.sort_by(|a, b| b.2.cmp(&a.2)),
+        Some("size") => items.sort_by_key(|item| std::cmp::Reverse(item.2)),
         _ => items.sort_by(|a, b| a.0.cmp(&b.0)),
     }
 
@@ -218,11 +224,11 @@ pub fn list_directory_with_sizes(
 }
 
 pub fn directory_tree(
-    validator: &PathValidator,
+    ws: &dyn Workspace,
     path: &str,
     exclude_patterns: &[String],
 ) -> Result<String, String> {
-    let resolved = validator.validate_existing(path)?;
+    let resolved = ws.resolve_existing(path)?;
     let name = resolved
         .file_name()
         .map(|n| n.to_string_lossy().to_string())
@@ -236,6 +242,7 @@ pub fn directory_tree(
         max_per_dir: 100,
     };
     build_tree(
+        ws,
         &resolved,
         "",
         exclude_patterns,
@@ -255,33 +262,34 @@ pub fn directory_tree(
 // ── File management ──
 
 pub fn move_file(
-    validator: &PathValidator,
+    ws: &dyn Workspace,
     source: &str,
     destination: &str,
 ) -> Result<String, String> {
-    let src = validator.validate_existing(source)?;
-    let dst = validator.validate(destination)?;
+    let src = ws.resolve_existing(source)?;
+    let dst = ws.resolve(destination)?;
 
     // Ensure destination parent exists
     if let Some(parent) = dst.parent() {
-        fs::create_dir_all(parent)
+        ws.create_dir_all(parent)
             .map_err(|e| format!("Failed to create destination directory: {}", e))?;
     }
 
-    fs::rename(&src, &dst).map_err(|e| format!("Failed to move '{}' to '{}': {}", source, destination, e))?;
+    ws.rename(&src, &dst).map_err(|e| format!("Failed to move '{}' to '{}': {}", source, destination, e))?;
     Ok(format!("Successfully moved {} to {}", source, destination))
 }
 
 pub fn search_files(
-    validator: &PathValidator,
+    ws: &dyn Workspace,
     path: &str,
     pattern: &str,
     exclude_patterns: &[String],
 ) -> Result<String, String> {
-    let resolved = validator.validate_existing(path)?;
+    let resolved = ws.resolve_existing(path)?;
     let mut results = Vec::new();
 
     search_recursive(
+        ws,
         &resolved,
         pattern,
         exclude_patterns,
@@ -309,14 +317,15 @@ pub fn search_files(
     }
 }
 
-pub fn get_file_info(validator: &PathValidator, path: &str) -> Result<String, String> {
-    let resolved = validator.validate_existing(path)?;
-    let meta =
-        fs::metadata(&resolved).map_err(|e| format!("Failed to get info for '{}': {}", path, e))?;
+pub fn get_file_info(ws: &dyn Workspace, path: &str) -> Result<String, String> {
+    let resolved = ws.resolve_existing(path)?;
+    let meta = ws
+        .metadata(&resolved)
+        .map_err(|e| format!("Failed to get info for '{}': {}", path, e))?;
 
-    let file_type = if meta.is_dir() {
+    let file_type = if meta.is_dir {
         "directory"
-    } else if meta.is_symlink() {
+    } else if meta.is_symlink {
         "symlink"
     } else {
         "file"
@@ -325,34 +334,28 @@ pub fn get_file_info(validator: &PathValidator, path: &str) -> Result<String, St
     let mut info = vec![
         format!("Path: {}", resolved.display()),
         format!("Type: {}", file_type),
-        format!("Size: {} ({})", meta.len(), format_size(meta.len())),
+        format!("Size: {} ({})", meta.len, format_size(meta.len)),
     ];
 
-    if let Ok(modified) = meta.modified() {
-        let dt: chrono::DateTime<chrono::Utc> = modified.into();
-        info.push(format!("Modified: {}", dt.to_rfc3339()));
+    if let Some(modified) = meta.modified {
+        info.push(format!("Modified: {}", modified.to_rfc3339()));
     }
-    if let Ok(accessed) = meta.accessed() {
-        let dt: chrono::DateTime<chrono::Utc> = accessed.into();
-        info.push(format!("Accessed: {}", dt.to_rfc3339()));
+    if let Some(accessed) = meta.accessed {
+        info.push(format!("Accessed: {}", accessed.to_rfc3339()));
     }
-    if let Ok(created) = meta.created() {
-        let dt: chrono::DateTime<chrono::Utc> = created.into();
-        info.push(format!("Created: {}", dt.to_rfc3339()));
+    if let Some(created) = meta.created {
+        info.push(format!("Created: {}", created.to_rfc3339()));
     }
-
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        info.push(format!("Permissions: {:o}", meta.permissions().mode() & 0o777));
+    if let Some(mode) = meta.mode {
+        info.push(format!("Permissions: {:o}", mode));
     }
 
     Ok(info.join("\n"))
 }
 
-pub fn list_allowed_directories(validator: &PathValidator) -> String {
-    let dirs: Vec<String> = validator
-        .allowed_dirs()
+pub fn list_allowed_directories(ws: &dyn Workspace) -> String {
+    let dirs: Vec<String> = ws
+        .roots()
         .iter()
         .map(|d| d.display().to_string())
         .collect();
@@ -468,7 +471,9 @@ struct TreeLimits {
     max_per_dir: usize,
 }
 
+#[allow(clippy::too_many_arguments)]
 fn build_tree(
+    ws: &dyn Workspace,
     dir: &Path,
     prefix: &str,
     exclude: &[String],
@@ -481,15 +486,15 @@ fn build_tree(
         return;
     }
 
-    let Ok(read_dir) = fs::read_dir(dir) else {
+    let Ok(entries) = ws.read_dir(dir) else {
         return;
     };
 
-    let mut entries: Vec<_> = read_dir
-        .filter_map(|e| e.ok())
-        .filter(|e| !should_exclude(&e.file_name().to_string_lossy(), exclude))
+    let mut entries: Vec<Entry> = entries
+        .into_iter()
+        .filter(|e| !should_exclude(&e.name, exclude))
         .collect();
-    entries.sort_by_key(|e| e.file_name());
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
 
     let total = entries.len();
     let truncated = total > limits.max_per_dir;
@@ -502,17 +507,16 @@ fn build_tree(
         *entry_count += 1;
 
         let is_last = i == visible - 1 && !truncated;
-        let name = entry.file_name().to_string_lossy().to_string();
         let connector = if is_last { "└── " } else { "├── " };
         let child_prefix = if is_last { "    " } else { "│   " };
-        let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
-        let suffix = if is_dir { "/" } else { "" };
+        let suffix = if entry.is_dir { "/" } else { "" };
 
-        output.push_str(&format!("{}{}{}{}\n", prefix, connector, name, suffix));
+        output.push_str(&format!("{}{}{}{}\n", prefix, connector, entry.name, suffix));
 
-        if is_dir {
+        if entry.is_dir {
             build_tree(
-                &entry.path(),
+                ws,
+                &dir.join(&entry.name),
                 &format!("{}{}", prefix, child_prefix),
                 exclude,
                 output,
@@ -533,6 +537,7 @@ fn build_tree(
 }
 
 fn search_recursive(
+    ws: &dyn Workspace,
     dir: &Path,
     pattern: &str,
     exclude: &[String],
@@ -544,34 +549,33 @@ fn search_recursive(
         return;
     }
 
-    let Ok(read_dir) = fs::read_dir(dir) else {
+    let Ok(entries) = ws.read_dir(dir) else {
         return;
     };
 
     let pattern_lower = pattern.to_lowercase();
 
-    for entry in read_dir.filter_map(|e| e.ok()) {
+    for entry in entries {
         if results.len() >= limit {
             return;
         }
 
-        let name = entry.file_name().to_string_lossy().to_string();
-        if should_exclude(&name, exclude) {
+        if should_exclude(&entry.name, exclude) {
             continue;
         }
 
+        let path = dir.join(&entry.name);
         // Case-insensitive substring match on filename
-        if name.to_lowercase().contains(&pattern_lower) {
-            let rel = entry
-                .path()
+        if entry.name.to_lowercase().contains(&pattern_lower) {
+            let rel = path
                 .strip_prefix(base)
                 .map(|p| p.display().to_string())
-                .unwrap_or_else(|_| entry.path().display().to_string());
+                .unwrap_or_else(|_| path.display().to_string());
             results.push(rel);
         }
 
-        if entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
-            search_recursive(&entry.path(), pattern, exclude, results, base, limit);
+        if entry.is_dir {
+            search_recursive(ws, &path, pattern, exclude, results, base, limit);
         }
     }
 }
@@ -0,0 +1,549 @@
+//! Storage behind the filesystem tools.
+//!
+//! The tools in [`ops`](super::ops) only touch files through a [`Workspace`],
+//! so the same suite can run against:
+//! - [`LocalWorkspace`]: directories on disk, sandboxed by [`PathValidator`]
+//!   (what the daemon uses; point it at a temp copy to sandbox a run);
+//! - [`MemoryWorkspace`]: an in-memory tree, for fixtures in tests;
+//! - [`GitTreeWorkspace`]: a read-only snapshot of a git revision.
+//!
+//! Paths handed to the trait's file methods are the ones returned by
+//! [`Workspace::resolve`]; only `resolve` sees what the model typed.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+
+use super::validate::PathValidator;
+
+/// One directory entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub name: String,
+    pub is_dir: bool,
+    pub len: u64,
+}
+
+/// What `get_file_info` reports. Fields a backend can't know are `None`.
+#[derive(Debug, Clone, Default)]
+pub struct Metadata {
+    pub is_dir: bool,
+    pub is_symlink: bool,
+    pub len: u64,
+    pub modified: Option<DateTime<Utc>>,
+    pub accessed: Option<DateTime<Utc>>,
+    pub created: Option<DateTime<Utc>>,
+    /// Unix permission bits.
+    pub mode: Option<u32>,
+}
+
+pub trait Workspace: Send + Sync {
+    /// Directories the tools may access, as shown to the model.
+    fn roots(&self) -> Vec<PathBuf>;
+
+    /// Resolve a tool-supplied path, rejecting anything outside the workspace.
+    fn resolve(&self, path: &str) -> Result<PathBuf, String>;
+
+    fn exists(&self, path: &Path) -> bool;
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+    /// Replace the file's contents. The parent directory must exist.
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<Entry>>;
+    fn metadata(&self, path: &Path) -> io::Result<Metadata>;
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()>;
+
+    /// [`resolve`](Self::resolve) a path that must already exist.
+    fn resolve_existing(&self, path: &str) -> Result<PathBuf, String> {
+        let resolved = self.resolve(path)?;
+        if !self.exists(&resolved) {
+            return Err(format!("Path does not exist: '{}'", path));
+        }
+        Ok(resolved)
+    }
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        String::from_utf8(self.read(path)?)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "stream did not contain valid UTF-8"))
+    }
+}
+
+// ── Local directories ──
+
+/// Directories on disk. Writes go through a temp file and a rename.
+pub struct LocalWorkspace {
+    validator: PathValidator,
+}
+
+impl LocalWorkspace {
+    pub fn new(allowed_directories: &[String]) -> Self {
+        Self { validator: PathValidator::new(allowed_directories) }
+    }
+}
+
+impl From<PathValidator> for LocalWorkspace {
+    fn from(validator: PathValidator) -> Self {
+        Self { validator }
+    }
+}
+
+impl Workspace for LocalWorkspace {
+    fn roots(&self) -> Vec<PathBuf> {
+        self.validator.allowed_dirs().into_iter().map(Path::to_path_buf).collect()
+    }
+
+    fn resolve(&self, path: &str) -> Result<PathBuf, String> {
+        self.validator.validate(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        std::fs::read(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        // Atomic write: temp file → rename (prevents TOCTOU races)
+        let temp_path = path.with_extension("nexus-write-tmp");
+        std::fs::write(&temp_path, contents)?;
+        std::fs::rename(&temp_path, path).inspect_err(|_| {
+            let _ = std::fs::remove_file(&temp_path);
+        })
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        std::fs::create_dir_all(path)
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<Entry>> {
+        Ok(std::fs::read_dir(path)?
+            .filter_map(|e| e.ok())
+            .map(|e| {
+                let meta = e.metadata().ok();
+                Entry {
+                    name: e.file_name().to_string_lossy().to_string(),
+                    is_dir: e.file_type().map(|t| t.is_dir()).unwrap_or(false),
+                    len: meta.map(|m| m.len()).unwrap_or(0),
+                }
+            })
+            .collect())
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        let meta = std::fs::metadata(path)?;
+        #[cfg(unix)]
+        let mode = {
+            use std::os::unix::fs::PermissionsExt;
+            Some(meta.permissions().mode() & 0o777)
+        };
+        #[cfg(not(unix))]
+        let mode = None;
+        Ok(Metadata {
+            is_dir: meta.is_dir(),
+            is_symlink: meta.is_symlink(),
+            len: meta.len(),
+            modified: meta.modified().ok().map(Into::into),
+            accessed: meta.accessed().ok().map(Into::into),
+            created: meta.created().ok().map(Into::into),
+            mode,
+        })
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        std::fs::rename(from, to)
+    }
+}
+
+// ── Virtual paths ──
+
+/// Resolve `path` against `root` without touching any filesystem: relative
+/// paths are taken from the root, `.`/`..` are collapsed, and results outside
+/// the root are rejected.
+fn resolve_virtual(root: &Path, path: &str) -> Result<PathBuf, String> {
+    let clean = path.replace('\0', "");
+    if clean.is_empty() {
+        return Err("Path is empty".into());
+    }
+    let mut resolved = PathBuf::from("/");
+    for component in root.join(&clean).components() {
+        match component {
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::Normal(part) => resolved.push(part),
+            Component::RootDir | Component::CurDir | Component::Prefix(_) => {}
+        }
+    }
+    if resolved.starts_with(root) {
+        Ok(resolved)
+    } else {
+        Err(format!(
+            "Access denied: '{}' is outside allowed directories [{}]",
+            path,
+            root.display()
+        ))
+    }
+}
+
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("{} not found", path.display()))
+}
+
+// ── In memory ──
+
+#[derive(Debug, Clone)]
+enum Node {
+    File(Vec<u8>),
+    Dir,
+}
+
+/// An in-memory tree under a virtual root (e.g. `/workspace`).
+pub struct MemoryWorkspace {
+    root: PathBuf,
+    nodes: Mutex<BTreeMap<PathBuf, Node>>,
+}
+
+impl MemoryWorkspace {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let root = root.into();
+        let nodes = BTreeMap::from([(root.clone(), Node::Dir)]);
+        Self { root, nodes: Mutex::new(nodes) }
+    }
+
+    /// Add a file (path relative to the root), creating its directories.
+    pub fn with_file(self, path: &str, contents: impl Into<Vec<u8>>) -> Self {
+        let path = self.root.join(path);
+        if let Some(parent) = path.parent() {
+            self.create_dir_all(parent).expect("fixture directories");
+        }
+        self.nodes.lock().unwrap().insert(path, Node::File(contents.into()));
+        self
+    }
+
+    fn parent_is_dir(nodes: &BTreeMap<PathBuf, Node>, path: &Path) -> io::Result<()> {
+        match path.parent().and_then(|p| nodes.get(p)) {
+            Some(Node::Dir) => Ok(()),
+            _ => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("parent of {} is not a directory", path.display()),
+            )),
+        }
+    }
+}
+
+impl Workspace for MemoryWorkspace {
+    fn roots(&self) -> Vec<PathBuf> {
+        vec![self.root.clone()]
+    }
+
+    fn resolve(&self, path: &str) -> Result<PathBuf, String> {
+        resolve_virtual(&self.root, path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.nodes.lock().unwrap().contains_key(path)
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        match self.nodes.lock().unwrap().get(path) {
+            Some(Node::File(bytes)) => Ok(bytes.clone()),
+            Some(Node::Dir) => Err(io::Error::new(io::ErrorKind::IsADirectory, "is a directory")),
+            None => Err(not_found(path)),
+        }
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let mut nodes = self.nodes.lock().unwrap();
+        Self::parent_is_dir(&nodes, path)?;
+        if let Some(Node::Dir) = nodes.get(path) {
+            return Err(io::Error::new(io::ErrorKind::IsADirectory, "is a directory"));
+        }
+        nodes.insert(path.to_path_buf(), Node::File(contents.to_vec()));
+        Ok(())
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        let mut nodes = self.nodes.lock().unwrap();
+        for dir in path.ancestors().filter(|a| a.starts_with(&self.root)) {
+            match nodes.get(dir) {
+                Some(Node::File(_)) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::AlreadyExists,
+                        format!("{} is a file", dir.display()),
+                    ))
+                }
+                Some(Node::Dir) => {}
+                None => {
+                    nodes.insert(dir.to_path_buf(), Node::Dir);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<Entry>> {
+        let nodes = self.nodes.lock().unwrap();
+        match nodes.get(path) {
+            Some(Node::Dir) => {}
+            Some(Node::File(_)) => {
+                return Err(io::Error::new(io::ErrorKind::NotADirectory, "not a directory"))
+            }
+            None => return Err(not_found(path)),
+        }
+        Ok(nodes
+            .iter()
+            .filter(|(p, _)| p.parent() == Some(path))
+            .map(|(p, node)| Entry {
+                name: p.file_name().unwrap_or_default().to_string_lossy().to_string(),
+                is_dir: matches!(node, Node::Dir),
+                len: match node {
+                    Node::File(bytes) => bytes.len() as u64,
+                    Node::Dir => 0,
+                },
+            })
+            .collect())
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        match self.nodes.lock().unwrap().get(path) {
+            Some(Node::File(bytes)) => Ok(Metadata { len: bytes.len() as u64, ..Default::default() }),
+            Some(Node::Dir) => Ok(Metadata { is_dir: true, ..Default::default() }),
+            None => Err(not_found(path)),
+        }
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let mut nodes = self.nodes.lock().unwrap();
+        if !nodes.contains_key(from) {
+            return Err(not_found(from));
+        }
+        Self::parent_is_dir(&nodes, to)?;
+        let moved: Vec<PathBuf> = nodes.keys().filter(|p| p.starts_with(from)).cloned().collect();
+        for old in moved {
+            let node = nodes.remove(&old).expect("listed above");
+            let new = to.join(old.strip_prefix(from).expect("listed above"));
+            nodes.insert(new, node);
+        }
+        Ok(())
+    }
+}
+
+// ── Git revision (read-only) ──
+
+/// A read-only view of a git revision, read with the `git` CLI. Paths are
+/// shown under the repository's directory; writes fail.
+pub struct GitTreeWorkspace {
+    repo: PathBuf,
+    rev: String,
+}
+
+impl GitTreeWorkspace {
+    pub fn new(repo: impl Into<PathBuf>, rev: impl Into<String>) -> Self {
+        Self { repo: repo.into(), rev: rev.into() }
+    }
+
+    /// `rev:path` for a resolved path (`rev:` is the root tree).
+    fn object(&self, path: &Path) -> String {
+        let rel = path.strip_prefix(&self.repo).unwrap_or(path);
+        format!("{}:{}", self.rev, rel.display())
+    }
+
+    fn git(&self, args: &[&str]) -> io::Result<Vec<u8>> {
+        let output = Command::new("git").arg("-C").arg(&self.repo).args(args).output()?;
+        if !output.status.success() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+        Ok(output.stdout)
+    }
+
+    fn object_type(&self, path: &Path) -> io::Result<String> {
+        let out = self.git(&["cat-file", "-t", &self.object(path)])?;
+        Ok(String::from_utf8_lossy(&out).trim().to_string())
+    }
+}
+
+fn read_only() -> io::Error {
+    io::Error::new(io::ErrorKind::PermissionDenied, "workspace is a read-only git tree")
+}
+
+impl Workspace for GitTreeWorkspace {
+    fn roots(&self) -> Vec<PathBuf> {
+        vec![self.repo.clone()]
+    }
+
+    fn resolve(&self, path: &str) -> Result<PathBuf, String> {
+        resolve_virtual(&self.repo, path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.object_type(path).is_ok()
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.git(&["cat-file", "blob", &self.object(path)])
+    }
+
+    fn write(&self, _path: &Path, _contents: &[u8]) -> io::Result<()> {
+        Err(read_only())
+    }
+
+    fn create_dir_all(&self, _path: &Path) -> io::Result<()> {
+        Err(read_only())
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<Entry>> {
+        let out = self.git(&["ls-tree", "-l", &self.object(path)])?;
+        // `<mode> <type> <object> <size>\t<name>`; size is `-` for trees.
+        Ok(String::from_utf8_lossy(&out)
+            .lines()
+            .filter_map(|line| {
+                let (meta, name) = line.split_once('\t')?;
+                let fields: Vec<&str> = meta.split_whitespace().collect();
+                Some(Entry {
+                    name: name.to_string(),
+                    is_dir: fields.get(1) == Some(&"tree"),
+                    len: fields.get(3).and_then(|s| s.parse().ok()).unwrap_or(0),
+                })
+            })
+            .collect())
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        if self.object_type(path)? == "tree" {
+            return Ok(Metadata { is_dir: true, ..Default::default() });
+        }
+        let size = self.git(&["cat-file", "-s", &self.object(path)])?;
+        Ok(Metadata {
+            len: String::from_utf8_lossy(&size).trim().parse().unwrap_or(0),
+            ..Default::default()
+        })
+    }
+
+    fn rename(&self, _from: &Path, _to: &Path) -> io::Result<()> {
+        Err(read_only())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filesystem::execute;
+
+    fn fixture() -> MemoryWorkspace {
+        MemoryWorkspace::new("/ws")
+            .with_file("src/main.rs", "fn main() {}\n")
+            .with_file("README.md", "# Demo\n")
+    }
+
+    #[test]
+    fn virtual_paths_stay_inside_the_root() {
+        let root = Path::new("/ws");
+        assert_eq!(resolve_virtual(root, "src/../a.rs").unwrap(), Path::new("/ws/a.rs"));
+        assert_eq!(resolve_virtual(root, "/ws/./b").unwrap(), Path::new("/ws/b"));
+        assert!(resolve_virtual(root, "../etc/passwd").is_err());
+        assert!(resolve_virtual(root, "/etc/passwd").is_err());
+        assert!(resolve_virtual(root, "").is_err());
+    }
+
+    #[test]
+    fn tools_run_against_an_in_memory_workspace() {
+        let ws = fixture();
+        let read = execute("read_text_file", r#"{"path":"src/main.rs"}"#, &ws).unwrap();
+        assert_eq!(read, "fn main() {}\n");
+
+        execute("write_file", r#"{"path":"docs/notes.md","content":"hi"}"#, &ws).unwrap();
+        let edit = r#"{"path":"/ws/docs/notes.md","edits":[{"oldText":"hi","newText":"hello"}]}"#;
+        execute("edit_file", edit, &ws).unwrap();
+        assert_eq!(ws.read_to_string(Path::new("/ws/docs/notes.md")).unwrap(), "hello");
+
+        let listing = execute("list_directory", r#"{"path":"."}"#, &ws).unwrap();
+        assert_eq!(listing, "[FILE] README.md\n[DIR]  docs\n[DIR]  src");
+        let tree = execute("directory_tree", r#"{"path":"/ws"}"#, &ws).unwrap();
+        assert!(tree.contains("└── src/\n    └── main.rs"), "{tree}");
+        let found = execute("search_files", r#"{"path":".","pattern":"MAIN"}"#, &ws).unwrap();
+        assert!(found.contains("src/main.rs"));
+
+        execute("move_file", r#"{"source":"src","destination":"lib"}"#, &ws).unwrap();
+        assert!(ws.exists(Path::new("/ws/lib/main.rs")));
+        assert!(!ws.exists(Path::new("/ws/src")));
+
+        let denied = execute("read_text_file", r#"{"path":"../secret"}"#, &ws).unwrap_err();
+        assert!(denied.starts_with("Access denied"));
+        let allowed = execute("list_allowed_directories", "{}", &ws).unwrap();
+        assert_eq!(allowed, "Allowed directories:\n  /ws");
+    }
+
+    #[test]
+    fn edit_file_applies_all_edits_or_none() {
+        let ws = MemoryWorkspace::new("/ws").with_file("a.rs", "let x = 1;\nlet y = 1;\n");
+        let main = Path::new("/ws/a.rs");
+
+        let ambiguous = r#"{"path":"a.rs","edits":[{"oldText":"= 1","newText":"= 3"}]}"#;
+        let err = execute("edit_file", ambiguous, &ws).unwrap_err();
+        assert!(err.contains("Edit 1/1: oldText matches 2 times"), "{err}");
+        let missing = r#"{"path":"a.rs","edits":[{"oldText":"x = 1","newText":"x = 2"},{"oldText":"z","newText":""}]}"#;
+        let err = execute("edit_file", missing, &ws).unwrap_err();
+        assert!(err.starts_with("No changes made to a.rs."), "{err}");
+        assert_eq!(ws.read_to_string(main).unwrap(), "let x = 1;\nlet y = 1;\n");
+
+        let edit = r#"{"path":"a.rs","edits":[{"oldText":"y = 1","newText":"y = 2"}],"backup":true}"#;
+        let diff = execute("edit_file", edit, &ws).unwrap();
+        assert_eq!(
+            diff,
+            "--- a.rs\n+++ a.rs\n@@ -1,2 +1,2 @@\n let x = 1;\n-let y = 1;\n+let y = 2;\n"
+        );
+        assert_eq!(ws.read_to_string(main).unwrap(), "let x = 1;\nlet y = 2;\n");
+        assert_eq!(
+            ws.read_to_string(Path::new("/ws/a.rs.bak")).unwrap(),
+            "let x = 1;\nlet y = 1;\n"
+        );
+
+        let preview = r#"{"path":"a.rs","edits":[{"oldText":"x","newText":"z"}],"dryRun":true}"#;
+        assert!(execute("edit_file", preview, &ws).unwrap().starts_with("DRY RUN — --- a.rs"));
+        assert_eq!(ws.read_to_string(main).unwrap(), "let x = 1;\nlet y = 2;\n");
+    }
+
+    #[test]
+    fn git_tree_is_read_only() {
+        let dir = std::env::temp_dir().join(format!("nexus-test-git-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::write(dir.join("src/lib.rs"), "pub fn one() {}\n").unwrap();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .arg("-C")
+                .arg(&dir)
+                .args(["-c", "user.name=t", "-c", "user.email=t@t", "-c", "commit.gpgsign=false"])
+                .args(args)
+                .output()
+                .map(|o| o.status.success())
+                .unwrap_or(false)
+        };
+        if !git(&["init", "-q"]) {
+            return; // git not installed
+        }
+        assert!(git(&["add", "."]));
+        assert!(git(&["commit", "-qm", "init"]));
+        // Uncommitted changes are not part of the snapshot.
+        std::fs::write(dir.join("src/lib.rs"), "changed").unwrap();
+
+        let ws = GitTreeWorkspace::new(&dir, "HEAD");
+        let read = execute("read_text_file", r#"{"path":"src/lib.rs"}"#, &ws).unwrap();
+        assert_eq!(read, "pub fn one() {}\n");
+        let listing = execute("list_directory", r#"{"path":"."}"#, &ws).unwrap();
+        assert_eq!(listing, "[DIR]  src");
+        let info = execute("get_file_info", r#"{"path":"src/lib.rs"}"#, &ws).unwrap();
+        assert!(info.contains("Size: 16"), "{info}");
+        assert!(execute("write_file", r#"{"path":"src/lib.rs","content":"x"}"#, &ws).is_err());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
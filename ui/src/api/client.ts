@@ -138,11 +138,13 @@ export async function startChat(
   message: string,
   userMessageId?: string,
   assistantMessageId?: string,
+  /** Overrides the agent's thinking budget for this turn; 0 disables thinking. */
+  thinkingBudget?: number,
 ): Promise<{ messageId: string }> {
   const res = await fetch("/api/chat", {
     method: "POST",
     headers: { "Content-Type": "application/json" },
-    body: JSON.stringify({ conversationId, message, userMessageId, assistantMessageId }),
+    body: JSON.stringify({ conversationId, message, userMessageId, assistantMessageId, thinkingBudget }),
   });
   if (!res.ok) {
     const body = await res.json().catch(() => ({ error: "Chat failed" }));
@@ -335,6 +337,10 @@ export interface AgentConfig {
   temperature?: number;
   max_tokens?: number;
   mcp_server_ids?: string[];
+  /** Second provider/model raced against this agent's own */
+  race?: { provider_id: string; model: string };
+  /** Set for agents loaded from ~/.nexus/agents; these are read-only */
+  source?: string;
   created_at: string;
   updated_at: string;
 }
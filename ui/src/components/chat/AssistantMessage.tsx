@@ -366,6 +366,7 @@ const AssistantMessageImpl: FC<{
                 result={tc.result}
                 status={tc.status}
                 durationMs={toolTimingMap.get(tc.toolCallId)}
+                repairs={tc.repairs}
               />
             );
           }
@@ -16,6 +16,7 @@ import { ThreadWelcome } from "./ThreadWelcome";
 import { UserMessage } from "./UserMessage";
 import { AssistantMessage } from "./AssistantMessage";
 import { SpanDivider } from "./SpanDivider";
+import { TurnTimeline } from "./TurnTimeline";
 
 export const Thread: FC = () => {
   const activeThreadId = useThreadListStore((s) => s.activeThreadId);
@@ -30,6 +31,11 @@ export const Thread: FC = () => {
       s.conversations[activeThreadId ?? ""]?.sealedSpans ??
       EMPTY_CONV.sealedSpans,
   );
+  const turnSummaries = useThreadStore(
+    (s) =>
+      s.conversations[activeThreadId ?? ""]?.turnSummaries ??
+      EMPTY_CONV.turnSummaries,
+  );
   const { sendMessage, branchMessage, regenerate, abort, isStreaming } =
     useChatStream();
   useStreamBroadcasts();
@@ -85,6 +91,8 @@ export const Thread: FC = () => {
             />
           )}
 
+          {turnSummaries.length > 0 && <TurnTimeline summaries={turnSummaries} />}
+
           {messages.map((msg, idx) => {
             // Skip user messages that only carry tool results (API plumbing)
             if (
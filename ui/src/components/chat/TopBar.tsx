@@ -1,5 +1,5 @@
 import type { FC } from "react";
-import { MenuIcon, SettingsIcon } from "lucide-react";
+import { AlertTriangleIcon, MenuIcon, SettingsIcon, XIcon } from "lucide-react";
 import { useThreadListStore } from "../../stores/threadListStore";
 import { useUIStore } from "../../stores/uiStore";
 import { AgentSwitcher } from "../agent/AgentSwitcher";
@@ -13,6 +13,8 @@ export const TopBar: FC<TopBarProps> = ({ onMenuPress }) => {
   const activeThreadId = useThreadListStore((s) => s.activeThreadId);
   const threads = useThreadListStore((s) => s.threads);
   const setSettingsOpen = useUIStore((s) => s.setSettingsOpen);
+  const notice = useUIStore((s) => s.notice);
+  const dismissNotice = useUIStore((s) => s.dismissNotice);
 
   const activeThread = threads.find((t) => t.id === activeThreadId);
   const title = activeThread?.title;
@@ -37,6 +39,23 @@ export const TopBar: FC<TopBarProps> = ({ onMenuPress }) => {
 
       <span className="flex-1" />
 
+      {notice && (
+        <span
+          role="status"
+          className="mr-1 flex min-w-0 items-center gap-1.5 rounded px-2 py-0.5 text-[12px] bg-warning-100 dark:bg-warning-100/30 text-warning-600"
+        >
+          <AlertTriangleIcon className="size-3 shrink-0" />
+          <span className="truncate">{notice}</span>
+          <button
+            onClick={dismissNotice}
+            className="shrink-0 opacity-60 hover:opacity-100"
+            aria-label="Dismiss notice"
+          >
+            <XIcon className="size-3" />
+          </button>
+        </span>
+      )}
+
       <WorkspaceSwitcher />
       <AgentSwitcher />
 
@@ -0,0 +1,55 @@
+import { useState, type FC } from "react";
+import { ChevronUpIcon, ChevronDownIcon, HistoryIcon } from "lucide-react";
+import { motion, AnimatePresence } from "framer-motion";
+import type { TurnSummary } from "../../stores/threadStore";
+
+interface TurnTimelineProps {
+  summaries: TurnSummary[];
+}
+
+/** Collapsible list of one-line turn recaps (`turn_summary` events). */
+export const TurnTimeline: FC<TurnTimelineProps> = ({ summaries }) => {
+  const [visible, setVisible] = useState(false);
+  const ordered = [...summaries].sort((a, b) => a.turn - b.turn);
+
+  return (
+    <div className="mx-auto w-full max-w-(--thread-max-width) px-2 py-2">
+      <button
+        type="button"
+        onClick={() => setVisible((v) => !v)}
+        className="mx-auto flex items-center gap-2 rounded-full border border-default-200/50 bg-default-50/50 dark:bg-default-50/20 backdrop-blur-sm px-4 py-1.5 text-xs text-default-400 hover:text-default-600 hover:bg-default-100/50 dark:hover:bg-default-100/20 transition-colors cursor-pointer"
+      >
+        {visible ? <ChevronDownIcon size={12} /> : <ChevronUpIcon size={12} />}
+        <HistoryIcon size={12} />
+        <span>
+          {visible ? "Hide" : "Show"} timeline
+          <span className="ml-1 text-default-300">· {ordered.length}</span>
+        </span>
+      </button>
+
+      <AnimatePresence>
+        {visible && (
+          <motion.ol
+            initial={{ height: 0, opacity: 0 }}
+            animate={{ height: "auto", opacity: 1 }}
+            exit={{ height: 0, opacity: 0 }}
+            transition={{ duration: 0.2 }}
+            className="mt-2 overflow-hidden rounded-lg border border-default-200/30 bg-default-50/30 dark:bg-default-50/10"
+          >
+            {ordered.map((s) => (
+              <li
+                key={s.turn}
+                className="flex items-start gap-2 px-3 py-1.5 border-b border-default-200/20 last:border-b-0 text-xs"
+              >
+                <span className="text-[10px] font-medium tabular-nums text-default-300 shrink-0 pt-0.5">
+                  {s.turn}
+                </span>
+                <span className="min-w-0 flex-1 text-default-500">{s.text}</span>
+              </li>
+            ))}
+          </motion.ol>
+        )}
+      </AnimatePresence>
+    </div>
+  );
+};
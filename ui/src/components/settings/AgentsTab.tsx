@@ -71,32 +71,44 @@ export const AgentsTab: FC = () => {
                     Active
                   </span>
                 )}
+                {a.source && (
+                  <span
+                    title={a.source}
+                    className="text-[9px] px-1.5 py-0.5 rounded-full bg-default-200/50 text-default-500 font-medium uppercase tracking-wide"
+                  >
+                    File
+                  </span>
+                )}
               </div>
               <div className="text-[11px] text-default-400 mt-0.5">
                 {providerName(a.provider_id)} · {a.model}
                 {a.max_tokens && ` · ${a.max_tokens} tokens`}
               </div>
             </div>
-            <button
-              onClick={(e) => {
-                e.stopPropagation();
-                setMode({ type: "edit", agent: a });
-              }}
-              className="text-[11px] text-default-500 hover:text-foreground px-2 py-1 rounded hover:bg-default-200/40 transition-colors"
-            >
-              Edit
-            </button>
-            <button
-              onClick={(e) => {
-                e.stopPropagation();
-                if (confirm(`Delete agent "${a.name}"?`)) {
-                  deleteAgent(a.id);
-                }
-              }}
-              className="text-default-400 hover:text-danger p-1 rounded hover:bg-danger/10 transition-colors"
-            >
-              <TrashIcon className="size-3.5" />
-            </button>
+            {!a.source && (
+              <>
+                <button
+                  onClick={(e) => {
+                    e.stopPropagation();
+                    setMode({ type: "edit", agent: a });
+                  }}
+                  className="text-[11px] text-default-500 hover:text-foreground px-2 py-1 rounded hover:bg-default-200/40 transition-colors"
+                >
+                  Edit
+                </button>
+                <button
+                  onClick={(e) => {
+                    e.stopPropagation();
+                    if (confirm(`Delete agent "${a.name}"?`)) {
+                      deleteAgent(a.id);
+                    }
+                  }}
+                  className="text-default-400 hover:text-danger p-1 rounded hover:bg-danger/10 transition-colors"
+                >
+                  <TrashIcon className="size-3.5" />
+                </button>
+              </>
+            )}
           </div>
         );
       })}
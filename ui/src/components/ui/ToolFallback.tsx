@@ -105,11 +105,13 @@ function ToolFallbackTrigger({
   argsText,
   status,
   durationMs,
+  repairs,
 }: {
   toolName: string;
   argsText?: string;
   status?: ToolCallStatus;
   durationMs?: number;
+  repairs?: string[];
 }) {
   const { isOpen } = useContext(ToolFallbackContext);
   const statusType = status?.type ?? "complete";
@@ -143,6 +145,14 @@ function ToolFallbackTrigger({
           <span className="leading-none">{fallbackDescription}</span>
         )}
       </span>
+      {repairs && repairs.length > 0 && (
+        <span
+          className="shrink-0 text-[10px] px-1.5 py-0.5 rounded-full bg-warning-100 dark:bg-warning-100/30 text-warning-600 border border-warning-200/50"
+          title={`Malformed arguments were repaired: ${repairs.map((r) => r.replace(/_/g, " ")).join(", ")}`}
+        >
+          args repaired
+        </span>
+      )}
       {durationMs != null && statusType !== "running" && (
         <span className="shrink-0 text-[10px] font-mono text-default-400">
           {durationMs < 1000
@@ -377,6 +387,7 @@ interface ToolFallbackProps {
   result?: unknown;
   status?: ToolCallStatus;
   durationMs?: number;
+  repairs?: string[];
 }
 
 const ToolFallbackImpl: FC<ToolFallbackProps> = ({
@@ -385,6 +396,7 @@ const ToolFallbackImpl: FC<ToolFallbackProps> = ({
   result,
   status,
   durationMs,
+  repairs,
 }) => {
   const isCancelled =
     status?.type === "incomplete" && status.reason === "cancelled";
@@ -400,6 +412,7 @@ const ToolFallbackImpl: FC<ToolFallbackProps> = ({
         argsText={argsText}
         status={status}
         durationMs={durationMs}
+        repairs={repairs}
       />
       <ToolFallbackContent>
         <ToolFallbackArgs
@@ -1,14 +1,14 @@
 import { useEffect } from "react";
 import { useThreadListStore } from "../stores/threadListStore";
-import { useThreadStore } from "../stores/threadStore";
-import { useUsageStore } from "../stores/usageStore";
+import { useThreadStore, type TurnSummary } from "../stores/threadStore";
+import { useUsageStore, type CallUsage } from "../stores/usageStore";
 import { useProcessStore, type BgProcess } from "../stores/processStore";
 import { useAgentStore } from "../stores/agentStore";
 import { useProviderStore } from "../stores/providerStore";
 import { useProjectStore } from "../stores/projectStore";
 import { useWorkspaceStore } from "../stores/workspaceStore";
 import { useUIStore } from "../stores/uiStore";
-import { eventBus } from "../runtime/event-bus";
+import { eventBus, EVENT_SCHEMA_VERSION } from "../runtime/event-bus";
 import { consumeStream } from "../lib/stream-consumer";
 import { snowflake } from "../lib/snowflake";
 import { mcpAppService } from "../lib/mcp-app-service";
@@ -53,9 +53,44 @@ export function useStreamBroadcasts(): void {
       }
     });
 
+    const unsubCallUsage = eventBus.on("inference_usage", (event) => {
+      const val = event.value as Omit<CallUsage, "at"> | undefined;
+      const threadId = event.threadId as string | undefined;
+      if (threadId && val) {
+        useUsageStore.getState().addCall(threadId, { ...val, at: Date.now() });
+      }
+    });
+
     const unsubCompaction = eventBus.on("compaction", (event) => {
+      const val = event.value as
+        | {
+            sealed_span_index: number;
+            consumed_count: number;
+            summary?: string;
+            compaction_number?: number;
+            full?: boolean;
+            trigger?: "threshold" | "manual";
+          }
+        | undefined;
       if (event.threadId) {
-        useThreadStore.getState().loadHistory(event.threadId as string);
+        const threadId = event.threadId as string;
+        // The sealed span's summary arrives with the reloaded history.
+        useThreadStore.getState().loadHistory(threadId);
+        if (val?.trigger === "threshold") {
+          useThreadStore
+            .getState()
+            .setActivity(
+              threadId,
+              `Context compacted (#${val.compaction_number}): ${val.consumed_count} messages summarized`,
+            );
+        }
+      }
+    });
+
+    const unsubTurnSummary = eventBus.on("turn_summary", (event) => {
+      const val = event.value as TurnSummary | undefined;
+      if (val && event.threadId) {
+        useThreadStore.getState().addTurnSummary(event.threadId as string, val);
       }
     });
 
@@ -103,6 +138,24 @@ export function useStreamBroadcasts(): void {
       if (id) useThreadListStore.getState().removeThread(id);
     });
 
+    const unsubThreadsPurged = eventBus.on("threads_purged", () => {
+      useThreadListStore.getState().loadThreads();
+    });
+
+    // The daemon dropped events for this tab (it fell behind). Streamed
+    // state may be missing pieces, so reload from what was persisted.
+    const unsubEventsDropped = eventBus.on("events_dropped", (event) => {
+      const count = (event.value as { count?: number })?.count ?? 0;
+      useUIStore
+        .getState()
+        .showNotice(`Missed ${count} live update${count === 1 ? "" : "s"}; reloaded from saved state`);
+      const { activeThreadId, loadThreads } = useThreadListStore.getState();
+      loadThreads();
+      if (activeThreadId) {
+        useThreadStore.getState().loadHistory(activeThreadId);
+      }
+    });
+
     // Agent sync (cross-tab)
     const unsubAgentCreated = eventBus.on("agent_created", () => {
       useAgentStore.getState().loadAgents();
@@ -219,6 +272,12 @@ export function useStreamBroadcasts(): void {
     // loadHistory (called by Thread.tsx on mount) will fetch persisted
     // messages and merge them under the streaming message.
     const unsubSync = eventBus.on("SYNC", (event) => {
+      const schemaVersion = event.schemaVersion as number | undefined;
+      if (schemaVersion !== undefined && schemaVersion !== EVENT_SCHEMA_VERSION) {
+        console.warn(
+          `[SYNC] Daemon event schema v${schemaVersion}, UI expects v${EVENT_SCHEMA_VERSION}`,
+        );
+      }
       const activeRuns = event.activeRuns as string[] | undefined;
       if (!activeRuns?.length) return;
       console.debug(`[SYNC] Active runs:`, activeRuns);
@@ -230,12 +289,16 @@ export function useStreamBroadcasts(): void {
     return () => {
       unsubTitle();
       unsubUsage();
+      unsubCallUsage();
       unsubCompaction();
+      unsubTurnSummary();
       unsubBgStarted();
       unsubBgCompleted();
       unsubBgCancelled();
       unsubThreadCreated();
       unsubThreadDeleted();
+      unsubThreadsPurged();
+      unsubEventsDropped();
       unsubAgentCreated();
       unsubAgentUpdated();
       unsubAgentDeleted();
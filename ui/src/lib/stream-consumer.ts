@@ -213,6 +213,66 @@ export async function consumeStream(
             };
             useQuestionStore.getState().setPending(val);
             useThreadStore.getState().setActivity(conversationId, "Waiting for your input...");
+          } else if (name === "agent_warning") {
+            const val = event.value as {
+              kind: "tool_error" | "retry" | "compaction_failed" | "refusal" | "stop_reason" | "guardrail";
+              message: string;
+              details?: {
+                attempt?: number;
+                maxAttempts?: number;
+                delayMs?: number;
+                maxRetries?: number;
+                exhausted?: boolean;
+              };
+            };
+            console.warn(`[agent_warning] ${val.kind}: ${val.message}`);
+            // Tool errors already render on the tool card; surface the
+            // others in the activity line so a stalled-looking run explains itself.
+            if (val.kind === "retry" && val.details) {
+              const secs = Math.round((val.details.delayMs ?? 0) / 1000);
+              useThreadStore
+                .getState()
+                .setActivity(
+                  conversationId,
+                  `Provider error, retrying in ${secs}s (attempt ${val.details.attempt}/${val.details.maxAttempts})...`,
+                );
+            } else if (val.kind === "compaction_failed") {
+              useThreadStore
+                .getState()
+                .setActivity(conversationId, "Compaction failed — continuing with full context");
+            } else if (val.kind === "guardrail" && val.details && !val.details.exhausted) {
+              useThreadStore
+                .getState()
+                .setActivity(
+                  conversationId,
+                  `Response failed validation, repairing (attempt ${val.details.attempt}/${val.details.maxRetries})...`,
+                );
+            }
+          } else if (name === "tool_args_repaired") {
+            const val = event.value as { toolCallId: string; toolName: string; repairs: string[] };
+            const toolIdx = parts.findIndex(
+              (p) => p.type === "tool-call" && p.toolCallId === val.toolCallId,
+            );
+            if (toolIdx !== -1) {
+              parts[toolIdx] = { ...(parts[toolIdx] as ToolCallPart), repairs: val.repairs };
+              pushToStore();
+            }
+          } else if (name === "moderated") {
+            const val = event.value as {
+              stage: "input" | "output";
+              action: "block" | "rewrite" | "annotate";
+              moderator: string;
+              reason: string;
+              text?: string;
+            };
+            // Output text is held back until moderated, so what streamed is
+            // already the final text; just say why it looks the way it does.
+            if (val.stage === "output") {
+              const verb = { block: "withheld", rewrite: "edited", annotate: "flagged" }[val.action];
+              useThreadStore
+                .getState()
+                .setActivity(conversationId, `Response ${verb} by moderation: ${val.reason}`);
+            }
           } else if (name === "ask_user_answered") {
             const val = event.value as { toolCallId: string };
             useQuestionStore.getState().remove(val.toolCallId);
@@ -24,6 +24,9 @@ export const EventType = {
 
 export type EventType = (typeof EventType)[keyof typeof EventType];
 
+/** Wire format version this UI understands (daemon sends it in SYNC). */
+export const EVENT_SCHEMA_VERSION = 1;
+
 export interface AgUiEvent {
   type: string;
   threadId?: string;
@@ -20,6 +20,9 @@ export type ToolCallStatus =
 
 export type TextPart = { type: "text"; text: string };
 export type ThinkingPart = { type: "thinking"; thinking: string };
+/** Base64 image returned by a tool (e.g. a screenshot). */
+export type ImageSource = { type: "base64"; media_type: string; data: string };
+
 export type ToolCallPart = {
   type: "tool-call";
   toolCallId: string;
@@ -27,14 +30,18 @@ export type ToolCallPart = {
   args: Record<string, unknown>;
   argsText?: string;
   result?: unknown;
+  images?: ImageSource[];
   isError?: boolean;
   status?: ToolCallStatus;
+  /** Fixes the daemon applied to malformed argument JSON before running the tool. */
+  repairs?: string[];
 };
 
 export type ToolResultPart = {
   type: "tool-result";
   toolCallId: string;
   result: string;
+  images?: ImageSource[];
   isError?: boolean;
 };
 
@@ -56,6 +63,8 @@ export interface ProviderErrorDetails {
   message: string;
   status_code?: number;
   retryable: boolean;
+  code?: string;
+  retry_after_ms?: number;
   provider: string;
 }
 
@@ -100,6 +109,13 @@ export interface SealedSpan {
   messages?: ChatMessage[];
 }
 
+/** One-line recap of a finished turn (`turn_summary` event). */
+export interface TurnSummary {
+  turn: number;
+  text: string;
+  source: "heuristic" | "model";
+}
+
 export interface ConvState {
   messages: ChatMessage[];
   /** Sealed conversation spans — compacted segments with summaries */
@@ -107,6 +123,8 @@ export interface ConvState {
   isStreaming: boolean;
   isLoadingHistory: boolean;
   activity: string | null;
+  /** Timeline breadcrumbs, oldest first. */
+  turnSummaries: TurnSummary[];
   repository: MessageNode[];
   childrenMap: Record<string, string[]>;
   branchSelections: Record<string, number>;
@@ -118,6 +136,7 @@ export const EMPTY_CONV: ConvState = {
   isStreaming: false,
   isLoadingHistory: false,
   activity: null,
+  turnSummaries: [],
   repository: [],
   childrenMap: {},
   branchSelections: {},
@@ -159,6 +178,7 @@ interface ThreadState {
   ) => void;
   getLastMessageId: (convId: string) => string | null;
   setActivity: (convId: string, activity: string | null) => void;
+  addTurnSummary: (convId: string, summary: TurnSummary) => void;
   loadAllSpanMessages: (convId: string) => void;
   syncRepository: (
     convId: string,
@@ -246,6 +266,7 @@ function mergeAssistantTurns(messages: ChatMessage[]): ChatMessage[] {
               mergedParts[tcIdx] = {
                 ...tc,
                 result: tr.result,
+                images: tr.images,
                 isError: tr.isError,
                 status: { type: "complete" },
               };
@@ -303,6 +324,7 @@ function toClientMessage(m: {
           type: "tool-result",
           toolCallId: (p.toolCallId ?? p.tool_call_id) as string,
           result: p.result as string,
+          images: p.images as ImageSource[] | undefined,
           isError: (p.is_error ?? p.isError) as boolean | undefined,
         };
       return {
@@ -571,6 +593,15 @@ export const useThreadStore = create<ThreadState>((set, get) => ({
     set((s) => patchConv(s, convId, { activity }));
   },
 
+  addTurnSummary: (convId, summary) => {
+    set((s) => {
+      const existing = getConv(s, convId).turnSummaries;
+      // A regenerated turn replaces its earlier summary.
+      const kept = existing.filter((t) => t.turn !== summary.turn);
+      return patchConv(s, convId, { turnSummaries: [...kept, summary] });
+    });
+  },
+
   loadAllSpanMessages: (convId) => {
     const conv = get().conversations[convId];
     if (!conv) return;
@@ -3,6 +3,9 @@ import { create } from "zustand";
 export type Theme = "light" | "dark" | "system";
 
 const STORAGE_KEY = "nexus-theme";
+const NOTICE_MS = 8000;
+
+let noticeTimer: ReturnType<typeof setTimeout> | undefined;
 
 function getStoredTheme(): Theme {
   const stored = localStorage.getItem(STORAGE_KEY);
@@ -30,6 +33,10 @@ interface UIState {
   setSettingsOpen: (open: boolean) => void;
   theme: Theme;
   setTheme: (theme: Theme) => void;
+  /** Short app-level notice shown in the top bar; clears itself. */
+  notice: string | null;
+  showNotice: (notice: string) => void;
+  dismissNotice: () => void;
 }
 
 export const useUIStore = create<UIState>((set) => ({
@@ -44,4 +51,14 @@ export const useUIStore = create<UIState>((set) => ({
     applyTheme(theme);
     set({ theme });
   },
+  notice: null,
+  showNotice: (notice) => {
+    clearTimeout(noticeTimer);
+    noticeTimer = setTimeout(() => set({ notice: null }), NOTICE_MS);
+    set({ notice });
+  },
+  dismissNotice: () => {
+    clearTimeout(noticeTimer);
+    set({ notice: null });
+  },
 }));
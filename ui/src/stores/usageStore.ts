@@ -9,13 +9,39 @@ export interface ConversationUsage {
   totalCost: number;
 }
 
+/** Usage of a single provider call (`inference_usage` event). */
+export interface CallUsage {
+  source: "turn" | "compaction" | "best_of";
+  round?: number;
+  model: string;
+  inputTokens: number;
+  outputTokens: number;
+  cacheReadInputTokens: number;
+  cacheCreationInputTokens: number;
+  cost: number;
+  at: number;
+}
+
+/** Per-conversation cap on retained call records. */
+const MAX_CALLS = 200;
+
 interface UsageState {
   usage: Record<string, ConversationUsage>;
+  calls: Record<string, CallUsage[]>;
   setUsage: (convId: string, usage: ConversationUsage) => void;
+  addCall: (convId: string, call: CallUsage) => void;
 }
 
 export const useUsageStore = create<UsageState>((set) => ({
   usage: {},
+  calls: {},
   setUsage: (convId, usage) =>
     set((s) => ({ usage: { ...s.usage, [convId]: usage } })),
+  addCall: (convId, call) =>
+    set((s) => ({
+      calls: {
+        ...s.calls,
+        [convId]: [...(s.calls[convId] ?? []), call].slice(-MAX_CALLS),
+      },
+    })),
 }));